@@ -1,6 +1,8 @@
 use napi::{CallContext, JsObject, JsUnknown};
 use napi_derive::{js_function, module_exports};
 
+pub mod turbopack;
+
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[js_function(1)]
 fn transform(ctx: CallContext) -> napi::Result<JsUnknown> {
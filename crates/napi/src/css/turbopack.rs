@@ -0,0 +1,74 @@
+use napi::bindgen_prelude::*;
+use turbopack_css::standalone::{
+    process_standalone_css, StandaloneCssOptions, StandaloneCssTransformOptions,
+};
+
+use crate::util::MapErr;
+
+#[napi(object)]
+pub struct TurbopackCssTransformOutput {
+    pub code: String,
+    pub map: Option<String>,
+    /// The class names of a CSS module, keyed by their local name.
+    pub exports: Option<serde_json::Value>,
+    /// Values exported with `@value` and `:export`.
+    pub icss_exports: serde_json::Value,
+    pub warnings: Vec<String>,
+}
+
+fn transform(
+    input: &str,
+    options: &StandaloneCssOptions,
+) -> napi::Result<TurbopackCssTransformOutput> {
+    let result = process_standalone_css(input, options).convert_err()?;
+    Ok(TurbopackCssTransformOutput {
+        code: result.code,
+        map: result.source_map,
+        exports: result.exports.map(serde_json::to_value).transpose()?,
+        icss_exports: serde_json::to_value(result.icss_exports)?,
+        warnings: result.warnings,
+    })
+}
+
+pub struct TurbopackCssTransformTask {
+    input: String,
+    options: StandaloneCssOptions,
+}
+
+#[napi]
+impl Task for TurbopackCssTransformTask {
+    type Output = TurbopackCssTransformOutput;
+    type JsValue = TurbopackCssTransformOutput;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        transform(&self.input, &self.options)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Processes css the same way as turbopack builds do.
+#[napi]
+pub fn turbopack_css_transform(
+    input: String,
+    options: Buffer,
+    signal: Option<AbortSignal>,
+) -> napi::Result<AsyncTask<TurbopackCssTransformTask>> {
+    let options: StandaloneCssTransformOptions = serde_json::from_slice(&options)?;
+    let task = TurbopackCssTransformTask {
+        input,
+        options: options.into(),
+    };
+    Ok(AsyncTask::with_optional_signal(task, signal))
+}
+
+#[napi]
+pub fn turbopack_css_transform_sync(
+    input: String,
+    options: Buffer,
+) -> napi::Result<TurbopackCssTransformOutput> {
+    let options: StandaloneCssTransformOptions = serde_json::from_slice(&options)?;
+    transform(&input, &options.into())
+}
@@ -11,7 +11,7 @@ use turbo_tasks::{Completion, ReadRef, Vc, VcValueType};
 use turbopack_core::{
     diagnostics::PlainDiagnostic,
     error::PrettyPrintError,
-    issue::{IssueSeverity, PlainIssue},
+    issue::{rules::IssueRules, IssueSeverity, PlainIssue},
 };
 
 use super::utils::{
@@ -86,7 +86,16 @@ impl From<Option<WrittenEndpoint>> for NapiWrittenEndpoint {
 //    some async functions (in this case `endpoint_write_to_disk`) can cause
 //    higher-ranked lifetime errors. See https://github.com/rust-lang/rust/issues/102211
 // 2. the type_complexity clippy lint.
-pub struct ExternalEndpoint(pub VcArc<Vc<Box<dyn Endpoint>>>);
+//
+// The endpoint is paired with the issue rules of its project, which are applied
+// to the issues reported for it.
+pub struct ExternalEndpoint(pub VcArc<Vc<Box<dyn Endpoint>>>, pub Vc<IssueRules>);
+
+impl ExternalEndpoint {
+    pub fn issue_rules(&self) -> Vc<IssueRules> {
+        self.1
+    }
+}
 
 impl Deref for ExternalEndpoint {
     type Target = VcArc<Vc<Box<dyn Endpoint>>>;
@@ -100,13 +109,14 @@ impl Deref for ExternalEndpoint {
 // propagate any actual error results.
 async fn strongly_consistent_catch_collectables<R: VcValueType + Send>(
     source: Vc<R>,
+    issue_rules: Vc<IssueRules>,
 ) -> Result<(
     Option<ReadRef<R>>,
     Arc<Vec<ReadRef<PlainIssue>>>,
     Arc<Vec<ReadRef<PlainDiagnostic>>>,
 )> {
     let result = source.strongly_consistent().await;
-    let issues = get_issues(source).await?;
+    let issues = get_issues(source, issue_rules).await?;
     let diagnostics = get_diagnostics(source).await?;
 
     let result = if result.is_err() && issues.iter().any(|i| i.severity <= IssueSeverity::Error) {
@@ -128,10 +138,11 @@ struct WrittenEndpointWithIssues {
 #[turbo_tasks::function]
 async fn get_written_endpoint_with_issues(
     endpoint: Vc<Box<dyn Endpoint>>,
+    issue_rules: Vc<IssueRules>,
 ) -> Result<Vc<WrittenEndpointWithIssues>> {
     let write_to_disk = endpoint.write_to_disk();
     let (written, issues, diagnostics) =
-        strongly_consistent_catch_collectables(write_to_disk).await?;
+        strongly_consistent_catch_collectables(write_to_disk, issue_rules).await?;
     Ok(WrittenEndpointWithIssues {
         written,
         issues,
@@ -146,6 +157,7 @@ pub async fn endpoint_write_to_disk(
     #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
 ) -> napi::Result<TurbopackResult<NapiWrittenEndpoint>> {
     let turbo_tasks = endpoint.turbo_tasks().clone();
+    let issue_rules = endpoint.issue_rules();
     let endpoint = ***endpoint;
     let (written, issues, diags) = turbo_tasks
         .run_once(async move {
@@ -153,7 +165,7 @@ pub async fn endpoint_write_to_disk(
                 written,
                 issues,
                 diagnostics,
-            } = &*get_written_endpoint_with_issues(endpoint)
+            } = &*get_written_endpoint_with_issues(endpoint, issue_rules)
                 .strongly_consistent()
                 .await?;
             Ok((written.clone(), issues.clone(), diagnostics.clone()))
@@ -174,13 +186,14 @@ pub fn endpoint_server_changed_subscribe(
     func: JsFunction,
 ) -> napi::Result<External<RootTask>> {
     let turbo_tasks = endpoint.turbo_tasks().clone();
+    let issue_rules = endpoint.issue_rules();
     let endpoint = ***endpoint;
     subscribe(
         turbo_tasks,
         func,
         move || {
             async move {
-                subscribe_issues_and_diags(endpoint, issues)
+                subscribe_issues_and_diags(endpoint, issues, issue_rules)
                     .strongly_consistent()
                     .await
             }
@@ -229,12 +242,13 @@ impl Eq for EndpointIssuesAndDiags {}
 async fn subscribe_issues_and_diags(
     endpoint: Vc<Box<dyn Endpoint>>,
     should_include_issues: bool,
+    issue_rules: Vc<IssueRules>,
 ) -> Result<Vc<EndpointIssuesAndDiags>> {
     let changed = endpoint.server_changed();
 
     if should_include_issues {
         let (changed_value, issues, diagnostics) =
-            strongly_consistent_catch_collectables(changed).await?;
+            strongly_consistent_catch_collectables(changed, issue_rules).await?;
         Ok(EndpointIssuesAndDiags {
             changed: changed_value,
             issues,
@@ -23,14 +23,14 @@ use rand::Rng;
 use tokio::{io::AsyncWriteExt, time::Instant};
 use tracing::Instrument;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
-use turbo_tasks::{Completion, RcStr, ReadRef, TransientInstance, UpdateInfo, Vc};
+use turbo_tasks::{Completion, RcStr, ReadRef, ResolvedVc, TransientInstance, UpdateInfo, Vc};
 use turbo_tasks_fs::{
     util::uri_from_file, DiskFileSystem, FileContent, FileSystem, FileSystemPath,
 };
 use turbopack_core::{
     diagnostics::PlainDiagnostic,
     error::PrettyPrintError,
-    issue::PlainIssue,
+    issue::{rules::IssueRules, PlainIssue},
     source_map::{SourceMap, Token},
     version::{PartialUpdate, TotalUpdate, Update, VersionState},
     SOURCE_MAP_PREFIX,
@@ -46,8 +46,8 @@ use url::Url;
 use super::{
     endpoint::ExternalEndpoint,
     utils::{
-        create_turbo_tasks, get_diagnostics, get_issues, subscribe, NapiDiagnostic, NapiIssue,
-        NextTurboTasks, RootTask, TurbopackResult, VcArc,
+        create_turbo_tasks, get_diagnostics, get_issues, subscribe, NapiDiagnostic,
+        NapiInvalidationExplanation, NapiIssue, NextTurboTasks, RootTask, TurbopackResult, VcArc,
     },
 };
 use crate::register;
@@ -512,12 +512,17 @@ struct NapiRoute {
 }
 
 impl NapiRoute {
-    fn from_route(pathname: String, value: Route, turbo_tasks: &NextTurboTasks) -> Self {
+    fn from_route(
+        pathname: String,
+        value: Route,
+        issue_rules: Vc<IssueRules>,
+        turbo_tasks: &NextTurboTasks,
+    ) -> Self {
         let convert_endpoint = |endpoint: Vc<Box<dyn Endpoint>>| {
-            Some(External::new(ExternalEndpoint(VcArc::new(
-                turbo_tasks.clone(),
-                endpoint,
-            ))))
+            Some(External::new(ExternalEndpoint(
+                VcArc::new(turbo_tasks.clone(), endpoint),
+                issue_rules,
+            )))
         };
         match value {
             Route::Page {
@@ -576,12 +581,16 @@ struct NapiMiddleware {
 }
 
 impl NapiMiddleware {
-    fn from_middleware(value: &Middleware, turbo_tasks: &NextTurboTasks) -> Result<Self> {
+    fn from_middleware(
+        value: &Middleware,
+        issue_rules: Vc<IssueRules>,
+        turbo_tasks: &NextTurboTasks,
+    ) -> Result<Self> {
         Ok(NapiMiddleware {
-            endpoint: External::new(ExternalEndpoint(VcArc::new(
-                turbo_tasks.clone(),
-                value.endpoint,
-            ))),
+            endpoint: External::new(ExternalEndpoint(
+                VcArc::new(turbo_tasks.clone(), value.endpoint),
+                issue_rules,
+            )),
         })
     }
 }
@@ -593,16 +602,20 @@ struct NapiInstrumentation {
 }
 
 impl NapiInstrumentation {
-    fn from_instrumentation(value: &Instrumentation, turbo_tasks: &NextTurboTasks) -> Result<Self> {
+    fn from_instrumentation(
+        value: &Instrumentation,
+        issue_rules: Vc<IssueRules>,
+        turbo_tasks: &NextTurboTasks,
+    ) -> Result<Self> {
         Ok(NapiInstrumentation {
-            node_js: External::new(ExternalEndpoint(VcArc::new(
-                turbo_tasks.clone(),
-                value.node_js,
-            ))),
-            edge: External::new(ExternalEndpoint(VcArc::new(
-                turbo_tasks.clone(),
-                value.edge,
-            ))),
+            node_js: External::new(ExternalEndpoint(
+                VcArc::new(turbo_tasks.clone(), value.node_js),
+                issue_rules,
+            )),
+            edge: External::new(ExternalEndpoint(
+                VcArc::new(turbo_tasks.clone(), value.edge),
+                issue_rules,
+            )),
         })
     }
 }
@@ -620,6 +633,7 @@ struct NapiEntrypoints {
 #[turbo_tasks::value(serialization = "none")]
 struct EntrypointsWithIssues {
     entrypoints: ReadRef<Entrypoints>,
+    issue_rules: ResolvedVc<IssueRules>,
     issues: Arc<Vec<ReadRef<PlainIssue>>>,
     diagnostics: Arc<Vec<ReadRef<PlainDiagnostic>>>,
 }
@@ -630,10 +644,12 @@ async fn get_entrypoints_with_issues(
 ) -> Result<Vc<EntrypointsWithIssues>> {
     let entrypoints_operation = container.entrypoints();
     let entrypoints = entrypoints_operation.strongly_consistent().await?;
-    let issues = get_issues(entrypoints_operation).await?;
+    let issue_rules = container.project().issue_rules().to_resolved().await?;
+    let issues = get_issues(entrypoints_operation, *issue_rules).await?;
     let diagnostics = get_diagnostics(entrypoints_operation).await?;
     Ok(EntrypointsWithIssues {
         entrypoints,
+        issue_rules,
         issues,
         diagnostics,
     }
@@ -654,17 +670,23 @@ pub fn project_entrypoints_subscribe(
             async move {
                 let EntrypointsWithIssues {
                     entrypoints,
+                    issue_rules,
                     issues,
                     diagnostics,
                 } = &*get_entrypoints_with_issues(container)
                     .strongly_consistent()
                     .await?;
-                Ok((entrypoints.clone(), issues.clone(), diagnostics.clone()))
+                Ok((
+                    entrypoints.clone(),
+                    **issue_rules,
+                    issues.clone(),
+                    diagnostics.clone(),
+                ))
             }
             .instrument(tracing::info_span!("entrypoints subscription"))
         },
         move |ctx| {
-            let (entrypoints, issues, diags) = ctx.value;
+            let (entrypoints, issue_rules, issues, diags) = ctx.value;
 
             Ok(vec![TurbopackResult {
                 result: NapiEntrypoints {
@@ -675,6 +697,7 @@ pub fn project_entrypoints_subscribe(
                             NapiRoute::from_route(
                                 pathname.clone().into(),
                                 route.clone(),
+                                issue_rules,
                                 &turbo_tasks,
                             )
                         })
@@ -682,25 +705,27 @@ pub fn project_entrypoints_subscribe(
                     middleware: entrypoints
                         .middleware
                         .as_ref()
-                        .map(|m| NapiMiddleware::from_middleware(m, &turbo_tasks))
+                        .map(|m| NapiMiddleware::from_middleware(m, issue_rules, &turbo_tasks))
                         .transpose()?,
                     instrumentation: entrypoints
                         .instrumentation
                         .as_ref()
-                        .map(|m| NapiInstrumentation::from_instrumentation(m, &turbo_tasks))
+                        .map(|m| {
+                            NapiInstrumentation::from_instrumentation(m, issue_rules, &turbo_tasks)
+                        })
                         .transpose()?,
-                    pages_document_endpoint: External::new(ExternalEndpoint(VcArc::new(
-                        turbo_tasks.clone(),
-                        entrypoints.pages_document_endpoint,
-                    ))),
-                    pages_app_endpoint: External::new(ExternalEndpoint(VcArc::new(
-                        turbo_tasks.clone(),
-                        entrypoints.pages_app_endpoint,
-                    ))),
-                    pages_error_endpoint: External::new(ExternalEndpoint(VcArc::new(
-                        turbo_tasks.clone(),
-                        entrypoints.pages_error_endpoint,
-                    ))),
+                    pages_document_endpoint: External::new(ExternalEndpoint(
+                        VcArc::new(turbo_tasks.clone(), entrypoints.pages_document_endpoint),
+                        issue_rules,
+                    )),
+                    pages_app_endpoint: External::new(ExternalEndpoint(
+                        VcArc::new(turbo_tasks.clone(), entrypoints.pages_app_endpoint),
+                        issue_rules,
+                    )),
+                    pages_error_endpoint: External::new(ExternalEndpoint(
+                        VcArc::new(turbo_tasks.clone(), entrypoints.pages_error_endpoint),
+                        issue_rules,
+                    )),
                 },
                 issues: issues
                     .iter()
@@ -727,7 +752,7 @@ async fn hmr_update(
 ) -> Result<Vc<HmrUpdateWithIssues>> {
     let update_operation = project.hmr_update(identifier, state);
     let update = update_operation.strongly_consistent().await?;
-    let issues = get_issues(update_operation).await?;
+    let issues = get_issues(update_operation, project.issue_rules()).await?;
     let diagnostics = get_diagnostics(update_operation).await?;
     Ok(HmrUpdateWithIssues {
         update,
@@ -839,7 +864,7 @@ async fn get_hmr_identifiers_with_issues(
 ) -> Result<Vc<HmrIdentifiersWithIssues>> {
     let hmr_identifiers_operation = container.hmr_identifiers();
     let hmr_identifiers = hmr_identifiers_operation.strongly_consistent().await?;
-    let issues = get_issues(hmr_identifiers_operation).await?;
+    let issues = get_issues(hmr_identifiers_operation, container.project().issue_rules()).await?;
     let diagnostics = get_diagnostics(hmr_identifiers_operation).await?;
     Ok(HmrIdentifiersWithIssues {
         identifiers: hmr_identifiers,
@@ -993,6 +1018,32 @@ pub fn project_update_info_subscribe(
     Ok(())
 }
 
+/// Enables or disables recording of invalidation causes. Enabling it discards everything that was
+/// recorded before. The recorded causes can be inspected with `projectExplainInvalidations` and
+/// `rootTaskExplainInvalidations`.
+#[napi]
+pub fn project_set_invalidation_tracing(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    enabled: bool,
+) -> napi::Result<()> {
+    project.turbo_tasks.set_invalidation_tracing(enabled);
+    Ok(())
+}
+
+/// Explains why tasks were recomputed since invalidation tracing was enabled or since the last
+/// call with `clear` set.
+#[napi]
+pub fn project_explain_invalidations(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    clear: bool,
+) -> napi::Result<NapiInvalidationExplanation> {
+    let explanation = project.turbo_tasks.explain_invalidations(None);
+    if clear {
+        project.turbo_tasks.clear_invalidation_trace();
+    }
+    Ok(explanation.into())
+}
+
 #[turbo_tasks::value]
 #[derive(Debug)]
 #[napi(object)]
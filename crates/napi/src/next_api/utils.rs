@@ -10,14 +10,18 @@ use napi::{
 };
 use serde::Serialize;
 use turbo_tasks::{
-    trace::TraceRawVcs, ReadRef, TaskId, TryJoinIterExt, TurboTasks, UpdateInfo, Vc,
+    trace::TraceRawVcs, InvalidationExplanation, InvalidationTree, ReadRef, TaskId, TryJoinIterExt,
+    TurboTasks, UpdateInfo, Vc,
 };
-use turbo_tasks_backend::{default_backing_storage, DefaultBackingStorage};
+use turbo_tasks_backend::{default_backing_storage, BackendOptions, DefaultBackingStorage};
 use turbo_tasks_fs::FileContent;
 use turbopack_core::{
     diagnostics::{Diagnostic, DiagnosticContextExt, PlainDiagnostic},
     error::PrettyPrintError,
-    issue::{IssueDescriptionExt, PlainIssue, PlainIssueSource, PlainSource, StyledString},
+    issue::{
+        rules::IssueRules, IssueDescriptionExt, PlainIssue, PlainIssueSource, PlainSource,
+        StyledString,
+    },
     source_pos::SourcePos,
 };
 
@@ -106,6 +110,33 @@ impl NextTurboTasks {
         }
     }
 
+    pub fn set_invalidation_tracing(&self, enabled: bool) {
+        match self {
+            NextTurboTasks::Memory(turbo_tasks) => turbo_tasks.set_invalidation_tracing(enabled),
+            NextTurboTasks::PersistentCaching(turbo_tasks) => {
+                turbo_tasks.set_invalidation_tracing(enabled)
+            }
+        }
+    }
+
+    pub fn clear_invalidation_trace(&self) {
+        match self {
+            NextTurboTasks::Memory(turbo_tasks) => turbo_tasks.clear_invalidation_trace(),
+            NextTurboTasks::PersistentCaching(turbo_tasks) => {
+                turbo_tasks.clear_invalidation_trace()
+            }
+        }
+    }
+
+    pub fn explain_invalidations(&self, root: Option<TaskId>) -> InvalidationExplanation {
+        match self {
+            NextTurboTasks::Memory(turbo_tasks) => turbo_tasks.explain_invalidations(root),
+            NextTurboTasks::PersistentCaching(turbo_tasks) => {
+                turbo_tasks.explain_invalidations(root)
+            }
+        }
+    }
+
     pub fn memory_backend(&self) -> Option<&turbo_tasks_memory::MemoryBackend> {
         match self {
             NextTurboTasks::Memory(turbo_tasks) => Some(turbo_tasks.backend()),
@@ -128,9 +159,12 @@ pub fn create_turbo_tasks(
 ) -> Result<NextTurboTasks> {
     Ok(if persistent_caching {
         NextTurboTasks::PersistentCaching(TurboTasks::new(
-            turbo_tasks_backend::TurboTasksBackend::new(default_backing_storage(
-                &output_path.join("cache/turbopack"),
-            )?),
+            turbo_tasks_backend::TurboTasksBackend::new_with_options(
+                default_backing_storage(&output_path.join("cache/turbopack"))?,
+                BackendOptions {
+                    memory_limit: (memory_limit != usize::MAX).then_some(memory_limit),
+                },
+            ),
         ))
     } else {
         let mut backend = turbo_tasks_memory::MemoryBackend::new(memory_limit);
@@ -190,6 +224,58 @@ impl Drop for RootTask {
     }
 }
 
+#[napi(object)]
+pub struct NapiInvalidationTree {
+    pub label: String,
+    pub count: u32,
+    pub children: Vec<NapiInvalidationTree>,
+    pub repeated: bool,
+}
+
+impl From<InvalidationTree> for NapiInvalidationTree {
+    fn from(tree: InvalidationTree) -> Self {
+        Self {
+            label: tree.label,
+            count: tree.count as u32,
+            children: tree.children.into_iter().map(Into::into).collect(),
+            repeated: tree.repeated,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct NapiInvalidationExplanation {
+    /// The causal chains, starting with the external invalidation reasons.
+    pub roots: Vec<NapiInvalidationTree>,
+    /// The causal chains printed as a tree.
+    pub formatted: String,
+}
+
+impl From<InvalidationExplanation> for NapiInvalidationExplanation {
+    fn from(explanation: InvalidationExplanation) -> Self {
+        let formatted = explanation.to_string();
+        Self {
+            roots: explanation.roots.into_iter().map(Into::into).collect(),
+            formatted,
+        }
+    }
+}
+
+/// Explains why the root task was recomputed since invalidation tracing was enabled (see
+/// `projectSetInvalidationTracing`), e.g. `file changed -> read -> parse -> references -> root`.
+#[napi]
+pub fn root_task_explain_invalidations(
+    #[napi(ts_arg_type = "{ __napiType: \"RootTask\" }")] root_task: External<RootTask>,
+) -> napi::Result<NapiInvalidationExplanation> {
+    let Some(task) = root_task.task_id else {
+        return Ok(InvalidationExplanation::default().into());
+    };
+    Ok(root_task
+        .turbo_tasks
+        .explain_invalidations(Some(task))
+        .into())
+}
+
 #[napi]
 pub fn root_task_dispose(
     #[napi(ts_arg_type = "{ __napiType: \"RootTask\" }")] mut root_task: External<RootTask>,
@@ -200,8 +286,16 @@ pub fn root_task_dispose(
     Ok(())
 }
 
-pub async fn get_issues<T: Send>(source: Vc<T>) -> Result<Arc<Vec<ReadRef<PlainIssue>>>> {
-    let issues = source.peek_issues_with_path().await?;
+/// Reads the issues held by the given source, with `issue_rules` applied.
+pub async fn get_issues<T: Send>(
+    source: Vc<T>,
+    issue_rules: Vc<IssueRules>,
+) -> Result<Arc<Vec<ReadRef<PlainIssue>>>> {
+    let issues = source
+        .peek_issues_with_path()
+        .await?
+        .with_rules(&issue_rules.await?)
+        .await?;
     Ok(Arc::new(issues.get_plain_issues().await?))
 }
 
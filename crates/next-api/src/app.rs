@@ -72,6 +72,7 @@ use crate::{
         VisitedDynamicImportModules,
     },
     font::create_font_manifest,
+    functions_config::create_functions_config_manifest,
     loadable_manifest::create_react_loadable_manifest,
     nft_json::NftJsonAsset,
     paths::{
@@ -1185,6 +1186,16 @@ impl AppEndpoint {
         .await?;
         server_assets.insert(next_font_manifest_output);
 
+        let functions_config_manifest_output = create_functions_config_manifest(
+            node_root,
+            &app_entry.original_name,
+            &app_entry.original_name,
+            app_entry.config.await?.max_duration,
+            true,
+        )
+        .await?;
+        server_assets.insert(functions_config_manifest_output);
+
         let endpoint_output = match runtime {
             NextRuntime::Edge => {
                 // the next-edge-ssr-loader templates expect the manifests to be stored in
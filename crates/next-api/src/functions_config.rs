@@ -0,0 +1,43 @@
+use anyhow::Result;
+use next_core::next_manifests::{FunctionConfig, FunctionsConfigManifest};
+use turbo_tasks::{ResolvedVc, Vc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+};
+
+/// Creates the partial `functions-config-manifest.json` for a single route.
+/// The route is only listed when it configures something that affects the
+/// function, e.g. `export const maxDuration = 60`.
+pub(crate) async fn create_functions_config_manifest(
+    node_root: Vc<FileSystemPath>,
+    original_name: &str,
+    manifest_path_prefix: &str,
+    max_duration: Option<u32>,
+    app_dir: bool,
+) -> Result<ResolvedVc<Box<dyn OutputAsset>>> {
+    let path = if app_dir {
+        node_root
+            .join(format!("server/app{manifest_path_prefix}/functions-config-manifest.json").into())
+    } else {
+        node_root.join(
+            format!("server/pages{manifest_path_prefix}/functions-config-manifest.json").into(),
+        )
+    };
+
+    let mut manifest = FunctionsConfigManifest::default();
+    if max_duration.is_some() {
+        manifest
+            .functions
+            .insert(original_name.into(), FunctionConfig { max_duration });
+    }
+
+    Ok(ResolvedVc::upcast(
+        VirtualOutputAsset::new(
+            path,
+            AssetContent::file(File::from(serde_json::to_string_pretty(&manifest)?).into()),
+        )
+        .to_resolved()
+        .await?,
+    ))
+}
@@ -8,6 +8,7 @@ mod dynamic_imports;
 mod empty;
 pub mod entrypoints;
 mod font;
+mod functions_config;
 pub mod global_module_id_strategy;
 mod instrumentation;
 mod loadable_manifest;
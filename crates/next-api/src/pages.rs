@@ -65,6 +65,7 @@ use crate::{
         DynamicImportedChunks, VisitedDynamicImportModules,
     },
     font::create_font_manifest,
+    functions_config::create_functions_config_manifest,
     loadable_manifest::create_react_loadable_manifest,
     nft_json::NftJsonAsset,
     paths::{
@@ -793,6 +794,7 @@ impl PageEndpoint {
         Ok(InternalSsrChunkModule {
             ssr_module: ssr_module.to_resolved().await?,
             runtime: config.runtime,
+            max_duration: config.max_duration,
         }
         .cell())
     }
@@ -813,6 +815,7 @@ impl PageEndpoint {
             let InternalSsrChunkModule {
                 ssr_module,
                 runtime,
+                ..
             } = *self.internal_ssr_chunk_module().await?;
 
             let dynamic_import_modules = collect_next_dynamic_imports(
@@ -1097,6 +1100,16 @@ impl PageEndpoint {
         .await?;
         server_assets.push(next_font_manifest_output);
 
+        let functions_config_manifest_output = create_functions_config_manifest(
+            node_root,
+            original_name,
+            &manifest_path_prefix,
+            self.internal_ssr_chunk_module().await?.max_duration,
+            false,
+        )
+        .await?;
+        server_assets.push(functions_config_manifest_output);
+
         if *this
             .pages_project
             .project()
@@ -1248,6 +1261,7 @@ impl PageEndpoint {
 pub struct InternalSsrChunkModule {
     pub ssr_module: ResolvedVc<Box<dyn Module>>,
     pub runtime: NextRuntime,
+    pub max_duration: Option<u32>,
 }
 
 #[turbo_tasks::value_impl]
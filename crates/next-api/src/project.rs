@@ -45,7 +45,10 @@ use turbopack_core::{
     context::AssetContext,
     diagnostics::DiagnosticExt,
     file_source::FileSource,
-    issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+    issue::{
+        rules::IssueRules, Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString,
+        StyledString,
+    },
     module::Modules,
     output::{OutputAsset, OutputAssets},
     resolve::{find_context_file, FindContextFileResult},
@@ -613,6 +616,12 @@ impl Project {
         self.next_config
     }
 
+    /// The rules applied to the issues of this project.
+    #[turbo_tasks::function]
+    pub fn issue_rules(self: Vc<Self>) -> Vc<IssueRules> {
+        self.next_config().issue_rules(self.project_path())
+    }
+
     #[turbo_tasks::function]
     pub(super) fn next_mode(&self) -> Vc<NextMode> {
         self.mode
@@ -24,7 +24,10 @@ use turbopack_ecmascript::{
     EcmascriptInputTransforms, EcmascriptModuleAssetType,
 };
 
-use crate::{app_structure::AppPageLoaderTree, util::NextRuntime};
+use crate::{
+    app_structure::AppPageLoaderTree,
+    util::{parse_max_duration, NextRuntime},
+};
 
 #[derive(Default, PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -69,6 +72,9 @@ pub struct NextSegmentConfig {
     pub runtime: Option<NextRuntime>,
     pub preferred_region: Option<Vec<RcStr>>,
     pub experimental_ppr: Option<bool>,
+    /// The maximum duration in seconds a serverless function for this segment
+    /// is allowed to run.
+    pub max_duration: Option<u32>,
     /// Whether these metadata exports are defined in the source file.
     pub generate_image_metadata: bool,
     pub generate_sitemaps: bool,
@@ -94,6 +100,7 @@ impl NextSegmentConfig {
             runtime,
             preferred_region,
             experimental_ppr,
+            max_duration,
             ..
         } = self;
         *dynamic = dynamic.or(parent.dynamic);
@@ -103,6 +110,7 @@ impl NextSegmentConfig {
         *runtime = runtime.or(parent.runtime);
         *preferred_region = preferred_region.take().or(parent.preferred_region.clone());
         *experimental_ppr = experimental_ppr.or(parent.experimental_ppr);
+        *max_duration = max_duration.or(parent.max_duration);
     }
 
     /// Applies a config from a parallel route to this config, returning an
@@ -137,6 +145,7 @@ impl NextSegmentConfig {
             runtime,
             preferred_region,
             experimental_ppr,
+            max_duration,
             ..
         } = self;
         merge_parallel(dynamic, &parallel_config.dynamic, "dynamic")?;
@@ -158,6 +167,7 @@ impl NextSegmentConfig {
             &parallel_config.experimental_ppr,
             "experimental_ppr",
         )?;
+        merge_parallel(max_duration, &parallel_config.max_duration, "maxDuration")?;
         Ok(())
     }
 }
@@ -462,6 +472,18 @@ fn parse_config_value(
 
             config.experimental_ppr = Some(val);
         }
+        "maxDuration" => {
+            let value = eval_context.eval(init);
+            let Some(max_duration) = parse_max_duration(&value) else {
+                invalid_config(
+                    "`maxDuration` needs to be a static non-negative integer",
+                    &value,
+                );
+                return;
+            };
+
+            config.max_duration = Some(max_duration);
+        }
         _ => {}
     }
 }
@@ -506,3 +528,66 @@ pub async fn parse_segment_config_from_loader_tree_internal(
 
     Ok(config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{NextRevalidate, NextSegmentConfig, NextSegmentDynamic};
+
+    #[test]
+    fn inherits_unset_values_from_parent() {
+        let parent = NextSegmentConfig {
+            dynamic: Some(NextSegmentDynamic::ForceStatic),
+            revalidate: Some(NextRevalidate::Frequency { seconds: 10 }),
+            max_duration: Some(30),
+            ..Default::default()
+        };
+
+        let mut config = NextSegmentConfig {
+            max_duration: Some(60),
+            ..Default::default()
+        };
+        config.apply_parent_config(&parent);
+        assert_eq!(config.max_duration, Some(60));
+        assert_eq!(config.dynamic, Some(NextSegmentDynamic::ForceStatic));
+        assert_eq!(
+            config.revalidate,
+            Some(NextRevalidate::Frequency { seconds: 10 })
+        );
+
+        let mut config = NextSegmentConfig::default();
+        config.apply_parent_config(&parent);
+        assert_eq!(config.max_duration, Some(30));
+    }
+
+    #[test]
+    fn merges_parallel_routes() {
+        let mut config = NextSegmentConfig::default();
+        config
+            .apply_parallel_config(&NextSegmentConfig {
+                max_duration: Some(60),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(config.max_duration, Some(60));
+
+        // Routes without a value don't conflict with one that has it.
+        config
+            .apply_parallel_config(&NextSegmentConfig::default())
+            .unwrap();
+        config
+            .apply_parallel_config(&NextSegmentConfig {
+                max_duration: Some(60),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(config.max_duration, Some(60));
+
+        let err = config
+            .apply_parallel_config(&NextSegmentConfig {
+                max_duration: Some(30),
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("maxDuration"), "{err}");
+    }
+}
@@ -53,7 +53,7 @@ use crate::{
             styled_jsx::get_styled_jsx_transform_rule,
             swc_ecma_transform_plugins::get_swc_ecma_transform_plugin_rule,
         },
-        webpack_rules::webpack_loader_options,
+        webpack_rules::{sass::sass_transform_options, webpack_loader_options},
     },
     transform_options::{
         get_decorators_transform_options, get_jsx_transform_options,
@@ -266,6 +266,11 @@ pub async fn get_client_module_options_context(
         .tree_shaking_mode_for_foreign_code(next_mode.is_development())
         .await?;
     let use_swc_css = *next_config.use_swc_css().await?;
+    let enable_sass = if *next_config.use_native_sass().await? {
+        Some(sass_transform_options(project_path, next_config.sass_config()))
+    } else {
+        None
+    };
     let target_browsers = env.runtime_versions();
 
     let mut next_client_rules =
@@ -356,6 +361,7 @@ pub async fn get_client_module_options_context(
         enable_mdx_rs,
         css: CssOptionsContext {
             use_swc_css,
+            enable_sass,
             minify_type: if *next_config.turbo_minify(mode).await? {
                 MinifyType::Minify
             } else {
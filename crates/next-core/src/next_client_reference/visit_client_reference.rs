@@ -1,4 +1,8 @@
-use std::{collections::HashSet, future::Future};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    future::Future,
+    hash::Hash,
+};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -7,11 +11,17 @@ use turbo_tasks::{
     debug::ValueDebugFormat,
     graph::{AdjacencyMap, GraphTraversal, Visit, VisitControlFlow, VisitedNodes},
     trace::TraceRawVcs,
-    FxIndexMap, FxIndexSet, RcStr, ReadRef, ResolvedVc, TryJoinIterExt, ValueToString, Vc,
+    FxIndexMap, FxIndexSet, RcStr, ReadRef, ResolvedVc, TryFlatJoinIterExt, TryJoinIterExt,
+    ValueToString, Vc,
 };
 use turbo_tasks_fs::FileSystemPath;
 use turbopack::css::CssModuleAsset;
-use turbopack_core::{module::Module, reference::primary_referenced_modules};
+use turbopack_core::{
+    issue::{Issue, IssueExt, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+    module::Module,
+    reference::primary_referenced_modules,
+};
+use turbopack_ecmascript::references::{cjs::CjsRequireAssetReference, esm::EsmAssetReference};
 
 use super::ecmascript_client_reference::ecmascript_client_reference_module::EcmascriptClientReferenceModule;
 use crate::{
@@ -170,6 +180,8 @@ pub async fn client_reference_graph(
             .completed()?
             .into_inner_with_visited();
 
+        check_module_graph_violations(&graph).await?;
+
         for node in graph.into_reverse_topological() {
             match &node.ty {
                 VisitClientReferenceNodeType::Internal(_asset, _) => {
@@ -213,6 +225,404 @@ pub async fn client_reference_graph(
     .await
 }
 
+/// Requests that must not be imported from Client Components. Mirrors
+/// `invalid_client_imports` of the `react_server_components` transform.
+const INVALID_CLIENT_IMPORTS: &[&str] = &["server-only", "next/headers"];
+
+/// Requests that must not be imported from Server Components. Mirrors
+/// `invalid_server_imports` of the `react_server_components` transform.
+const INVALID_SERVER_IMPORTS: &[&str] = &[
+    "client-only",
+    "react-dom/client",
+    "react-dom/server",
+    "next/router",
+];
+
+/// Imported by the implementation of Server Actions, which the server actions
+/// transform only generates in the server layers. In the client layers,
+/// `"use server"` modules are replaced by references to the actions.
+const SERVER_REFERENCE_IMPORT: &str = "private-next-rsc-server-reference";
+
+/// The layers Client Components are compiled in.
+const CLIENT_LAYERS: &[&str] = &["app-ssr", "app-edge-ssr", "app-client"];
+
+/// The layers Server Components are compiled in.
+const SERVER_LAYERS: &[&str] = &["app-rsc", "app-edge-rsc"];
+
+/// An import that must not be part of the module graph of a layer.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, ValueDebugFormat, TraceRawVcs,
+)]
+pub enum ModuleGraphViolationKind {
+    /// `client-only` or a client API is imported from a Server Component.
+    ClientOnlyInServer,
+    /// `server-only` or a server API is imported from a Client Component.
+    ServerOnlyInClient,
+    /// The implementation of Server Actions is imported from a Client
+    /// Component, so it ends up in the client chunks.
+    ServerActionInClient,
+}
+
+impl ModuleGraphViolationKind {
+    /// Returns the violation a module compiled in `layer` causes by importing
+    /// `request`.
+    fn for_request(layer: &str, request: &str) -> Option<Self> {
+        if CLIENT_LAYERS.contains(&layer) {
+            if INVALID_CLIENT_IMPORTS.contains(&request) {
+                Some(Self::ServerOnlyInClient)
+            } else if request == SERVER_REFERENCE_IMPORT {
+                Some(Self::ServerActionInClient)
+            } else {
+                None
+            }
+        } else if SERVER_LAYERS.contains(&layer) && INVALID_SERVER_IMPORTS.contains(&request) {
+            Some(Self::ClientOnlyInServer)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the `react_server_components` transform already reports the
+    /// import in files outside of `node_modules`.
+    fn is_checked_per_file(self) -> bool {
+        !matches!(self, Self::ServerActionInClient)
+    }
+}
+
+/// Returns the first import of `module` that must not be part of the module
+/// graph of its layer.
+async fn module_graph_violation(
+    module: Vc<Box<dyn Module>>,
+) -> Result<Option<(ModuleGraphViolationKind, RcStr)>> {
+    let ident = module.ident().await?;
+    let Some(layer) = ident.layer else {
+        return Ok(None);
+    };
+    let layer = layer.await?;
+    if !CLIENT_LAYERS.contains(&layer.as_str()) && !SERVER_LAYERS.contains(&layer.as_str()) {
+        return Ok(None);
+    }
+
+    for &reference in module.references().await?.iter() {
+        let request = if let Some(reference) =
+            Vc::try_resolve_downcast_type::<EsmAssetReference>(reference).await?
+        {
+            reference.await?.request
+        } else if let Some(reference) =
+            Vc::try_resolve_downcast_type::<CjsRequireAssetReference>(reference).await?
+        {
+            reference.await?.request
+        } else {
+            continue;
+        };
+        if let Some(request) = request.await?.request() {
+            if let Some(kind) = ModuleGraphViolationKind::for_request(&layer, &request) {
+                return Ok(Some((kind, request)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[derive(
+    Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug, ValueDebugFormat, TraceRawVcs,
+)]
+pub struct ModuleGraphViolation {
+    pub kind: ModuleGraphViolationKind,
+    /// The request that must not be imported.
+    pub request: RcStr,
+    /// The module that imports `request`.
+    pub importer: ResolvedVc<Box<dyn Module>>,
+    /// The root of the traversal the violation was found from.
+    pub root: ResolvedVc<Box<dyn Module>>,
+    /// The idents of all modules from the root to the importer.
+    pub import_chain: Vec<RcStr>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct ModuleGraphViolations(Vec<ModuleGraphViolation>);
+
+/// Finds the modules in the client module graphs of the given `"use client"`
+/// boundaries that import server-only modules or Server Actions. The graph is
+/// traversed breadth-first, so each violation is reported with the shortest
+/// import chain from one of the boundaries.
+#[turbo_tasks::function]
+pub async fn client_graph_violations(
+    boundaries: Vec<ResolvedVc<Box<dyn Module>>>,
+) -> Result<Vc<ModuleGraphViolations>> {
+    let mut parents: HashMap<ResolvedVc<Box<dyn Module>>, Option<ResolvedVc<Box<dyn Module>>>> =
+        boundaries.iter().map(|&module| (module, None)).collect();
+    let mut current: Vec<_> = parents.keys().copied().collect();
+    let mut violations = vec![];
+
+    while !current.is_empty() {
+        let visited = current
+            .iter()
+            .map(|&module| async move {
+                Ok((
+                    module,
+                    module_graph_violation(*module).await?,
+                    primary_referenced_modules(*module).await?,
+                ))
+            })
+            .try_join()
+            .await?;
+
+        let mut next = vec![];
+        for (module, violation, children) in visited {
+            if let Some((kind, request)) = violation {
+                let chain = import_chain(&parents, module);
+                let import_chain = chain
+                    .iter()
+                    .map(|module| async move { Ok((*module.ident().to_string().await?).clone()) })
+                    .try_join()
+                    .await?;
+                violations.push(ModuleGraphViolation {
+                    kind,
+                    request,
+                    importer: module,
+                    root: chain[0],
+                    import_chain,
+                });
+            }
+            for child in children.iter() {
+                let child = child.to_resolved().await?;
+                if let Entry::Vacant(entry) = parents.entry(child) {
+                    entry.insert(Some(module));
+                    next.push(child);
+                }
+            }
+        }
+        current = next;
+    }
+
+    Ok(Vc::cell(violations))
+}
+
+/// Finds the shortest path from one of the `roots` to every reachable node and
+/// returns the parent of each node on it.
+fn shortest_paths<T: Copy + Eq + Hash>(
+    roots: impl IntoIterator<Item = T>,
+    mut children: impl FnMut(T) -> Vec<T>,
+) -> HashMap<T, Option<T>> {
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+    for root in roots {
+        if parents.insert(root, None).is_none() {
+            queue.push_back(root);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        for child in children(node) {
+            if let Entry::Vacant(entry) = parents.entry(child) {
+                entry.insert(Some(node));
+                queue.push_back(child);
+            }
+        }
+    }
+    parents
+}
+
+/// Walks the `parents` map from `node` back to the root of the traversal.
+fn import_chain<T: Copy + Eq + Hash>(parents: &HashMap<T, Option<T>>, node: T) -> Vec<T> {
+    let mut chain = vec![node];
+    let mut current = node;
+    while let Some(Some(parent)) = parents.get(&current) {
+        chain.push(*parent);
+        current = *parent;
+    }
+    chain.reverse();
+    chain
+}
+
+/// Joins the chain from the entry to a client reference with the chain in the
+/// client graph behind it, which starts with the SSR version of the client
+/// reference and replaces the client reference itself.
+fn join_import_chains(mut server_chain: Vec<RcStr>, client_chain: &[RcStr]) -> Vec<RcStr> {
+    server_chain.pop();
+    server_chain.extend(client_chain.iter().cloned());
+    server_chain
+}
+
+/// Reports modules in the server part of the graph that import client-only
+/// modules, and modules in the client graphs behind each client reference that
+/// import server-only modules or Server Actions, together with the full import
+/// chain from the entry.
+async fn check_module_graph_violations(
+    graph: &AdjacencyMap<VisitClientReferenceNode>,
+) -> Result<()> {
+    let parents = shortest_paths(graph.roots(), |node| {
+        graph.get(node).into_iter().flatten().collect()
+    });
+    let server_chain = |node| {
+        import_chain(&parents, node)
+            .into_iter()
+            .map(|node| node.ty.name())
+            .collect::<Vec<_>>()
+    };
+
+    let server_violations = parents
+        .keys()
+        .filter(|node| !matches!(node.ty, VisitClientReferenceNodeType::ClientReference(..)))
+        .map(|&node| async move {
+            let module = node.ty.module();
+            Ok(module_graph_violation(module)
+                .await?
+                .map(|violation| (node, module, violation)))
+        })
+        .try_flat_join()
+        .await?;
+    for (node, module, (kind, request)) in server_violations {
+        ModuleGraphViolationIssue::new(module, kind, request, server_chain(node))
+            .await?
+            .cell()
+            .emit();
+    }
+
+    let mut boundaries = HashMap::new();
+    for &node in parents.keys() {
+        if let VisitClientReferenceNodeType::ClientReference(
+            ClientReference {
+                ty: ClientReferenceType::EcmascriptClientReference { module, .. },
+                ..
+            },
+            _,
+        ) = &node.ty
+        {
+            let ssr_module = ResolvedVc::upcast(module.await?.ssr_module);
+            boundaries.entry(ssr_module).or_insert(node);
+        }
+    }
+    if boundaries.is_empty() {
+        return Ok(());
+    }
+
+    let violations = client_graph_violations(boundaries.keys().copied().collect()).await?;
+    for violation in violations.iter() {
+        ModuleGraphViolationIssue::new(
+            *violation.importer,
+            violation.kind,
+            violation.request.clone(),
+            join_import_chains(
+                server_chain(boundaries[&violation.root]),
+                &violation.import_chain,
+            ),
+        )
+        .await?
+        .cell()
+        .emit();
+    }
+
+    Ok(())
+}
+
+/// An issue reporting a [`ModuleGraphViolation`] with the import chain that
+/// causes it.
+#[turbo_tasks::value(shared)]
+pub struct ModuleGraphViolationIssue {
+    file_path: Vc<FileSystemPath>,
+    kind: ModuleGraphViolationKind,
+    request: RcStr,
+    import_chain: Vec<RcStr>,
+    /// The import itself is already reported as an error by the
+    /// `react_server_components` transform, so this only adds the import
+    /// chain.
+    is_reported_per_file: bool,
+}
+
+impl ModuleGraphViolationIssue {
+    async fn new(
+        importer: Vc<Box<dyn Module>>,
+        kind: ModuleGraphViolationKind,
+        request: RcStr,
+        import_chain: Vec<RcStr>,
+    ) -> Result<Self> {
+        let file_path = importer.ident().path();
+        let is_reported_per_file =
+            kind.is_checked_per_file() && !file_path.await?.path.contains("node_modules/");
+        Ok(ModuleGraphViolationIssue {
+            file_path,
+            kind,
+            request,
+            import_chain,
+            is_reported_per_file,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ModuleGraphViolationIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        if self.is_reported_per_file {
+            IssueSeverity::Warning.into()
+        } else {
+            IssueSeverity::Error.into()
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Analysis.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(
+            match self.kind {
+                ModuleGraphViolationKind::ClientOnlyInServer => format!(
+                    "'{}' cannot be imported from a Server Component module",
+                    self.request
+                ),
+                ModuleGraphViolationKind::ServerOnlyInClient => format!(
+                    "'{}' cannot be imported from a Client Component module",
+                    self.request
+                ),
+                ModuleGraphViolationKind::ServerActionInClient => {
+                    "Server Action implementation is part of the client module graph".to_string()
+                }
+            }
+            .into(),
+        )
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let message = match self.kind {
+            ModuleGraphViolationKind::ClientOnlyInServer => {
+                "It should only be used from a Client Component, but it's reachable from the entry \
+                 through the import chain below."
+            }
+            ModuleGraphViolationKind::ServerOnlyInClient => {
+                "It should only be used from a Server Component, but it's reachable from a \"use \
+                 client\" boundary through the import chain below."
+            }
+            ModuleGraphViolationKind::ServerActionInClient => {
+                "Client Components can only import Server Actions from a \"use server\" module, \
+                 which is replaced by references to the actions. This module contains their \
+                 implementation, which is reachable from a \"use client\" boundary through the \
+                 import chain below."
+            }
+        };
+        let mut lines = vec![
+            StyledString::Text(message.into()),
+            StyledString::Strong("Import trace:".into()),
+        ];
+        lines.extend(
+            self.import_chain
+                .iter()
+                .map(|ident| StyledString::Code(format!("  {ident}").into())),
+        );
+        Vc::cell(Some(StyledString::Stack(lines).cell()))
+    }
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Clone, Debug)]
 pub struct ServerEntries {
@@ -310,6 +720,35 @@ enum VisitClientReferenceNodeType {
     Internal(ResolvedVc<Box<dyn Module>>, ReadRef<RcStr>),
 }
 
+impl VisitClientReferenceNodeType {
+    fn name(&self) -> RcStr {
+        match self {
+            VisitClientReferenceNodeType::ClientReference(_, name)
+            | VisitClientReferenceNodeType::ServerComponentEntry(_, name)
+            | VisitClientReferenceNodeType::ServerUtilEntry(_, name)
+            | VisitClientReferenceNodeType::Internal(_, name) => (**name).clone(),
+        }
+    }
+
+    fn module(&self) -> Vc<Box<dyn Module>> {
+        match self {
+            VisitClientReferenceNodeType::ClientReference(client_reference, _) => {
+                match client_reference.ty() {
+                    ClientReferenceType::EcmascriptClientReference { module, .. } => {
+                        Vc::upcast(module)
+                    }
+                    ClientReferenceType::CssClientReference(module) => Vc::upcast(module),
+                }
+            }
+            VisitClientReferenceNodeType::ServerComponentEntry(module, _) => {
+                *ResolvedVc::upcast::<Box<dyn Module>>(*module)
+            }
+            VisitClientReferenceNodeType::ServerUtilEntry(module, _)
+            | VisitClientReferenceNodeType::Internal(module, _) => **module,
+        }
+    }
+}
+
 impl Visit<VisitClientReferenceNode> for VisitClientReference {
     type Edge = VisitClientReferenceNode;
     type EdgesIntoIter = Vec<Self::Edge>;
@@ -453,3 +892,78 @@ impl Visit<VisitClientReferenceNode> for VisitClientReference {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use turbo_tasks::RcStr;
+
+    use super::{import_chain, join_import_chains, shortest_paths, ModuleGraphViolationKind};
+
+    #[test]
+    fn test_import_chain() {
+        let edges: HashMap<u32, Vec<u32>> =
+            HashMap::from([(0, vec![1, 3]), (1, vec![2]), (2, vec![4]), (3, vec![4])]);
+        let parents = shortest_paths([0], |node| edges.get(&node).cloned().unwrap_or_default());
+
+        assert_eq!(import_chain(&parents, 0), vec![0]);
+        assert_eq!(import_chain(&parents, 2), vec![0, 1, 2]);
+        // The shortest chain is reported.
+        assert_eq!(import_chain(&parents, 4), vec![0, 3, 4]);
+    }
+
+    #[test]
+    fn test_join_import_chains() {
+        let server_chain: Vec<RcStr> = vec![
+            "[project]/app/page.tsx [app-rsc]".into(),
+            "[project]/app/button.tsx (client reference)".into(),
+        ];
+        let client_chain: Vec<RcStr> = vec![
+            "[project]/app/button.tsx [app-ssr]".into(),
+            "[project]/node_modules/db/index.js [app-ssr]".into(),
+        ];
+        assert_eq!(
+            join_import_chains(server_chain, &client_chain),
+            vec![
+                RcStr::from("[project]/app/page.tsx [app-rsc]"),
+                "[project]/app/button.tsx [app-ssr]".into(),
+                "[project]/node_modules/db/index.js [app-ssr]".into(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_violation_for_request() {
+        use ModuleGraphViolationKind::*;
+
+        for (layer, request, expected) in [
+            ("app-ssr", "server-only", Some(ServerOnlyInClient)),
+            ("app-client", "next/headers", Some(ServerOnlyInClient)),
+            ("app-edge-ssr", "server-only", Some(ServerOnlyInClient)),
+            (
+                "app-client",
+                "private-next-rsc-server-reference",
+                Some(ServerActionInClient),
+            ),
+            ("app-rsc", "client-only", Some(ClientOnlyInServer)),
+            ("app-edge-rsc", "react-dom/client", Some(ClientOnlyInServer)),
+            // Allowed in their own layers.
+            ("app-rsc", "server-only", None),
+            ("app-rsc", "private-next-rsc-server-reference", None),
+            ("app-ssr", "client-only", None),
+            // Other layers aren't checked.
+            ("app-route", "client-only", None),
+            ("app-ssr", "react", None),
+        ] {
+            assert_eq!(
+                ModuleGraphViolationKind::for_request(layer, request),
+                expected,
+                "{request} in {layer}"
+            );
+        }
+        assert!(ServerOnlyInClient.is_checked_per_file());
+        assert!(ClientOnlyInServer.is_checked_per_file());
+        assert!(!ServerActionInClient.is_checked_per_file());
+    }
+}
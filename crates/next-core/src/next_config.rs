@@ -10,7 +10,10 @@ use turbopack::module_options::{
     module_options_context::MdxTransformOptions, LoaderRuleItem, OptionWebpackRules,
 };
 use turbopack_core::{
-    issue::{Issue, IssueSeverity, IssueStage, OptionStyledString, StyledString},
+    issue::{
+        rules::{IssueRule, IssueRules},
+        Issue, IssueSeverity, IssueStage, OptionStyledString, StyledString,
+    },
     resolve::ResolveAliasMap,
 };
 use turbopack_ecmascript::{OptionTreeShaking, TreeShakingMode};
@@ -402,9 +405,12 @@ pub struct ExperimentalTurboConfig {
     pub resolve_alias: Option<FxIndexMap<RcStr, JsonValue>>,
     pub resolve_extensions: Option<Vec<RcStr>>,
     pub use_swc_css: Option<bool>,
+    pub use_native_sass: Option<bool>,
     pub tree_shaking: Option<bool>,
     pub module_id_strategy: Option<ModuleIdStrategy>,
     pub minify: Option<bool>,
+    /// Rules to ignore issues or change their severity.
+    pub issue_rules: Option<Vec<IssueRule>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -1167,6 +1173,32 @@ impl NextConfig {
         )
     }
 
+    #[turbo_tasks::function]
+    pub fn use_native_sass(&self) -> Vc<bool> {
+        Vc::cell(
+            self.experimental
+                .turbo
+                .as_ref()
+                .and_then(|turbo| turbo.use_native_sass)
+                .unwrap_or(false),
+        )
+    }
+
+    /// The issue rules, with path globs relative to `project_path`.
+    #[turbo_tasks::function]
+    pub fn issue_rules(&self, project_path: ResolvedVc<FileSystemPath>) -> Vc<IssueRules> {
+        IssueRules {
+            rules: self
+                .experimental
+                .turbo
+                .as_ref()
+                .and_then(|turbo| turbo.issue_rules.clone())
+                .unwrap_or_default(),
+            project_path: Some(project_path),
+        }
+        .cell()
+    }
+
     #[turbo_tasks::function]
     pub fn optimize_package_imports(&self) -> Vc<Vec<RcStr>> {
         Vc::cell(
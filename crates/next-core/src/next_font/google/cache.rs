@@ -0,0 +1,372 @@
+use std::{path::Path, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, RcStr, Vc};
+use turbo_tasks_env::{CommandLineProcessEnv, ProcessEnv};
+use turbo_tasks_fs::{
+    json::parse_json_with_source_context, DiskFileSystem, File, FileContent, FileSystem,
+    FileSystemPath,
+};
+use turbo_tasks_hash::hash_xxh3_hash128;
+use turbopack_core::issue::{IssueExt, IssueSeverity, StyledString};
+
+use crate::next_font::issue::NextFontIssue;
+
+/// The directory of the font cache. When set, stylesheets and font files
+/// fetched from Google Fonts are stored in it, and can be read from it without
+/// network access.
+pub const CACHE_DIR_ENV: &str = "NEXT_FONT_GOOGLE_CACHE_DIR";
+/// How the font cache is used, see [GoogleFontsCacheMode].
+pub const CACHE_MODE_ENV: &str = "NEXT_FONT_GOOGLE_CACHE_MODE";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub(super) enum GoogleFontsCacheMode {
+    /// Fetches from Google Fonts and stores the responses in the cache. The
+    /// cache is only read when a fetch fails.
+    #[default]
+    Populate,
+    /// Only reads from the cache and never accesses the network. Missing
+    /// entries fail the build.
+    UseOnly,
+    /// Like [GoogleFontsCacheMode::UseOnly], but also checks that the cached
+    /// contents match their content hash.
+    Verify,
+}
+
+impl FromStr for GoogleFontsCacheMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "populate" => GoogleFontsCacheMode::Populate,
+            "use-only" => GoogleFontsCacheMode::UseOnly,
+            "verify" => GoogleFontsCacheMode::Verify,
+            _ => bail!(
+                "Invalid {} `{}`, expected one of `populate`, `use-only` or `verify`",
+                CACHE_MODE_ENV,
+                s
+            ),
+        })
+    }
+}
+
+/// A persistent, content-addressed cache of Google Fonts responses.
+///
+/// Contents are stored in `objects/<hash>`, keyed by the hash of their bytes.
+/// Each fetched url has a record in `urls/<hash of url>.json` pointing to the
+/// object of its response. Records are written per url, so concurrent
+/// requests never write the same file with different contents.
+#[turbo_tasks::value(shared)]
+pub(super) struct GoogleFontsCache {
+    pub dir: RcStr,
+    pub mode: GoogleFontsCacheMode,
+    root: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub(super) struct OptionGoogleFontsCache(Option<Vc<GoogleFontsCache>>);
+
+#[derive(Serialize, Deserialize)]
+struct UrlRecord {
+    url: String,
+    object: String,
+}
+
+/// Reads the font cache configuration from the environment.
+#[turbo_tasks::function]
+pub(super) async fn google_fonts_cache() -> Result<Vc<OptionGoogleFontsCache>> {
+    let env = Vc::upcast::<Box<dyn ProcessEnv>>(CommandLineProcessEnv::new());
+    let Some(dir) = &*env.read(CACHE_DIR_ENV.into()).await? else {
+        if env.read(CACHE_MODE_ENV.into()).await?.is_some() {
+            bail!("{} requires {} to be set", CACHE_MODE_ENV, CACHE_DIR_ENV);
+        }
+        return Ok(Vc::cell(None));
+    };
+    let mode = match &*env.read(CACHE_MODE_ENV.into()).await? {
+        Some(mode) => mode.parse()?,
+        None => GoogleFontsCacheMode::default(),
+    };
+
+    let dir: RcStr = std::env::current_dir()
+        .context("Unable to resolve the font cache directory")?
+        .join(Path::new(dir.as_str()))
+        .to_str()
+        .context("The font cache directory must be valid UTF-8")?
+        .into();
+
+    Ok(Vc::cell(Some(GoogleFontsCache::new(dir, mode).cell())))
+}
+
+fn hash_hex(bytes: &[u8]) -> String {
+    format!("{:032x}", hash_xxh3_hash128(bytes))
+}
+
+fn url_record_path(url: &str) -> String {
+    format!("urls/{}.json", hash_hex(url.as_bytes()))
+}
+
+fn object_path(hash: &str) -> String {
+    format!("objects/{}", hash)
+}
+
+impl GoogleFontsCache {
+    /// Creates a cache in the absolute directory `dir`.
+    pub(super) fn new(dir: RcStr, mode: GoogleFontsCacheMode) -> Self {
+        let fs = DiskFileSystem::new("next-font-google-cache".into(), dir.clone(), vec![]);
+        GoogleFontsCache {
+            dir,
+            mode,
+            root: fs.root(),
+        }
+    }
+
+    /// Reads the cached response for `url`. Returns `None` when there is no
+    /// entry for it. In [GoogleFontsCacheMode::Verify] mode, fails when the
+    /// cached contents don't match their hash.
+    pub(super) async fn read(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        let record_path = self.root.join(url_record_path(url).into());
+        let FileContent::Content(record) = &*record_path.read().await? else {
+            return Ok(None);
+        };
+        let record: UrlRecord = parse_json_with_source_context(&record.content().to_str()?)
+            .with_context(|| format!("Invalid font cache record for {}", url))?;
+        if record.url != url {
+            bail!(
+                "The font cache record for {} belongs to {}. The font cache at {} is corrupted.",
+                url,
+                record.url,
+                self.dir
+            );
+        }
+
+        let object_path = self.root.join(object_path(&record.object).into());
+        let FileContent::Content(object) = &*object_path.read().await? else {
+            return Ok(None);
+        };
+        let bytes = object.content().to_bytes()?.into_owned();
+        if self.mode == GoogleFontsCacheMode::Verify {
+            let hash = hash_hex(&bytes);
+            if hash != record.object {
+                bail!(
+                    "The cached contents of {} have the hash {}, expected {}. The font cache at \
+                     {} is corrupted.",
+                    url,
+                    hash,
+                    record.object,
+                    self.dir
+                );
+            }
+        }
+        Ok(Some(bytes))
+    }
+
+    /// Stores `bytes` as the response for `url`.
+    pub(super) async fn write(&self, url: &str, bytes: &[u8]) -> Result<()> {
+        let hash = hash_hex(bytes);
+        let object_path = self.root.join(object_path(&hash).into());
+        object_path
+            .write(FileContent::Content(File::from(bytes)).cell())
+            .await?;
+
+        let record = serde_json::to_string_pretty(&UrlRecord {
+            url: url.to_string(),
+            object: hash,
+        })?;
+        let record_path = self.root.join(url_record_path(url).into());
+        record_path
+            .write(FileContent::Content(File::from(record)).cell())
+            .await?;
+        Ok(())
+    }
+
+    /// Reads the cached response for `url` like [GoogleFontsCache::read], but
+    /// emits an error when there is no entry for it. `resource` describes what
+    /// the url was needed for, e.g. a font family or subset.
+    pub(super) async fn read_or_emit_missing(
+        &self,
+        url: &str,
+        resource: &str,
+        path: Vc<FileSystemPath>,
+    ) -> Result<Option<Vec<u8>>> {
+        let bytes = self.read(url).await?;
+        if bytes.is_none() {
+            self.emit_missing(url, resource, path);
+        }
+        Ok(bytes)
+    }
+
+    fn emit_missing(&self, url: &str, resource: &str, path: Vc<FileSystemPath>) {
+        NextFontIssue {
+            path,
+            title: StyledString::Text(
+                format!("Missing {} in the Google Fonts cache", resource).into(),
+            )
+            .cell(),
+            description: StyledString::Text(
+                format!(
+                    "{} is not in the font cache at {}, and the cache is used in `{}` mode, which \
+                     doesn't access the network. Run the build with {}=populate and network \
+                     access to add it to the cache.",
+                    url,
+                    self.dir,
+                    match self.mode {
+                        GoogleFontsCacheMode::Populate => "populate",
+                        GoogleFontsCacheMode::UseOnly => "use-only",
+                        GoogleFontsCacheMode::Verify => "verify",
+                    },
+                    CACHE_MODE_ENV,
+                )
+                .into(),
+            )
+            .cell(),
+            severity: IssueSeverity::Error.cell(),
+        }
+        .cell()
+        .emit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use turbo_tasks::{RcStr, TurboTasks, Vc};
+    use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_core::issue::{Issue, IssueDescriptionExt, IssueSeverity};
+
+    use super::{hash_hex, object_path, url_record_path, GoogleFontsCache, GoogleFontsCacheMode};
+
+    const URL: &str = "https://fonts.googleapis.com/css2?family=Inter:wght@400&display=swap";
+    const STYLESHEET: &[u8] = b"@font-face { font-family: 'Inter'; }";
+
+    /// Runs `test` with a new font cache directory.
+    async fn run_with_cache_dir<T: Send + 'static>(
+        test: impl FnOnce(RcStr) -> T + Send + 'static,
+    ) -> Result<()>
+    where
+        T: std::future::Future<Output = Result<()>> + Send,
+    {
+        crate::register();
+        let dir = tempfile::tempdir()?;
+        let path: RcStr = dir.path().to_str().unwrap().into();
+        let tt = TurboTasks::new(MemoryBackend::new(usize::MAX));
+        tt.run_once(async move { test(path).await }).await?;
+        drop(dir);
+        Ok(())
+    }
+
+    #[turbo_tasks::function]
+    async fn read_or_emit_missing(
+        cache: Vc<GoogleFontsCache>,
+        path: Vc<FileSystemPath>,
+    ) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            cache
+                .await?
+                .read_or_emit_missing(URL, "the stylesheet of `Inter`", path)
+                .await?
+                .is_some(),
+        ))
+    }
+
+    #[test]
+    fn test_parses_cache_modes() -> Result<()> {
+        assert_eq!(
+            "populate".parse::<GoogleFontsCacheMode>()?,
+            GoogleFontsCacheMode::Populate
+        );
+        assert_eq!(
+            "use-only".parse::<GoogleFontsCacheMode>()?,
+            GoogleFontsCacheMode::UseOnly
+        );
+        assert_eq!(
+            "verify".parse::<GoogleFontsCacheMode>()?,
+            GoogleFontsCacheMode::Verify
+        );
+        assert!("offline".parse::<GoogleFontsCacheMode>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_paths_are_content_addressed() {
+        let url = "https://fonts.googleapis.com/css2?family=Inter:wght@400&display=swap";
+        assert_eq!(url_record_path(url), url_record_path(url));
+        assert_ne!(
+            url_record_path(url),
+            url_record_path("https://fonts.googleapis.com/css2?family=Roboto")
+        );
+        assert!(url_record_path(url).starts_with("urls/"));
+        assert!(object_path("abc").starts_with("objects/"));
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() -> Result<()> {
+        run_with_cache_dir(|dir| async move {
+            GoogleFontsCache::new(dir.clone(), GoogleFontsCacheMode::Populate)
+                .write(URL, STYLESHEET)
+                .await?;
+
+            for mode in [GoogleFontsCacheMode::UseOnly, GoogleFontsCacheMode::Verify] {
+                let cache = GoogleFontsCache::new(dir.clone(), mode);
+                assert_eq!(cache.read(URL).await?.as_deref(), Some(STYLESHEET));
+                assert_eq!(
+                    cache
+                        .read("https://fonts.googleapis.com/css2?family=Roboto")
+                        .await?,
+                    None
+                );
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_verify_detects_corrupted_objects() -> Result<()> {
+        run_with_cache_dir(|dir| async move {
+            GoogleFontsCache::new(dir.clone(), GoogleFontsCacheMode::Populate)
+                .write(URL, STYLESHEET)
+                .await?;
+            std::fs::write(
+                std::path::Path::new(dir.as_str()).join(object_path(&hash_hex(STYLESHEET))),
+                b"corrupted",
+            )?;
+
+            let cache = GoogleFontsCache::new(dir.clone(), GoogleFontsCacheMode::UseOnly);
+            assert_eq!(cache.read(URL).await?.as_deref(), Some(&b"corrupted"[..]));
+            let cache = GoogleFontsCache::new(dir, GoogleFontsCacheMode::Verify);
+            assert!(cache.read(URL).await.is_err());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_missing_entry_emits_issue() -> Result<()> {
+        run_with_cache_dir(|dir| async move {
+            let cache = GoogleFontsCache::new(dir.clone(), GoogleFontsCacheMode::UseOnly).cell();
+            let path = DiskFileSystem::new("project".into(), dir, vec![])
+                .root()
+                .join("app/layout.js".into());
+
+            let found = read_or_emit_missing(cache, path);
+            assert!(!*found.strongly_consistent().await?);
+
+            let issues = found.peek_issues_with_path().await?;
+            assert_eq!(issues.len(), 1);
+            let issue = issues.iter().next().unwrap();
+            assert_eq!(*issue.severity().await?, IssueSeverity::Error);
+            assert_eq!(
+                issue.title().await?.to_unstyled_string(),
+                "Missing the stylesheet of `Inter` in the Google Fonts cache"
+            );
+            let description = (*issue.description().await?).unwrap().await?;
+            let description = description.to_unstyled_string();
+            assert!(description.contains(URL), "{description}");
+            assert!(description.contains("`use-only` mode"), "{description}");
+            Ok(())
+        })
+        .await
+    }
+}
@@ -32,6 +32,7 @@ use turbopack_node::{
 };
 
 use self::{
+    cache::{google_fonts_cache, GoogleFontsCacheMode},
     font_fallback::get_font_fallback,
     options::{options_from_request, FontDataEntry, FontWeights, NextFontGoogleOptions},
     stylesheet::build_stylesheet,
@@ -48,6 +49,7 @@ use crate::{
     embed_js::next_js_file_path, next_app::metadata::split_extension, util::load_next_js_templateon,
 };
 
+pub mod cache;
 pub mod font_fallback;
 pub mod options;
 pub mod request;
@@ -221,7 +223,7 @@ impl NextFontGoogleCssModuleReplacer {
         let stylesheet_str = mocked_responses_path
             .as_ref()
             .map_or_else(
-                || fetch_real_stylesheet(stylesheet_url, css_virtual_path).boxed(),
+                || fetch_real_stylesheet(stylesheet_url, css_virtual_path, options).boxed(),
                 |p| get_mock_stylesheet(stylesheet_url, p, self.execution_context).boxed(),
             )
             .await?;
@@ -310,6 +312,8 @@ struct NextFontGoogleFontFileOptions {
     pub url: String,
     pub preload: bool,
     pub has_size_adjust: bool,
+    pub font_family: RcStr,
+    pub subset: Option<RcStr>,
 }
 
 #[turbo_tasks::value(shared)]
@@ -356,6 +360,8 @@ impl ImportMappingReplacement for NextFontGoogleFontFileReplacer {
             url,
             preload,
             has_size_adjust: size_adjust,
+            font_family,
+            subset,
         } = font_file_options_from_query_map(*query_vc).await?;
 
         let (filename, ext) = split_extension(&url);
@@ -376,7 +382,12 @@ impl ImportMappingReplacement for NextFontGoogleFontFileReplacer {
 
         // doesn't seem ideal to download the font into a string, but probably doesn't
         // really matter either.
-        let Some(font) = fetch_from_google_fonts(Vc::cell(url.into()), font_virtual_path).await?
+        let resource = match subset {
+            Some(subset) => format!("the `{}` subset of `{}`", subset, font_family),
+            None => format!("a font file of `{}`", font_family),
+        };
+        let Some(font) =
+            fetch_from_google_fonts(Vc::cell(url.into()), font_virtual_path, &resource).await?
         else {
             return Ok(
                 ImportMapResult::Result(ResolveResult::unresolvable().resolved_cell()).cell(),
@@ -437,11 +448,18 @@ async fn update_google_stylesheet(
 
     let has_size_adjust = *has_size_adjust.await?;
 
-    for FontFile { font_url, preload } in font_files {
+    for FontFile {
+        font_url,
+        preload,
+        subset,
+    } in font_files
+    {
         let query = NextFontGoogleFontFileOptions {
             url: font_url.clone(),
             preload,
             has_size_adjust,
+            font_family: options.font_family.clone(),
+            subset,
         };
         let query_str = qstring::QString::from(serde_json::to_string(&query)?.as_str());
 
@@ -458,16 +476,19 @@ async fn update_google_stylesheet(
 struct FontFile {
     font_url: String,
     preload: bool,
+    /// The subset of the font file, from the comment preceding its
+    /// `@font-face` rule.
+    subset: Option<RcStr>,
 }
 
 // https://github.com/vercel/next.js/blob/b95e45a5112e9f65e939eac9445ef550db072ea7/packages/font/src/google/find-font-files-in-css.ts
 fn find_font_files_in_css(css: &str, subsets_to_preload: &[RcStr]) -> Vec<FontFile> {
     let mut font_files: Vec<FontFile> = Vec::new();
-    let mut current_subset = "";
+    let mut current_subset = None;
 
     for line in css.lines() {
         if let Some((_, new_subset)) = lazy_regex::regex_captures!(r#"/\* (.+?) \*/"#, line) {
-            current_subset = new_subset;
+            current_subset = Some(new_subset);
             continue;
         }
 
@@ -481,9 +502,9 @@ fn find_font_files_in_css(css: &str, subsets_to_preload: &[RcStr]) -> Vec<FontFi
 
         font_files.push(FontFile {
             font_url: font_url.to_string(),
-            preload: subsets_to_preload
-                .iter()
-                .any(|subset| subset == current_subset),
+            preload: current_subset
+                .is_some_and(|current| subsets_to_preload.iter().any(|subset| subset == current)),
+            subset: current_subset.map(RcStr::from),
         });
     }
 
@@ -608,16 +629,34 @@ async fn font_file_options_from_query_map(
 async fn fetch_real_stylesheet(
     stylesheet_url: Vc<RcStr>,
     css_virtual_path: Vc<FileSystemPath>,
+    options: Vc<NextFontGoogleOptions>,
 ) -> Result<Option<Vc<RcStr>>> {
-    let body = fetch_from_google_fonts(stylesheet_url, css_virtual_path).await?;
+    let resource = format!("the stylesheet of `{}`", options.await?.font_family);
+    let body = fetch_from_google_fonts(stylesheet_url, css_virtual_path, &resource).await?;
 
     Ok(body.map(|body| body.to_string()))
 }
 
+/// Fetches `url` from Google Fonts, or reads it from the font cache when one is
+/// configured. `resource` describes what is fetched for error messages.
 async fn fetch_from_google_fonts(
     url: Vc<RcStr>,
     virtual_path: Vc<FileSystemPath>,
+    resource: &str,
 ) -> Result<Option<Vc<HttpResponseBody>>> {
+    let cache = match *google_fonts_cache().await? {
+        Some(cache) => Some(cache.await?),
+        None => None,
+    };
+    if let Some(cache) = cache.as_ref() {
+        if cache.mode != GoogleFontsCacheMode::Populate {
+            return Ok(cache
+                .read_or_emit_missing(&url.await?, resource, virtual_path)
+                .await?
+                .map(|bytes| HttpResponseBody(bytes).cell()));
+        }
+    }
+
     let result = fetch(
         url,
         Vc::cell(Some(USER_AGENT_FOR_GOOGLE_FONTS.into())),
@@ -626,8 +665,22 @@ async fn fetch_from_google_fonts(
     .await?;
 
     Ok(match &*result {
-        Ok(r) => Some(r.await?.body),
+        Ok(r) => {
+            let body = r.await?.body;
+            if let Some(cache) = cache.as_ref() {
+                cache.write(&url.await?, &body.await?.0).await?;
+            }
+            Some(body)
+        }
         Err(err) => {
+            // Builds populating the cache can still fall back to previously
+            // cached responses when offline.
+            if let Some(cache) = cache.as_ref() {
+                if let Some(bytes) = cache.read(&url.await?).await? {
+                    return Ok(Some(HttpResponseBody(bytes).cell()));
+                }
+            }
+
             // Inform the user of the failure to retreive the stylesheet / font, but don't
             // propagate this error. We don't want e.g. offline connections to prevent page
             // renders during development. During production builds, however, this error
@@ -131,6 +131,28 @@ pub struct MiddlewaresManifestV2 {
     pub functions: HashMap<RcStr, EdgeFunctionDefinition>,
 }
 
+#[derive(Serialize, Debug)]
+pub struct FunctionsConfigManifest {
+    pub version: u32,
+    pub functions: HashMap<RcStr, FunctionConfig>,
+}
+
+impl Default for FunctionsConfigManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            functions: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u32>,
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ReactLoadableManifest {
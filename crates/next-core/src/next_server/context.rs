@@ -60,7 +60,7 @@ use crate::{
             styled_jsx::get_styled_jsx_transform_rule,
             swc_ecma_transform_plugins::get_swc_ecma_transform_plugin_rule,
         },
-        webpack_rules::webpack_loader_options,
+        webpack_rules::{sass::sass_transform_options, webpack_loader_options},
     },
     transform_options::{
         get_decorators_transform_options, get_jsx_transform_options,
@@ -473,6 +473,11 @@ pub async fn get_server_module_options_context(
         .tree_shaking_mode_for_foreign_code(next_mode.is_development())
         .await?;
     let use_swc_css = *next_config.use_swc_css().await?;
+    let enable_sass = if *next_config.use_native_sass().await? {
+        Some(sass_transform_options(project_path, next_config.sass_config()))
+    } else {
+        None
+    };
     let versions = RuntimeVersions(Default::default()).cell();
 
     // ModuleOptionsContext related options
@@ -520,6 +525,7 @@ pub async fn get_server_module_options_context(
         execution_context: Some(execution_context),
         css: CssOptionsContext {
             use_swc_css,
+            enable_sass,
             ..Default::default()
         },
         tree_shaking_mode: tree_shaking_mode_for_user_code,
@@ -17,7 +17,11 @@ pub async fn webpack_loader_options(
     conditions: Vec<RcStr>,
 ) -> Result<Option<Vc<WebpackLoadersOptions>>> {
     let rules = *next_config.webpack_rules(conditions).await?;
-    let rules = *maybe_add_sass_loader(next_config.sass_config(), rules).await?;
+    let rules = if *next_config.use_native_sass().await? {
+        rules
+    } else {
+        *maybe_add_sass_loader(next_config.sass_config(), rules).await?
+    };
     let rules = if foreign {
         rules
     } else {
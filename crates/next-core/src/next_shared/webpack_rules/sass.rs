@@ -1,9 +1,11 @@
-use std::mem::take;
+use std::{mem::take, path::Path};
 
 use anyhow::{bail, Result};
 use serde_json::Value as JsonValue;
 use turbo_tasks::Vc;
+use turbo_tasks_fs::{util::sys_to_unix, DiskFileSystem, FileSystemPath};
 use turbopack::module_options::{LoaderRuleItem, OptionWebpackRules, WebpackRules};
+use turbopack_css::sass::SassTransformOptions;
 use turbopack_node::transforms::webpack::WebpackLoaderItem;
 
 #[turbo_tasks::function]
@@ -85,3 +87,60 @@ pub async fn maybe_add_sass_loader(
 
     Ok(Vc::cell(Some(Vc::cell(rules))))
 }
+
+/// Converts `sassOptions` into options for the native Sass transform.
+#[turbo_tasks::function]
+pub async fn sass_transform_options(
+    project_path: Vc<FileSystemPath>,
+    sass_options: Vc<JsonValue>,
+) -> Result<Vc<SassTransformOptions>> {
+    let sass_options = sass_options.await?;
+    let Some(sass_options) = sass_options.as_object() else {
+        bail!("sass_options must be an object");
+    };
+
+    let include_paths = sass_options
+        .get("includePaths")
+        .or(sass_options.get("loadPaths"))
+        .and_then(|paths| paths.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|path| path.as_str());
+    let disk_fs = Vc::try_resolve_downcast_type::<DiskFileSystem>(project_path.fs()).await?;
+    let mut resolved_include_paths = Vec::new();
+    for include_path in include_paths {
+        // Include paths are usually absolute, e.g. `path.join(__dirname, "styles")`.
+        let path = if Path::new(include_path).is_absolute() {
+            let Some(disk_fs) = disk_fs else {
+                continue;
+            };
+            let root = disk_fs.await?.root.clone();
+            let Ok(relative) = Path::new(include_path).strip_prefix(&*root) else {
+                continue;
+            };
+            project_path
+                .root()
+                .try_join(sys_to_unix(&relative.to_string_lossy()).into())
+                .await?
+        } else {
+            project_path.try_join(include_path.into()).await?
+        };
+        if let Some(path) = *path {
+            resolved_include_paths.push(path);
+        }
+    }
+
+    // additionalData is a loader option but Next.js has it under `sassOptions` in
+    // `next.config.js`
+    let additional_data = sass_options
+        .get("prependData")
+        .or(sass_options.get("additionalData"))
+        .and_then(|data| data.as_str())
+        .map(|data| data.into());
+
+    Ok(SassTransformOptions {
+        include_paths: resolved_include_paths,
+        additional_data,
+    }
+    .cell())
+}
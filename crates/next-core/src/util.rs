@@ -21,7 +21,7 @@ use turbopack_core::{
     virtual_source::VirtualSource,
 };
 use turbopack_ecmascript::{
-    analyzer::{ConstantValue, JsValue, ObjectPart},
+    analyzer::{ConstantNumber, ConstantValue, JsValue, ObjectPart},
     parse::ParseResult,
     utils::StringifyJs,
     EcmascriptParsable,
@@ -181,6 +181,9 @@ pub struct NextSourceConfig {
     pub matcher: Option<Vec<MiddlewareMatcherKind>>,
 
     pub regions: Option<Vec<RcStr>>,
+
+    /// The maximum duration in seconds the function is allowed to run.
+    pub max_duration: Option<u32>,
 }
 
 #[turbo_tasks::value_impl]
@@ -373,6 +376,9 @@ fn parse_route_matcher_from_js_value(
 
 #[turbo_tasks::function]
 pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<NextSourceConfig>> {
+    let mut config = None;
+    let mut max_duration = None;
+
     if let Some(ecmascript_asset) =
         Vc::try_resolve_sidecast::<Box<dyn EcmascriptParsable>>(module).await?
     {
@@ -390,91 +396,131 @@ pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<
                     .and_then(|export_decl| export_decl.decl.as_var())
                 {
                     for decl in &decl.decls {
-                        let decl_ident = decl.name.as_ident();
-
-                        // Check if there is exported config object `export const config = {...}`
-                        // https://nextjs.org/docs/app/building-your-application/routing/middleware#matcher
-                        if decl_ident
-                            .map(|ident| &*ident.sym == "config")
-                            .unwrap_or_default()
-                        {
-                            if let Some(init) = decl.init.as_ref() {
-                                return GLOBALS.set(globals, || {
-                                    let value = eval_context.eval(init);
-                                    Ok(parse_config_from_js_value(module, &value).cell())
-                                });
-                            } else {
-                                NextSourceConfigParsingIssue {
+                        let Some(decl_ident) = decl.name.as_ident() else {
+                            continue;
+                        };
+
+                        match &*decl_ident.sym {
+                            // Check if there is exported config object `export const config =
+                            // {...}` https://nextjs.org/docs/app/building-your-application/routing/middleware#matcher
+                            "config" if config.is_none() => {
+                                if let Some(init) = decl.init.as_ref() {
+                                    config = Some(GLOBALS.set(globals, || {
+                                        let value = eval_context.eval(init);
+                                        parse_config_from_js_value(module, &value)
+                                    }));
+                                } else {
+                                    NextSourceConfigParsingIssue {
+                                        ident: module.ident(),
+                                        detail: StyledString::Text(
+                                            "The exported config object must contain an variable \
+                                             initializer."
+                                                .into(),
+                                        )
+                                        .cell(),
+                                    }
+                                    .cell()
+                                    .emit()
+                                }
+                            }
+                            // Or, check if there is segment runtime option
+                            // https://nextjs.org/docs/app/building-your-application/rendering/edge-and-nodejs-runtimes#segment-runtime-Option
+                            "runtime" if config.is_none() => {
+                                let runtime_value_issue = NextSourceConfigParsingIssue {
                                     ident: module.ident(),
                                     detail: StyledString::Text(
-                                        "The exported config object must contain an variable \
-                                         initializer."
+                                        "The runtime property must be either \"nodejs\" or \
+                                         \"edge\"."
                                             .into(),
                                     )
                                     .cell(),
                                 }
-                                .cell()
-                                .emit()
-                            }
-                        }
-                        // Or, check if there is segment runtime option
-                        // https://nextjs.org/docs/app/building-your-application/rendering/edge-and-nodejs-runtimes#segment-runtime-Option
-                        else if decl_ident
-                            .map(|ident| &*ident.sym == "runtime")
-                            .unwrap_or_default()
-                        {
-                            let runtime_value_issue = NextSourceConfigParsingIssue {
-                                ident: module.ident(),
-                                detail: StyledString::Text(
-                                    "The runtime property must be either \"nodejs\" or \"edge\"."
-                                        .into(),
-                                )
-                                .cell(),
-                            }
-                            .cell();
-                            if let Some(init) = decl.init.as_ref() {
-                                // skipping eval and directly read the expr's value, as we know it
-                                // should be a const string
-                                if let Expr::Lit(Lit::Str(str_value)) = &**init {
-                                    let mut config = NextSourceConfig::default();
-
-                                    let runtime = str_value.value.to_string();
-                                    match runtime.as_str() {
-                                        "edge" | "experimental-edge" => {
-                                            config.runtime = NextRuntime::Edge;
-                                        }
-                                        "nodejs" => {
-                                            config.runtime = NextRuntime::NodeJs;
-                                        }
-                                        _ => {
-                                            runtime_value_issue.emit();
+                                .cell();
+                                if let Some(init) = decl.init.as_ref() {
+                                    // skipping eval and directly read the expr's value, as we
+                                    // know it should be a const string
+                                    if let Expr::Lit(Lit::Str(str_value)) = &**init {
+                                        let mut runtime_config = NextSourceConfig::default();
+
+                                        let runtime = str_value.value.to_string();
+                                        match runtime.as_str() {
+                                            "edge" | "experimental-edge" => {
+                                                runtime_config.runtime = NextRuntime::Edge;
+                                            }
+                                            "nodejs" => {
+                                                runtime_config.runtime = NextRuntime::NodeJs;
+                                            }
+                                            _ => {
+                                                runtime_value_issue.emit();
+                                            }
                                         }
-                                    }
 
-                                    return Ok(config.cell());
+                                        config = Some(runtime_config);
+                                    } else {
+                                        runtime_value_issue.emit();
+                                    }
                                 } else {
-                                    runtime_value_issue.emit();
+                                    NextSourceConfigParsingIssue {
+                                        ident: module.ident(),
+                                        detail: StyledString::Text(
+                                            "The exported segment runtime option must contain an \
+                                             variable initializer."
+                                                .into(),
+                                        )
+                                        .cell(),
+                                    }
+                                    .cell()
+                                    .emit()
                                 }
-                            } else {
-                                NextSourceConfigParsingIssue {
-                                    ident: module.ident(),
-                                    detail: StyledString::Text(
-                                        "The exported segment runtime option must contain an \
-                                         variable initializer."
-                                            .into(),
-                                    )
-                                    .cell(),
+                            }
+                            // `export const maxDuration = 60`, which takes precedence over
+                            // `config.maxDuration`.
+                            "maxDuration" => {
+                                if let Some(init) = decl.init.as_ref() {
+                                    max_duration = GLOBALS.set(globals, || {
+                                        let value = eval_context.eval(init);
+                                        parse_max_duration_from_js_value(module.ident(), &value)
+                                    });
                                 }
-                                .cell()
-                                .emit()
                             }
+                            _ => {}
                         }
                     }
                 }
             }
         }
     }
-    Ok(Default::default())
+
+    let mut config = config.unwrap_or_default();
+    if max_duration.is_some() {
+        config.max_duration = max_duration;
+    }
+    Ok(config.cell())
+}
+
+/// Parses a `maxDuration` in seconds, which needs to be a static
+/// non-negative integer. Returns `None` for any other value.
+pub fn parse_max_duration(value: &JsValue) -> Option<u32> {
+    match value {
+        JsValue::Constant(ConstantValue::Num(ConstantNumber(val)))
+            if *val >= 0.0 && val.fract() == 0.0 && *val <= u32::MAX as f64 =>
+        {
+            Some(*val as u32)
+        }
+        _ => None,
+    }
+}
+
+fn parse_max_duration_from_js_value(ident: Vc<AssetIdent>, value: &JsValue) -> Option<u32> {
+    let max_duration = parse_max_duration(value);
+    if max_duration.is_none() {
+        emit_invalid_config_warning(
+            ident,
+            "The maxDuration property must be a static non-negative integer.",
+            value,
+        );
+    }
+    max_duration
 }
 
 fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> NextSourceConfig {
@@ -519,6 +565,10 @@ fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> N
                                     );
                                 }
                             }
+                            "maxDuration" => {
+                                config.max_duration =
+                                    parse_max_duration_from_js_value(module.ident(), value);
+                            }
                             "matcher" => {
                                 config.matcher =
                                     parse_route_matcher_from_js_value(module.ident(), value);
@@ -875,3 +925,29 @@ pub async fn load_next_js_templateon<T: DeserializeOwned>(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use turbopack_ecmascript::analyzer::JsValue;
+
+    use super::parse_max_duration;
+
+    #[test]
+    fn parses_max_duration() {
+        assert_eq!(parse_max_duration(&JsValue::from(60.0)), Some(60));
+        assert_eq!(parse_max_duration(&JsValue::from(0.0)), Some(0));
+        assert_eq!(
+            parse_max_duration(&JsValue::from(u32::MAX as f64)),
+            Some(u32::MAX)
+        );
+
+        assert_eq!(parse_max_duration(&JsValue::from(-1.0)), None);
+        assert_eq!(parse_max_duration(&JsValue::from(1.5)), None);
+        assert_eq!(
+            parse_max_duration(&JsValue::from(u32::MAX as f64 + 1.0)),
+            None
+        );
+        assert_eq!(parse_max_duration(&JsValue::from(f64::NAN)), None);
+        assert_eq!(parse_max_duration(&JsValue::from("60")), None);
+    }
+}
@@ -0,0 +1,28 @@
+use js_sys::JsString;
+use serde::Serialize;
+use turbopack_css::standalone::{process_standalone_css, StandaloneCssTransformOptions};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::convert_err;
+
+/// Processes css the same way as turbopack builds do.
+#[wasm_bindgen(js_name = "turbopackCssTransformSync")]
+pub fn turbopack_css_transform_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
+    console_error_panic_hook::set_once();
+
+    let input: String = s.into();
+    let options: StandaloneCssTransformOptions = serde_wasm_bindgen::from_value(opts)?;
+    let result = process_standalone_css(&input, &options.into()).map_err(convert_err)?;
+
+    // The exports are maps, which must become plain objects like in the napi
+    // bindings.
+    Ok(result.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+#[wasm_bindgen(js_name = "turbopackCssTransform")]
+pub fn turbopack_css_transform(s: JsString, opts: JsValue) -> js_sys::Promise {
+    // TODO: This'll be properly scheduled once wasm have standard backed thread
+    // support.
+    future_to_promise(async { turbopack_css_transform_sync(s, opts) })
+}
@@ -18,6 +18,7 @@ use swc_core::{
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::future_to_promise;
 
+pub mod css;
 pub mod mdx;
 
 fn convert_err(err: Error) -> JsValue {
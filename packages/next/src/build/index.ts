@@ -194,7 +194,10 @@ import {
   isRelevantWarning,
   isPersistentCachingEnabled,
 } from '../server/dev/turbopack-utils'
-import { TurbopackManifestLoader } from '../server/dev/turbopack/manifest-loader'
+import {
+  type FunctionsConfigManifest,
+  TurbopackManifestLoader,
+} from '../server/dev/turbopack/manifest-loader'
 import type { Entrypoints } from '../server/dev/turbopack/types'
 import { buildCustomRoute } from '../lib/build-custom-route'
 import { createProgress } from './progress'
@@ -490,16 +493,6 @@ async function writeClientSsgManifest(
   )
 }
 
-interface FunctionsConfigManifest {
-  version: number
-  functions: Record<
-    string,
-    {
-      maxDuration?: number | undefined
-    }
-  >
-}
-
 async function writeFunctionsConfigManifest(
   distDir: string,
   manifest: FunctionsConfigManifest
@@ -1937,10 +1930,16 @@ export default async function build(
       const analysisBegin = process.hrtime()
       const staticCheckSpan = nextBuildSpan.traceChild('static-check')
 
-      const functionsConfigManifest: FunctionsConfigManifest = {
-        version: 1,
-        functions: {},
-      }
+      // Turbopack already wrote the segment configs of every route it
+      // built, which the static analysis below adds to.
+      const functionsConfigManifest: FunctionsConfigManifest = turboNextBuild
+        ? await readManifest<FunctionsConfigManifest>(
+            path.join(distDir, SERVER_DIRECTORY, FUNCTIONS_CONFIG_MANIFEST)
+          )
+        : {
+            version: 1,
+            functions: {},
+          }
 
       const {
         customAppGetInitialProps,
@@ -38,6 +38,25 @@ export interface TransformOutput {
   map?: string
   output?: string
 }
+export interface TurbopackCssTransformOutput {
+  code: string
+  map?: string
+  /** The class names of a CSS module, keyed by their local name. */
+  exports?: any
+  /** Values exported with `@value` and `:export`. */
+  icssExports: any
+  warnings: Array<string>
+}
+/** Processes css the same way as turbopack builds do. */
+export function turbopackCssTransform(
+  input: string,
+  options: Buffer,
+  signal?: AbortSignal | undefined | null
+): Promise<TurbopackCssTransformOutput>
+export function turbopackCssTransformSync(
+  input: string,
+  options: Buffer
+): TurbopackCssTransformOutput
 export function mdxCompile(
   value: string,
   option: Buffer,
@@ -265,6 +284,23 @@ export function projectUpdateInfoSubscribe(
   aggregationMs: number,
   func: (...args: any[]) => any
 ): void
+/**
+ * Enables or disables recording of invalidation causes. Enabling it discards everything that was
+ * recorded before. The recorded causes can be inspected with `projectExplainInvalidations` and
+ * `rootTaskExplainInvalidations`.
+ */
+export function projectSetInvalidationTracing(
+  project: { __napiType: 'Project' },
+  enabled: boolean
+): void
+/**
+ * Explains why tasks were recomputed since invalidation tracing was enabled or since the last
+ * call with `clear` set.
+ */
+export function projectExplainInvalidations(
+  project: { __napiType: 'Project' },
+  clear: boolean
+): NapiInvalidationExplanation
 export interface StackFrame {
   isServer: boolean
   isInternal?: boolean
@@ -287,6 +323,25 @@ export function projectGetSourceMap(
 ): Promise<string | null>
 /** Runs exit handlers for the project registered using the [`ExitHandler`] API. */
 export function projectOnExit(project: { __napiType: 'Project' }): Promise<void>
+export interface NapiInvalidationTree {
+  label: string
+  count: number
+  children: Array<NapiInvalidationTree>
+  repeated: boolean
+}
+export interface NapiInvalidationExplanation {
+  /** The causal chains, starting with the external invalidation reasons. */
+  roots: Array<NapiInvalidationTree>
+  /** The causal chains printed as a tree. */
+  formatted: string
+}
+/**
+ * Explains why the root task was recomputed since invalidation tracing was enabled (see
+ * `projectSetInvalidationTracing`), e.g. `file changed -> read -> parse -> references -> root`.
+ */
+export function rootTaskExplainInvalidations(rootTask: {
+  __napiType: 'RootTask'
+}): NapiInvalidationExplanation
 export function rootTaskDispose(rootTask: { __napiType: 'RootTask' }): void
 export interface NapiIssue {
   severity: string
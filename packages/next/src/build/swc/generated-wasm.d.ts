@@ -51,3 +51,15 @@ export function mdxCompileSync(value: string, opts: any): any
  * @returns {Promise<any>}
  */
 export function mdxCompile(value: string, opts: any): Promise<any>
+/**
+ * @param {string} s
+ * @param {any} opts
+ * @returns {any}
+ */
+export function turbopackCssTransformSync(s: string, opts: any): any
+/**
+ * @param {string} s
+ * @param {any} opts
+ * @returns {Promise<any>}
+ */
+export function turbopackCssTransform(s: string, opts: any): Promise<any>
@@ -1024,6 +1024,20 @@ async function loadWasm(importPath = '') {
               )
             },
           },
+          turbopack: {
+            transform(src: string, options: any) {
+              return bindings.turbopackCssTransform(
+                src.toString(),
+                options ?? {}
+              )
+            },
+            transformSync(src: string, options: any) {
+              return bindings.turbopackCssTransformSync(
+                src.toString(),
+                options ?? {}
+              )
+            },
+          },
         },
         isWasm: true,
         transform(src: string, options: any) {
@@ -1246,6 +1260,17 @@ function loadNative(importPath?: string) {
             )
           },
         },
+        turbopack: {
+          transform(src: string, options: any) {
+            return bindings.turbopackCssTransform(src, toBuffer(options ?? {}))
+          },
+          transformSync(src: string, options: any) {
+            return bindings.turbopackCssTransformSync(
+              src,
+              toBuffer(options ?? {})
+            )
+          },
+        },
       },
     }
     return nativeBindings
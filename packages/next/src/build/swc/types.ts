@@ -34,6 +34,10 @@ export interface Binding {
       transform(transformOptions: any): Promise<any>
       transformStyleAttr(transformAttrOptions: any): Promise<any>
     }
+    turbopack: {
+      transform(src: string, options: any): Promise<any>
+      transformSync(src: string, options: any): any
+    }
   }
 }
 
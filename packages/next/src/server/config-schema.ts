@@ -7,6 +7,8 @@ import type zod from 'next/dist/compiled/zod'
 import type { SizeLimit } from '../types'
 import type {
   ExportPathMap,
+  TurboIssueRule,
+  TurboIssueSeverity,
   TurboLoaderItem,
   TurboRuleConfigItem,
   TurboRuleConfigItemOptions,
@@ -126,6 +128,28 @@ const zTurboRuleConfigItem: zod.ZodType<TurboRuleConfigItem> = z.union([
 const zTurboRuleConfigItemOrShortcut: zod.ZodType<TurboRuleConfigItemOrShortcut> =
   z.union([z.array(zTurboLoaderItem), zTurboRuleConfigItem])
 
+const zTurboIssueSeverity: zod.ZodType<TurboIssueSeverity> = z.enum([
+  'bug',
+  'fatal',
+  'error',
+  'warning',
+  'hint',
+  'note',
+  'suggestion',
+  'info',
+])
+
+const zTurboIssueRule: zod.ZodType<TurboIssueRule> = z.object({
+  stage: z.string().optional(),
+  title: z.string().optional(),
+  path: z.string().optional(),
+  severity: zTurboIssueSeverity.optional(),
+  action: z.union([
+    z.literal('ignore'),
+    z.object({ severity: zTurboIssueSeverity }),
+  ]),
+})
+
 export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
   z.strictObject({
     amp: z
@@ -405,6 +429,8 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
               .optional(),
             resolveExtensions: z.array(z.string()).optional(),
             useSwcCss: z.boolean().optional(),
+            useNativeSass: z.boolean().optional(),
+            issueRules: z.array(zTurboIssueRule).optional(),
             treeShaking: z.boolean().optional(),
             persistentCaching: z
               .union([z.number(), z.literal(false)])
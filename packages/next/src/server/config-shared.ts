@@ -113,6 +113,24 @@ export type TurboRuleConfigItem =
   | { [condition: string]: TurboRuleConfigItem }
   | false
 
+export type TurboIssueSeverity =
+  | 'bug'
+  | 'fatal'
+  | 'error'
+  | 'warning'
+  | 'hint'
+  | 'note'
+  | 'suggestion'
+  | 'info'
+
+export type TurboIssueRule = {
+  stage?: string
+  title?: string
+  path?: string
+  severity?: TurboIssueSeverity
+  action: 'ignore' | { severity: TurboIssueSeverity }
+}
+
 export interface ExperimentalTurboOptions {
   /**
    * (`next --turbopack` only) A mapping of aliased imports to modules to load in their place.
@@ -150,6 +168,19 @@ export interface ExperimentalTurboOptions {
    */
   useSwcCss?: boolean
 
+  /**
+   * Compile Sass natively instead of through sass-loader for Turbopack
+   */
+  useNativeSass?: boolean
+
+  /**
+   * Rules to ignore Turbopack issues or change their severity. The first
+   * matching rule applies. `title` is a regular expression and `path` a glob
+   * relative to the project root. Files outside of the project start with
+   * `../`.
+   */
+  issueRules?: TurboIssueRule[]
+
   /**
    * A target memory limit for turbo, in bytes.
    */
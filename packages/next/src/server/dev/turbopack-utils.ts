@@ -437,6 +437,7 @@ export async function handleRouteType({
 
         await manifestLoader.loadBuildManifest(page)
         await manifestLoader.loadPagesManifest(page)
+        await manifestLoader.loadFunctionsConfigManifest(page, 'pages')
         if (type === 'edge') {
           await manifestLoader.loadMiddlewareManifest(page, 'pages')
         } else {
@@ -535,6 +536,7 @@ export async function handleRouteType({
       const type = writtenEndpoint.type
 
       await manifestLoader.loadPagesManifest(page)
+      await manifestLoader.loadFunctionsConfigManifest(page, 'pages')
       if (type === 'edge') {
         await manifestLoader.loadMiddlewareManifest(page, 'pages')
       } else {
@@ -596,6 +598,7 @@ export async function handleRouteType({
       await manifestLoader.loadAppBuildManifest(page)
       await manifestLoader.loadBuildManifest(page, 'app')
       await manifestLoader.loadAppPathsManifest(page)
+      await manifestLoader.loadFunctionsConfigManifest(page, 'app')
       await manifestLoader.loadActionManifest(page)
       await manifestLoader.loadLoadableManifest(page, 'app')
       await manifestLoader.loadFontManifest(page, 'app')
@@ -623,6 +626,7 @@ export async function handleRouteType({
       const type = writtenEndpoint.type
 
       await manifestLoader.loadAppPathsManifest(page)
+      await manifestLoader.loadFunctionsConfigManifest(page, 'app')
 
       if (type === 'edge') {
         await manifestLoader.loadMiddlewareManifest(page, 'app')
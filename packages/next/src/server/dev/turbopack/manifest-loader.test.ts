@@ -0,0 +1,85 @@
+import { mkdir, mkdtemp, readFile, writeFile } from 'fs/promises'
+import { tmpdir } from 'os'
+import { dirname, join } from 'path'
+import { TurbopackManifestLoader } from './manifest-loader'
+import { getEntryKey } from './entry-key'
+
+async function writePartialManifest(
+  distDir: string,
+  path: string,
+  functions: Record<string, { maxDuration?: number }>
+) {
+  const manifestPath = join(distDir, 'server', path)
+  await mkdir(dirname(manifestPath), { recursive: true })
+  await writeFile(manifestPath, JSON.stringify({ version: 1, functions }))
+}
+
+describe('TurbopackManifestLoader', () => {
+  it('merges the functions config manifests of all routes', async () => {
+    const distDir = await mkdtemp(join(tmpdir(), 'manifest-loader'))
+    await mkdir(join(distDir, 'static', 'build-id'), { recursive: true })
+
+    await writePartialManifest(
+      distDir,
+      'app/dashboard/page/functions-config-manifest.json',
+      { '/dashboard/page': { maxDuration: 30 } }
+    )
+    await writePartialManifest(
+      distDir,
+      'app/api/hello/route/functions-config-manifest.json',
+      {}
+    )
+    await writePartialManifest(
+      distDir,
+      'pages/api/users/functions-config-manifest.json',
+      { '/api/users': { maxDuration: 60 } }
+    )
+    await writePartialManifest(
+      distDir,
+      'pages/about/functions-config-manifest.json',
+      { '/about': { maxDuration: 10 } }
+    )
+
+    const loader = new TurbopackManifestLoader({
+      distDir,
+      buildId: 'build-id',
+      encryptionKey: '',
+    })
+    await loader.loadFunctionsConfigManifest('/dashboard/page', 'app')
+    await loader.loadFunctionsConfigManifest('/api/hello/route', 'app')
+    await loader.loadFunctionsConfigManifest('/api/users', 'pages')
+    await loader.loadFunctionsConfigManifest('/about', 'pages')
+    // Removed routes are no longer listed.
+    loader.delete(getEntryKey('pages', 'server', '/about'))
+
+    await loader.writeManifests({
+      devRewrites: undefined,
+      productionRewrites: undefined,
+      entrypoints: {
+        global: {
+          app: undefined,
+          document: undefined,
+          error: undefined,
+          middleware: undefined,
+          instrumentation: undefined,
+        },
+        page: new Map(),
+        app: new Map(),
+      },
+    })
+
+    const manifest = JSON.parse(
+      await readFile(
+        join(distDir, 'server', 'functions-config-manifest.json'),
+        'utf8'
+      )
+    )
+    expect(manifest).toEqual({
+      version: 1,
+      functions: {
+        '/dashboard/page': { maxDuration: 30 },
+        '/api/users': { maxDuration: 60 },
+      },
+    })
+  })
+})
@@ -14,6 +14,7 @@ import {
   APP_BUILD_MANIFEST,
   APP_PATHS_MANIFEST,
   BUILD_MANIFEST,
+  FUNCTIONS_CONFIG_MANIFEST,
   INTERCEPTION_ROUTE_REWRITE_MANIFEST,
   MIDDLEWARE_BUILD_MANIFEST,
   MIDDLEWARE_MANIFEST,
@@ -46,6 +47,16 @@ import { existsSync } from 'fs'
 import { addMetadataIdToRoute, addRouteSuffix, removeRouteSuffix } from '../turbopack-utils'
 import { tryToParsePath } from '../../../lib/try-to-parse-path'
 
+export interface FunctionsConfigManifest {
+  version: number
+  functions: Record<
+    string,
+    {
+      maxDuration?: number | undefined
+    }
+  >
+}
+
 interface InstrumentationDefinition {
   files: string[]
   name: 'instrumentation'
@@ -76,6 +87,7 @@ async function readPartialManifest<T>(
     | typeof MIDDLEWARE_MANIFEST
     | typeof BUILD_MANIFEST
     | typeof APP_BUILD_MANIFEST
+    | typeof FUNCTIONS_CONFIG_MANIFEST
     | typeof PAGES_MANIFEST
     | typeof WEBPACK_STATS
     | typeof APP_PATHS_MANIFEST
@@ -108,6 +120,8 @@ export class TurbopackManifestLoader {
   private appPathsManifests: Map<EntryKey, PagesManifest> = new Map()
   private buildManifests: Map<EntryKey, BuildManifest> = new Map()
   private fontManifests: Map<EntryKey, NextFontManifest> = new Map()
+  private functionsConfigManifests: Map<EntryKey, FunctionsConfigManifest> =
+    new Map()
   private loadableManifests: Map<EntryKey, LoadableManifest> = new Map()
   private middlewareManifests: Map<EntryKey, TurbopackMiddlewareManifest> =
     new Map()
@@ -138,6 +152,7 @@ export class TurbopackManifestLoader {
     this.appPathsManifests.delete(key)
     this.buildManifests.delete(key)
     this.fontManifests.delete(key)
+    this.functionsConfigManifests.delete(key)
     this.loadableManifests.delete(key)
     this.middlewareManifests.delete(key)
     this.pagesManifests.delete(key)
@@ -567,6 +582,50 @@ export class TurbopackManifestLoader {
     )
   }
 
+  async loadFunctionsConfigManifest(
+    pageName: string,
+    type: 'app' | 'pages' = 'pages'
+  ): Promise<void> {
+    this.functionsConfigManifests.set(
+      getEntryKey(type, 'server', pageName),
+      await readPartialManifest(
+        this.distDir,
+        FUNCTIONS_CONFIG_MANIFEST,
+        pageName,
+        type
+      )
+    )
+  }
+
+  private mergeFunctionsConfigManifests(
+    manifests: Iterable<FunctionsConfigManifest>
+  ) {
+    const manifest: FunctionsConfigManifest = {
+      version: 1,
+      functions: {},
+    }
+    for (const m of manifests) {
+      Object.assign(manifest.functions, m.functions)
+    }
+    return manifest
+  }
+
+  private async writeFunctionsConfigManifest(): Promise<void> {
+    const functionsConfigManifest = this.mergeFunctionsConfigManifests(
+      this.functionsConfigManifests.values()
+    )
+    const functionsConfigManifestPath = join(
+      this.distDir,
+      'server',
+      FUNCTIONS_CONFIG_MANIFEST
+    )
+    deleteCache(functionsConfigManifestPath)
+    await writeFileAtomic(
+      functionsConfigManifestPath,
+      JSON.stringify(functionsConfigManifest, null, 2)
+    )
+  }
+
   async loadLoadableManifest(
     pageName: string,
     type: 'app' | 'pages' = 'pages'
@@ -759,6 +818,7 @@ export class TurbopackManifestLoader {
     await this.writeAppPathsManifest()
     await this.writeBuildManifest(entrypoints, devRewrites, productionRewrites)
     await this.writeFallbackBuildManifest()
+    await this.writeFunctionsConfigManifest()
     await this.writeLoadableManifest()
     await this.writeMiddlewareManifest()
     await this.writeClientMiddlewareManifest()
@@ -0,0 +1,72 @@
+/* eslint-env jest */
+import { loadBindings } from 'next/dist/build/swc'
+
+const transform = async (src: string, options?: any) => {
+  const bindings = await loadBindings()
+  return bindings.css.turbopack.transform(src, options)
+}
+
+describe('css.turbopack.transform', () => {
+  it('should export the class names of css modules', async () => {
+    const output = await transform(
+      '.title { color: red } .subtitle { composes: title; }',
+      { filename: 'styles.module.css', cssModules: true }
+    )
+
+    expect(output.exports.title.name).toMatch(/__title$/)
+    expect(output.exports.subtitle.composes).toEqual([
+      { type: 'local', name: output.exports.title.name },
+    ])
+    expect(output.code).toContain(`.${output.exports.title.name}`)
+    expect(output.warnings).toEqual([])
+  })
+
+  it('should export icss values', async () => {
+    const output = await transform(
+      '@value primary: #f00;\n.button { color: primary }',
+      { filename: 'button.module.css', cssModules: true }
+    )
+
+    expect(output.icssExports).toEqual({ primary: '#f00' })
+    expect(output.code).toContain('color: red')
+  })
+
+  it('should compile the css for the targets', async () => {
+    const output = await transform('.a { .b { user-select: none } }', {
+      targets: 'safari 12',
+    })
+
+    expect(output.code).toContain('.a .b')
+    expect(output.code).toContain('-webkit-user-select: none')
+  })
+
+  it('should minify and generate a source map', async () => {
+    const output = await transform('.a {\n  color: #ff0000;\n}\n', {
+      filename: 'input.css',
+      minify: true,
+      sourceMaps: true,
+    })
+
+    expect(output.code).toBe('.a{color:red}')
+    expect(JSON.parse(output.map).sources).toEqual(['input.css'])
+  })
+
+  it('should warn about selectors that are not pure', async () => {
+    const output = await transform('div { color: red }', {
+      filename: 'styles.module.css',
+      cssModules: true,
+    })
+
+    expect(output.warnings).toHaveLength(1)
+  })
+
+  it('should have a sync variant', async () => {
+    const bindings = await loadBindings()
+    const output = bindings.css.turbopack.transformSync('.a { color: red }', {
+      minify: true,
+    })
+
+    expect(output.code).toBe('.a{color:red}')
+    expect(output.map ?? undefined).toBeUndefined()
+  })
+})
@@ -20,7 +20,7 @@ use anyhow::{bail, Result};
 use auto_hash_map::{AutoMap, AutoSet};
 use dashmap::DashMap;
 use parking_lot::{Condvar, Mutex};
-use rustc_hash::FxHasher;
+use rustc_hash::{FxHashMap, FxHasher};
 use smallvec::smallvec;
 use tokio::time::{Duration, Instant};
 use turbo_tasks::{
@@ -34,6 +34,7 @@ use turbo_tasks::{
     CellId, FunctionId, RawVc, ReadConsistency, SessionId, TaskId, TraitTypeId,
     TurboTasksBackendApi, ValueTypeId, TRANSIENT_TASK_BIT,
 };
+use turbo_tasks_malloc::TurboMalloc;
 
 pub use self::{operation::AnyOperation, storage::TaskDataCategory};
 use crate::{
@@ -95,9 +96,19 @@ pub enum TransientTask {
     Once(TransientTaskOnce),
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct BackendOptions {
+    /// When the memory usage exceeds this limit (in bytes), data of tasks that weren't accessed
+    /// recently is evicted from memory after a snapshot. Evicted data is restored from the backing
+    /// storage on the next access.
+    pub memory_limit: Option<usize>,
+}
+
 pub struct TurboTasksBackend<B: BackingStorage>(Arc<TurboTasksBackendInner<B>>);
 
 struct TurboTasksBackendInner<B: BackingStorage> {
+    options: BackendOptions,
+
     start_time: Instant,
     session_id: SessionId,
 
@@ -140,15 +151,30 @@ struct TurboTasksBackendInner<B: BackingStorage> {
 
 impl<B: BackingStorage> TurboTasksBackend<B> {
     pub fn new(backing_storage: B) -> Self {
-        Self(Arc::new(TurboTasksBackendInner::new(backing_storage)))
+        Self::new_with_options(backing_storage, BackendOptions::default())
+    }
+
+    pub fn new_with_options(backing_storage: B, options: BackendOptions) -> Self {
+        Self(Arc::new(TurboTasksBackendInner::new(
+            backing_storage,
+            options,
+        )))
+    }
+
+    /// Persists all changes and evicts the data of all tasks that weren't accessed since the last
+    /// eviction, regardless of the memory usage.
+    pub fn snapshot_and_evict(&self) {
+        self.0.snapshot();
+        self.0.evict_cold_tasks_while(|_| true);
     }
 }
 
 impl<B: BackingStorage> TurboTasksBackendInner<B> {
-    pub fn new(backing_storage: B) -> Self {
+    pub fn new(backing_storage: B, options: BackendOptions) -> Self {
         let shard_amount =
             (available_parallelism().map_or(4, |v| v.get()) * 64).next_power_of_two();
         Self {
+            options,
             start_time: Instant::now(),
             session_id: backing_storage.next_session_id(),
             persisted_task_id_factory: IdFactoryWithReuse::new(
@@ -600,7 +626,9 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
         }
     }
 
-    fn snapshot(&self) -> Option<(Instant, bool)> {
+    /// Waits until all operations are completed or suspended, and blocks new operations until
+    /// [`Self::resume_operations`] is called. Returns the suspended operations.
+    fn suspend_operations(&self) -> Vec<Arc<AnyOperation>> {
         let mut snapshot_request = self.snapshot_request.lock();
         snapshot_request.snapshot_requested = true;
         let active_operations = self
@@ -612,35 +640,40 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
                     self.in_progress_operations.load(Ordering::Relaxed) != SNAPSHOT_REQUESTED_BIT
                 });
         }
-        let suspended_operations = snapshot_request
+        snapshot_request
             .suspended_operations
             .iter()
             .map(|op| op.arc().clone())
-            .collect::<Vec<_>>();
-        drop(snapshot_request);
-        let persisted_storage_meta_log = self.persisted_storage_meta_log.take();
-        let persisted_storage_data_log = self.persisted_storage_data_log.take();
-        let persisted_task_cache_log = self.persisted_task_cache_log.take();
+            .collect::<Vec<_>>()
+    }
+
+    fn resume_operations(&self) {
         let mut snapshot_request = self.snapshot_request.lock();
         snapshot_request.snapshot_requested = false;
         self.in_progress_operations
             .fetch_sub(SNAPSHOT_REQUESTED_BIT, Ordering::Relaxed);
         self.snapshot_completed.notify_all();
+    }
+
+    fn snapshot(&self) -> Option<(Instant, bool)> {
+        let suspended_operations = self.suspend_operations();
+        let persisted_storage_meta_log = self.persisted_storage_meta_log.take();
+        let persisted_storage_data_log = self.persisted_storage_data_log.take();
+        let persisted_task_cache_log = self.persisted_task_cache_log.take();
+        self.resume_operations();
         let snapshot_time = Instant::now();
-        drop(snapshot_request);
 
-        // TODO track which items are persisting
         // TODO This is very inefficient, maybe the BackingStorage could compute that since it need
         // to iterate items anyway.
-        // let mut counts: FxHashMap<TaskId, u32> =
-        // FxHashMap::with_capacity_and_hasher(); for log in persisted_storage_meta_log
-        //     .iter()
-        //     .chain(persisted_storage_data_log.iter())
-        // {
-        //     for CachedDataUpdate { task, .. } in log.iter() {
-        //         *counts.entry(*task).or_default() += 1;
-        //     }
-        // }
+        let mut counts: FxHashMap<TaskId, u32> = FxHashMap::default();
+        for log in persisted_storage_meta_log
+            .iter()
+            .chain(persisted_storage_data_log.iter())
+        {
+            for CachedDataUpdate { task, .. } in log.iter() {
+                *counts.entry(*task).or_default() += 1;
+            }
+        }
 
         let mut new_items = false;
 
@@ -665,17 +698,42 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
             }
         }
 
-        // TODO add when we need to track persisted items
-        // for (task_id, count) in counts {
-        //     self.storage
-        //         .access_mut(task_id)
-        //         .persistance_state_mut()
-        //         .finish_persisting_items(count);
-        // }
+        for (task_id, count) in counts {
+            self.storage
+                .access_mut(task_id)
+                .persistance_state_mut()
+                .finish_persisting_items(count);
+        }
 
         Some((snapshot_time, new_items))
     }
 
+    /// Evicts data of tasks that weren't accessed recently until the memory usage is below the
+    /// configured limit. Only data that is fully persisted can be evicted, so this should run
+    /// after a snapshot.
+    fn evict_cold_tasks(&self) {
+        let Some(memory_limit) = self.options.memory_limit else {
+            return;
+        };
+        if TurboMalloc::memory_usage() <= memory_limit {
+            return;
+        }
+        // Evict a bit more than necessary to avoid evicting again on the next snapshot.
+        let target = memory_limit / 4 * 3;
+        self.evict_cold_tasks_while(|evicted| {
+            evicted % 1024 != 0 || TurboMalloc::memory_usage() > target
+        });
+    }
+
+    fn evict_cold_tasks_while(&self, should_continue: impl FnMut(usize) -> bool) {
+        let _span = tracing::info_span!("evict cold tasks").entered();
+        // Operations might expect data to stay in memory between steps, so only evict when no
+        // operation is running.
+        self.suspend_operations();
+        self.storage.evict_cold_tasks(should_continue);
+        self.resume_operations();
+    }
+
     fn startup(&self, turbo_tasks: &dyn TurboTasksBackendApi<TurboTasksBackend<B>>) {
         // Continue all uncompleted operations
         // They can't be interrupted by a snapshot since the snapshotting job has not been scheduled
@@ -1386,6 +1444,10 @@ impl<B: BackingStorage> TurboTasksBackendInner<B> {
                     let snapshot = turbo_tasks::spawn_blocking(move || this.snapshot()).await;
                     if let Some((snapshot_start, new_data)) = snapshot {
                         last_snapshot = snapshot_start;
+                        if self.options.memory_limit.is_some() {
+                            let this = self.clone();
+                            turbo_tasks::spawn_blocking(move || this.evict_cold_tasks()).await;
+                        }
                         if new_data {
                             continue;
                         }
@@ -137,9 +137,20 @@ pub fn make_task_dirty_internal(
     queue: &mut AggregationUpdateQueue,
     ctx: &impl ExecuteContext,
 ) {
+    match cause {
+        TaskDirtyCause::CellChange { .. }
+        | TaskDirtyCause::CellRemoved { .. }
+        | TaskDirtyCause::OutputChange
+        | TaskDirtyCause::CollectiblesChange { .. } => ctx.record_dependent_invalidation(task_id),
+        // External invalidations are recorded by the caller with their reason.
+        TaskDirtyCause::InitialDirty | TaskDirtyCause::Unknown => {}
+    }
     if make_stale {
         if let Some(InProgressState::InProgress { stale, .. }) = get_mut!(task, InProgress) {
-            *stale = true;
+            if !*stale {
+                *stale = true;
+                ctx.cancel_task_execution(task_id);
+            }
         }
     }
     let old = task.insert(CachedDataItem::Dirty {
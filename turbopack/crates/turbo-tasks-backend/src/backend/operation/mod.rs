@@ -73,6 +73,8 @@ pub trait ExecuteContext<'e>: Sized {
         category: TaskDataCategory,
     ) -> (impl TaskGuard + 'e, impl TaskGuard + 'e);
     fn schedule(&self, task_id: TaskId);
+    fn cancel_task_execution(&self, task_id: TaskId);
+    fn record_dependent_invalidation(&self, task_id: TaskId);
     fn operation_suspend_point<T>(&mut self, op: &T)
     where
         T: Clone + Into<AnyOperation>;
@@ -271,6 +273,14 @@ where
         self.turbo_tasks.schedule(task_id);
     }
 
+    fn cancel_task_execution(&self, task_id: TaskId) {
+        self.turbo_tasks.cancel_task_execution(task_id);
+    }
+
+    fn record_dependent_invalidation(&self, task_id: TaskId) {
+        self.turbo_tasks.record_dependent_invalidation(&[task_id]);
+    }
+
     fn operation_suspend_point<T: Clone + Into<AnyOperation>>(&mut self, op: &T) {
         if self.parent.is_some() {
             self.backend.operation_suspend_point(|| {
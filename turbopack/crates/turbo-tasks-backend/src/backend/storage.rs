@@ -9,15 +9,20 @@ use auto_hash_map::{map::Entry, AutoMap};
 use dashmap::DashMap;
 use either::Either;
 use rustc_hash::FxHasher;
-use turbo_tasks::KeyValuePair;
+use turbo_tasks::{KeyValuePair, TaskId};
 
 use crate::{
     backend::indexed::Indexed,
+    data::{CachedDataItem, CachedDataItemKey, CachedDataItemValue},
     utils::dash_map_multi::{get_multiple_mut, RefMut},
 };
 
 const META_UNRESTORED: u32 = 1 << 31;
 const DATA_UNRESTORED: u32 = 1 << 30;
+/// Set on every access and cleared by eviction, which gives recently used tasks a second chance.
+const RECENTLY_ACCESSED: u32 = 1 << 29;
+/// The remaining bits count the items that are not yet persisted by a snapshot.
+const PERSISTING_COUNT_MASK: u32 = RECENTLY_ACCESSED - 1;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TaskDataCategory {
@@ -96,26 +101,50 @@ impl PersistanceState {
         self.value &= !category.flag();
     }
 
+    /// Marks the category as not restored, so the next access restores it from the backing
+    /// storage.
+    pub fn set_unrestored(&mut self, category: TaskDataCategory) {
+        self.value |= category.flag();
+    }
+
     pub fn add_persisting_item(&mut self) {
-        // TODO add when we need to track unpersisted items
-        // self.value += 1;
+        self.add_persisting_items(1);
+    }
+
+    pub fn add_persisting_items(&mut self, count: u32) {
+        debug_assert!(self.persisting_count() + count <= PERSISTING_COUNT_MASK);
+        self.value += count;
     }
 
-    pub fn add_persisting_items(&mut self, _count: u32) {
-        // TODO add when we need to track unpersisted items
-        // self.value += count;
+    pub fn finish_persisting_items(&mut self, count: u32) {
+        debug_assert!(self.persisting_count() >= count);
+        self.value -= count;
     }
 
-    // TODO remove when we need to track unpersisted items
-    #[allow(dead_code)]
-    pub fn finish_persisting_items(&mut self, _count: u32) {
-        // TODO add when we need to track unpersisted items
-        // self.value -= count;
+    /// Returns true when all items of the task have been persisted by a snapshot, so the in-memory
+    /// data matches the backing storage.
+    pub fn is_fully_persisted(&self) -> bool {
+        self.persisting_count() == 0
+    }
+
+    fn persisting_count(&self) -> u32 {
+        self.value & PERSISTING_COUNT_MASK
     }
 
     pub fn is_restored(&self, category: TaskDataCategory) -> bool {
         (self.value & category.flag()) == 0
     }
+
+    fn mark_accessed(&mut self) {
+        self.value |= RECENTLY_ACCESSED;
+    }
+
+    /// Returns if the task was accessed since the last call and resets the flag.
+    fn take_accessed(&mut self) -> bool {
+        let accessed = (self.value & RECENTLY_ACCESSED) != 0;
+        self.value &= !RECENTLY_ACCESSED;
+        accessed
+    }
 }
 
 const INDEX_THRESHOLD: usize = 1024;
@@ -332,10 +361,11 @@ where
     }
 
     pub fn access_mut(&self, key: K) -> StorageWriteGuard<'_, K, T> {
-        let inner = match self.map.entry(key) {
+        let mut inner = match self.map.entry(key) {
             dashmap::mapref::entry::Entry::Occupied(e) => e.into_ref(),
             dashmap::mapref::entry::Entry::Vacant(e) => e.insert(InnerStorage::new()),
         };
+        inner.persistance_state_mut().mark_accessed();
         StorageWriteGuard {
             inner: inner.into(),
         }
@@ -346,7 +376,9 @@ where
         key1: K,
         key2: K,
     ) -> (StorageWriteGuard<'_, K, T>, StorageWriteGuard<'_, K, T>) {
-        let (a, b) = get_multiple_mut(&self.map, key1, key2, || InnerStorage::new());
+        let (mut a, mut b) = get_multiple_mut(&self.map, key1, key2, || InnerStorage::new());
+        a.persistance_state_mut().mark_accessed();
+        b.persistance_state_mut().mark_accessed();
         (
             StorageWriteGuard { inner: a },
             StorageWriteGuard { inner: b },
@@ -354,6 +386,86 @@ where
     }
 }
 
+impl InnerStorage<CachedDataItem> {
+    /// Removes all items of the category from memory. They are restored from the backing storage
+    /// on the next access.
+    ///
+    /// This only succeeds when the in-memory data matches the backing storage, i.e. all items are
+    /// persisted and there are no items that are never persisted (e.g. in progress state).
+    fn evict(&mut self, category: TaskDataCategory) -> bool {
+        let persistance_state = self.persistance_state();
+        if !persistance_state.is_restored(category) || !persistance_state.is_fully_persisted() {
+            return false;
+        }
+        let evictable = self
+            .iter_all()
+            .filter(|(key, _)| key.category() == category)
+            .all(|(key, value)| key.is_persistent() && value.is_persistent());
+        if !evictable {
+            return false;
+        }
+        let retain =
+            |key: &CachedDataItemKey, _: &mut CachedDataItemValue| key.category() != category;
+        match self {
+            InnerStorage::Plain { map, .. } => {
+                map.retain(retain);
+                map.shrink_to_fit();
+            }
+            InnerStorage::Indexed { map, .. } => {
+                map.retain(|_, map| {
+                    map.retain(retain);
+                    map.shrink_to_fit();
+                    !map.is_empty()
+                });
+                map.shrink_to_fit();
+            }
+        }
+        self.persistance_state_mut().set_unrestored(category);
+        true
+    }
+}
+
+impl Storage<TaskId, CachedDataItem> {
+    /// Evicts the data of persistent tasks that weren't accessed since the last eviction pass
+    /// (see [`PersistanceState::take_accessed`]), until `should_continue` returns false. The
+    /// evicted data is restored from the backing storage on the next access.
+    ///
+    /// The candidates are collected first and evicted shard by shard afterwards, so every shard is
+    /// only locked for a single task at a time.
+    ///
+    /// Returns the number of evicted tasks.
+    pub fn evict_cold_tasks(&self, mut should_continue: impl FnMut(usize) -> bool) -> usize {
+        let mut candidates = self
+            .map
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|task_id| !task_id.is_transient())
+            .map(|task_id| (self.map.determine_map(&task_id), task_id))
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+
+        let mut evicted = 0;
+        for (_, task_id) in candidates {
+            let Some(mut inner) = self.map.get_mut(&task_id) else {
+                continue;
+            };
+            if inner.persistance_state_mut().take_accessed() {
+                continue;
+            }
+            let data_evicted = inner.evict(TaskDataCategory::Data);
+            let meta_evicted = inner.evict(TaskDataCategory::Meta);
+            drop(inner);
+            if data_evicted || meta_evicted {
+                evicted += 1;
+                if !should_continue(evicted) {
+                    break;
+                }
+            }
+        }
+        evicted
+    }
+}
+
 pub struct StorageWriteGuard<'a, K, T>
 where
     T: KeyValuePair,
@@ -11,7 +11,7 @@ use std::{
 use anyhow::{Ok, Result};
 use byteorder::WriteBytesExt;
 use dashmap::DashMap;
-use rustc_hash::{FxHashMap, FxHasher};
+use rustc_hash::FxHasher;
 
 use crate::database::{
     by_key_space::ByKeySpace,
@@ -39,6 +39,7 @@ impl<T: KeyValueDatabase> Borrow<[u8]> for ValueBuffer<'_, T> {
 }
 
 type Cache = ByKeySpace<DashMap<Vec<u8>, Option<Vec<u8>>, BuildHasherDefault<FxHasher>>>;
+type RestoredMap = ByKeySpace<DashMap<&'static [u8], &'static [u8], BuildHasherDefault<FxHasher>>>;
 
 pub struct StartupCacheLayer<T: KeyValueDatabase> {
     database: T,
@@ -46,7 +47,9 @@ pub struct StartupCacheLayer<T: KeyValueDatabase> {
     fresh_db: bool,
     cache_size: AtomicUsize,
     cache: Cache,
-    restored_map: ByKeySpace<FxHashMap<&'static [u8], &'static [u8]>>,
+    /// The cache of the previous session. Keys are removed when they are written, so that stale
+    /// values are never read after their task data has been evicted from memory.
+    restored_map: RestoredMap,
     // Need to be kept around to keep the restored_map reference alive
     _restored: Vec<u8>,
 }
@@ -54,7 +57,7 @@ pub struct StartupCacheLayer<T: KeyValueDatabase> {
 impl<T: KeyValueDatabase> StartupCacheLayer<T> {
     pub fn new(database: T, path: PathBuf, fresh_db: bool) -> Result<Self> {
         let mut restored = Vec::new();
-        let mut restored_map = ByKeySpace::new(|_| FxHashMap::default());
+        let mut restored_map: RestoredMap = ByKeySpace::new(|_| DashMap::default());
         if !fresh_db {
             if let Result::Ok(mut cache_file) = File::open(&path) {
                 cache_file.read_to_end(&mut restored)?;
@@ -131,7 +134,12 @@ impl<T: KeyValueDatabase> KeyValueDatabase for StartupCacheLayer<T> {
                 .map(ValueBuffer::Database));
         }
         let value = {
-            if let Some(value) = self.restored_map.get(key_space).get(key) {
+            let restored = self
+                .restored_map
+                .get(key_space)
+                .get(key)
+                .map(|entry| *entry.value());
+            if let Some(value) = restored {
                 Some(ValueBuffer::Cached(value))
             } else {
                 self.database
@@ -176,6 +184,7 @@ pub struct StartupCacheWriteBatch<'a, T: KeyValueDatabase> {
 impl<'a, T: KeyValueDatabase> WriteBatch<'a> for StartupCacheWriteBatch<'a, T> {
     fn put(&mut self, key_space: KeySpace, key: Cow<[u8]>, value: Cow<[u8]>) -> Result<()> {
         if !self.this.fresh_db {
+            self.this.restored_map.get(key_space).remove(&*key);
             let cache = self.this.cache.get(key_space);
             cache.insert(key.to_vec(), Some(value.to_vec()));
         }
@@ -197,6 +206,7 @@ impl<'a, T: KeyValueDatabase> WriteBatch<'a> for StartupCacheWriteBatch<'a, T> {
 
     fn delete(&mut self, key_space: KeySpace, key: Cow<[u8]>) -> Result<()> {
         if !self.this.fresh_db {
+            self.this.restored_map.get(key_space).remove(&*key);
             let cache = self.this.cache.get(key_space);
             cache.insert(key.to_vec(), None);
         }
@@ -230,7 +240,8 @@ impl<'a, T: KeyValueDatabase> WriteBatch<'a> for StartupCacheWriteBatch<'a, T> {
             }
             for (key_space, map) in self.this.restored_map.iter() {
                 let cache = self.this.cache.get(key_space);
-                for (key, value) in map.iter() {
+                for entry in map.iter() {
+                    let (key, value) = entry.pair();
                     if !cache.contains_key(*key) {
                         let size = key.len() + value.len() + PAIR_HEADER_SIZE;
                         if pos + size < CACHE_SIZE_LIMIT {
@@ -0,0 +1,118 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Once},
+};
+
+use anyhow::Result;
+use turbo_tasks::{run_once, State, TryJoinIterExt, TurboTasks, Vc};
+use turbo_tasks_backend::{
+    default_backing_storage, BackendOptions, DefaultBackingStorage, TurboTasksBackend,
+};
+
+static REGISTER: Once = Once::new();
+
+fn register() {
+    REGISTER.call_once(|| {
+        turbo_tasks::register();
+        include!(concat!(env!("OUT_DIR"), "/register_test_eviction.rs"));
+    });
+}
+
+type TestTurboTasks = Arc<TurboTasks<TurboTasksBackend<DefaultBackingStorage>>>;
+
+fn create_turbo_tasks(path: &Path) -> TestTurboTasks {
+    TurboTasks::new(TurboTasksBackend::new_with_options(
+        default_backing_storage(path).unwrap(),
+        BackendOptions {
+            memory_limit: Some(1),
+        },
+    ))
+}
+
+/// Evicts all tasks. Tasks that were accessed since the last eviction are only evicted by the
+/// second pass.
+async fn evict_all(tt: &TestTurboTasks) {
+    let tt = tt.clone();
+    tokio::task::spawn_blocking(move || {
+        tt.backend().snapshot_and_evict();
+        tt.backend().snapshot_and_evict();
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn evicted_tasks_restore_current_values() {
+    register();
+    let path = PathBuf::from(concat!(env!("OUT_DIR"), "/.cache/eviction"));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+
+    let tt = create_turbo_tasks(&path);
+    run_once(tt.clone(), {
+        let tt = tt.clone();
+        async move {
+            assert_eq!(*sum(4).strongly_consistent().await?, 12);
+            evict_all(&tt).await;
+            assert_eq!(*sum(4).strongly_consistent().await?, 12);
+
+            input(1).await?.state.set(11);
+            assert_eq!(*sum(4).strongly_consistent().await?, 32);
+            evict_all(&tt).await;
+            assert_eq!(*sum(4).strongly_consistent().await?, 32);
+            assert_eq!(*input(1).await?.state.get(), 11);
+            Ok(())
+        }
+    })
+    .await
+    .unwrap();
+    tt.stop_and_wait().await;
+
+    // The second session starts with the data of the first session in the startup cache, which
+    // must not be read again after it has been changed and evicted.
+    let tt = create_turbo_tasks(&path);
+    run_once(tt.clone(), {
+        let tt = tt.clone();
+        async move {
+            assert_eq!(*sum(4).strongly_consistent().await?, 32);
+
+            input(2).await?.state.set(12);
+            assert_eq!(*sum(4).strongly_consistent().await?, 52);
+            evict_all(&tt).await;
+            assert_eq!(*sum(4).strongly_consistent().await?, 52);
+            assert_eq!(*input(2).await?.state.get(), 12);
+            Ok(())
+        }
+    })
+    .await
+    .unwrap();
+    tt.stop_and_wait().await;
+}
+
+#[turbo_tasks::value]
+struct Input {
+    state: State<u32>,
+}
+
+#[turbo_tasks::function]
+fn input(value: u32) -> Vc<Input> {
+    Input {
+        state: State::new(value),
+    }
+    .cell()
+}
+
+#[turbo_tasks::function]
+async fn double(value: u32) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*input(value).await?.state.get() * 2))
+}
+
+#[turbo_tasks::function]
+async fn sum(n: u32) -> Result<Vc<u32>> {
+    let values = (0..n).map(double).try_join().await?;
+    Ok(Vc::cell(values.iter().map(|value| **value).sum()))
+}
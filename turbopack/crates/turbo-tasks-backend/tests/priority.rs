@@ -0,0 +1 @@
+../../turbo-tasks-testing/tests/priority.rs
\ No newline at end of file
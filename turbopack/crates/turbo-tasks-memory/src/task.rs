@@ -1175,6 +1175,7 @@ impl Task {
                     if !*stale {
                         *clean = false;
                         *stale = true;
+                        turbo_tasks.cancel_task_execution(self.id);
                         let change_job = if *count_as_finished {
                             *count_as_finished = false;
                             let change = TaskChange {
@@ -21,7 +21,7 @@ use turbo_tasks::{
     test_helpers::with_turbo_tasks_for_testing,
     util::{SharedError, StaticOrArc},
     CellId, ExecutionId, InvalidationReason, LocalTaskId, MagicAny, RawVc, ReadConsistency, TaskId,
-    TaskPersistence, TraitTypeId, TurboTasksApi, TurboTasksCallApi,
+    TaskPersistence, TaskPriority, TraitTypeId, TurboTasksApi, TurboTasksCallApi,
 };
 
 pub use crate::run::{run, run_with_tt, run_without_cache_check, Registration};
@@ -158,6 +158,14 @@ impl TurboTasksCallApi for VcStorage {
     ) -> TaskId {
         unreachable!()
     }
+
+    fn run_once_with_priority(
+        &self,
+        _priority: TaskPriority,
+        _future: std::pin::Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
+    ) -> TaskId {
+        unreachable!()
+    }
 }
 
 impl TurboTasksApi for VcStorage {
@@ -0,0 +1,78 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use anyhow::Result;
+use turbo_tasks::{State, Vc};
+use turbo_tasks_testing::{register, run, Registration};
+
+static REGISTRATION: Registration = register!();
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+#[tokio::test]
+async fn cancel_stale_execution() {
+    run(&REGISTRATION, || async {
+        STARTED.store(false, Ordering::SeqCst);
+        CANCELLED.store(false, Ordering::SeqCst);
+
+        let input = ChangingInput {
+            state: State::new(1),
+        }
+        .cell();
+        let input_val = input.await?;
+        let output = slow_compute(input);
+
+        // Without cancellation, the stale execution would never finish.
+        let (read, ()) = tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(
+                async { *output.strongly_consistent().await.unwrap() },
+                async {
+                    // Wait until the first execution is blocked.
+                    while !STARTED.load(Ordering::SeqCst) {
+                        tokio::task::yield_now().await;
+                    }
+                    input_val.state.set(2);
+                }
+            )
+        })
+        .await
+        .expect("stale execution was not cancelled");
+        assert_eq!(read, 2);
+        assert!(CANCELLED.load(Ordering::SeqCst));
+        anyhow::Ok(read)
+    })
+    .await
+    .unwrap()
+}
+
+#[turbo_tasks::value]
+struct ChangingInput {
+    state: State<u32>,
+}
+
+/// Sets [`CANCELLED`] when the blocked execution is dropped.
+struct DropGuard;
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        CANCELLED.store(true, Ordering::SeqCst);
+    }
+}
+
+#[turbo_tasks::function]
+async fn slow_compute(input: Vc<ChangingInput>) -> Result<Vc<u32>> {
+    let value = *input.await?.state.get();
+    if value == 1 {
+        let _guard = DropGuard;
+        STARTED.store(true, Ordering::SeqCst);
+        std::future::pending::<()>().await;
+    }
+    Ok(Vc::cell(value))
+}
@@ -0,0 +1,92 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+
+use std::{
+    future::{poll_fn, Future},
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    task::Poll,
+};
+
+use anyhow::Result;
+use turbo_tasks::{run_once_with_priority, RcStr, TaskPriority, TryJoinIterExt, Vc};
+use turbo_tasks_testing::{register, run_with_tt, Registration};
+
+static REGISTRATION: Registration = register!();
+
+static EXECUTED: Mutex<Vec<RcStr>> = Mutex::new(Vec::new());
+
+static BLOCKED: AtomicBool = AtomicBool::new(false);
+static RELEASED: AtomicBool = AtomicBool::new(false);
+
+// A single worker thread is blocked until both root tasks are queued, so the order in which the
+// queued tasks are picked only depends on their priority.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn high_priority_overtakes_low_priority() {
+    run_with_tt(&REGISTRATION, |tt| async move {
+        EXECUTED.lock().unwrap().clear();
+        BLOCKED.store(false, Ordering::SeqCst);
+        RELEASED.store(false, Ordering::SeqCst);
+
+        let blocker = tokio::spawn(async {
+            BLOCKED.store(true, Ordering::SeqCst);
+            while !RELEASED.load(Ordering::SeqCst) {
+                std::thread::yield_now();
+            }
+        });
+        while !BLOCKED.load(Ordering::SeqCst) {
+            tokio::task::yield_now().await;
+        }
+
+        // The low priority work is scheduled first, but all high priority work must be executed
+        // before it.
+        let mut low = pin!(run_once_with_priority(
+            tt.clone(),
+            TaskPriority::Low,
+            compute_all("low")
+        ));
+        let mut high = pin!(run_once_with_priority(
+            tt.clone(),
+            TaskPriority::High,
+            compute_all("high")
+        ));
+        // Polling the futures once schedules the root tasks.
+        poll_fn(|cx| {
+            let _ = low.as_mut().poll(cx);
+            let _ = high.as_mut().poll(cx);
+            Poll::Ready(())
+        })
+        .await;
+        RELEASED.store(true, Ordering::SeqCst);
+        blocker.await?;
+
+        let (low, high) = tokio::try_join!(low, high)?;
+        assert_eq!(low, high);
+
+        let executed = EXECUTED.lock().unwrap().clone();
+        if let Some(first_low) = executed.iter().position(|label| label == "low") {
+            assert!(
+                executed[first_low..].iter().all(|label| label == "low"),
+                "high priority work was executed after low priority work: {executed:?}"
+            );
+        }
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+async fn compute_all(label: &'static str) -> Result<u32> {
+    let values = (0..10).map(|i| compute(label.into(), i)).try_join().await?;
+    Ok(values.iter().map(|value| **value).sum())
+}
+
+#[turbo_tasks::function]
+fn compute(label: RcStr, i: u32) -> Vc<u32> {
+    EXECUTED.lock().unwrap().push(label);
+    Vc::cell(i * 2)
+}
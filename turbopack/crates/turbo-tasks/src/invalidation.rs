@@ -8,6 +8,7 @@ use std::{
 
 use anyhow::Result;
 use indexmap::map::Entry;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{de::Visitor, Deserialize, Serialize};
 use tokio::runtime::Handle;
 
@@ -315,3 +316,263 @@ impl Display for InvalidationReasonSet {
         Ok(())
     }
 }
+
+/// The cause of a single task invalidation, as recorded by [`InvalidationGraph`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) enum InvalidationCause {
+    /// An external event, e.g. a file change.
+    Reason(StaticOrArc<dyn InvalidationReason>),
+    /// A cell or the output of another task changed.
+    Task(TaskId),
+    /// The task was invalidated without a reason, or outside of a task execution.
+    Unknown,
+}
+
+/// Records which invalidation caused which task to be invalidated, so that we can later explain
+/// why a task was recomputed.
+#[derive(Default)]
+pub(crate) struct InvalidationGraph {
+    /// For every cause, the tasks it invalidated and how often that happened.
+    edges: FxIndexMap<InvalidationCause, FxIndexMap<TaskId, usize>>,
+}
+
+impl InvalidationGraph {
+    pub fn record(&mut self, cause: InvalidationCause, tasks: impl IntoIterator<Item = TaskId>) {
+        let invalidated = self.edges.entry(cause).or_default();
+        for task in tasks {
+            *invalidated.entry(task).or_default() += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Builds the tree of causal chains from the external reasons to the invalidated tasks.
+    ///
+    /// When `root` is given, only chains that end in `root` are included.
+    pub fn explain(
+        &self,
+        root: Option<TaskId>,
+        describe: impl Fn(TaskId) -> String,
+    ) -> InvalidationExplanation {
+        // Only causes that (transitively) invalidated the root are relevant.
+        let relevant = root.map(|root| {
+            let mut causes_of: FxHashMap<TaskId, Vec<&InvalidationCause>> = FxHashMap::default();
+            for (cause, tasks) in &self.edges {
+                for &task in tasks.keys() {
+                    causes_of.entry(task).or_default().push(cause);
+                }
+            }
+            let mut relevant = FxHashSet::default();
+            let mut queue = vec![root];
+            while let Some(task) = queue.pop() {
+                if !relevant.insert(InvalidationCause::Task(task)) {
+                    continue;
+                }
+                for &cause in causes_of.get(&task).into_iter().flatten() {
+                    match cause {
+                        InvalidationCause::Task(task) => queue.push(*task),
+                        cause => {
+                            relevant.insert(cause.clone());
+                        }
+                    }
+                }
+            }
+            relevant
+        });
+        let is_relevant = |cause: &InvalidationCause| {
+            relevant
+                .as_ref()
+                .map_or(true, |relevant| relevant.contains(cause))
+        };
+
+        let mut builder = TreeBuilder {
+            graph: self,
+            describe,
+            is_relevant,
+            expanded: FxHashSet::default(),
+        };
+        let mut roots = Vec::new();
+        for (cause, tasks) in &self.edges {
+            let label = match cause {
+                InvalidationCause::Reason(reason) => reason.to_string(),
+                InvalidationCause::Unknown => "unknown reason".to_string(),
+                InvalidationCause::Task(_) => continue,
+            };
+            if !is_relevant(cause) {
+                continue;
+            }
+            roots.push(InvalidationTree {
+                label,
+                count: tasks.values().sum(),
+                children: builder.children(tasks),
+                repeated: false,
+            });
+        }
+        InvalidationExplanation { roots }
+    }
+}
+
+struct TreeBuilder<'a, D, R> {
+    graph: &'a InvalidationGraph,
+    describe: D,
+    is_relevant: R,
+    /// Tasks that are already shown with their children somewhere in the tree.
+    expanded: FxHashSet<TaskId>,
+}
+
+impl<D: Fn(TaskId) -> String, R: Fn(&InvalidationCause) -> bool> TreeBuilder<'_, D, R> {
+    fn children(&mut self, tasks: &FxIndexMap<TaskId, usize>) -> Vec<InvalidationTree> {
+        let mut children = Vec::new();
+        for (&task, &count) in tasks {
+            let cause = InvalidationCause::Task(task);
+            if !(self.is_relevant)(&cause) {
+                continue;
+            }
+            let label = (self.describe)(task);
+            if !self.expanded.insert(task) {
+                children.push(InvalidationTree {
+                    label,
+                    count,
+                    children: Vec::new(),
+                    repeated: true,
+                });
+                continue;
+            }
+            let grand_children = match self.graph.edges.get(&cause) {
+                Some(tasks) => self.children(tasks),
+                None => Vec::new(),
+            };
+            children.push(InvalidationTree {
+                label,
+                count,
+                children: grand_children,
+                repeated: false,
+            });
+        }
+        children
+    }
+}
+
+/// Explains why tasks were recomputed. Every root of the forest is an external reason (e.g. a
+/// file change), and the children of a node are the tasks that were invalidated because of it.
+///
+/// The [Display] implementation prints the forest as a tree.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct InvalidationExplanation {
+    pub roots: Vec<InvalidationTree>,
+}
+
+/// A node in an [InvalidationExplanation].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidationTree {
+    /// The invalidation reason or the description of the invalidated task.
+    pub label: String,
+    /// How often the parent invalidated this task. For reasons, how many tasks the reason
+    /// invalidated directly.
+    pub count: usize,
+    pub children: Vec<InvalidationTree>,
+    /// The task is already shown with its children at another place in the tree.
+    pub repeated: bool,
+}
+
+impl InvalidationExplanation {
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+}
+
+impl Display for InvalidationExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn fmt_children(
+            children: &[InvalidationTree],
+            prefix: &str,
+            f: &mut std::fmt::Formatter<'_>,
+        ) -> std::fmt::Result {
+            for (i, child) in children.iter().enumerate() {
+                let last = i == children.len() - 1;
+                let (branch, indent) = if last {
+                    ("└─ ", "   ")
+                } else {
+                    ("├─ ", "│  ")
+                };
+                write!(f, "{prefix}{branch}")?;
+                fmt_node(child, f)?;
+                fmt_children(&child.children, &format!("{prefix}{indent}"), f)?;
+            }
+            Ok(())
+        }
+
+        fn fmt_node(node: &InvalidationTree, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{} ({}x)", node.label, node.count)?;
+            if node.repeated {
+                write!(f, " (see above)")?;
+            }
+            writeln!(f)
+        }
+
+        for root in &self.roots {
+            fmt_node(root, f)?;
+            fmt_children(&root.children, "", f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(PartialEq, Eq, Hash)]
+    struct FileChanged;
+
+    impl Display for FileChanged {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "file changed")
+        }
+    }
+
+    impl InvalidationReason for FileChanged {}
+
+    static FILE_CHANGED: FileChanged = FileChanged;
+
+    fn task(id: u32) -> TaskId {
+        TaskId::from(id)
+    }
+
+    fn describe(task: TaskId) -> String {
+        format!("task {}", *task)
+    }
+
+    fn graph() -> InvalidationGraph {
+        let mut graph = InvalidationGraph::default();
+        let reason = InvalidationCause::Reason((&FILE_CHANGED as &dyn InvalidationReason).into());
+        graph.record(reason.clone(), [task(1)]);
+        graph.record(InvalidationCause::Task(task(1)), [task(2), task(3)]);
+        graph.record(InvalidationCause::Task(task(2)), [task(4)]);
+        graph.record(InvalidationCause::Task(task(3)), [task(4)]);
+        graph.record(reason, [task(1)]);
+        graph
+    }
+
+    #[test]
+    fn explains_causal_chain() {
+        let explanation = graph().explain(None, describe);
+        assert_eq!(
+            explanation.to_string(),
+            "file changed (2x)\n└─ task 1 (2x)\n   ├─ task 2 (1x)\n   │  └─ task 4 (1x)\n   └─ \
+             task 3 (1x)\n      └─ task 4 (1x) (see above)\n"
+        );
+    }
+
+    #[test]
+    fn filters_by_root() {
+        let explanation = graph().explain(Some(task(2)), describe);
+        assert_eq!(
+            explanation.to_string(),
+            "file changed (2x)\n└─ task 1 (2x)\n   └─ task 2 (1x)\n"
+        );
+    }
+}
@@ -16,11 +16,12 @@ use std::{
 
 use anyhow::{anyhow, Result};
 use auto_hash_map::AutoMap;
+use dashmap::DashMap;
 use futures::FutureExt;
 use rustc_hash::FxHasher;
 use serde::{Deserialize, Serialize};
 use tokio::{runtime::Handle, select, task_local};
-use tokio_util::task::TaskTracker;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use tracing::{info_span, instrument, trace_span, Instrument, Level};
 use turbo_tasks_malloc::TurboMalloc;
 
@@ -36,7 +37,9 @@ use crate::{
         TRANSIENT_TASK_BIT,
     },
     id_factory::{IdFactory, IdFactoryWithReuse},
+    invalidation::{InvalidationCause, InvalidationExplanation, InvalidationGraph},
     magic_any::MagicAny,
+    priority::{TaskPriority, TaskQueue},
     raw_vc::{CellId, RawVc},
     registry::{self, get_function},
     serialization_invalidation::SerializationInvalidator,
@@ -108,6 +111,11 @@ pub trait TurboTasksCallApi: Sync + Send {
         &self,
         future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
     ) -> TaskId;
+    fn run_once_with_priority(
+        &self,
+        priority: TaskPriority,
+        future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
+    ) -> TaskId;
 }
 
 pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
@@ -247,6 +255,20 @@ pub trait TurboTasksBackendApi<B: Backend + 'static>: TurboTasksCallApi + Sync +
     fn schedule_backend_background_job(&self, id: BackendJobId);
     fn schedule_backend_foreground_job(&self, id: BackendJobId);
 
+    /// Cancels the current execution of the task, if it is executing. The backend must have marked
+    /// the execution as stale before calling this, since the result of a cancelled execution is
+    /// discarded and the task is expected to be rescheduled on completion.
+    ///
+    /// Cancellation is cooperative: the task future is dropped at its next await point, and
+    /// synchronous work can poll [`cancellation_token`].
+    fn cancel_task_execution(&self, task: TaskId);
+
+    /// Records that the tasks were invalidated because a cell or the output of the currently
+    /// executing task changed. Backends that invalidate dependent tasks without going through
+    /// [`TurboTasksBackendApi::schedule_notify_tasks`] must call this to make the invalidation
+    /// show up in [`TurboTasks::explain_invalidations`].
+    fn record_dependent_invalidation(&self, tasks: &[TaskId]);
+
     fn try_foreground_done(&self) -> Result<(), EventListener>;
     fn wait_foreground_done_excluding_own<'a>(
         &'a self,
@@ -373,6 +395,15 @@ pub struct TurboTasks<B: Backend + 'static> {
     scheduled_tasks: AtomicUsize,
     start: Mutex<Option<Instant>>,
     aggregated_update: Mutex<(Option<(Duration, usize)>, InvalidationReasonSet)>,
+    /// Cancellation tokens of all currently executing tasks, see
+    /// [`TurboTasksBackendApi::cancel_task_execution`].
+    running_executions: DashMap<TaskId, CancellationToken, BuildHasherDefault<FxHasher>>,
+    /// Scheduled task executions that are waiting for an executor, ordered by [`TaskPriority`].
+    task_queue: TaskQueue,
+    /// Records the causes of invalidations while invalidation tracing is enabled, see
+    /// [`TurboTasks::set_invalidation_tracing`].
+    invalidation_tracing: AtomicBool,
+    invalidation_graph: Mutex<InvalidationGraph>,
     event: Event,
     event_start: Event,
     event_foreground: Event,
@@ -413,11 +444,19 @@ struct CurrentGlobalTaskState {
     /// complete.
     local_task_tracker: TaskTracker,
 
+    /// Cancelled when the backend decides that this execution is stale, e.g. because one of its
+    /// dependencies was invalidated while it was running.
+    cancellation: CancellationToken,
+
     backend_state: Box<dyn Any + Send + Sync>,
 }
 
 impl CurrentGlobalTaskState {
-    fn new(task_id: TaskId, backend_state: Box<dyn Any + Send + Sync>) -> Self {
+    fn new(
+        task_id: TaskId,
+        cancellation: CancellationToken,
+        backend_state: Box<dyn Any + Send + Sync>,
+    ) -> Self {
         Self {
             task_id,
             tasks_to_notify: Vec::new(),
@@ -425,6 +464,7 @@ impl CurrentGlobalTaskState {
             cell_counters: Some(AutoMap::default()),
             local_cells: Vec::new(),
             local_task_tracker: TaskTracker::new(),
+            cancellation,
             backend_state,
         }
     }
@@ -444,13 +484,21 @@ struct CurrentLocalTaskState {
     /// The function's metadata if this is a persistent task. Contains information about arguments
     /// passed to the `#[turbo_tasks::function(...)]` macro.
     function_meta: Option<&'static FunctionMeta>,
+
+    /// The priority the task is executed with. Tasks scheduled by this task inherit it.
+    priority: TaskPriority,
 }
 
 impl CurrentLocalTaskState {
-    fn new(execution_id: ExecutionId, function_meta: Option<&'static FunctionMeta>) -> Self {
+    fn new(
+        execution_id: ExecutionId,
+        function_meta: Option<&'static FunctionMeta>,
+        priority: TaskPriority,
+    ) -> Self {
         Self {
             execution_id,
             function_meta,
+            priority,
         }
     }
 }
@@ -487,6 +535,10 @@ impl<B: Backend + 'static> TurboTasks<B> {
             scheduled_tasks: AtomicUsize::new(0),
             start: Default::default(),
             aggregated_update: Default::default(),
+            running_executions: Default::default(),
+            task_queue: Default::default(),
+            invalidation_tracing: AtomicBool::new(false),
+            invalidation_graph: Default::default(),
             event: Event::new(|| "TurboTasks::event".to_string()),
             event_start: Event::new(|| "TurboTasks::event_start".to_string()),
             event_foreground: Event::new(|| "TurboTasks::event_foreground".to_string()),
@@ -503,6 +555,21 @@ impl<B: Backend + 'static> TurboTasks<B> {
 
     /// Creates a new root task
     pub fn spawn_root_task<T, F, Fut>(&self, functor: F) -> TaskId
+    where
+        T: Send,
+        F: Fn() -> Fut + Send + Sync + Clone + 'static,
+        Fut: Future<Output = Result<Vc<T>>> + Send,
+    {
+        self.spawn_root_task_with_priority(TaskPriority::Normal, functor)
+    }
+
+    /// Creates a new root task that is executed with the given [`TaskPriority`]. All tasks
+    /// scheduled while executing the root task inherit the priority.
+    pub fn spawn_root_task_with_priority<T, F, Fut>(
+        &self,
+        priority: TaskPriority,
+        functor: F,
+    ) -> TaskId
     where
         T: Send,
         F: Fn() -> Fut + Send + Sync + Clone + 'static,
@@ -515,7 +582,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
             })),
             self,
         );
-        self.schedule(id);
+        self.schedule_with_priority(id, priority);
         id
     }
 
@@ -528,6 +595,21 @@ impl<B: Backend + 'static> TurboTasks<B> {
     /// Dependencies will not invalidate the task.
     #[track_caller]
     pub fn spawn_once_task<T, Fut>(&self, future: Fut) -> TaskId
+    where
+        T: Send,
+        Fut: Future<Output = Result<Vc<T>>> + Send + 'static,
+    {
+        self.spawn_once_task_with_priority(TaskPriority::Normal, future)
+    }
+
+    /// Like [`TurboTasks::spawn_once_task`], but executes the task with the given
+    /// [`TaskPriority`].
+    #[track_caller]
+    pub fn spawn_once_task_with_priority<T, Fut>(
+        &self,
+        priority: TaskPriority,
+        future: Fut,
+    ) -> TaskId
     where
         T: Send,
         Fut: Future<Output = Result<Vc<T>>> + Send + 'static,
@@ -536,7 +618,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
             TransientTaskType::Once(Box::pin(async move { Ok(future.await?.node) })),
             self,
         );
-        self.schedule(id);
+        self.schedule_with_priority(id, priority);
         id
     }
 
@@ -751,21 +833,41 @@ impl<B: Backend + 'static> TurboTasks<B> {
         }
     }
 
+    /// Schedules the task with the priority of the currently executing task, or with
+    /// [`TaskPriority::Normal`] when called outside of a task.
     #[track_caller]
     pub(crate) fn schedule(&self, task_id: TaskId) {
+        self.schedule_with_priority(task_id, current_task_priority());
+    }
+
+    /// Schedules the task with the given priority.
+    ///
+    /// Every scheduled task spawns exactly one executor, but that executor doesn't necessarily
+    /// execute the scheduled task. It picks the task with the highest priority from the queue
+    /// instead, so that important work overtakes work that was scheduled earlier.
+    #[track_caller]
+    pub(crate) fn schedule_with_priority(&self, task_id: TaskId, priority: TaskPriority) {
         self.begin_primary_job();
         self.scheduled_tasks.fetch_add(1, Ordering::AcqRel);
+        self.task_queue.push(task_id, priority);
 
         #[cfg(feature = "tokio_tracing")]
         let description = self.backend.get_task_description(task_id);
 
         let this = self.pin();
         let future = async move {
+            let Some((task_id, priority)) = this.task_queue.pop() else {
+                // The task was already picked up by another executor.
+                this.finish_primary_job();
+                return anyhow::Ok(());
+            };
             let mut schedule_again = true;
             while schedule_again {
                 let backend_state = this.backend.new_task_state(task_id);
+                let cancellation = CancellationToken::new();
                 let global_task_state = Arc::new(RwLock::new(CurrentGlobalTaskState::new(
                     task_id,
+                    cancellation.clone(),
                     Box::new(backend_state),
                 )));
                 let local_task_state = CurrentLocalTaskState::new(
@@ -773,6 +875,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
                     this.backend
                         .try_get_function_id(task_id)
                         .map(|func_id| &get_function(func_id).function_meta),
+                    priority,
                 );
                 let single_execution_future = async {
                     if this.stopped.load(Ordering::Acquire) {
@@ -785,7 +888,21 @@ impl<B: Backend + 'static> TurboTasks<B> {
                         return false;
                     };
 
+                    this.running_executions
+                        .insert(task_id, cancellation.clone());
+
                     async {
+                        // The backend ignores the result of stale executions, so the error
+                        // returned on cancellation is never observed by readers.
+                        let future = async {
+                            select! {
+                                biased;
+                                result = future => result,
+                                _ = cancellation.cancelled() => {
+                                    Err(anyhow!("Task execution was cancelled"))
+                                }
+                            }
+                        };
                         let (result, duration, memory_usage) =
                             CaptureFuture::new(AssertUnwindSafe(future).catch_unwind()).await;
 
@@ -802,6 +919,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
                                 Err(_) => None,
                             },
                         });
+                        this.running_executions.remove(&task_id);
                         this.backend.task_execution_result(task_id, result, &*this);
                         let stateful = this.finish_current_task_state();
                         let cell_counters = CURRENT_GLOBAL_TASK_STATE
@@ -843,6 +961,69 @@ impl<B: Backend + 'static> TurboTasks<B> {
         tokio::task::spawn(future);
     }
 
+    /// Enables or disables recording of invalidation causes. Enabling it discards everything that
+    /// was recorded before.
+    pub fn set_invalidation_tracing(&self, enabled: bool) {
+        *self.invalidation_graph.lock().unwrap() = InvalidationGraph::default();
+        self.invalidation_tracing.store(enabled, Ordering::Release);
+    }
+
+    /// Discards all recorded invalidation causes, e.g. after they were reported for an update.
+    pub fn clear_invalidation_trace(&self) {
+        *self.invalidation_graph.lock().unwrap() = InvalidationGraph::default();
+    }
+
+    /// Explains why tasks were recomputed since invalidation tracing was enabled (or last
+    /// cleared). The result is a tree from the external invalidation reasons (e.g. file changes)
+    /// through all tasks that were invalidated in turn.
+    ///
+    /// When `root` is given, only causal chains that end in the `root` task are included.
+    pub fn explain_invalidations(&self, root: Option<TaskId>) -> InvalidationExplanation {
+        self.invalidation_graph
+            .lock()
+            .unwrap()
+            .explain(root, |task| self.backend.get_task_description(task))
+    }
+
+    fn record_invalidation(&self, cause: InvalidationCause, tasks: &[TaskId]) {
+        if tasks.is_empty() || !self.invalidation_tracing.load(Ordering::Acquire) {
+            return;
+        }
+        self.invalidation_graph
+            .lock()
+            .unwrap()
+            .record(cause, tasks.iter().copied());
+    }
+
+    /// Records that `tasks` were invalidated because the currently executing task changed a cell
+    /// or its output.
+    fn record_invalidation_by_current_task(&self, tasks: &[TaskId]) {
+        if !self.invalidation_tracing.load(Ordering::Acquire) {
+            return;
+        }
+        // The backend might invalidate while the task state is locked (e.g. in
+        // `write_task_state`), so this must not block.
+        let cause = CURRENT_GLOBAL_TASK_STATE
+            .try_with(|ts| ts.try_read().ok().map(|ts| ts.task_id))
+            .ok()
+            .flatten()
+            .map_or(InvalidationCause::Unknown, InvalidationCause::Task);
+        self.record_invalidation(cause, tasks);
+    }
+
+    /// Raises the priority of `task` to the priority of the current task if the read has to wait
+    /// for `task` and it hasn't started executing yet.
+    fn promote_if_pending<T>(
+        &self,
+        task: TaskId,
+        result: Result<T, EventListener>,
+    ) -> Result<T, EventListener> {
+        if result.is_err() {
+            self.task_queue.promote(task, current_task_priority());
+        }
+        result
+    }
+
     fn begin_primary_job(&self) {
         if self
             .currently_scheduled_tasks
@@ -1148,6 +1329,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
         });
 
         if !tasks.is_empty() {
+            self.record_invalidation_by_current_task(&tasks);
             self.backend.invalidate_tasks(&tasks, self);
         }
         stateful
@@ -1215,6 +1397,18 @@ impl<B: Backend + 'static> TurboTasksCallApi for TurboTasks<B> {
         })
     }
 
+    #[track_caller]
+    fn run_once_with_priority(
+        &self,
+        priority: TaskPriority,
+        future: Pin<Box<dyn Future<Output = Result<()>> + Send + 'static>>,
+    ) -> TaskId {
+        self.spawn_once_task_with_priority(priority, async move {
+            future.await?;
+            Ok(Completion::new())
+        })
+    }
+
     #[track_caller]
     fn run_once_with_reason(
         &self,
@@ -1253,6 +1447,7 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
 
     #[instrument(level = Level::INFO, skip_all, name = "invalidate")]
     fn invalidate(&self, task: TaskId) {
+        self.record_invalidation(InvalidationCause::Unknown, &[task]);
         self.backend.invalidate_task(task, self);
     }
 
@@ -1260,8 +1455,9 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
     fn invalidate_with_reason(&self, task: TaskId, reason: StaticOrArc<dyn InvalidationReason>) {
         {
             let (_, reason_set) = &mut *self.aggregated_update.lock().unwrap();
-            reason_set.insert(reason);
+            reason_set.insert(reason.clone());
         }
+        self.record_invalidation(InvalidationCause::Reason(reason), &[task]);
         self.backend.invalidate_task(task, self);
     }
 
@@ -1280,6 +1476,7 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
             if tasks.is_empty() {
                 return;
             }
+            self.record_invalidation_by_current_task(&tasks);
             self.backend.invalidate_tasks(&tasks, self);
         });
     }
@@ -1289,8 +1486,13 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         task: TaskId,
         consistency: ReadConsistency,
     ) -> Result<Result<RawVc, EventListener>> {
-        self.backend
-            .try_read_task_output(task, current_task("reading Vcs"), consistency, self)
+        let result = self.backend.try_read_task_output(
+            task,
+            current_task("reading Vcs"),
+            consistency,
+            self,
+        )?;
+        Ok(self.promote_if_pending(task, result))
     }
 
     fn try_read_task_output_untracked(
@@ -1298,8 +1500,10 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         task: TaskId,
         consistency: ReadConsistency,
     ) -> Result<Result<RawVc, EventListener>> {
-        self.backend
-            .try_read_task_output_untracked(task, consistency, self)
+        let result = self
+            .backend
+            .try_read_task_output_untracked(task, consistency, self)?;
+        Ok(self.promote_if_pending(task, result))
     }
 
     fn try_read_task_cell(
@@ -1307,8 +1511,10 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         task: TaskId,
         index: CellId,
     ) -> Result<Result<TypedCellContent, EventListener>> {
-        self.backend
-            .try_read_task_cell(task, index, current_task("reading Vcs"), self)
+        let result =
+            self.backend
+                .try_read_task_cell(task, index, current_task("reading Vcs"), self)?;
+        Ok(self.promote_if_pending(task, result))
     }
 
     fn try_read_task_cell_untracked(
@@ -1316,7 +1522,10 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         task: TaskId,
         index: CellId,
     ) -> Result<Result<TypedCellContent, EventListener>> {
-        self.backend.try_read_task_cell_untracked(task, index, self)
+        let result = self
+            .backend
+            .try_read_task_cell_untracked(task, index, self)?;
+        Ok(self.promote_if_pending(task, result))
     }
 
     fn try_read_own_task_cell_untracked(
@@ -1512,6 +1721,7 @@ impl<B: Backend + 'static> TurboTasksBackendApi<B> for TurboTasks<B> {
         });
         if result.is_err() {
             let _guard = trace_span!("schedule_notify_tasks", count = tasks.len()).entered();
+            self.record_invalidation(InvalidationCause::Unknown, tasks);
             self.backend.invalidate_tasks(tasks, self);
         }
     }
@@ -1527,6 +1737,10 @@ impl<B: Backend + 'static> TurboTasksBackendApi<B> for TurboTasks<B> {
         });
         if result.is_err() {
             let _guard = trace_span!("schedule_notify_tasks_set", count = tasks.len()).entered();
+            if self.invalidation_tracing.load(Ordering::Acquire) {
+                let tasks = tasks.iter().copied().collect::<Vec<_>>();
+                self.record_invalidation(InvalidationCause::Unknown, &tasks);
+            }
             self.backend.invalidate_tasks_set(tasks, self);
         };
     }
@@ -1536,6 +1750,16 @@ impl<B: Backend + 'static> TurboTasksBackendApi<B> for TurboTasks<B> {
         self.schedule(task)
     }
 
+    fn record_dependent_invalidation(&self, tasks: &[TaskId]) {
+        self.record_invalidation_by_current_task(tasks);
+    }
+
+    fn cancel_task_execution(&self, task: TaskId) {
+        if let Some(cancellation) = self.running_executions.get(&task) {
+            cancellation.cancel();
+        }
+    }
+
     fn program_duration_until(&self, instant: Instant) -> Duration {
         instant - self.program_start
     }
@@ -1573,6 +1797,14 @@ impl<B: Backend + 'static> TurboTasksBackendApi<B> for TurboTasks<B> {
     }
 }
 
+/// Returns the [`TaskPriority`] of the currently executing task, or [`TaskPriority::Normal`] when
+/// called outside of a task.
+fn current_task_priority() -> TaskPriority {
+    CURRENT_LOCAL_TASK_STATE
+        .try_with(|ts| ts.priority)
+        .unwrap_or_default()
+}
+
 pub(crate) fn current_task(from: &str) -> TaskId {
     match CURRENT_GLOBAL_TASK_STATE.try_with(|ts| ts.read().unwrap().task_id) {
         Ok(id) => id,
@@ -1604,6 +1836,33 @@ pub async fn run_once<T: Send + 'static>(
     Ok(rx.await?)
 }
 
+/// Like [`run_once`], but executes the future (and all tasks it schedules) with the given
+/// [`TaskPriority`].
+pub async fn run_once_with_priority<T: Send + 'static>(
+    tt: Arc<dyn TurboTasksApi>,
+    priority: TaskPriority,
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let task_id = tt.run_once_with_priority(
+        priority,
+        Box::pin(async move {
+            let result = future.await?;
+            tx.send(result)
+                .map_err(|_| anyhow!("unable to send result"))?;
+            Ok(())
+        }),
+    );
+
+    // INVALIDATION: A Once task will never invalidate, therefore we don't need to
+    // track a dependency
+    let raw_result = read_task_output_untracked(&*tt, task_id, ReadConsistency::Eventual).await?;
+    ReadVcFuture::<Completion>::from(raw_result.into_read_untracked_with_turbo_tasks(&*tt)).await?;
+
+    Ok(rx.await?)
+}
+
 pub async fn run_once_with_reason<T: Send + 'static>(
     tt: Arc<dyn TurboTasksApi>,
     reason: impl InvalidationReason,
@@ -1683,9 +1942,13 @@ pub fn with_turbo_tasks_for_testing<T>(
         CURRENT_GLOBAL_TASK_STATE.scope(
             Arc::new(RwLock::new(CurrentGlobalTaskState::new(
                 current_task,
+                CancellationToken::new(),
                 Box::new(()),
             ))),
-            CURRENT_LOCAL_TASK_STATE.scope(CurrentLocalTaskState::new(execution_id, None), f),
+            CURRENT_LOCAL_TASK_STATE.scope(
+                CurrentLocalTaskState::new(execution_id, None, TaskPriority::Normal),
+                f,
+            ),
         ),
     )
 }
@@ -1741,6 +2004,21 @@ pub fn notify_scheduled_tasks() {
     with_turbo_tasks(|tt| tt.notify_scheduled_tasks())
 }
 
+/// Returns a token that is cancelled once the current task execution has become stale, e.g.
+/// because a dependency was invalidated while it was running. The task will be re-executed with the
+/// new inputs, so any remaining work of this execution is wasted.
+///
+/// Awaiting futures are dropped automatically on cancellation. Long running synchronous work (e.g.
+/// in [`spawn_blocking`]) should clone the token beforehand and check
+/// [`CancellationToken::is_cancelled`] periodically.
+///
+/// Outside of a task, this returns a token that is never cancelled.
+pub fn cancellation_token() -> CancellationToken {
+    CURRENT_GLOBAL_TASK_STATE
+        .try_with(|ts| ts.read().unwrap().cancellation.clone())
+        .unwrap_or_default()
+}
+
 pub fn emit<T: VcValueTrait + Send>(collectible: Vc<T>) {
     with_turbo_tasks(|tt| tt.emit_collectible(T::get_trait_type_id(), collectible.node))
 }
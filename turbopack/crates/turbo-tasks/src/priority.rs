@@ -0,0 +1,138 @@
+use std::hash::BuildHasherDefault;
+
+use crossbeam_queue::SegQueue;
+use dashmap::DashMap;
+use rustc_hash::FxHasher;
+use serde::{Deserialize, Serialize};
+
+use crate::TaskId;
+
+/// The priority of a task execution.
+///
+/// Root tasks are spawned with a priority (see
+/// [`crate::TurboTasks::spawn_root_task_with_priority`]) and every task scheduled while executing a
+/// task inherits the priority of that task. Tasks that are scheduled outside of a task (e.g. on
+/// invalidation) use [`TaskPriority::Normal`].
+///
+/// When an executor thread becomes free, it always picks the scheduled task with the highest
+/// priority. Tasks with the same priority are executed in the order they were scheduled.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum TaskPriority {
+    /// Speculative work that nobody is waiting for, e.g. prefetching or HMR subscriptions.
+    Low,
+    #[default]
+    Normal,
+    /// Work that a user is actively waiting for, e.g. the route requested by the browser.
+    High,
+}
+
+impl TaskPriority {
+    /// All priorities, the most important first.
+    const ALL: [TaskPriority; 3] = [TaskPriority::High, TaskPriority::Normal, TaskPriority::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The queue of scheduled, but not yet started task executions.
+///
+/// Every [`TaskQueue::push`] must be followed by exactly one [`TaskQueue::pop`], which doesn't
+/// necessarily return the pushed task, but the most important task at the time of the pop.
+#[derive(Default)]
+pub(crate) struct TaskQueue {
+    /// A lock-free FIFO queue per priority.
+    queues: [SegQueue<TaskId>; 3],
+    /// The current priority of every task that is scheduled but not started yet. Queue entries
+    /// that don't match this priority are stale (the task was promoted) and are skipped.
+    pending: DashMap<TaskId, TaskPriority, BuildHasherDefault<FxHasher>>,
+}
+
+impl TaskQueue {
+    pub fn push(&self, task_id: TaskId, priority: TaskPriority) {
+        // The task must be pending before it can be popped.
+        self.pending.insert(task_id, priority);
+        self.queues[priority.index()].push(task_id);
+    }
+
+    pub fn pop(&self) -> Option<(TaskId, TaskPriority)> {
+        loop {
+            let mut skipped_stale = false;
+            for priority in TaskPriority::ALL {
+                while let Some(task_id) = self.queues[priority.index()].pop() {
+                    if self
+                        .pending
+                        .remove_if(&task_id, |_, pending| *pending == priority)
+                        .is_some()
+                    {
+                        return Some((task_id, priority));
+                    }
+                    skipped_stale = true;
+                }
+            }
+            // A stale entry might have been promoted into a queue that was already checked,
+            // check again so that the promoted entry isn't missed.
+            if !skipped_stale {
+                return None;
+            }
+        }
+    }
+
+    /// Raises the priority of a task that is still waiting to be executed, e.g. because a task
+    /// with a higher priority is waiting for its result.
+    pub fn promote(&self, task_id: TaskId, priority: TaskPriority) {
+        if priority == TaskPriority::Low {
+            return;
+        }
+        let Some(mut current) = self.pending.get_mut(&task_id) else {
+            return;
+        };
+        if *current >= priority {
+            return;
+        }
+        *current = priority;
+        // The entry is pushed while the pending entry is still locked, so a pop that skips the
+        // old entry always sees the new one.
+        self.queues[priority.index()].push(task_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u32) -> TaskId {
+        TaskId::from(id)
+    }
+
+    #[test]
+    fn pops_highest_priority_first() {
+        let queue = TaskQueue::default();
+        queue.push(task(1), TaskPriority::Low);
+        queue.push(task(2), TaskPriority::Normal);
+        queue.push(task(3), TaskPriority::High);
+        queue.push(task(4), TaskPriority::Normal);
+
+        assert_eq!(queue.pop(), Some((task(3), TaskPriority::High)));
+        assert_eq!(queue.pop(), Some((task(2), TaskPriority::Normal)));
+        assert_eq!(queue.pop(), Some((task(4), TaskPriority::Normal)));
+        assert_eq!(queue.pop(), Some((task(1), TaskPriority::Low)));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn promotes_pending_tasks() {
+        let queue = TaskQueue::default();
+        queue.push(task(1), TaskPriority::Low);
+        queue.push(task(2), TaskPriority::Normal);
+        queue.promote(task(1), TaskPriority::High);
+        // promoting to a lower priority has no effect
+        queue.promote(task(2), TaskPriority::Low);
+
+        assert_eq!(queue.pop(), Some((task(1), TaskPriority::High)));
+        assert_eq!(queue.pop(), Some((task(2), TaskPriority::Normal)));
+        assert_eq!(queue.pop(), None);
+    }
+}
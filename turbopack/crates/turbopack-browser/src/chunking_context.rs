@@ -6,7 +6,9 @@ use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo,
         chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        module_concatenation::ModuleConcatenation,
         module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
+        used_exports::UsedExports,
         Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingContext,
         EntryChunkGroupResult, EvaluatableAssets, MinifyType, ModuleId,
     },
@@ -19,6 +21,7 @@ use turbopack_ecmascript::{
     async_chunk::module::AsyncLoaderModule,
     chunk::EcmascriptChunk,
     manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
+    minify::MinifyOptions,
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
@@ -88,11 +91,26 @@ impl BrowserChunkingContextBuilder {
         self
     }
 
+    pub fn minify_options(mut self, minify_options: Vc<MinifyOptions>) -> Self {
+        self.chunking_context.minify_options = minify_options;
+        self
+    }
+
     pub fn module_id_strategy(mut self, module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>) -> Self {
         self.chunking_context.module_id_strategy = module_id_strategy;
         self
     }
 
+    pub fn module_concatenation(mut self, module_concatenation: Vc<ModuleConcatenation>) -> Self {
+        self.chunking_context.module_concatenation = module_concatenation;
+        self
+    }
+
+    pub fn used_exports(mut self, used_exports: Vc<UsedExports>) -> Self {
+        self.chunking_context.used_exports = used_exports;
+        self
+    }
+
     pub fn build(self) -> Vc<BrowserChunkingContext> {
         BrowserChunkingContext::new(Value::new(self.chunking_context))
     }
@@ -141,10 +159,16 @@ pub struct BrowserChunkingContext {
     runtime_type: RuntimeType,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// The options for minifying Ecmascript chunks
+    minify_options: Vc<MinifyOptions>,
     /// Whether to use manifest chunks for lazy compilation
     manifest_chunks: bool,
     /// The module id strategy to use
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    /// The groups of modules to concatenate into a single scope
+    module_concatenation: Vc<ModuleConcatenation>,
+    /// The exports of modules that are used by any importer
+    used_exports: Vc<UsedExports>,
 }
 
 impl BrowserChunkingContext {
@@ -175,8 +199,11 @@ impl BrowserChunkingContext {
                 environment,
                 runtime_type,
                 minify_type: MinifyType::NoMinify,
+                minify_options: MinifyOptions::default().cell(),
                 manifest_chunks: false,
                 module_id_strategy: Vc::upcast(DevModuleIdStrategy::new()),
+                module_concatenation: ModuleConcatenation::empty(),
+                used_exports: UsedExports::all(),
             },
         }
     }
@@ -200,6 +227,11 @@ impl BrowserChunkingContext {
     pub fn minify_type(&self) -> MinifyType {
         self.minify_type
     }
+
+    /// Returns the options for minifying Ecmascript chunks.
+    pub fn minify_options(&self) -> Vc<MinifyOptions> {
+        self.minify_options
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -376,6 +408,16 @@ impl ChunkingContext for BrowserChunkingContext {
         Vc::cell(self.enable_tracing)
     }
 
+    #[turbo_tasks::function]
+    fn module_concatenation(&self) -> Vc<ModuleConcatenation> {
+        self.module_concatenation
+    }
+
+    #[turbo_tasks::function]
+    fn used_exports(&self) -> Vc<UsedExports> {
+        self.used_exports
+    }
+
     #[turbo_tasks::function]
     async fn chunk_group(
         self: Vc<Self>,
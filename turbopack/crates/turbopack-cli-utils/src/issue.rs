@@ -6,12 +6,16 @@ use std::{
     path::{Path, PathBuf},
     str::FromStr,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use crossterm::style::{StyledContent, Stylize};
 use owo_colors::{OwoColorize as _, Style};
-use turbo_tasks::{RawVc, ReadRef, TransientInstance, TransientValue, TryJoinIterExt, Vc};
+use turbo_tasks::{
+    trace::TraceRawVcs, util::FormatDuration, RawVc, ReadRef, TransientInstance, TransientValue,
+    TryJoinIterExt, Vc,
+};
 use turbo_tasks_fs::{source_context::get_source_context, FileLinesContent};
 use turbopack_core::issue::{
     CapturedIssues, Issue, IssueReporter, IssueSeverity, PlainIssue, PlainIssueProcessingPathItem,
@@ -201,6 +205,31 @@ const ORDERED_GROUPS: &[IssueSeverity] = &[
     IssueSeverity::Info,
 ];
 
+/// How issues are printed.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    TraceRawVcs,
+    clap::ValueEnum,
+)]
+pub enum IssueFormat {
+    /// Grouped and colored for humans.
+    #[default]
+    Human,
+    /// One JSON object per issue and line.
+    Json,
+    /// A SARIF log per batch of reported issues, for code scanning tools. With
+    /// an issue output file, a single SARIF log of all current issues.
+    Sarif,
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Debug, Clone)]
 pub struct LogOptions {
@@ -209,6 +238,12 @@ pub struct LogOptions {
     pub show_all: bool,
     pub log_detail: bool,
     pub log_level: IssueSeverity,
+    pub issue_format: IssueFormat,
+    /// A file the issues are written to as a single document in the
+    /// `issue_format`, which is rewritten with all current issues whenever
+    /// issues are reported. Issues are printed in the human format then.
+    /// Unused with the human format.
+    pub issue_output: Option<PathBuf>,
 }
 
 /// Tracks the state of currently seen issues.
@@ -312,6 +347,11 @@ pub struct ConsoleUi {
 
     #[turbo_tasks(trace_ignore, debug_ignore)]
     seen: Arc<Mutex<SeenIssues>>,
+
+    /// The issues last reported by each source, written to the issue output
+    /// file.
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    reported: Arc<Mutex<HashMap<RawVc, Vec<ReadRef<PlainIssue>>>>>,
 }
 
 impl PartialEq for ConsoleUi {
@@ -327,11 +367,106 @@ impl ConsoleUi {
         ConsoleUi {
             options: (*options).clone(),
             seen: Arc::new(Mutex::new(SeenIssues::new())),
+            reported: Default::default(),
         }
         .cell()
     }
 }
 
+impl ConsoleUi {
+    /// Prints a one-line summary of a finished build, e. g. after a rebuild in
+    /// watch mode. `tasks` is the number of tasks the build executed, and
+    /// `error` the error it failed with.
+    ///
+    /// When issues are printed in a machine readable format, the summary goes
+    /// to stderr, so stdout only contains issues.
+    pub fn report_build(
+        &self,
+        duration: Duration,
+        tasks: Option<usize>,
+        error: Option<&anyhow::Error>,
+    ) {
+        let summary = match error {
+            None => format!(
+                "{event_type} - built in {duration}{tasks}",
+                event_type = "event".style(Style::new().purple()),
+                duration = FormatDuration(duration),
+                tasks = tasks
+                    .map(|tasks| format!(" ({tasks} tasks)"))
+                    .unwrap_or_default(),
+            ),
+            Some(error) => format!(
+                "{event_type} - build failed in {duration}: {error:#}",
+                event_type = "error".style(Style::new().red()),
+                duration = FormatDuration(duration),
+            ),
+        };
+        if self.prints_machine_readable_issues() {
+            eprintln!("{summary}");
+        } else {
+            println!("{summary}");
+        }
+    }
+
+    /// The format issues are printed in. It's the human format when issues are
+    /// written to an output file.
+    fn console_format(&self) -> IssueFormat {
+        if self.issue_output().is_some() {
+            IssueFormat::Human
+        } else {
+            self.options.issue_format
+        }
+    }
+
+    fn prints_machine_readable_issues(&self) -> bool {
+        self.console_format() != IssueFormat::Human
+    }
+
+    /// The issue output file. The human format is always printed.
+    fn issue_output(&self) -> Option<&Path> {
+        self.options
+            .issue_output
+            .as_deref()
+            .filter(|_| self.options.issue_format != IssueFormat::Human)
+    }
+
+    /// Replaces the issues of `source` and rewrites the issue output file with
+    /// the issues of all sources, as a single document.
+    fn write_issue_output(
+        &self,
+        path: &Path,
+        source: RawVc,
+        issues: Vec<ReadRef<PlainIssue>>,
+    ) -> Result<()> {
+        let LogOptions {
+            ref current_dir,
+            ref project_dir,
+            issue_format,
+            ..
+        } = self.options;
+        let mut all_issues = {
+            let mut reported = self.reported.lock().unwrap();
+            reported.insert(source, issues);
+            reported.values().flatten().cloned().collect::<Vec<_>>()
+        };
+        all_issues.sort_by(|a, b| (**a).cmp(&**b));
+
+        let content = match issue_format {
+            IssueFormat::Human => return Ok(()),
+            IssueFormat::Json => all_issues
+                .iter()
+                .map(|issue| format!("{}\n", issue_to_json(issue, project_dir, current_dir)))
+                .collect::<String>(),
+            IssueFormat::Sarif => format!(
+                "{:#}\n",
+                issues_to_sarif(&all_issues, project_dir, current_dir)
+            ),
+        };
+        std::fs::write(path, content)
+            .with_context(|| format!("failed to write issues to {}", path.display()))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl IssueReporter for ConsoleUi {
     #[turbo_tasks::function]
@@ -350,7 +485,9 @@ impl IssueReporter for ConsoleUi {
             log_level,
             ..
         } = self.options;
+        let issue_format = self.console_format();
         let mut grouped_issues: GroupedIssues = HashMap::new();
+        let mut formatted_issues = Vec::new();
 
         let issues = issues
             .iter_with_shortest_path()
@@ -362,12 +499,18 @@ impl IssueReporter for ConsoleUi {
             .try_join()
             .await?;
 
+        let source = source.into_value();
+        if let Some(issue_output) = self.issue_output() {
+            let reported_issues = issues
+                .iter()
+                .filter(|(issue, _)| issue.severity <= log_level)
+                .map(|(issue, _)| issue.clone())
+                .collect();
+            self.write_issue_output(issue_output, source, reported_issues)?;
+        }
+
         let issue_ids = issues.iter().map(|(_, id)| *id).collect::<HashSet<_>>();
-        let mut new_ids = self
-            .seen
-            .lock()
-            .unwrap()
-            .new_ids(source.into_value(), issue_ids);
+        let mut new_ids = self.seen.lock().unwrap().new_ids(source, issue_ids);
 
         let mut has_fatal = false;
         for (plain_issue, id) in issues {
@@ -380,6 +523,13 @@ impl IssueReporter for ConsoleUi {
                 has_fatal = true;
             }
 
+            if issue_format != IssueFormat::Human {
+                if severity <= log_level {
+                    formatted_issues.push(plain_issue);
+                }
+                continue;
+            }
+
             let context_path =
                 make_relative_to_cwd(&plain_issue.file_path, project_dir, current_dir);
             let stage = plain_issue.stage.to_string();
@@ -415,6 +565,25 @@ impl IssueReporter for ConsoleUi {
             issues.push(styled_issue);
         }
 
+        match issue_format {
+            IssueFormat::Human => {}
+            IssueFormat::Json => {
+                for plain_issue in &formatted_issues {
+                    println!("{}", issue_to_json(plain_issue, project_dir, current_dir));
+                }
+                return Ok(Vc::cell(has_fatal));
+            }
+            IssueFormat::Sarif => {
+                if !formatted_issues.is_empty() {
+                    println!(
+                        "{}",
+                        issues_to_sarif(&formatted_issues, project_dir, current_dir)
+                    );
+                }
+                return Ok(Vc::cell(has_fatal));
+            }
+        }
+
         for severity in ORDERED_GROUPS.iter().copied().filter(|l| *l <= log_level) {
             if let Some(severity_map) = grouped_issues.get_mut(&severity) {
                 let severity_map_size = severity_map.len();
@@ -548,6 +717,164 @@ fn show_all_message_with_shown_count(
     }
 }
 
+/// Converts a `[project]/...` path to a `/` separated path relative to the
+/// current directory, as expected by tools consuming the issues.
+fn issue_path_uri(path: &str, project_dir: &Path, current_dir: &Path) -> String {
+    make_relative_to_cwd(path, project_dir, current_dir).replace('\\', "/")
+}
+
+/// Serializes an issue with all of its details. Lines and columns of the
+/// source range are 0-indexed.
+pub fn issue_to_json(
+    plain_issue: &PlainIssue,
+    project_dir: &Path,
+    current_dir: &Path,
+) -> serde_json::Value {
+    let source = plain_issue.source.as_ref().map(|source| {
+        serde_json::json!({
+            "filePath": issue_path_uri(&source.asset.ident, project_dir, current_dir),
+            "range": source.range.map(|(start, end)| serde_json::json!({
+                "start": { "line": start.line, "column": start.column },
+                "end": { "line": end.line, "column": end.column },
+            })),
+        })
+    });
+    let processing_path: &Option<Vec<_>> = &plain_issue.processing_path;
+    let processing_path = processing_path.as_ref().map(|path| {
+        path.iter()
+            .map(|item| {
+                serde_json::json!({
+                    "filePath": item
+                        .file_path
+                        .as_ref()
+                        .map(|path| issue_path_uri(path, project_dir, current_dir)),
+                    "description": &**item.description,
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+
+    serde_json::json!({
+        "severity": plain_issue.severity.as_str(),
+        "stage": plain_issue.stage.to_string(),
+        "filePath": issue_path_uri(&plain_issue.file_path, project_dir, current_dir),
+        "title": plain_issue.title.to_unstyled_string(),
+        "description": plain_issue.description.as_ref().map(StyledString::to_unstyled_string),
+        "detail": plain_issue.detail.as_ref().map(StyledString::to_unstyled_string),
+        "documentationLink": (!plain_issue.documentation_link.is_empty())
+            .then_some(&*plain_issue.documentation_link),
+        "source": source,
+        "processingPath": processing_path,
+    })
+}
+
+fn sarif_level(severity: IssueSeverity) -> &'static str {
+    match severity {
+        IssueSeverity::Bug | IssueSeverity::Fatal | IssueSeverity::Error => "error",
+        IssueSeverity::Warning => "warning",
+        IssueSeverity::Hint | IssueSeverity::Note | IssueSeverity::Suggestion => "note",
+        IssueSeverity::Info => "none",
+    }
+}
+
+/// Identifies the kind of an issue for SARIF: its code if it has one (e.g.
+/// `TP1205`), otherwise its title in kebab case. Quoted parts of the title are
+/// left out, as they usually name the module or request the issue is about.
+fn sarif_rule_id(title: &StyledString) -> String {
+    if let StyledString::Line(parts) = title {
+        if let Some(StyledString::Strong(code)) = parts.first() {
+            return code.to_string();
+        }
+    }
+
+    let mut rule_id = String::new();
+    let mut quote = None;
+    let mut previous = ' ';
+    for c in title.to_unstyled_string().chars() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            // An apostrophe within a word doesn't start a quote.
+            None if matches!(c, '\'' | '"' | '`') && !previous.is_alphanumeric() => quote = Some(c),
+            None if c.is_alphanumeric() => rule_id.extend(c.to_lowercase()),
+            None if !rule_id.is_empty() && !rule_id.ends_with('-') => rule_id.push('-'),
+            None => {}
+        }
+        previous = c;
+    }
+    rule_id.truncate(rule_id.trim_end_matches('-').len());
+    rule_id
+}
+
+/// Serializes issues as a [SARIF](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html)
+/// log with a single run. Issues are identified by their code or title.
+pub fn issues_to_sarif(
+    plain_issues: &[ReadRef<PlainIssue>],
+    project_dir: &Path,
+    current_dir: &Path,
+) -> serde_json::Value {
+    let results = plain_issues
+        .iter()
+        .map(|plain_issue| {
+            let mut message = plain_issue.title.to_unstyled_string();
+            if let Some(description) = &plain_issue.description {
+                message.push_str("\n\n");
+                message.push_str(&description.to_unstyled_string());
+            }
+            let (file_path, region) = match &plain_issue.source {
+                Some(source) => (
+                    &*source.asset.ident,
+                    source.range.map(|(start, end)| {
+                        serde_json::json!({
+                            "startLine": start.line + 1,
+                            "startColumn": start.column + 1,
+                            "endLine": end.line + 1,
+                            "endColumn": end.column + 1,
+                        })
+                    }),
+                ),
+                None => (&plain_issue.file_path, None),
+            };
+            let mut physical_location = serde_json::json!({
+                "artifactLocation": {
+                    "uri": issue_path_uri(file_path, project_dir, current_dir),
+                },
+            });
+            if let Some(region) = region {
+                physical_location["region"] = region;
+            }
+            let issue = issue_to_json(plain_issue, project_dir, current_dir);
+
+            serde_json::json!({
+                "ruleId": sarif_rule_id(&plain_issue.title),
+                "level": sarif_level(plain_issue.severity),
+                "message": { "text": message },
+                "locations": [{ "physicalLocation": physical_location }],
+                "properties": {
+                    "severity": issue["severity"],
+                    "detail": issue["detail"],
+                    "documentationLink": issue["documentationLink"],
+                    "processingPath": issue["processingPath"],
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "turbopack",
+                    "informationUri": "https://turbo.build/pack",
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
 fn render_styled_string_to_ansi(styled_string: &StyledString) -> String {
     match styled_string {
         StyledString::Line(parts) => {
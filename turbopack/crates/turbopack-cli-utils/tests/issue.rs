@@ -0,0 +1,219 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::json;
+use turbo_tasks::{RcStr, ReadRef, Vc};
+use turbo_tasks_fs::{File, FileSystem, VirtualFileSystem};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_cli_utils::issue::{issue_to_json, issues_to_sarif};
+use turbopack_core::{
+    asset::AssetContent,
+    ident::AssetIdent,
+    issue::{
+        analyze::AnalyzeIssue, Issue, IssueSeverity, IssueSource, OptionIssueProcessingPathItems,
+        PlainIssue, StyledString,
+    },
+    source_pos::SourcePos,
+    virtual_source::VirtualSource,
+};
+
+static REGISTRATION: Registration = register!(turbopack_cli_utils::register);
+
+/// An error in `src/index.js` of the project, pointing at the `;` of
+/// `let a = ;`.
+async fn plain_issue(severity: IssueSeverity, code: Option<RcStr>) -> Result<ReadRef<PlainIssue>> {
+    issue_in_index(severity, "Unexpected token", code, true).await
+}
+
+/// An issue in `src/index.js` of the project. With `has_source`, it points at
+/// the `;` of `let a = ;`.
+async fn issue_in_index(
+    severity: IssueSeverity,
+    title: &str,
+    code: Option<RcStr>,
+    has_source: bool,
+) -> Result<ReadRef<PlainIssue>> {
+    let path = VirtualFileSystem::new_with_name("project".into())
+        .root()
+        .join("src/index.js".into());
+    let source = VirtualSource::new(path, AssetContent::file(File::from("let a = ;\n").into()));
+    let issue = AnalyzeIssue {
+        severity: severity.cell(),
+        source_ident: AssetIdent::from_path(path),
+        title: Vc::cell(title.into()),
+        message: StyledString::Line(vec![
+            StyledString::Text("Expected ".into()),
+            StyledString::Code("an expression".into()),
+        ])
+        .cell(),
+        code,
+        source: has_source.then(|| {
+            IssueSource::from_line_col(
+                Vc::upcast(source),
+                SourcePos { line: 0, column: 8 },
+                SourcePos { line: 0, column: 9 },
+            )
+        }),
+    }
+    .cell();
+
+    Ok(Vc::upcast::<Box<dyn Issue>>(issue)
+        .into_plain(OptionIssueProcessingPathItems::none())
+        .await?)
+}
+
+#[tokio::test]
+async fn issue_to_json_includes_unstyled_texts_and_relative_paths() {
+    run(&REGISTRATION, || async {
+        let issue = plain_issue(IssueSeverity::Error, Some("E1".into())).await?;
+
+        assert_eq!(
+            issue_to_json(&issue, Path::new("/app"), Path::new("/app")),
+            json!({
+                "severity": "error",
+                "stage": "analysis",
+                "filePath": "src/index.js",
+                "title": "E1 Unexpected token",
+                "description": "Expected an expression",
+                "detail": null,
+                "documentationLink": null,
+                "source": {
+                    "filePath": "src/index.js",
+                    "range": {
+                        "start": { "line": 0, "column": 8 },
+                        "end": { "line": 0, "column": 9 },
+                    },
+                },
+                "processingPath": null,
+            })
+        );
+
+        // Paths are relative to the current directory, not the project.
+        let json = issue_to_json(&issue, Path::new("/app"), Path::new("/"));
+        assert_eq!(json["filePath"], "app/src/index.js");
+        assert_eq!(json["source"]["filePath"], "app/src/index.js");
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn issues_to_sarif_reports_a_result_per_issue() {
+    run(&REGISTRATION, || async {
+        let issues = vec![
+            plain_issue(IssueSeverity::Error, None).await?,
+            plain_issue(IssueSeverity::Warning, None).await?,
+            plain_issue(IssueSeverity::Info, None).await?,
+        ];
+
+        let sarif = issues_to_sarif(&issues, Path::new("/app"), Path::new("/app"));
+        assert_eq!(sarif["version"], "2.1.0");
+        let runs = sarif["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0]["tool"]["driver"]["name"], "turbopack");
+
+        let results = runs[0]["results"].as_array().unwrap();
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result["level"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["error", "warning", "none"]
+        );
+        assert_eq!(
+            results[0],
+            json!({
+                "ruleId": "unexpected-token",
+                "level": "error",
+                "message": { "text": "Unexpected token\n\nExpected an expression" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/index.js" },
+                        // SARIF lines and columns are 1-indexed.
+                        "region": {
+                            "startLine": 1,
+                            "startColumn": 9,
+                            "endLine": 1,
+                            "endColumn": 10,
+                        },
+                    },
+                }],
+                "properties": {
+                    "severity": "error",
+                    "detail": null,
+                    "documentationLink": null,
+                    "processingPath": null,
+                },
+            })
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn issues_to_sarif_identifies_rules_by_code_or_title() {
+    run(&REGISTRATION, || async {
+        let issues = vec![
+            plain_issue(IssueSeverity::Error, Some("TP1205".into())).await?,
+            issue_in_index(
+                IssueSeverity::Error,
+                "Module not found: Can't resolve './missing'",
+                None,
+                true,
+            )
+            .await?,
+            issue_in_index(
+                IssueSeverity::Error,
+                "Module not found: Can't resolve \"other\"",
+                None,
+                true,
+            )
+            .await?,
+        ];
+
+        let sarif = issues_to_sarif(&issues, Path::new("/app"), Path::new("/app"));
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result["ruleId"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            [
+                "TP1205",
+                "module-not-found-can-t-resolve",
+                "module-not-found-can-t-resolve"
+            ]
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn issues_to_sarif_omits_the_region_of_issues_without_source() {
+    run(&REGISTRATION, || async {
+        let issues =
+            vec![issue_in_index(IssueSeverity::Warning, "Unexpected token", None, false).await?];
+
+        let sarif = issues_to_sarif(&issues, Path::new("/app"), Path::new("/app"));
+        // The issue is reported for its file, without a `region` key.
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["locations"],
+            json!([{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "src/index.js" },
+                },
+            }])
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
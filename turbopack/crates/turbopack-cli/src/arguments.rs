@@ -4,7 +4,9 @@ use std::{
 };
 
 use clap::{Args, Parser};
-use turbopack_cli_utils::issue::IssueSeverityCliOption;
+use turbopack_cli_utils::issue::{IssueFormat, IssueSeverityCliOption};
+
+use crate::contexts::BuildTarget;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -41,6 +43,13 @@ pub struct CommonArguments {
     #[clap(long, value_parser)]
     pub root: Option<PathBuf>,
 
+    /// The config file of the project. Resolved relative to the project's
+    /// directory (`--dir`).
+    /// If no file is provided, `turbopack.config.json` or
+    /// `turbopack.config.toml` will be used if present.
+    #[clap(long, value_parser)]
+    pub config: Option<String>,
+
     /// Filter by issue severity.
     #[clap(short, long)]
     pub log_level: Option<IssueSeverityCliOption>,
@@ -53,6 +62,18 @@ pub struct CommonArguments {
     #[clap(long)]
     pub log_detail: bool,
 
+    /// How issues are printed. `json` prints an object per issue and line,
+    /// `sarif` prints a SARIF log for code scanning tools.
+    #[clap(long, value_enum, default_value_t)]
+    pub issue_format: IssueFormat,
+
+    /// Write issues to this file as a single `json` or `sarif` document, e. g.
+    /// for code scanning tools, and print them in the human format. The file
+    /// is rewritten whenever the issues change. Only used with a
+    /// `--issue-format` other than `human`.
+    #[clap(long, value_parser)]
+    pub issue_output: Option<PathBuf>,
+
     /// Whether to enable full task stats recording in Turbo Engine.
     #[clap(long)]
     pub full_stats: bool,
@@ -89,6 +110,11 @@ pub struct DevArguments {
     #[clap(long)]
     pub no_open: bool,
 
+    /// Print why tasks were recomputed after every update, as a tree from the changed files to
+    /// the recomputed tasks.
+    #[clap(long)]
+    pub explain_invalidations: bool,
+
     // ==
     // = Inherited options from next-dev, need revisit later.
     // ==
@@ -107,4 +133,35 @@ pub struct BuildArguments {
     /// Don't minify build output.
     #[clap(long)]
     pub no_minify: bool,
+
+    /// The environment the build output runs in. Browser builds emit an
+    /// `index.html` for a single entry, or an HTML page named after each entry
+    /// otherwise.
+    #[clap(long, value_enum, default_value_t)]
+    pub target: BuildTarget,
+
+    /// Emit a `.d.ts` declaration file with a source map for each TypeScript
+    /// module of the project. Exports need explicit type annotations, as with
+    /// the `isolatedDeclarations` TypeScript option.
+    #[clap(long)]
+    pub declarations: bool,
+
+    /// Don't concatenate ES modules into the scope of the module importing
+    /// them. Every module is emitted as a separate module factory instead.
+    #[clap(long)]
+    pub no_scope_hoisting: bool,
+
+    /// Keep exports that no module imports. By default, unused exports and
+    /// the declarations only they reference are removed from the output.
+    #[clap(long)]
+    pub keep_unused_exports: bool,
+
+    /// Keep running and rebuild when files change. Only the changed output
+    /// assets are written again.
+    #[clap(short, long)]
+    pub watch: bool,
+
+    /// A shell command to run after each successful build in watch mode.
+    #[clap(long, requires = "watch")]
+    pub on_rebuild: Option<String>,
 }
@@ -1,45 +1,63 @@
 use std::{
     collections::HashSet,
     env::current_dir,
-    path::{PathBuf, MAIN_SEPARATOR},
-    sync::Arc,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use tokio::process::Command;
 use turbo_tasks::{
-    RcStr, ReadConsistency, ResolvedVc, TransientInstance, TryJoinIterExt, TurboTasks, Value, Vc,
+    RcStr, ReadConsistency, ReadRef, ResolvedVc, TransientInstance, TryJoinIterExt, TurboTasks,
+    UpdateInfo, Value, Vc,
 };
-use turbo_tasks_fs::FileSystem;
+use turbo_tasks_env::ProcessEnv;
+use turbo_tasks_fs::{FileContent, FileSystem, FileSystemPath};
 use turbo_tasks_memory::MemoryBackend;
-use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
+use turbopack_browser::BrowserChunkingContext;
+use turbopack_cli_utils::issue::{ConsoleUi, IssueFormat, LogOptions};
 use turbopack_core::{
     asset::Asset,
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableModule, ChunkingContext, ChunkingContextExt,
-        EvaluatableAsset, EvaluatableAssets, MinifyType,
+        availability_info::AvailabilityInfo,
+        module_concatenation::ModuleConcatenation,
+        module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
+        used_exports::UsedExports,
+        ChunkableModule, ChunkingContext, ChunkingContextExt, EvaluatableAsset, EvaluatableAssets,
+        MinifyType,
     },
-    environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
-    issue::{handle_issues, IssueReporter, IssueSeverity},
+    issue::{handle_issues, rules::IssueRulesReporter, IssueReporter, IssueSeverity},
     module::Module,
     output::{OutputAsset, OutputAssets},
-    reference::all_assets_from_entries,
+    reference::{all_assets_from_entries, all_modules_and_affecting_sources},
     reference_type::{EntryReferenceSubType, ReferenceType},
     resolve::{
         origin::{PlainResolveOrigin, ResolveOriginExt},
         parse::Request,
     },
 };
-use turbopack_ecmascript_runtime::RuntimeType;
-use turbopack_env::dotenv::load_env;
+use turbopack_dev_server::html::DevHtmlAsset;
+use turbopack_ecmascript::{
+    concatenation::module_concatenation, declarations::declaration_assets,
+    global_module_id_strategy::deterministic_module_id_strategy, minify::MinifyOptions,
+    property_mangling::property_name_cache, used_exports::used_exports,
+};
+use turbopack_ecmascript_runtime::{
+    get_browser_runtime_code, get_nodejs_runtime_code, RuntimeType,
+};
+use turbopack_env::{dotenv::load_env, schema::ValidatedProcessEnv};
 use turbopack_node::execution_context::ExecutionContext;
 use turbopack_nodejs::NodeJsChunkingContext;
 
 use crate::{
     arguments::BuildArguments,
-    contexts::{get_client_asset_context, get_client_compile_time_info, NodeEnv},
+    config::{load_config, ModuleIds},
+    contexts::{get_build_compile_time_info, get_client_asset_context, BuildTarget, NodeEnv},
     util::{
-        normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, EntryRequests,
-        NormalizedDirs,
+        normalize_dirs, normalize_entries, output_fs, project_path, resolve_entry_requests,
+        EntryRequest, EntryRequests, NormalizedDirs,
     },
 };
 
@@ -48,6 +66,7 @@ pub fn register() {
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
 
+#[derive(Clone)]
 pub struct TurbopackBuildBuilder {
     turbo_tasks: Arc<TurboTasks<MemoryBackend>>,
     project_dir: RcStr,
@@ -57,7 +76,16 @@ pub struct TurbopackBuildBuilder {
     log_level: IssueSeverity,
     show_all: bool,
     log_detail: bool,
+    issue_format: IssueFormat,
+    issue_output: Option<PathBuf>,
     minify_type: MinifyType,
+    target: BuildTarget,
+    declarations: bool,
+    scope_hoisting: bool,
+    remove_unused_exports: bool,
+    config_file: Option<RcStr>,
+    watch: bool,
+    on_rebuild: Option<String>,
 }
 
 impl TurbopackBuildBuilder {
@@ -75,7 +103,16 @@ impl TurbopackBuildBuilder {
             log_level: IssueSeverity::Warning,
             show_all: false,
             log_detail: false,
+            issue_format: IssueFormat::Human,
+            issue_output: None,
             minify_type: MinifyType::Minify,
+            target: BuildTarget::Browser,
+            declarations: false,
+            scope_hoisting: true,
+            remove_unused_exports: true,
+            config_file: None,
+            watch: false,
+            on_rebuild: None,
         }
     }
 
@@ -104,60 +141,258 @@ impl TurbopackBuildBuilder {
         self
     }
 
+    pub fn issue_format(mut self, issue_format: IssueFormat) -> Self {
+        self.issue_format = issue_format;
+        self
+    }
+
+    pub fn issue_output(mut self, issue_output: Option<PathBuf>) -> Self {
+        self.issue_output = issue_output;
+        self
+    }
+
     pub fn minify_type(mut self, minify_type: MinifyType) -> Self {
         self.minify_type = minify_type;
         self
     }
 
+    pub fn target(mut self, target: BuildTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn declarations(mut self, declarations: bool) -> Self {
+        self.declarations = declarations;
+        self
+    }
+
+    pub fn scope_hoisting(mut self, scope_hoisting: bool) -> Self {
+        self.scope_hoisting = scope_hoisting;
+        self
+    }
+
+    pub fn remove_unused_exports(mut self, remove_unused_exports: bool) -> Self {
+        self.remove_unused_exports = remove_unused_exports;
+        self
+    }
+
+    pub fn config_file(mut self, config_file: Option<RcStr>) -> Self {
+        self.config_file = config_file;
+        self
+    }
+
+    pub fn watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    pub fn on_rebuild(mut self, on_rebuild: Option<String>) -> Self {
+        self.on_rebuild = on_rebuild;
+        self
+    }
+
     pub async fn build(self) -> Result<()> {
-        let task = self.turbo_tasks.spawn_once_task::<(), _>(async move {
-            let build_result = build_internal(
-                self.project_dir.clone(),
-                self.root_dir,
-                EntryRequests(
-                    self.entry_requests
-                        .iter()
-                        .cloned()
-                        .map(EntryRequest::resolved_cell)
-                        .collect(),
-                )
-                .cell(),
-                self.browserslist_query,
-                self.minify_type,
-            );
-
-            // Await the result to propagate any errors.
-            build_result.await?;
-
-            let issue_reporter: Vc<Box<dyn IssueReporter>> =
-                Vc::upcast(ConsoleUi::new(TransientInstance::new(LogOptions {
-                    project_dir: PathBuf::from(self.project_dir),
-                    current_dir: current_dir().unwrap(),
-                    show_all: self.show_all,
-                    log_detail: self.log_detail,
-                    log_level: self.log_level,
-                })));
-
-            handle_issues(
-                build_result,
-                issue_reporter,
-                IssueSeverity::Error.into(),
-                None,
-                None,
-            )
-            .await?;
+        if self.watch {
+            return self.build_and_watch().await;
+        }
+
+        let turbo_tasks = self.turbo_tasks.clone();
+        let task = turbo_tasks.spawn_once_task::<(), _>(async move {
+            let console_ui = ConsoleUi::new(TransientInstance::new(self.log_options()));
+            self.build_and_report_issues(console_ui).await?;
 
             Ok(Default::default())
         });
 
-        self.turbo_tasks
+        turbo_tasks
             .wait_task_completion(task, ReadConsistency::Strong)
             .await?;
 
         Ok(())
     }
+
+    /// Builds once and then keeps the build alive, so changes to its inputs
+    /// only recompute and re-emit the affected output assets. Outputs that a
+    /// rebuild doesn't emit anymore, e. g. the chunks of a removed entry, are
+    /// deleted.
+    async fn build_and_watch(self) -> Result<()> {
+        let turbo_tasks = self.turbo_tasks.clone();
+        let on_rebuild = self.on_rebuild.clone();
+        let log_options = TransientInstance::new(self.log_options());
+        let last_result = Arc::new(Mutex::new(None));
+        let written_paths = Arc::new(Mutex::new(HashSet::new()));
+
+        let start = Instant::now();
+        let task = turbo_tasks.spawn_root_task::<(), _, _>({
+            let last_result = last_result.clone();
+            move || {
+                let this = self.clone();
+                let log_options = log_options.clone();
+                let last_result = last_result.clone();
+                let written_paths = written_paths.clone();
+                async move {
+                    let console_ui = ConsoleUi::new(log_options);
+                    // The root task needs to succeed to stay alive, so the error is reported
+                    // separately. It's only recomputed when the error or the issues change.
+                    let error = match this.build_and_report_issues(console_ui).await {
+                        Ok(paths) => {
+                            let paths = paths.iter().copied().collect::<HashSet<_>>();
+                            let removed = {
+                                let mut written_paths = written_paths.lock().unwrap();
+                                let removed = written_paths
+                                    .difference(&paths)
+                                    .copied()
+                                    .collect::<Vec<_>>();
+                                *written_paths = paths;
+                                removed
+                            };
+                            remove_outputs(removed).await.err()
+                        }
+                        Err(error) => Some(error),
+                    };
+                    *last_result.lock().unwrap() = Some((console_ui.await?, error));
+
+                    Ok(Default::default())
+                }
+            }
+        });
+        turbo_tasks
+            .wait_task_completion(task, ReadConsistency::Strong)
+            .await?;
+        let mut duration = start.elapsed();
+        let mut tasks = None;
+
+        loop {
+            let succeeded = {
+                let last_result = last_result.lock().unwrap();
+                let (console_ui, error) = last_result
+                    .as_ref()
+                    .context("the build didn't report its result")?;
+                console_ui.report_build(duration, tasks, error.as_ref());
+                error.is_none()
+            };
+
+            if succeeded {
+                if let Some(command) = &on_rebuild {
+                    run_command(command).await?;
+                }
+            }
+
+            // Drop the updates caused by the build itself, and then wait for
+            // changes to the inputs.
+            turbo_tasks
+                .aggregated_update_info(Duration::ZERO, Duration::ZERO)
+                .await;
+            let UpdateInfo {
+                duration: update_duration,
+                tasks: update_tasks,
+                ..
+            } = turbo_tasks
+                .get_or_wait_aggregated_update_info(Duration::from_millis(100))
+                .await;
+            duration = update_duration;
+            tasks = Some(update_tasks);
+        }
+    }
+
+    fn log_options(&self) -> LogOptions {
+        LogOptions {
+            project_dir: PathBuf::from(&*self.project_dir),
+            current_dir: current_dir().unwrap(),
+            show_all: self.show_all,
+            log_detail: self.log_detail,
+            log_level: self.log_level,
+            issue_format: self.issue_format,
+            issue_output: self.issue_output.clone(),
+        }
+    }
+
+    /// Runs the build and reports its issues. Fails when the build fails or
+    /// has errors. Returns the paths of the written output assets.
+    async fn build_and_report_issues(
+        self,
+        console_ui: Vc<ConsoleUi>,
+    ) -> Result<ReadRef<OutputPaths>> {
+        let build_result = build_internal(
+            self.project_dir.clone(),
+            self.root_dir.clone(),
+            EntryRequests(
+                self.entry_requests
+                    .iter()
+                    .cloned()
+                    .map(EntryRequest::resolved_cell)
+                    .collect(),
+            )
+            .cell(),
+            self.browserslist_query,
+            self.minify_type,
+            self.target,
+            self.declarations,
+            self.scope_hoisting,
+            self.remove_unused_exports,
+            self.config_file.clone(),
+        );
+
+        // Await the result to propagate any errors.
+        let written_paths = build_result.await?;
+
+        let project_path = project_path(self.root_dir, self.project_dir.clone());
+        let issue_rules = load_config(project_path, self.config_file).issue_rules(project_path);
+        let issue_reporter =
+            Vc::upcast(IssueRulesReporter::new(Vc::upcast(console_ui), issue_rules));
+
+        handle_issues(
+            build_result,
+            issue_reporter,
+            IssueSeverity::Error.into(),
+            None,
+            None,
+        )
+        .await?;
+
+        Ok(written_paths)
+    }
+}
+
+/// Deletes output assets that were written by a previous build.
+async fn remove_outputs(paths: Vec<ResolvedVc<FileSystemPath>>) -> Result<()> {
+    paths
+        .into_iter()
+        .map(|path| path.write(FileContent::NotFound.cell()))
+        .try_join()
+        .await?;
+    Ok(())
+}
+
+/// Runs `command` in a shell, printing a warning when it fails.
+async fn run_command(command: &str) -> Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(command)
+        .status()
+        .await
+        .with_context(|| format!("failed to run `{command}`"))?;
+    if !status.success() {
+        println!(
+            "{event_type} - `{command}` exited with {status}",
+            event_type = "warn ".yellow(),
+        );
+    }
+    Ok(())
 }
 
+/// The paths of the output assets written by a build.
+#[turbo_tasks::value(transparent)]
+struct OutputPaths(Vec<ResolvedVc<FileSystemPath>>);
+
 #[turbo_tasks::function]
 async fn build_internal(
     project_dir: RcStr,
@@ -165,75 +400,140 @@ async fn build_internal(
     entry_requests: Vc<EntryRequests>,
     browserslist_query: RcStr,
     minify_type: MinifyType,
-) -> Result<Vc<()>> {
-    let env = Environment::new(Value::new(ExecutionEnvironment::Browser(
-        BrowserEnvironment {
-            dom: true,
-            web_worker: false,
-            service_worker: false,
-            browserslist_query: browserslist_query.clone(),
-        }
-        .into(),
-    )));
+    target: BuildTarget,
+    declarations: bool,
+    scope_hoisting: bool,
+    remove_unused_exports: bool,
+    config_file: Option<RcStr>,
+) -> Result<Vc<OutputPaths>> {
     let output_fs = output_fs(project_dir.clone());
-    let project_fs = project_fs(root_dir.clone());
-    let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
-    let project_relative: RcStr = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/")
-        .into();
-    let project_path = project_fs.root().join(project_relative);
-    let build_output_root = output_fs.root().join("dist".into());
+    let project_path = project_path(root_dir, project_dir.clone());
+    let config = load_config(project_path, config_file);
+
+    let config_value = config.await?;
+    let output = &config_value.output;
+    let build_output_root = output_fs
+        .root()
+        .join(output.path.clone().unwrap_or_else(|| "dist".into()));
+    let chunk_root_path = match &output.chunks {
+        Some(chunks) => build_output_root.join(chunks.clone()),
+        None => build_output_root,
+    };
+    let asset_root_path = match &output.assets {
+        Some(assets) => build_output_root.join(assets.clone()),
+        None => build_output_root,
+    };
 
     let node_env = NodeEnv::Production.cell();
-
-    let chunking_context = Vc::upcast(
-        NodeJsChunkingContext::builder(
-            project_path,
-            build_output_root,
-            build_output_root,
-            build_output_root,
-            build_output_root,
-            env,
-            match *node_env.await? {
-                NodeEnv::Development => RuntimeType::Development,
-                NodeEnv::Production => RuntimeType::Production,
-            },
-        )
-        .minify_type(minify_type)
-        .build(),
+    let runtime_type = match *node_env.await? {
+        NodeEnv::Development => RuntimeType::Development,
+        NodeEnv::Production => RuntimeType::Production,
+    };
+
+    let env: Vc<Box<dyn ProcessEnv>> = Vc::upcast(ValidatedProcessEnv::new(
+        load_env(project_path),
+        config.env_schema(),
+        project_path,
+    ));
+    let compile_time_info = get_build_compile_time_info(
+        target,
+        browserslist_query,
+        node_env,
+        env,
+        project_path,
+        config,
+    );
+    let environment = compile_time_info.environment();
+
+    let create_chunking_context = |module_concatenation: Vc<ModuleConcatenation>,
+                                   used_exports: Vc<UsedExports>,
+                                   minify_options: Vc<MinifyOptions>,
+                                   module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>|
+     -> Vc<Box<dyn ChunkingContext>> {
+        match target {
+            // Edge workers can't load chunks on demand, so they use the same chunk
+            // loading as browsers with all chunks loaded upfront by the host.
+            BuildTarget::Browser | BuildTarget::Edge => Vc::upcast(
+                BrowserChunkingContext::builder(
+                    project_path,
+                    build_output_root,
+                    build_output_root,
+                    chunk_root_path,
+                    asset_root_path,
+                    environment,
+                    runtime_type,
+                )
+                .minify_type(minify_type)
+                .minify_options(minify_options)
+                .module_concatenation(module_concatenation)
+                .used_exports(used_exports)
+                .module_id_strategy(module_id_strategy)
+                .build(),
+            ),
+            BuildTarget::Node => Vc::upcast(
+                NodeJsChunkingContext::builder(
+                    project_path,
+                    build_output_root,
+                    build_output_root,
+                    chunk_root_path,
+                    asset_root_path,
+                    environment,
+                    runtime_type,
+                )
+                .minify_type(minify_type)
+                .minify_options(minify_options)
+                .module_concatenation(module_concatenation)
+                .used_exports(used_exports)
+                .module_id_strategy(module_id_strategy)
+                .build(),
+            ),
+        }
+    };
+
+    // Node.js evaluation at build time, e.g. of webpack loaders, needs no module
+    // concatenation or unused export removal.
+    let execution_context = ExecutionContext::new(
+        project_path,
+        create_chunking_context(
+            ModuleConcatenation::empty(),
+            UsedExports::all(),
+            config.minify_options(None),
+            Vc::upcast(DevModuleIdStrategy::new()),
+        ),
+        env,
+    );
+    let asset_context = get_client_asset_context(
+        project_path,
+        execution_context,
+        compile_time_info,
+        node_env,
+        config,
     );
 
-    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
-    let execution_context =
-        ExecutionContext::new(project_path, chunking_context, load_env(project_path));
-    let asset_context =
-        get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
-
-    let entry_requests = (*entry_requests
+    let entry_requests = entry_requests
         .await?
         .iter()
-        .cloned()
-        .map(|r| async move {
-            Ok(match &*r.await? {
-                EntryRequest::Relative(p) => Request::relative(
-                    Value::new(p.clone().into()),
-                    Default::default(),
-                    Default::default(),
-                    false,
-                ),
-                EntryRequest::Module(m, p) => Request::module(
-                    m.clone(),
-                    Value::new(p.clone().into()),
-                    Default::default(),
-                    Default::default(),
-                ),
-            })
-        })
+        .map(|r| async move { Ok((*r.await?).clone()) })
         .try_join()
-        .await?)
-        .to_vec();
+        .await?;
+    let entry_requests = resolve_entry_requests(entry_requests, config)
+        .await?
+        .into_iter()
+        .map(|r| match r {
+            EntryRequest::Relative(p) => Request::relative(
+                Value::new(p.into()),
+                Default::default(),
+                Default::default(),
+                false,
+            ),
+            EntryRequest::Module(m, p) => Request::module(
+                m,
+                Value::new(p.into()),
+                Default::default(),
+                Default::default(),
+            ),
+        })
+        .collect::<Vec<_>>();
 
     let origin = PlainResolveOrigin::new(asset_context, output_fs.root().join("_".into()));
     let project_dir = &project_dir;
@@ -257,56 +557,154 @@ async fn build_internal(
         .try_join()
         .await?;
 
+    let mangle_properties_options = match minify_type {
+        MinifyType::Minify => *config.mangle_properties_options(project_path).await?,
+        MinifyType::NoMinify => None,
+    };
+    let mangled_properties = match mangle_properties_options {
+        Some(options) => {
+            // The runtime is minified with the chunks, so its properties must keep their
+            // names. They don't depend on the paths the runtime is configured with.
+            let runtime_code = match target {
+                BuildTarget::Browser | BuildTarget::Edge => get_browser_runtime_code(
+                    environment,
+                    Vc::cell(None),
+                    Value::new(runtime_type),
+                    Vc::cell(RcStr::default()),
+                ),
+                BuildTarget::Node => get_nodejs_runtime_code(environment),
+            };
+            Some(
+                property_name_cache(Vc::cell(entries.clone()), runtime_code, *options)
+                    .to_resolved()
+                    .await?,
+            )
+        }
+        None => None,
+    };
+
+    let records_path = output
+        .records_path
+        .as_ref()
+        .map(|records_path| project_path.join(records_path.clone()));
+    let deterministic_module_ids = match output.module_ids {
+        ModuleIds::Named => None,
+        ModuleIds::Deterministic => Some(deterministic_module_id_strategy(
+            Vc::cell(entries.clone()),
+            project_path.to_resolved().await?,
+            records_path,
+        )),
+    };
+
+    let chunking_context = create_chunking_context(
+        if scope_hoisting {
+            module_concatenation(Vc::cell(entries.clone()))
+        } else {
+            ModuleConcatenation::empty()
+        },
+        if remove_unused_exports {
+            used_exports(Vc::cell(entries.clone()))
+        } else {
+            UsedExports::all()
+        },
+        config.minify_options(mangled_properties),
+        match deterministic_module_ids {
+            Some(strategy) => Vc::upcast(strategy),
+            None => Vc::upcast(DevModuleIdStrategy::new()),
+        },
+    );
+
+    let declaration_assets = if declarations {
+        entries
+            .iter()
+            .map(|&entry_module| {
+                declaration_assets(
+                    all_modules_and_affecting_sources(*entry_module),
+                    project_path,
+                    build_output_root,
+                )
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let entry_count = entries.len();
     let entry_chunk_groups = entries
         .into_iter()
         .map(|entry_module| async move {
-            Ok(
-                if let Some(ecmascript) =
-                    ResolvedVc::try_sidecast::<Box<dyn EvaluatableAsset>>(entry_module).await?
-                {
-                    Vc::cell(vec![
-                        Vc::try_resolve_downcast_type::<NodeJsChunkingContext>(chunking_context)
-                            .await?
-                            .unwrap()
-                            .entry_chunk_group(
-                                build_output_root
-                                    .join(
-                                        ecmascript
-                                            .ident()
-                                            .path()
-                                            .file_stem()
-                                            .await?
-                                            .as_deref()
-                                            .unwrap()
-                                            .into(),
-                                    )
-                                    .with_extension("entry.js".into()),
-                                *ResolvedVc::upcast(ecmascript),
-                                EvaluatableAssets::one(*ResolvedVc::upcast(ecmascript)),
-                                OutputAssets::empty(),
-                                Value::new(AvailabilityInfo::Root),
-                            )
-                            .await?
-                            .asset,
-                    ])
-                } else if let Some(chunkable) =
-                    ResolvedVc::try_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
-                {
-                    chunking_context.root_chunk_group_assets(*chunkable)
-                } else {
-                    // TODO convert into a serve-able asset
-                    bail!(
-                        "Entry module is not chunkable, so it can't be used to bootstrap the \
-                         application"
+            let evaluatable =
+                ResolvedVc::try_sidecast::<Box<dyn EvaluatableAsset>>(entry_module).await?;
+            let Some(chunkable) =
+                ResolvedVc::try_sidecast::<Box<dyn ChunkableModule>>(entry_module).await?
+            else {
+                // TODO convert into a serve-able asset
+                bail!(
+                    "Entry module is not chunkable, so it can't be used to bootstrap the \
+                     application"
+                )
+            };
+            let entry_name: RcStr = entry_module
+                .ident()
+                .path()
+                .file_stem()
+                .await?
+                .as_deref()
+                .unwrap()
+                .into();
+
+            Ok(match (target, evaluatable) {
+                (BuildTarget::Browser, _) => {
+                    // A single entry becomes the index page, so the output can be
+                    // served as a single page application.
+                    let html_path = if entry_count == 1 {
+                        build_output_root.join("index.html".into())
+                    } else {
+                        build_output_root.join(format!("{entry_name}.html").into())
+                    };
+                    let html = DevHtmlAsset::new(
+                        html_path,
+                        vec![(
+                            chunkable,
+                            chunking_context,
+                            evaluatable.map(|_| EvaluatableAssets::empty()),
+                        )],
                     )
-                },
-            )
+                    .with_relative_chunk_paths();
+                    Vc::cell(vec![ResolvedVc::upcast(html.to_resolved().await?)])
+                }
+                (BuildTarget::Node, Some(ecmascript)) => Vc::cell(vec![
+                    Vc::try_resolve_downcast_type::<NodeJsChunkingContext>(chunking_context)
+                        .await?
+                        .unwrap()
+                        .entry_chunk_group(
+                            build_output_root
+                                .join(entry_name)
+                                .with_extension("entry.js".into()),
+                            *ResolvedVc::upcast(ecmascript),
+                            EvaluatableAssets::one(*ResolvedVc::upcast(ecmascript)),
+                            OutputAssets::empty(),
+                            Value::new(AvailabilityInfo::Root),
+                        )
+                        .await?
+                        .asset,
+                ]),
+                (BuildTarget::Edge, Some(ecmascript)) => chunking_context
+                    .evaluated_chunk_group_assets(
+                        ecmascript.ident(),
+                        EvaluatableAssets::one(*ecmascript),
+                        Value::new(AvailabilityInfo::Root),
+                    ),
+                (BuildTarget::Node | BuildTarget::Edge, None) => {
+                    chunking_context.root_chunk_group_assets(*chunkable)
+                }
+            })
         })
         .try_join()
         .await?;
 
     let mut chunks: HashSet<ResolvedVc<Box<dyn OutputAsset>>> = HashSet::new();
-    for chunk_group in entry_chunk_groups {
+    for chunk_group in entry_chunk_groups.into_iter().chain(declaration_assets) {
         chunks.extend(&*all_assets_from_entries(chunk_group).await?);
     }
 
@@ -315,8 +713,27 @@ async fn build_internal(
         .map(|c| c.content().write(c.ident().path()))
         .try_join()
         .await?;
+    let written_paths = chunks
+        .iter()
+        .map(|c| c.ident().path().to_resolved())
+        .try_join()
+        .await?;
+
+    // Persist the mangled property names, so the next build reuses them.
+    if let (Some(options), Some(mangled_properties)) =
+        (mangle_properties_options, mangled_properties)
+    {
+        if let Some(name_cache) = options.await?.name_cache {
+            mangled_properties.write(*name_cache).await?;
+        }
+    }
+
+    // Persist the module ids, so the next build keeps them.
+    if let (Some(strategy), Some(records_path)) = (deterministic_module_ids, records_path) {
+        strategy.write_records(records_path).await?;
+    }
 
-    Ok(Default::default())
+    Ok(Vc::cell(written_paths))
 }
 
 pub async fn build(args: &BuildArguments) -> Result<()> {
@@ -333,6 +750,8 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
 
     let mut builder = TurbopackBuildBuilder::new(tt, project_dir, root_dir)
         .log_detail(args.common.log_detail)
+        .issue_format(args.common.issue_format)
+        .issue_output(args.common.issue_output.clone())
         .log_level(
             args.common
                 .log_level
@@ -343,7 +762,14 @@ pub async fn build(args: &BuildArguments) -> Result<()> {
         } else {
             MinifyType::Minify
         })
-        .show_all(args.common.show_all);
+        .show_all(args.common.show_all)
+        .target(args.target)
+        .declarations(args.declarations)
+        .scope_hoisting(!args.no_scope_hoisting)
+        .remove_unused_exports(!args.keep_unused_exports)
+        .config_file(args.common.config.as_deref().map(RcStr::from))
+        .watch(args.watch)
+        .on_rebuild(args.on_rebuild.clone());
 
     for entry in normalize_entries(&args.common.entries) {
         builder = builder.entry_request(EntryRequest::Relative(entry));
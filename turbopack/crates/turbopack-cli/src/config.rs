@@ -0,0 +1,558 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, FxIndexMap, RcStr, ResolvedVc, ValueToString, Vc};
+use turbo_tasks_fs::{FileContent, FileSystemPath};
+use turbopack::module_options::{LoaderRuleItem, OptionWebpackRules, WebpackRules};
+use turbopack_core::{
+    compile_time_info::{CompileTimeDefineValue, CompileTimeDefines, DefineableNameSegment},
+    issue::{
+        rules::{IssueRule, IssueRules},
+        Issue, IssueExt, IssueStage, OptionStyledString, StyledString,
+    },
+};
+use turbopack_ecmascript::{
+    minify::MinifyOptions,
+    property_mangling::{ManglePropertiesOptions, PropertyNameCache},
+    references::constant_value::parse_expression,
+};
+use turbopack_env::schema::{EnvSchema, EnvVarSchema};
+use turbopack_node::transforms::webpack::{WebpackLoaderItem, WebpackLoaderItems};
+
+/// The config files that are looked up in the project directory when no
+/// config file is passed explicitly, in order of precedence.
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["turbopack.config.json", "turbopack.config.toml"];
+
+/// The contents of a `turbopack.config.json` or `turbopack.config.toml` file.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TurbopackConfig {
+    /// The entrypoints of the project, resolved relative to the project's
+    /// directory. Entries passed on the command line take precedence.
+    pub entries: Option<Vec<RcStr>>,
+    pub resolve: ResolveConfig,
+    /// Values to replace free variables and member expressions like
+    /// `process.env.API_URL` with at compile time. Keys starting with
+    /// `typeof ` replace `typeof` expressions, e.g. `typeof window`.
+    #[turbo_tasks(trace_ignore)]
+    pub define: FxIndexMap<RcStr, DefineConfig>,
+    /// Webpack loaders to apply, keyed by a glob matching the resource path.
+    pub rules: FxIndexMap<RcStr, RuleConfig>,
+    /// The browserslist query of the browsers to target.
+    pub browserslist: Option<RcStr>,
+    pub output: OutputConfig,
+    pub minify: MinifyConfig,
+    /// Rules to ignore issues or change their severity.
+    pub issue_rules: Vec<IssueRule>,
+    /// The env variables the project uses, keyed by their name. When set, the
+    /// env is validated against it, and only public variables can be used in
+    /// client code.
+    pub env: FxIndexMap<RcStr, EnvVarSchema>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ResolveConfig {
+    /// Maps requests to other requests, e.g. `"@/*": "./src/*"`. Targets are
+    /// resolved relative to the project's directory.
+    pub alias: FxIndexMap<RcStr, RcStr>,
+    /// The extensions to try when resolving requests without one.
+    pub extensions: Option<Vec<RcStr>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OutputConfig {
+    /// The output directory, relative to the project's directory. Defaults to
+    /// `dist`.
+    pub path: Option<RcStr>,
+    /// The directory chunks are emitted to, relative to `path`.
+    pub chunks: Option<RcStr>,
+    /// The directory static assets are emitted to, relative to `path`.
+    pub assets: Option<RcStr>,
+    /// How the ids of modules are assigned in builds.
+    pub module_ids: ModuleIds,
+    /// A file to persist the module ids in, relative to the project's
+    /// directory. The next build reuses the ids, so the ids of existing
+    /// modules don't change when modules are added or removed. Only used
+    /// with deterministic module ids.
+    pub records_path: Option<RcStr>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleIds {
+    /// The idents of the modules, which are long but readable.
+    #[default]
+    Named,
+    /// Short numbers derived from the idents of the modules relative to the
+    /// project, which are the same when building on another machine.
+    Deterministic,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MinifyConfig {
+    /// Keep the names of classes.
+    pub keep_classnames: bool,
+    /// Keep the names of functions.
+    pub keep_fnames: bool,
+    /// Remove calls to `console.*`.
+    pub drop_console: bool,
+    /// Functions whose calls can be removed when their result is unused, e.g.
+    /// `console.info`.
+    pub pure_funcs: Vec<RcStr>,
+    /// The ECMAScript version the minified code may use, e.g. `2015`.
+    pub ecma: Option<u32>,
+    /// The maximum number of times the compressor runs.
+    pub passes: Option<usize>,
+    /// Mangle the names of properties. Disabled by default, as it breaks code
+    /// that accesses the same property with a computed name.
+    pub mangle_properties: Option<ManglePropertiesConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct ManglePropertiesConfig {
+    /// Only properties matching this regex are mangled, e.g. `^_`.
+    pub regex: RcStr,
+    /// Properties that are never mangled.
+    #[serde(default)]
+    pub reserved: Vec<RcStr>,
+    /// A file to persist the mangled names in, relative to the project's
+    /// directory. The next build reuses the names, so they stay stable between
+    /// deploys.
+    #[serde(default)]
+    pub name_cache: Option<RcStr>,
+}
+
+/// The value of a `define` entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DefineConfig {
+    /// `{ "expression": "globalThis.__APP__" }`, a JavaScript expression that
+    /// is inlined as is.
+    Expression(ExpressionDefine),
+    /// `{ "file": "./VERSION" }`, the contents of a file. The define is
+    /// updated when the file changes.
+    File(FileDefine),
+    /// Any other JSON value, which is inlined as that value.
+    Value(serde_json::Value),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpressionDefine {
+    pub expression: RcStr,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileDefine {
+    /// The file, relative to the project's directory.
+    pub file: RcStr,
+    /// How the contents of the file are inlined.
+    #[serde(default, rename = "as")]
+    pub format: FileDefineFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileDefineFormat {
+    /// As a string, without surrounding whitespace.
+    #[default]
+    String,
+    /// As the JSON value the file contains.
+    Json,
+    /// As the JavaScript expression the file contains.
+    Expression,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum RuleConfig {
+    Loaders(Vec<LoaderConfig>),
+    Options {
+        loaders: Vec<LoaderConfig>,
+        #[serde(default, rename = "as")]
+        rename_as: Option<RcStr>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum LoaderConfig {
+    LoaderName(RcStr),
+    LoaderOptions(WebpackLoaderItem),
+}
+
+/// Splits a `define` key into member expression segments at `.`. A `typeof `
+/// prefix adds a trailing [DefineableNameSegment::TypeOf].
+fn define_name(key: &str) -> Vec<DefineableNameSegment> {
+    match key.strip_prefix("typeof ") {
+        Some(name) => name
+            .trim()
+            .split('.')
+            .map(DefineableNameSegment::from)
+            .chain(std::iter::once(DefineableNameSegment::TypeOf))
+            .collect(),
+        None => key.split('.').map(DefineableNameSegment::from).collect(),
+    }
+}
+
+/// The value of a file define with the contents of `path`.
+async fn file_define_value(
+    path: Vc<FileSystemPath>,
+    format: FileDefineFormat,
+) -> Result<CompileTimeDefineValue> {
+    let FileContent::Content(content) = &*path.read().await? else {
+        bail!("The file {} does not exist", path.to_string().await?);
+    };
+    let content = content.content().to_str()?;
+    let content = content.trim();
+    Ok(match format {
+        FileDefineFormat::String => CompileTimeDefineValue::String(content.into()),
+        FileDefineFormat::Json => {
+            serde_json::from_str::<serde_json::Value>(content)
+                .context("The file is not valid JSON")?;
+            CompileTimeDefineValue::JSON(content.into())
+        }
+        FileDefineFormat::Expression => {
+            parse_expression(content)?;
+            CompileTimeDefineValue::Expression(content.into())
+        }
+    })
+}
+
+/// A `define` entry that can't be inlined, e.g. because its expression doesn't
+/// parse.
+#[turbo_tasks::value(shared)]
+struct DefineIssue {
+    path: ResolvedVc<FileSystemPath>,
+    key: RcStr,
+    message: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DefineIssue {
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Invalid define ".into()),
+            StyledString::Code(self.key.clone()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Config.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        *self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.message.clone()).cell()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl TurbopackConfig {
+    /// Returns the `define` entries as compile time defines. Files of file
+    /// defines are resolved relative to `project_path`. Invalid entries are
+    /// reported as issues and left out.
+    #[turbo_tasks::function]
+    pub async fn defines(
+        &self,
+        project_path: Vc<FileSystemPath>,
+    ) -> Result<Vc<CompileTimeDefines>> {
+        let mut defines = FxIndexMap::default();
+        for (key, value) in &self.define {
+            let (value, path) = match value {
+                DefineConfig::Expression(ExpressionDefine { expression }) => (
+                    parse_expression(expression)
+                        .map(|_| CompileTimeDefineValue::Expression(expression.clone())),
+                    project_path,
+                ),
+                DefineConfig::File(FileDefine { file, format }) => {
+                    let path = project_path.join(file.clone());
+                    (file_define_value(path, *format).await, path)
+                }
+                DefineConfig::Value(serde_json::Value::Bool(value)) => {
+                    (Ok(CompileTimeDefineValue::Bool(*value)), project_path)
+                }
+                DefineConfig::Value(serde_json::Value::String(value)) => (
+                    Ok(CompileTimeDefineValue::String(value.as_str().into())),
+                    project_path,
+                ),
+                DefineConfig::Value(value) => (
+                    Ok(CompileTimeDefineValue::JSON(value.to_string().into())),
+                    project_path,
+                ),
+            };
+            match value {
+                Ok(value) => {
+                    defines.insert(define_name(key), value);
+                }
+                Err(err) => DefineIssue {
+                    path: path.to_resolved().await?,
+                    key: key.clone(),
+                    message: format!("{err:#}").into(),
+                }
+                .cell()
+                .emit(),
+            }
+        }
+        Ok(CompileTimeDefines(defines).cell())
+    }
+
+    #[turbo_tasks::function]
+    pub fn webpack_rules(&self) -> Vc<OptionWebpackRules> {
+        if self.rules.is_empty() {
+            return Vc::cell(None);
+        }
+        let rules = self
+            .rules
+            .iter()
+            .map(|(glob, rule)| {
+                let (loaders, rename_as) = match rule {
+                    RuleConfig::Loaders(loaders) => (loaders, None),
+                    RuleConfig::Options { loaders, rename_as } => (loaders, rename_as.clone()),
+                };
+                let loaders = loaders
+                    .iter()
+                    .map(|loader| match loader {
+                        LoaderConfig::LoaderName(name) => WebpackLoaderItem {
+                            loader: name.clone(),
+                            options: Default::default(),
+                        },
+                        LoaderConfig::LoaderOptions(options) => options.clone(),
+                    })
+                    .collect();
+                (
+                    glob.clone(),
+                    LoaderRuleItem {
+                        loaders: WebpackLoaderItems(loaders).cell(),
+                        rename_as,
+                    },
+                )
+            })
+            .collect();
+        Vc::cell(Some(WebpackRules::cell(rules)))
+    }
+
+    /// The issue rules, with path globs relative to `project_path`.
+    #[turbo_tasks::function]
+    pub fn issue_rules(&self, project_path: ResolvedVc<FileSystemPath>) -> Vc<IssueRules> {
+        IssueRules {
+            rules: self.issue_rules.clone(),
+            project_path: Some(project_path),
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    pub fn env_schema(&self) -> Vc<EnvSchema> {
+        Vc::cell(self.env.clone())
+    }
+
+    /// Returns the options to mangle properties with, if enabled.
+    #[turbo_tasks::function]
+    pub async fn mangle_properties_options(
+        &self,
+        project_path: Vc<FileSystemPath>,
+    ) -> Result<Vc<OptionManglePropertiesOptions>> {
+        let Some(config) = &self.minify.mangle_properties else {
+            return Ok(Vc::cell(None));
+        };
+        let name_cache = match &config.name_cache {
+            Some(name_cache) => Some(project_path.join(name_cache.clone()).to_resolved().await?),
+            None => None,
+        };
+        Ok(Vc::cell(Some(
+            ManglePropertiesOptions {
+                regex: config.regex.clone(),
+                reserved: config.reserved.clone(),
+                name_cache,
+            }
+            .resolved_cell(),
+        )))
+    }
+
+    /// Returns the options to minify Ecmascript chunks with. Properties are
+    /// mangled with the names of `mangled_properties`.
+    #[turbo_tasks::function]
+    pub fn minify_options(
+        &self,
+        mangled_properties: Option<ResolvedVc<PropertyNameCache>>,
+    ) -> Vc<MinifyOptions> {
+        let config = &self.minify;
+        MinifyOptions {
+            keep_classnames: config.keep_classnames,
+            keep_fnames: config.keep_fnames,
+            drop_console: config.drop_console,
+            pure_funcs: config.pure_funcs.clone(),
+            ecma: config.ecma,
+            passes: config.passes,
+            mangled_properties,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionManglePropertiesOptions(Option<ResolvedVc<ManglePropertiesOptions>>);
+
+/// Loads the config of the project in `project_path`. `config_file` is
+/// resolved relative to the project's directory. Without it, the first
+/// existing file of [CONFIG_FILE_NAMES] is used, and the default config if
+/// there is none.
+#[turbo_tasks::function]
+pub async fn load_config(
+    project_path: Vc<FileSystemPath>,
+    config_file: Option<RcStr>,
+) -> Result<Vc<TurbopackConfig>> {
+    let explicit = config_file.is_some();
+    let candidates = match config_file {
+        Some(config_file) => vec![config_file],
+        None => CONFIG_FILE_NAMES.iter().map(|&name| name.into()).collect(),
+    };
+
+    for name in candidates {
+        let path = project_path.join(name.clone());
+        let FileContent::Content(file) = &*path.read().await? else {
+            if explicit {
+                bail!("Config file {} does not exist", path.to_string().await?);
+            }
+            continue;
+        };
+        let content = file.content().to_str()?;
+        let config: TurbopackConfig = if name.ends_with(".toml") {
+            toml::from_str(&content).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&content).map_err(anyhow::Error::from)
+        }
+        .with_context(|| format!("Unable to parse config file {}", name))?;
+        return Ok(config.cell());
+    }
+
+    Ok(TurbopackConfig::default().cell())
+}
+
+#[cfg(test)]
+mod tests {
+    use turbopack_core::{compile_time_info::DefineableNameSegment, issue::rules::IssueRuleAction};
+
+    use super::{define_name, DefineConfig, ExpressionDefine, ModuleIds, TurbopackConfig};
+
+    #[test]
+    fn test_json_and_toml() {
+        let json: TurbopackConfig = serde_json::from_str(
+            r#"{
+                "entries": ["src/index.tsx"],
+                "resolve": { "alias": { "@/*": "./src/*" } },
+                "define": {
+                    "process.env.API_URL": "https://example.com",
+                    "__DEV__": false,
+                    "__APP__": { "expression": "globalThis.app" }
+                },
+                "browserslist": "chrome 100",
+                "output": { "path": "build", "moduleIds": "deterministic" },
+                "minify": { "dropConsole": true },
+                "issueRules": [{ "path": "vendor/**", "action": "ignore" }]
+            }"#,
+        )
+        .unwrap();
+        let toml: TurbopackConfig = toml::from_str(
+            r#"
+                entries = ["src/index.tsx"]
+                browserslist = "chrome 100"
+                issueRules = [{ path = "vendor/**", action = "ignore" }]
+
+                [resolve.alias]
+                "@/*" = "./src/*"
+
+                [define]
+                "process.env.API_URL" = "https://example.com"
+                __DEV__ = false
+                __APP__ = { expression = "globalThis.app" }
+
+                [output]
+                path = "build"
+                moduleIds = "deterministic"
+
+                [minify]
+                dropConsole = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(json, toml);
+
+        assert_eq!(json.entries, Some(vec!["src/index.tsx".into()]));
+        assert_eq!(json.resolve.alias["@/*"], "./src/*");
+        assert_eq!(
+            json.define["process.env.API_URL"],
+            DefineConfig::Value("https://example.com".into())
+        );
+        assert_eq!(json.define["__DEV__"], DefineConfig::Value(false.into()));
+        assert_eq!(
+            json.define["__APP__"],
+            DefineConfig::Expression(ExpressionDefine {
+                expression: "globalThis.app".into()
+            })
+        );
+        assert_eq!(json.output.path.as_deref(), Some("build"));
+        assert_eq!(json.output.module_ids, ModuleIds::Deterministic);
+        assert!(json.minify.drop_console);
+        assert_eq!(json.issue_rules[0].action, IssueRuleAction::Ignore);
+    }
+
+    #[test]
+    fn test_define_name() {
+        assert_eq!(
+            define_name("process.env.API_URL"),
+            vec![
+                DefineableNameSegment::from("process"),
+                DefineableNameSegment::from("env"),
+                DefineableNameSegment::from("API_URL"),
+            ]
+        );
+        assert_eq!(
+            define_name("typeof window"),
+            vec![
+                DefineableNameSegment::from("window"),
+                DefineableNameSegment::TypeOf
+            ]
+        );
+        assert_eq!(
+            define_name("typeof  import.meta"),
+            vec![
+                DefineableNameSegment::from("import"),
+                DefineableNameSegment::from("meta"),
+                DefineableNameSegment::TypeOf
+            ]
+        );
+        // Only a prefix makes it a `typeof` key.
+        assert_eq!(
+            define_name("typeofWindow"),
+            vec![DefineableNameSegment::from("typeofWindow")]
+        );
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config: TurbopackConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, TurbopackConfig::default());
+        assert_eq!(config.output.module_ids, ModuleIds::Named);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(serde_json::from_str::<TurbopackConfig>(r#"{ "entries": "src" }"#).is_err());
+        assert!(toml::from_str::<TurbopackConfig>(r#"output = { moduleIds = "hashed" }"#).is_err());
+    }
+}
@@ -1,35 +1,74 @@
 use std::fmt;
 
 use anyhow::Result;
-use turbo_tasks::{RcStr, ResolvedVc, Value, Vc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, RcStr, ResolvedVc, TaskInput, Value, Vc};
+use turbo_tasks_env::ProcessEnv;
 use turbo_tasks_fs::{FileSystem, FileSystemPath};
 use turbopack::{
     ecmascript::{EcmascriptInputTransform, TreeShakingMode},
     module_options::{
         EcmascriptOptionsContext, JsxTransformOptions, ModuleOptionsContext, ModuleRule,
-        ModuleRuleEffect, RuleCondition,
+        ModuleRuleEffect, RuleCondition, WebpackLoadersOptions,
     },
     ModuleAssetContext,
 };
 use turbopack_browser::react_refresh::assert_can_resolve_react_refresh;
 use turbopack_core::{
     compile_time_defines,
-    compile_time_info::{CompileTimeDefines, CompileTimeInfo},
+    compile_time_info::{CompileTimeDefines, CompileTimeInfo, FreeVarReferences},
     condition::ContextCondition,
     context::AssetContext,
-    environment::{BrowserEnvironment, Environment, ExecutionEnvironment},
-    resolve::options::{ImportMap, ImportMapping},
+    environment::{
+        BrowserEnvironment, ChunkLoading, EdgeWorkerEnvironment, Environment, ExecutionEnvironment,
+        NodeJsEnvironment,
+    },
+    free_var_references,
+    resolve::{
+        alias_map::AliasPattern,
+        options::{ImportMap, ImportMapping},
+    },
 };
 use turbopack_ecmascript_plugins::transform::{
     emotion::{EmotionTransformConfig, EmotionTransformer},
     styled_components::{StyledComponentsTransformConfig, StyledComponentsTransformer},
     styled_jsx::StyledJsxTransformer,
 };
+use turbopack_env::schema::{public_env_defines, public_env_vars};
 use turbopack_node::{
     execution_context::ExecutionContext, transforms::postcss::PostCssTransformOptions,
 };
 use turbopack_resolve::resolve_options_context::ResolveOptionsContext;
 
+use crate::config::TurbopackConfig;
+
+/// The environment the output of a build runs in.
+#[derive(
+    Debug,
+    Default,
+    TaskInput,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    TraceRawVcs,
+    ValueEnum,
+)]
+pub enum BuildTarget {
+    /// A browser, loading chunks with `<script>` tags. Emits an HTML page per
+    /// entry.
+    #[default]
+    Browser,
+    /// Node.js, loading chunks with `require`.
+    Node,
+    /// An edge worker, with all chunks loaded upfront.
+    Edge,
+}
+
 #[turbo_tasks::value(shared)]
 pub enum NodeEnv {
     Development,
@@ -52,6 +91,7 @@ async fn foreign_code_context_condition() -> Result<ContextCondition> {
 #[turbo_tasks::function]
 pub async fn get_client_import_map(
     project_path: ResolvedVc<FileSystemPath>,
+    config: Vc<TurbopackConfig>,
 ) -> Result<Vc<ImportMap>> {
     let mut import_map = ImportMap::empty();
 
@@ -74,19 +114,32 @@ pub async fn get_client_import_map(
         .resolved_cell(),
     );
 
+    for (alias, target) in config.await?.resolve.alias.iter() {
+        import_map.insert_alias(
+            AliasPattern::parse(alias.clone()),
+            ImportMapping::PrimaryAlternative(target.clone(), Some(project_path)).resolved_cell(),
+        );
+    }
+
     Ok(import_map.cell())
 }
 
 #[turbo_tasks::function]
 pub async fn get_client_resolve_options_context(
     project_path: Vc<FileSystemPath>,
+    environment: ResolvedVc<Environment>,
+    config: Vc<TurbopackConfig>,
 ) -> Result<Vc<ResolveOptionsContext>> {
-    let next_client_import_map = get_client_import_map(project_path).to_resolved().await?;
+    let next_client_import_map = get_client_import_map(project_path, config)
+        .to_resolved()
+        .await?;
     let module_options_context = ResolveOptionsContext {
         enable_node_modules: Some(project_path.root().to_resolved().await?),
+        emulate_environment: Some(environment),
         custom_conditions: vec!["development".into()],
+        custom_extensions: config.await?.resolve.extensions.clone(),
         import_map: Some(next_client_import_map),
-        browser: true,
+        browser: !matches!(*environment.chunk_loading().await?, ChunkLoading::NodeJs),
         module: true,
         ..Default::default()
     };
@@ -108,15 +161,25 @@ async fn get_client_module_options_context(
     execution_context: ResolvedVc<ExecutionContext>,
     env: ResolvedVc<Environment>,
     node_env: Vc<NodeEnv>,
+    config: Vc<TurbopackConfig>,
 ) -> Result<Vc<ModuleOptionsContext>> {
+    let enable_webpack_loaders = config.webpack_rules().await?.map(|rules| {
+        WebpackLoadersOptions {
+            rules,
+            loader_runner_package: None,
+        }
+        .cell()
+    });
+
     let module_options_context = ModuleOptionsContext {
         preset_env_versions: Some(env),
         execution_context: Some(execution_context),
         tree_shaking_mode: Some(TreeShakingMode::ReexportsOnly),
+        enable_webpack_loaders,
         ..Default::default()
     };
 
-    let resolve_options_context = get_client_resolve_options_context(project_path);
+    let resolve_options_context = get_client_resolve_options_context(project_path, *env, config);
 
     let enable_react_refresh = matches!(*node_env.await?, NodeEnv::Development)
         && assert_can_resolve_react_refresh(project_path, resolve_options_context)
@@ -185,13 +248,16 @@ pub fn get_client_asset_context(
     execution_context: Vc<ExecutionContext>,
     compile_time_info: Vc<CompileTimeInfo>,
     node_env: Vc<NodeEnv>,
+    config: Vc<TurbopackConfig>,
 ) -> Vc<Box<dyn AssetContext>> {
-    let resolve_options_context = get_client_resolve_options_context(project_path);
+    let resolve_options_context =
+        get_client_resolve_options_context(project_path, compile_time_info.environment(), config);
     let module_options_context = get_client_module_options_context(
         project_path,
         execution_context,
         compile_time_info.environment(),
         node_env,
+        config,
     );
 
     let asset_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
@@ -205,21 +271,50 @@ pub fn get_client_asset_context(
     asset_context
 }
 
-fn client_defines(node_env: &NodeEnv) -> Vc<CompileTimeDefines> {
-    compile_time_defines!(
+#[turbo_tasks::function]
+async fn client_defines(
+    node_env: Vc<NodeEnv>,
+    project_path: Vc<FileSystemPath>,
+    config: Vc<TurbopackConfig>,
+) -> Result<Vc<CompileTimeDefines>> {
+    let mut defines = compile_time_defines!(
         process.turbopack = true,
         process.env.TURBOPACK = true,
-        process.env.NODE_ENV = node_env.to_string()
-    )
-    .cell()
+        process.env.NODE_ENV = node_env.await?.to_string()
+    );
+    defines
+        .0
+        .extend(config.defines(project_path).await?.clone_value());
+    Ok(defines.cell())
 }
 
+/// Inlines the values of `defines` into the code.
+#[turbo_tasks::function]
+async fn define_free_var_references(
+    defines: Vc<CompileTimeDefines>,
+) -> Result<Vc<FreeVarReferences>> {
+    Ok(free_var_references!(..defines.await?.clone_value()).cell())
+}
+
+/// Creates the compile time info for the browser. The browserslist query of
+/// the config takes precedence over `browserslist_query`.
+///
+/// When the config has an env schema, the public variables of `env` are
+/// inlined, and referencing any other variable is an error.
 #[turbo_tasks::function]
 pub async fn get_client_compile_time_info(
     browserslist_query: RcStr,
     node_env: Vc<NodeEnv>,
+    env: Vc<Box<dyn ProcessEnv>>,
+    project_path: Vc<FileSystemPath>,
+    config: Vc<TurbopackConfig>,
 ) -> Result<Vc<CompileTimeInfo>> {
-    Ok(
+    let env_schema = config.env_schema();
+    let defines = client_defines(node_env, project_path, config);
+    let config = config.await?;
+    let browserslist_query = config.browserslist.clone().unwrap_or(browserslist_query);
+
+    let builder =
         CompileTimeInfo::builder(Environment::new(Value::new(ExecutionEnvironment::Browser(
             BrowserEnvironment {
                 dom: true,
@@ -228,8 +323,56 @@ pub async fn get_client_compile_time_info(
                 browserslist_query,
             }
             .into(),
-        ))))
-        .defines(client_defines(&*node_env.await?))
-        .cell(),
+        ))));
+    if config.env.is_empty() {
+        return Ok(builder
+            .defines(defines)
+            .free_var_references(define_free_var_references(defines))
+            .cell());
+    }
+
+    let mut defines = defines.await?.clone_value();
+    defines.extend(public_env_defines(env, env_schema).await?.clone_value());
+    let defines = CompileTimeDefines(defines).cell();
+    // Defined variables like `NODE_ENV` are inlined, so they are fine to read
+    // even if they aren't public.
+    Ok(builder
+        .defines(defines)
+        .free_var_references(define_free_var_references(defines))
+        .public_env_vars(public_env_vars(env_schema))
+        .cell())
+}
+
+/// Creates the compile time info for a build of `target`.
+#[turbo_tasks::function]
+pub async fn get_build_compile_time_info(
+    target: BuildTarget,
+    browserslist_query: RcStr,
+    node_env: Vc<NodeEnv>,
+    env: Vc<Box<dyn ProcessEnv>>,
+    project_path: Vc<FileSystemPath>,
+    config: Vc<TurbopackConfig>,
+) -> Result<Vc<CompileTimeInfo>> {
+    let execution = match target {
+        BuildTarget::Browser => {
+            return Ok(get_client_compile_time_info(
+                browserslist_query,
+                node_env,
+                env,
+                project_path,
+                config,
+            ))
+        }
+        BuildTarget::Node => {
+            ExecutionEnvironment::NodeJsLambda(NodeJsEnvironment::default().into())
+        }
+        BuildTarget::Edge => ExecutionEnvironment::EdgeWorker(EdgeWorkerEnvironment {}.into()),
+    };
+    let defines = client_defines(node_env, project_path, config);
+    Ok(
+        CompileTimeInfo::builder(Environment::new(Value::new(execution)))
+            .defines(defines)
+            .free_var_references(define_free_var_references(defines))
+            .cell(),
     )
 }
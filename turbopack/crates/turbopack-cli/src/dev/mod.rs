@@ -4,7 +4,7 @@ use std::{
     future::{join, Future},
     io::{stdout, Write},
     net::{IpAddr, SocketAddr},
-    path::{PathBuf, MAIN_SEPARATOR},
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -15,13 +15,14 @@ use turbo_tasks::{
     util::{FormatBytes, FormatDuration},
     RcStr, TransientInstance, TurboTasks, UpdateInfo, Value, Vc,
 };
+use turbo_tasks_env::ProcessEnv;
 use turbo_tasks_fs::FileSystem;
 use turbo_tasks_malloc::TurboMalloc;
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::evaluate_context::node_build_environment;
-use turbopack_cli_utils::issue::{ConsoleUi, LogOptions};
+use turbopack_cli_utils::issue::{ConsoleUi, IssueFormat, LogOptions};
 use turbopack_core::{
-    issue::{IssueReporter, IssueSeverity},
+    issue::{rules::IssueRulesReporter, IssueReporter, IssueSeverity},
     resolve::parse::Request,
     server_fs::ServerFileSystem,
 };
@@ -34,16 +35,18 @@ use turbopack_dev_server::{
     DevServer, DevServerBuilder,
 };
 use turbopack_ecmascript_runtime::RuntimeType;
-use turbopack_env::dotenv::load_env;
+use turbopack_env::{dotenv::load_env, schema::ValidatedProcessEnv};
 use turbopack_node::execution_context::ExecutionContext;
 use turbopack_nodejs::NodeJsChunkingContext;
 
 use self::web_entry_source::create_web_entry_source;
 use crate::{
     arguments::DevArguments,
+    config::load_config,
     contexts::NodeEnv,
     util::{
-        normalize_dirs, normalize_entries, output_fs, project_fs, EntryRequest, NormalizedDirs,
+        normalize_dirs, normalize_entries, output_fs, project_path, resolve_entry_requests,
+        EntryRequest, NormalizedDirs,
     },
 };
 
@@ -62,7 +65,10 @@ pub struct TurbopackDevServerBuilder {
     log_level: IssueSeverity,
     show_all: bool,
     log_detail: bool,
+    issue_format: IssueFormat,
+    issue_output: Option<PathBuf>,
     allow_retry: bool,
+    config_file: Option<RcStr>,
 }
 
 impl TurbopackDevServerBuilder {
@@ -86,7 +92,10 @@ impl TurbopackDevServerBuilder {
             log_level: IssueSeverity::Warning,
             show_all: false,
             log_detail: false,
+            issue_format: IssueFormat::Human,
+            issue_output: None,
             allow_retry: false,
+            config_file: None,
         }
     }
 
@@ -135,6 +144,21 @@ impl TurbopackDevServerBuilder {
         self
     }
 
+    pub fn issue_format(mut self, issue_format: IssueFormat) -> TurbopackDevServerBuilder {
+        self.issue_format = issue_format;
+        self
+    }
+
+    pub fn issue_output(mut self, issue_output: Option<PathBuf>) -> TurbopackDevServerBuilder {
+        self.issue_output = issue_output;
+        self
+    }
+
+    pub fn config_file(mut self, config_file: Option<RcStr>) -> TurbopackDevServerBuilder {
+        self.config_file = config_file;
+        self
+    }
+
     pub fn issue_reporter(
         mut self,
         issue_reporter: Box<dyn IssueReporterProvider>,
@@ -196,12 +220,15 @@ impl TurbopackDevServerBuilder {
         let show_all = self.show_all;
         let log_detail: bool = self.log_detail;
         let browserslist_query: RcStr = self.browserslist_query;
+        let config_file = self.config_file;
         let log_args = TransientInstance::new(LogOptions {
             current_dir: current_dir().unwrap(),
             project_dir: PathBuf::from(project_dir.clone()),
             show_all,
             log_detail,
             log_level: self.log_level,
+            issue_format: self.issue_format,
+            issue_output: self.issue_output,
         });
         let entry_requests = TransientInstance::new(self.entry_requests);
         let tasks = turbo_tasks.clone();
@@ -210,6 +237,15 @@ impl TurbopackDevServerBuilder {
             Box::new(move || Vc::upcast(ConsoleUi::new(log_args.clone())))
         });
 
+        let issue_rules = {
+            let root_dir = root_dir.clone();
+            let project_dir = project_dir.clone();
+            let config_file = config_file.clone();
+            move || {
+                let project_path = project_path(root_dir.clone(), project_dir.clone());
+                load_config(project_path, config_file.clone()).issue_rules(project_path)
+            }
+        };
         let source = move || {
             source(
                 root_dir.clone(),
@@ -217,10 +253,16 @@ impl TurbopackDevServerBuilder {
                 entry_requests.clone(),
                 eager_compile,
                 browserslist_query.clone(),
+                config_file.clone(),
             )
         };
 
-        let issue_reporter_arc = Arc::new(move || issue_provider.get_issue_reporter());
+        let issue_reporter_arc = Arc::new(move || {
+            Vc::upcast(IssueRulesReporter::new(
+                issue_provider.get_issue_reporter(),
+                issue_rules(),
+            ))
+        });
         Ok(server.serve(tasks, source, issue_reporter_arc))
     }
 }
@@ -232,19 +274,17 @@ async fn source(
     entry_requests: TransientInstance<Vec<EntryRequest>>,
     eager_compile: bool,
     browserslist_query: RcStr,
-) -> Vc<Box<dyn ContentSource>> {
-    let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
-    let project_relative: RcStr = project_relative
-        .strip_prefix(MAIN_SEPARATOR)
-        .unwrap_or(project_relative)
-        .replace(MAIN_SEPARATOR, "/")
-        .into();
-
-    let output_fs = output_fs(project_dir);
-    let fs = project_fs(root_dir);
-    let project_path: Vc<turbo_tasks_fs::FileSystemPath> = fs.root().join(project_relative);
-
-    let env = load_env(project_path);
+    config_file: Option<RcStr>,
+) -> Result<Vc<Box<dyn ContentSource>>> {
+    let output_fs = output_fs(project_dir.clone());
+    let project_path = project_path(root_dir, project_dir);
+    let config = load_config(project_path, config_file);
+
+    let env: Vc<Box<dyn ProcessEnv>> = Vc::upcast(ValidatedProcessEnv::new(
+        load_env(project_path),
+        config.env_schema(),
+        project_path,
+    ));
     let build_output_root = output_fs.root().join(".turbopack/build".into());
 
     let build_chunking_context = NodeJsChunkingContext::builder(
@@ -263,18 +303,19 @@ async fn source(
 
     let server_fs = Vc::upcast::<Box<dyn FileSystem>>(ServerFileSystem::new());
     let server_root = server_fs.root();
-    let entry_requests = entry_requests
-        .iter()
+    let entry_requests = resolve_entry_requests(entry_requests.to_vec(), config)
+        .await?
+        .into_iter()
         .map(|r| match r {
             EntryRequest::Relative(p) => Request::relative(
-                Value::new(p.clone().into()),
+                Value::new(p.into()),
                 Default::default(),
                 Default::default(),
                 false,
             ),
             EntryRequest::Module(m, p) => Request::module(
-                m.clone(),
-                Value::new(p.clone().into()),
+                m,
+                Value::new(p.into()),
                 Default::default(),
                 Default::default(),
             ),
@@ -290,6 +331,7 @@ async fn source(
         eager_compile,
         NodeEnv::Development.cell(),
         browserslist_query,
+        config,
     );
     let static_source = Vc::upcast(StaticAssetsContentSource::new(
         Default::default(),
@@ -303,11 +345,11 @@ async fn source(
         .cell(),
     );
     let main_source = Vc::upcast(main_source);
-    Vc::upcast(PrefixedRouterContentSource::new(
+    Ok(Vc::upcast(PrefixedRouterContentSource::new(
         Default::default(),
         vec![("__turbopack__".into(), introspect)],
         main_source,
-    ))
+    )))
 }
 
 pub fn register() {
@@ -334,6 +376,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
             .map_or(usize::MAX, |l| l * 1024 * 1024),
     ));
 
+    if args.explain_invalidations {
+        tt.set_invalidation_tracing(true);
+    }
+
     let tt_clone = tt.clone();
 
     let mut server = TurbopackDevServerBuilder::new(tt, project_dir, root_dir)
@@ -341,7 +387,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
         .hostname(args.hostname)
         .port(args.port)
         .log_detail(args.common.log_detail)
+        .issue_format(args.common.issue_format)
+        .issue_output(args.common.issue_output.clone())
         .show_all(args.common.show_all)
+        .config_file(args.common.config.as_deref().map(RcStr::from))
         .log_level(
             args.common
                 .log_level
@@ -448,6 +497,13 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
                         }
                     }
                 }
+                if args.explain_invalidations {
+                    let explanation = tt_clone.explain_invalidations(None);
+                    tt_clone.clear_invalidation_trace();
+                    if !explanation.is_empty() {
+                        print!("{explanation}");
+                    }
+                }
             } else {
                 progress_counter += 1;
                 if args.common.log_detail {
@@ -22,6 +22,7 @@ use turbopack_ecmascript_runtime::RuntimeType;
 use turbopack_node::execution_context::ExecutionContext;
 
 use crate::{
+    config::TurbopackConfig,
     contexts::{
         get_client_asset_context, get_client_compile_time_info, get_client_resolve_options_context,
         NodeEnv,
@@ -54,8 +55,11 @@ pub fn get_client_chunking_context(
 #[turbo_tasks::function]
 pub async fn get_client_runtime_entries(
     project_path: ResolvedVc<FileSystemPath>,
+    environment: Vc<Environment>,
+    config: Vc<TurbopackConfig>,
 ) -> Result<Vc<RuntimeEntries>> {
-    let resolve_options_context = get_client_resolve_options_context(*project_path);
+    let resolve_options_context =
+        get_client_resolve_options_context(*project_path, environment, config);
 
     let mut runtime_entries = Vec::new();
 
@@ -94,17 +98,24 @@ pub async fn create_web_entry_source(
     execution_context: Vc<ExecutionContext>,
     entry_requests: Vec<Vc<Request>>,
     server_root: Vc<FileSystemPath>,
-    _env: Vc<Box<dyn ProcessEnv>>,
+    env: Vc<Box<dyn ProcessEnv>>,
     eager_compile: bool,
     node_env: Vc<NodeEnv>,
     browserslist_query: RcStr,
+    config: Vc<TurbopackConfig>,
 ) -> Result<Vc<Box<dyn ContentSource>>> {
-    let compile_time_info = get_client_compile_time_info(browserslist_query, node_env);
-    let asset_context =
-        get_client_asset_context(project_path, execution_context, compile_time_info, node_env);
+    let compile_time_info =
+        get_client_compile_time_info(browserslist_query, node_env, env, project_path, config);
+    let asset_context = get_client_asset_context(
+        project_path,
+        execution_context,
+        compile_time_info,
+        node_env,
+        config,
+    );
     let chunking_context =
         get_client_chunking_context(project_path, server_root, compile_time_info.environment());
-    let entries = get_client_runtime_entries(project_path);
+    let entries = get_client_runtime_entries(project_path, compile_time_info.environment(), config);
 
     let runtime_entries = entries.resolve_entries(asset_context);
 
@@ -5,6 +5,7 @@
 
 pub mod arguments;
 pub mod build;
+pub mod config;
 pub(crate) mod contexts;
 pub mod dev;
 pub(crate) mod embed_js;
@@ -1,15 +1,20 @@
-use std::{env::current_dir, path::PathBuf};
+use std::{
+    env::current_dir,
+    path::{PathBuf, MAIN_SEPARATOR},
+};
 
 use anyhow::{Context, Result};
 use dunce::canonicalize;
 use turbo_tasks::{RcStr, ResolvedVc, Vc};
-use turbo_tasks_fs::{DiskFileSystem, FileSystem};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+
+use crate::config::TurbopackConfig;
 
 #[turbo_tasks::value(transparent)]
 pub struct EntryRequests(pub Vec<ResolvedVc<EntryRequest>>);
 
 #[turbo_tasks::value(shared)]
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum EntryRequest {
     Relative(RcStr),
     Module(RcStr, RcStr),
@@ -52,11 +57,54 @@ pub fn normalize_dirs(
     })
 }
 
+/// Returns the entries passed on the command line. When there are none, the
+/// entries of the config file are used, see [resolve_entry_requests].
 pub fn normalize_entries(entries: &Option<Vec<String>>) -> Vec<RcStr> {
     entries
         .as_ref()
         .map(|v| v.iter().map(|v| RcStr::from(&**v)).collect())
+        .unwrap_or_default()
+}
+
+/// Returns `entry_requests`, falling back to the entries of the config file
+/// or `src/entry` if there are none.
+pub async fn resolve_entry_requests(
+    entry_requests: Vec<EntryRequest>,
+    config: Vc<TurbopackConfig>,
+) -> Result<Vec<EntryRequest>> {
+    Ok(select_entry_requests(
+        entry_requests,
+        config.await?.entries.as_deref(),
+    ))
+}
+
+/// Entries passed on the command line take precedence over the ones of the
+/// config file.
+fn select_entry_requests(
+    entry_requests: Vec<EntryRequest>,
+    config_entries: Option<&[RcStr]>,
+) -> Vec<EntryRequest> {
+    if !entry_requests.is_empty() {
+        return entry_requests;
+    }
+    config_entries
+        .map(<[RcStr]>::to_vec)
         .unwrap_or_else(|| vec!["src/entry".into()])
+        .into_iter()
+        .map(EntryRequest::Relative)
+        .collect()
+}
+
+/// Returns the project directory in the file system of the root directory.
+#[turbo_tasks::function]
+pub fn project_path(root_dir: RcStr, project_dir: RcStr) -> Vc<FileSystemPath> {
+    let project_relative = project_dir.strip_prefix(&*root_dir).unwrap();
+    let project_relative: RcStr = project_relative
+        .strip_prefix(MAIN_SEPARATOR)
+        .unwrap_or(project_relative)
+        .replace(MAIN_SEPARATOR, "/")
+        .into();
+    project_fs(root_dir).root().join(project_relative)
 }
 
 #[turbo_tasks::function]
@@ -72,3 +120,36 @@ pub async fn output_fs(project_dir: RcStr) -> Result<Vc<Box<dyn FileSystem>>> {
     disk_fs.await?.start_watching(None).await?;
     Ok(Vc::upcast(disk_fs))
 }
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::RcStr;
+
+    use super::{select_entry_requests, EntryRequest};
+
+    fn relative(entries: &[&str]) -> Vec<EntryRequest> {
+        entries
+            .iter()
+            .map(|&entry| EntryRequest::Relative(entry.into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_entry_precedence() {
+        let config_entries: Vec<RcStr> = vec!["src/app.tsx".into()];
+
+        // Entries passed on the command line win over the config file.
+        assert_eq!(
+            select_entry_requests(relative(&["src/cli.tsx"]), Some(&config_entries[..])),
+            relative(&["src/cli.tsx"])
+        );
+        assert_eq!(
+            select_entry_requests(vec![], Some(&config_entries[..])),
+            relative(&["src/app.tsx"])
+        );
+        assert_eq!(
+            select_entry_requests(vec![], None),
+            relative(&["src/entry"])
+        );
+    }
+}
@@ -0,0 +1,624 @@
+#![cfg(test)]
+
+use std::{
+    fs,
+    path::Path,
+    sync::Once,
+    time::{Duration, Instant},
+};
+
+use clap::Parser;
+use regex::Regex;
+use tempfile::TempDir;
+use turbopack_cli::{
+    arguments::{Arguments, BuildArguments},
+    build::build,
+};
+
+static REGISTER: Once = Once::new();
+
+/// Creates a project with a single `src/index.js` entry that imports a
+/// module, so the output consists of more than one chunk item.
+fn project() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(
+        src.join("index.js"),
+        "import { greet } from './greet.js';\nconsole.log(greet('world'));\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("greet.js"),
+        "export function greet(name) { return `hello ${name}`; }\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn build_arguments(dir: &Path, minify: bool, extra_args: &[&str]) -> BuildArguments {
+    let dir = dir.to_str().unwrap();
+    let no_minify = (!minify).then_some("--no-minify");
+    let Arguments::Build(args) = Arguments::parse_from(
+        ["turbopack-cli", "build", "--dir", dir, "--root", dir]
+            .iter()
+            .chain(&no_minify)
+            .chain(extra_args),
+    ) else {
+        unreachable!()
+    };
+    args
+}
+
+async fn build_target(dir: &Path, target: &str) {
+    REGISTER.call_once(turbopack_cli::register);
+    build(&build_arguments(
+        dir,
+        false,
+        &["--target", target, "src/index.js"],
+    ))
+    .await
+    .unwrap();
+}
+
+/// Polls until `condition` holds, failing after a minute.
+async fn wait_for(description: &str, condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    while !condition() {
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting until {description}"
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Lists the files below `dist`, relative to it.
+fn output_files(dist: &Path) -> Vec<String> {
+    fn visit(dir: &Path, prefix: &str, files: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let name = format!("{prefix}{}", entry.file_name().into_string().unwrap());
+            if entry.file_type().unwrap().is_dir() {
+                visit(&entry.path(), &format!("{name}/"), files);
+            } else {
+                files.push(name);
+            }
+        }
+    }
+    let mut files = vec![];
+    visit(dist, "", &mut files);
+    files.sort();
+    files
+}
+
+/// Reads the emitted JavaScript files below `dist` into a single string.
+fn output_code(dist: &Path) -> String {
+    output_files(dist)
+        .iter()
+        .filter(|file| file.ends_with(".js"))
+        .map(|file| fs::read_to_string(dist.join(file)).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn browser_html_references_chunks_relatively() {
+    let dir = project();
+    build_target(dir.path(), "browser").await;
+
+    let dist = dir.path().join("dist");
+    let html = fs::read_to_string(dist.join("index.html")).unwrap();
+    let sources = Regex::new(r#"<script src="([^"]+)"></script>"#)
+        .unwrap()
+        .captures_iter(&html)
+        .map(|captures| captures[1].to_string())
+        .collect::<Vec<_>>();
+
+    assert!(!sources.is_empty(), "no scripts in {html}");
+    for source in sources {
+        let relative = source
+            .strip_prefix("./")
+            .unwrap_or_else(|| panic!("{source} is not relative to the HTML file"));
+        assert!(dist.join(relative).is_file(), "{source} was not emitted");
+    }
+}
+
+#[tokio::test]
+async fn node_emits_entry_file() {
+    let dir = project();
+    build_target(dir.path(), "node").await;
+
+    let dist = dir.path().join("dist");
+    let files = output_files(&dist);
+    assert!(
+        files.contains(&"index.entry.js".to_string()),
+        "no entry file in {files:?}"
+    );
+    assert!(!files.contains(&"index.html".to_string()));
+
+    assert!(
+        files
+            .iter()
+            .filter(|file| file.ends_with(".js"))
+            .any(|file| fs::read_to_string(dist.join(file))
+                .unwrap()
+                .contains("hello ")),
+        "greet is not bundled in {files:?}"
+    );
+}
+
+#[tokio::test]
+async fn edge_emits_chunks_without_html() {
+    let dir = project();
+    build_target(dir.path(), "edge").await;
+
+    let dist = dir.path().join("dist");
+    let files = output_files(&dist);
+    assert!(!files.contains(&"index.html".to_string()));
+    assert!(
+        files.iter().any(|file| file.ends_with(".js")),
+        "no chunks in {files:?}"
+    );
+}
+
+#[tokio::test]
+async fn watch_rebuilds_and_removes_outputs_of_removed_entries() {
+    let dir = project();
+    let src = dir.path().join("src");
+    fs::write(src.join("other.js"), "console.log('other');\n").unwrap();
+    let config = dir.path().join("turbopack.config.json");
+    fs::write(
+        &config,
+        r#"{ "entries": ["src/index.js", "src/other.js"] }"#,
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    let args = build_arguments(dir.path(), false, &["--target", "node", "--watch"]);
+
+    let dist = dir.path().join("dist");
+    let read_output = |name: &str| fs::read_to_string(dist.join(name)).unwrap_or_default();
+    let changes = async {
+        wait_for("both entries are built", || {
+            read_output("index.entry.js").contains("hello ")
+                && read_output("other.entry.js").contains("other")
+        })
+        .await;
+
+        fs::write(
+            src.join("greet.js"),
+            "export function greet(name) { return `goodbye ${name}`; }\n",
+        )
+        .unwrap();
+        wait_for("the changed module is rebuilt", || {
+            read_output("index.entry.js").contains("goodbye ")
+        })
+        .await;
+
+        fs::write(&config, r#"{ "entries": ["src/index.js"] }"#).unwrap();
+        wait_for("the outputs of the removed entry are deleted", || {
+            !dist.join("other.entry.js").exists()
+        })
+        .await;
+        assert!(dist.join("index.entry.js").is_file());
+    };
+
+    tokio::select! {
+        result = build(&args) => panic!("watching stopped: {result:?}"),
+        () = changes => {}
+    }
+}
+
+#[tokio::test]
+async fn mangle_properties_keeps_properties_of_json_and_css_modules() {
+    let dir = project();
+    let src = dir.path().join("src");
+    fs::write(
+        src.join("index.js"),
+        concat!(
+            "import data from './data.json';\n",
+            "import styles from './styles.module.css';\n",
+            "globalThis.state = { _secret: 1 };\n",
+            "console.log(data._version, styles._hidden, globalThis.state._secret);\n",
+        ),
+    )
+    .unwrap();
+    fs::write(src.join("data.json"), r#"{ "_version": 1 }"#).unwrap();
+    fs::write(
+        src.join("styles.module.css"),
+        "._hidden { display: none; }\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("turbopack.config.json"),
+        r#"{ "minify": { "mangleProperties": { "regex": "^_" } } }"#,
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    build(&build_arguments(
+        dir.path(),
+        true,
+        &["--target", "node", "src/index.js"],
+    ))
+    .await
+    .unwrap();
+
+    let dist = dir.path().join("dist");
+    let code = output_code(&dist);
+    assert!(!code.contains("_secret"), "_secret is not mangled");
+    assert!(
+        code.contains("._version"),
+        "_version of the JSON is mangled"
+    );
+    assert!(
+        code.contains("._hidden"),
+        "_hidden of the CSS module is mangled"
+    );
+}
+
+#[tokio::test]
+async fn shared_and_service_workers_are_bundled_at_stable_paths() {
+    let dir = project();
+    let src = dir.path().join("src");
+    fs::write(
+        src.join("index.js"),
+        concat!(
+            "new SharedWorker(new URL('./shared.js', import.meta.url));\n",
+            "navigator.serviceWorker.register(new URL('./sw.js', import.meta.url));\n",
+        ),
+    )
+    .unwrap();
+    fs::write(
+        src.join("shared.js"),
+        "import { greet } from './greet.js';\nconsole.log(greet('shared'));\n",
+    )
+    .unwrap();
+    fs::write(src.join("sw.js"), "console.log('service worker v1');\n").unwrap();
+
+    let dist = dir.path().join("dist");
+    let worker_urls = || {
+        let code = output_code(&dist);
+        Regex::new(r#"__turbopack_export_value__\("([^"]+)"\)"#)
+            .unwrap()
+            .captures_iter(&code)
+            .map(|captures| captures[1].to_string())
+            .collect::<Vec<_>>()
+    };
+    let worker_file = |url: &str| {
+        let name = url.rsplit('/').next().unwrap();
+        let file = output_files(&dist)
+            .into_iter()
+            .find(|file| file.rsplit('/').next() == Some(name))
+            .unwrap_or_else(|| panic!("{url} was not emitted"));
+        fs::read_to_string(dist.join(file)).unwrap()
+    };
+
+    build_target(dir.path(), "browser").await;
+    let urls = worker_urls();
+    assert_eq!(urls.len(), 2, "expected two worker URLs, found {urls:?}");
+    for url in &urls {
+        assert!(
+            worker_file(url).starts_with("self.TURBOPACK_WORKER_LOCATION = self.location.origin;"),
+            "{url} doesn't set the location chunks are loaded from"
+        );
+    }
+    let sw_url = urls
+        .iter()
+        .find(|url| worker_file(url).contains("service worker v1"))
+        .expect("the service worker is not bundled")
+        .clone();
+    assert!(
+        urls.iter().any(|url| worker_file(url).contains("hello ")),
+        "greet is not bundled into the shared worker"
+    );
+
+    // Changing the worker must not change its URL, so the browser updates the
+    // registered service worker in place.
+    fs::write(src.join("sw.js"), "console.log('service worker v2');\n").unwrap();
+    fs::remove_dir_all(&dist).unwrap();
+    build_target(dir.path(), "browser").await;
+    assert_eq!(worker_urls(), urls);
+    assert!(worker_file(&sw_url).contains("service worker v2"));
+}
+
+#[tokio::test]
+async fn css_module_values_are_imported_from_other_stylesheets() {
+    let dir = project();
+    let src = dir.path().join("src");
+    fs::write(
+        src.join("index.js"),
+        concat!(
+            "import { button, spacing } from './button.module.css';\n",
+            "console.log(button, spacing);\n",
+        ),
+    )
+    .unwrap();
+    fs::write(src.join("tokens.css"), "@value spacing: 12px;\n").unwrap();
+    fs::write(
+        src.join("button.module.css"),
+        "@value spacing from './tokens.css';\n.button { padding: spacing; }\n",
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    let args = build_arguments(
+        dir.path(),
+        false,
+        &["--target", "browser", "--watch", "src/index.js"],
+    );
+
+    let dist = dir.path().join("dist");
+    let output_css = || {
+        output_files(&dist)
+            .iter()
+            .filter(|file| file.ends_with(".css"))
+            .map(|file| fs::read_to_string(dist.join(file)).unwrap_or_default())
+            .collect::<String>()
+    };
+    let changes = async {
+        wait_for("the imported value is substituted and exported", || {
+            dist.is_dir()
+                && output_css().contains("padding: 12px")
+                && output_code(&dist).contains(r#""spacing": "12px""#)
+        })
+        .await;
+        assert!(!output_css().contains("@value"));
+
+        // The importing stylesheet depends on the values of the imported one.
+        fs::write(src.join("tokens.css"), "@value spacing: 16px;\n").unwrap();
+        wait_for("the importer is rebuilt with the changed value", || {
+            output_css().contains("padding: 16px")
+                && output_code(&dist).contains(r#""spacing": "16px""#)
+        })
+        .await;
+    };
+
+    tokio::select! {
+        result = build(&args) => panic!("watching stopped: {result:?}"),
+        () = changes => {}
+    }
+}
+
+#[tokio::test]
+async fn scope_hoisting_merges_modules_into_one_factory() {
+    REGISTER.call_once(turbopack_cli::register);
+    let factory = Regex::new(r#"\n"\[project\]/src/[^"]+": "#).unwrap();
+    for (extra_args, factories) in [(&[][..], 1), (&["--no-scope-hoisting"][..], 2)] {
+        let dir = project();
+        let mut args = extra_args.to_vec();
+        args.push("src/index.js");
+        build(&build_arguments(dir.path(), false, &args))
+            .await
+            .unwrap();
+
+        let code = output_code(&dir.path().join("dist"));
+        assert_eq!(
+            factory.find_iter(&code).count(),
+            factories,
+            "{extra_args:?}: {code}"
+        );
+        assert!(code.contains("hello "), "greet is not bundled in {code}");
+    }
+}
+
+#[tokio::test]
+async fn unused_barrel_reexports_are_removed() {
+    let dir = project();
+    let src = dir.path().join("src");
+    fs::write(
+        src.join("index.js"),
+        "import { a } from './barrel.js';\nconsole.log(a);\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("barrel.js"),
+        "export { a, b } from './ab.js';\nexport * from './c.js';\n",
+    )
+    .unwrap();
+    fs::write(
+        src.join("ab.js"),
+        concat!(
+            "import { format } from './format.js';\n",
+            "export const a = 'used a';\n",
+            "export function b() { return format('unused b'); }\n",
+        ),
+    )
+    .unwrap();
+    fs::write(src.join("c.js"), "export const c = 'unused c';\n").unwrap();
+    fs::write(
+        src.join("format.js"),
+        "export function format(value) { return `formatted ${value}`; }\n",
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    for (extra_args, keeps_unused) in [
+        (&[][..], false),
+        (&["--no-scope-hoisting"][..], false),
+        (&["--keep-unused-exports"][..], true),
+    ] {
+        let mut args = extra_args.to_vec();
+        args.push("src/index.js");
+        build(&build_arguments(dir.path(), false, &args))
+            .await
+            .unwrap();
+
+        let code = output_code(&dir.path().join("dist"));
+        assert!(code.contains("used a"), "{extra_args:?}: a is missing");
+        for unused in ["unused b", "unused c", "formatted "] {
+            assert_eq!(
+                code.contains(unused),
+                keeps_unused,
+                "{extra_args:?}: {unused:?} in {code}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn reading_non_public_env_vars_is_reported() {
+    let dir = project();
+    let src = dir.path().join("src");
+    fs::write(
+        src.join("index.js"),
+        concat!(
+            "import 'pkg';\n",
+            "console.log(process.env.NODE_ENV, process.env.PUBLIC_URL);\n",
+            "console.log(process.env.SECRET, process.env['API_TOKEN']);\n",
+            "process.env.SECRET = 'secret';\n",
+        ),
+    )
+    .unwrap();
+    let pkg = dir.path().join("node_modules/pkg");
+    fs::create_dir_all(&pkg).unwrap();
+    fs::write(pkg.join("package.json"), r#"{ "main": "index.js" }"#).unwrap();
+    fs::write(
+        pkg.join("index.js"),
+        "console.log(process.env.PKG_DEBUG);\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("turbopack.config.json"),
+        r#"{ "env": { "PUBLIC_URL": { "public": true } } }"#,
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    let issues_path = dir.path().join("issues.json");
+    let result = build(&build_arguments(
+        dir.path(),
+        false,
+        &[
+            "--issue-format",
+            "json",
+            "--issue-output",
+            issues_path.to_str().unwrap(),
+            "src/index.js",
+        ],
+    ))
+    .await;
+    assert!(result.is_err(), "reading SECRET doesn't fail the build");
+
+    let mut reported = fs::read_to_string(&issues_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter_map(|issue| {
+            let title = issue["title"].as_str().unwrap();
+            let name = title
+                .split_once("`process.env.")?
+                .1
+                .split_once("` is not a public environment variable")?
+                .0
+                .to_string();
+            let file_path = issue["filePath"].as_str().unwrap();
+            let file = if file_path.ends_with("node_modules/pkg/index.js") {
+                "pkg"
+            } else {
+                assert!(file_path.ends_with("src/index.js"), "{issue}");
+                "index"
+            };
+            Some((
+                name,
+                file,
+                issue["severity"].as_str().unwrap().to_string(),
+                issue["source"]["range"]["start"]["line"].as_u64().unwrap(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    reported.sort();
+
+    // The assignment to `process.env.SECRET` on line 3 isn't reported, and
+    // reads in packages are only warnings.
+    assert_eq!(
+        reported,
+        [
+            ("API_TOKEN".to_string(), "index", "error".to_string(), 2),
+            ("PKG_DEBUG".to_string(), "pkg", "warning".to_string(), 0),
+            ("SECRET".to_string(), "index", "error".to_string(), 2),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn defines_are_inlined() {
+    let dir = project();
+    fs::write(
+        dir.path().join("src/index.js"),
+        concat!(
+            "console.log(DEFINED_EXPRESSION.name, DEFINED_EXPRESSION.tags);\n",
+            "DEFINED_METHOD('called');\n",
+            "if (typeof DEFINED_TYPEOF === 'object') { console.log('DEFINED_TYPEOF'); }\n",
+        ),
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("turbopack.config.json"),
+        r#"{
+            "define": {
+                "DEFINED_EXPRESSION": { "expression": "{ name: \"app\", tags: [1, 2] }" },
+                "DEFINED_METHOD": { "expression": "globalThis.logger.log" },
+                "typeof DEFINED_TYPEOF": "object"
+            }
+        }"#,
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    build(&build_arguments(dir.path(), false, &["src/index.js"]))
+        .await
+        .unwrap();
+
+    let code = output_code(&dir.path().join("dist"));
+    assert!(!code.contains("DEFINED_EXPRESSION"), "{code}");
+    assert!(code.contains(r#"name: "app""#), "{code}");
+    // The method is called on `globalThis.logger`, not through a sequence
+    // expression that would drop `this`.
+    assert!(
+        Regex::new(r#"globalThis\.logger\.log\)?\(['"]called['"]\)"#)
+            .unwrap()
+            .is_match(&code),
+        "{code}"
+    );
+    assert!(!code.contains("typeof DEFINED_TYPEOF"), "{code}");
+    assert!(
+        Regex::new(r#"\("TURBOPACK compile-time value", "object"\) === ['"]object['"]"#)
+            .unwrap()
+            .is_match(&code),
+        "{code}"
+    );
+}
+
+#[tokio::test]
+async fn file_defines_are_updated_when_the_file_changes() {
+    let dir = project();
+    fs::write(dir.path().join("src/index.js"), "console.log(VERSION);\n").unwrap();
+    fs::write(dir.path().join("VERSION"), "1.0.0\n").unwrap();
+    fs::write(
+        dir.path().join("turbopack.config.json"),
+        r#"{ "define": { "VERSION": { "file": "./VERSION" } } }"#,
+    )
+    .unwrap();
+
+    REGISTER.call_once(turbopack_cli::register);
+    let args = build_arguments(dir.path(), false, &["--watch", "src/index.js"]);
+
+    let dist = dir.path().join("dist");
+    let inlined = |version: &str| {
+        dist.is_dir()
+            && output_code(&dist)
+                .contains(&format!(r#"("TURBOPACK compile-time value", "{version}")"#))
+    };
+    let changes = async {
+        wait_for("the contents of the file are inlined", || inlined("1.0.0")).await;
+
+        fs::write(dir.path().join("VERSION"), "1.1.0\n").unwrap();
+        wait_for("the changed contents are inlined", || inlined("1.1.0")).await;
+    };
+
+    tokio::select! {
+        result = build(&args) => panic!("watching stopped: {result:?}"),
+        () = changes => {}
+    }
+}
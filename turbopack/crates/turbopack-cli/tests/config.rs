@@ -0,0 +1,75 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::{RcStr, Vc};
+use turbo_tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_cli::config::load_config;
+
+static REGISTRATION: Registration = register!(turbopack_cli::register);
+
+fn fixture(name: &str) -> Vc<FileSystemPath> {
+    DiskFileSystem::new(
+        "fixtures".into(),
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/config").into(),
+        vec![],
+    )
+    .root()
+    .join(name.into())
+}
+
+async fn browserslist(project: &str, config_file: Option<&str>) -> Result<Option<RcStr>> {
+    Ok(load_config(fixture(project), config_file.map(RcStr::from))
+        .await?
+        .browserslist
+        .clone())
+}
+
+#[tokio::test]
+async fn json_takes_precedence_over_toml() {
+    run(&REGISTRATION, || async {
+        assert_eq!(
+            browserslist("both", None).await?.as_deref(),
+            Some("chrome 100")
+        );
+        assert_eq!(
+            browserslist("toml", None).await?.as_deref(),
+            Some("safari 16")
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn explicit_config_file() {
+    run(&REGISTRATION, || async {
+        // An explicit file is used even when the default files exist.
+        assert_eq!(
+            browserslist("both", Some("turbopack.config.toml"))
+                .await?
+                .as_deref(),
+            Some("firefox 100")
+        );
+        assert_eq!(
+            browserslist("none", Some("custom.json")).await?.as_deref(),
+            Some("edge 100")
+        );
+        assert!(browserslist("none", Some("missing.json")).await.is_err());
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn default_config() {
+    run(&REGISTRATION, || async {
+        assert_eq!(browserslist("none", None).await?, None);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
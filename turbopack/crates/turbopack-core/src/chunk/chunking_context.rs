@@ -4,7 +4,10 @@ use turbo_tasks::{trace::TraceRawVcs, RcStr, ResolvedVc, TaskInput, Upcast, Valu
 use turbo_tasks_fs::FileSystemPath;
 use turbo_tasks_hash::DeterministicHash;
 
-use super::{availability_info::AvailabilityInfo, ChunkableModule, EvaluatableAssets};
+use super::{
+    availability_info::AvailabilityInfo, module_concatenation::ModuleConcatenation,
+    used_exports::UsedExports, ChunkableModule, EvaluatableAssets,
+};
 use crate::{
     chunk::{ChunkItem, ModuleId},
     environment::Environment,
@@ -88,6 +91,19 @@ pub trait ChunkingContext {
         Vc::cell(false)
     }
 
+    /// The groups of modules that are concatenated into a single scope. Only
+    /// used for production builds, where no module needs to be replaced
+    /// individually.
+    fn module_concatenation(self: Vc<Self>) -> Vc<ModuleConcatenation> {
+        ModuleConcatenation::empty()
+    }
+
+    /// The exports of modules that are used by any importer. Unused exports
+    /// are omitted from the generated code. Only used for production builds.
+    fn used_exports(self: Vc<Self>) -> Vc<UsedExports> {
+        UsedExports::all()
+    }
+
     fn async_loader_chunk_item(
         &self,
         module: Vc<Box<dyn ChunkableModule>>,
@@ -6,8 +6,10 @@ pub(crate) mod chunking_context;
 pub(crate) mod containment_tree;
 pub(crate) mod data;
 pub(crate) mod evaluate;
+pub mod module_concatenation;
 pub mod module_id_strategies;
 pub mod optimize;
+pub mod used_exports;
 
 use std::{
     collections::{HashMap, HashSet},
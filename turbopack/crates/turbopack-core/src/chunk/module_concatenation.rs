@@ -0,0 +1,41 @@
+use turbo_tasks::{FxIndexMap, ResolvedVc, Vc};
+
+use crate::module::{Module, Modules};
+
+/// Groups of modules that are emitted into a single scope, keyed by the
+/// module through which the group is entered.
+///
+/// The root module of a group is the only module of the group that is
+/// emitted as a chunk item. All other modules of the group are inlined into
+/// its content, so they don't have a module id at runtime.
+#[turbo_tasks::value]
+pub struct ModuleConcatenation {
+    groups: FxIndexMap<ResolvedVc<Box<dyn Module>>, ResolvedVc<Modules>>,
+}
+
+impl ModuleConcatenation {
+    pub fn new(groups: FxIndexMap<ResolvedVc<Box<dyn Module>>, ResolvedVc<Modules>>) -> Vc<Self> {
+        ModuleConcatenation { groups }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleConcatenation {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        ModuleConcatenation {
+            groups: FxIndexMap::default(),
+        }
+        .cell()
+    }
+
+    /// Returns the modules of the group rooted at `module`, including `module`
+    /// itself. Returns an empty list when `module` isn't the root of a group.
+    #[turbo_tasks::function]
+    pub fn concatenated_modules(&self, module: ResolvedVc<Box<dyn Module>>) -> Vc<Modules> {
+        match self.groups.get(&module) {
+            Some(modules) => **modules,
+            None => Modules::empty(),
+        }
+    }
+}
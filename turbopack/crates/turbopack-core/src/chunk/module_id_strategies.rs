@@ -1,5 +1,9 @@
-use anyhow::Result;
-use turbo_tasks::{FxIndexMap, RcStr, ValueToString, Vc};
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{Completion, FxIndexMap, RcStr, ResolvedVc, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
 use turbo_tasks_hash::hash_xxh3_hash64;
 
 use super::ModuleId;
@@ -54,3 +58,78 @@ impl ModuleIdStrategy for GlobalModuleIdStrategy {
         .cell())
     }
 }
+
+/// Uses short numeric ids that are derived from the idents of the modules
+/// relative to the project, so the ids are the same for every build of the
+/// same modules, on any machine.
+#[turbo_tasks::value]
+pub struct DeterministicModuleIdStrategy {
+    root: ResolvedVc<FileSystemPath>,
+    /// The ids of the modules, keyed by their ident relative to `root`.
+    module_id_map: FxIndexMap<RcStr, u64>,
+}
+
+impl DeterministicModuleIdStrategy {
+    pub fn new(
+        root: ResolvedVc<FileSystemPath>,
+        module_id_map: FxIndexMap<RcStr, u64>,
+    ) -> Vc<Self> {
+        DeterministicModuleIdStrategy {
+            root,
+            module_id_map,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl DeterministicModuleIdStrategy {
+    /// Writes the ids to a records file, to be used by the next build.
+    #[turbo_tasks::function]
+    pub async fn write_records(&self, path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+        let content = serde_json::to_string_pretty(&ModuleIdRecords {
+            modules: self
+                .module_id_map
+                .iter()
+                .map(|(ident, id)| (ident.clone(), *id))
+                .collect(),
+        })?;
+        Ok(path.write(FileContent::Content(File::from(content)).cell()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleIdStrategy for DeterministicModuleIdStrategy {
+    #[turbo_tasks::function]
+    async fn get_module_id(&self, ident: Vc<AssetIdent>) -> Result<Vc<ModuleId>> {
+        let ident_string = ident.to_relative_string(*self.root).await?;
+        if let Some(module_id) = self.module_id_map.get(&*ident_string) {
+            return Ok(ModuleId::Number(*module_id).cell());
+        }
+        // Modules that aren't part of the module graph, e.g. the parts of a
+        // module, still get an id that doesn't depend on the machine.
+        Ok(ModuleId::String(hash_xxh3_hash64(&*ident_string).to_string().into()).cell())
+    }
+}
+
+/// The contents of a records file, which keeps the module ids of previous
+/// builds, like webpack's `recordsPath`. Modules keep their recorded id, so
+/// adding or removing modules doesn't change the ids of other modules.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ModuleIdRecords {
+    /// The ids of the modules, keyed by their ident relative to the project.
+    /// Sorted, so the file diffs well when it's checked in.
+    #[serde(default)]
+    pub modules: BTreeMap<RcStr, u64>,
+}
+
+impl ModuleIdRecords {
+    /// Reads a records file. A missing file has no records.
+    pub async fn read(path: Vc<FileSystemPath>) -> Result<Self> {
+        match &*path.read().await? {
+            FileContent::Content(file) => serde_json::from_str(&file.content().to_str()?)
+                .context("Unable to parse the module id records"),
+            FileContent::NotFound => Ok(Self::default()),
+        }
+    }
+}
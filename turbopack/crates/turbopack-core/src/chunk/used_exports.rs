@@ -0,0 +1,80 @@
+use turbo_tasks::{FxIndexMap, FxIndexSet, RcStr, ResolvedVc, Vc};
+
+use crate::module::Module;
+
+/// The exports of a module that are used by any of its importers.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub enum ModuleUsedExports {
+    /// Any export might be used, e.g. because the module is an entry or its
+    /// namespace object is accessed dynamically.
+    All,
+    /// Only the listed exports are used.
+    Only(FxIndexSet<RcStr>),
+}
+
+impl ModuleUsedExports {
+    pub fn none() -> Self {
+        ModuleUsedExports::Only(FxIndexSet::default())
+    }
+
+    pub fn is_used(&self, export: &str) -> bool {
+        match self {
+            ModuleUsedExports::All => true,
+            ModuleUsedExports::Only(exports) => exports.contains(export),
+        }
+    }
+
+    /// Adds the exports used by `other`. Returns whether any export was
+    /// added.
+    pub fn extend(&mut self, other: &ModuleUsedExports) -> bool {
+        match (&mut *self, other) {
+            (ModuleUsedExports::All, _) => false,
+            (_, ModuleUsedExports::All) => {
+                *self = ModuleUsedExports::All;
+                true
+            }
+            (ModuleUsedExports::Only(exports), ModuleUsedExports::Only(other)) => {
+                let len = exports.len();
+                exports.extend(other.iter().cloned());
+                exports.len() != len
+            }
+        }
+    }
+}
+
+/// The used exports of the modules of a module graph. Modules that are not
+/// part of the analysis are assumed to have all of their exports used.
+#[turbo_tasks::value]
+pub struct UsedExports {
+    modules: FxIndexMap<ResolvedVc<Box<dyn Module>>, ModuleUsedExports>,
+}
+
+impl UsedExports {
+    pub fn new(modules: FxIndexMap<ResolvedVc<Box<dyn Module>>, ModuleUsedExports>) -> Vc<Self> {
+        UsedExports { modules }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl UsedExports {
+    #[turbo_tasks::function]
+    pub fn all() -> Vc<Self> {
+        UsedExports {
+            modules: FxIndexMap::default(),
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    pub fn module_used_exports(
+        &self,
+        module: ResolvedVc<Box<dyn Module>>,
+    ) -> Vc<ModuleUsedExports> {
+        self.modules
+            .get(&module)
+            .cloned()
+            .unwrap_or(ModuleUsedExports::All)
+            .cell()
+    }
+}
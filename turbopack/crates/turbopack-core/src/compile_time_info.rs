@@ -104,6 +104,9 @@ pub enum CompileTimeDefineValue {
     Bool(bool),
     String(RcStr),
     JSON(RcStr),
+    /// A JavaScript expression, e.g. `globalThis.__APP__` or `new Date(0)`,
+    /// that is inlined as is. Its value isn't known at compile time.
+    Expression(RcStr),
 }
 
 impl From<bool> for CompileTimeDefineValue {
@@ -262,12 +265,21 @@ impl FreeVarReferences {
     }
 }
 
+/// The names of the env variables that code may read with `process.env.X`.
+#[turbo_tasks::value(transparent)]
+#[derive(Debug, Clone)]
+pub struct PublicEnvVars(pub Vec<RcStr>);
+
 #[turbo_tasks::value(shared)]
 #[derive(Debug, Clone)]
 pub struct CompileTimeInfo {
     pub environment: Vc<Environment>,
     pub defines: Vc<CompileTimeDefines>,
     pub free_var_references: Vc<FreeVarReferences>,
+    /// When set, reading any other env variable than these with
+    /// `process.env.X` is an error, as it would be `undefined`. Env variables
+    /// with a free var reference are always fine to read.
+    pub public_env_vars: Option<Vc<PublicEnvVars>>,
 }
 
 impl CompileTimeInfo {
@@ -276,6 +288,7 @@ impl CompileTimeInfo {
             environment,
             defines: None,
             free_var_references: None,
+            public_env_vars: None,
         }
     }
 }
@@ -288,6 +301,7 @@ impl CompileTimeInfo {
             environment,
             defines: CompileTimeDefines::empty(),
             free_var_references: FreeVarReferences::empty(),
+            public_env_vars: None,
         }
         .cell()
     }
@@ -302,6 +316,7 @@ pub struct CompileTimeInfoBuilder {
     environment: Vc<Environment>,
     defines: Option<Vc<CompileTimeDefines>>,
     free_var_references: Option<Vc<FreeVarReferences>>,
+    public_env_vars: Option<Vc<PublicEnvVars>>,
 }
 
 impl CompileTimeInfoBuilder {
@@ -315,6 +330,11 @@ impl CompileTimeInfoBuilder {
         self
     }
 
+    pub fn public_env_vars(mut self, public_env_vars: Vc<PublicEnvVars>) -> Self {
+        self.public_env_vars = Some(public_env_vars);
+        self
+    }
+
     pub fn build(self) -> CompileTimeInfo {
         CompileTimeInfo {
             environment: self.environment,
@@ -322,6 +342,7 @@ impl CompileTimeInfoBuilder {
             free_var_references: self
                 .free_var_references
                 .unwrap_or_else(FreeVarReferences::empty),
+            public_env_vars: self.public_env_vars,
         }
     }
 
@@ -50,7 +50,22 @@ impl AssetIdent {
 impl ValueToString for AssetIdent {
     #[turbo_tasks::function]
     async fn to_string(&self) -> Result<Vc<RcStr>> {
-        let mut s = self.path.to_string().await?.clone_value().into_owned();
+        Ok(Vc::cell(self.format(None).await?))
+    }
+}
+
+impl AssetIdent {
+    /// Formats the ident. Paths are relative to `root` when it's passed and
+    /// they are on the same filesystem.
+    async fn format(&self, root: Option<Vc<FileSystemPath>>) -> Result<RcStr> {
+        let relative_path = match root {
+            Some(root) => root.await?.get_relative_path_to(&*self.path.await?),
+            None => None,
+        };
+        let mut s = match relative_path {
+            Some(path) => path.into_owned(),
+            None => self.path.to_string().await?.clone_value().into_owned(),
+        };
 
         let query = self.query.await?;
         if !query.is_empty() {
@@ -70,7 +85,10 @@ impl ValueToString for AssetIdent {
                 }
 
                 let key_str = key.await?;
-                let asset_str = asset.to_string().await?;
+                let asset_str = match root {
+                    Some(root) => asset.to_relative_string(root).await?,
+                    None => asset.to_string().await?,
+                };
                 write!(s, " {} => {:?}", key_str, asset_str)?;
             }
 
@@ -106,7 +124,7 @@ impl ValueToString for AssetIdent {
             }
         }
 
-        Ok(Vc::cell(s.into()))
+        Ok(s.into())
     }
 }
 
@@ -183,6 +201,14 @@ impl AssetIdent {
         self.query
     }
 
+    /// Like `to_string`, but with paths relative to `root`. Unlike the
+    /// absolute paths, this doesn't depend on where the project is located,
+    /// so it's the same on every machine.
+    #[turbo_tasks::function]
+    pub async fn to_relative_string(&self, root: Vc<FileSystemPath>) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(self.format(Some(root)).await?))
+    }
+
     /// Computes a unique output asset name for the given asset identifier.
     /// TODO(alexkirsz) This is `turbopack-browser` specific, as
     /// `turbopack-nodejs` would use a content hash instead. But for now
@@ -2,6 +2,7 @@ pub mod analyze;
 pub mod code_gen;
 pub mod module;
 pub mod resolve;
+pub mod rules;
 
 use std::{
     cmp::{min, Ordering},
@@ -96,6 +97,24 @@ pub enum StyledString {
     Strong(RcStr),
 }
 
+impl StyledString {
+    /// Returns the text without any styling. Parts of a [StyledString::Stack]
+    /// are separated by newlines.
+    pub fn to_unstyled_string(&self) -> String {
+        match self {
+            StyledString::Line(parts) => parts.iter().map(|p| p.to_unstyled_string()).collect(),
+            StyledString::Stack(parts) => parts
+                .iter()
+                .map(|p| p.to_unstyled_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            StyledString::Text(string)
+            | StyledString::Code(string)
+            | StyledString::Strong(string) => string.to_string(),
+        }
+    }
+}
+
 #[turbo_tasks::value_trait]
 pub trait Issue {
     /// Severity allows the user to filter out unimportant issues, with Bug
@@ -359,7 +378,7 @@ pub struct Issues(Vec<ResolvedVc<Box<dyn Issue>>>);
 /// A list of issues captured with [`Issue::peek_issues_with_path`] and
 /// [`Issue::take_issues_with_path`].
 #[turbo_tasks::value(shared)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CapturedIssues {
     issues: AutoSet<Vc<Box<dyn Issue>>>,
     #[cfg(feature = "issue_path")]
@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use auto_hash_map::AutoSet;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{
+    trace::TraceRawVcs, RawVc, RcStr, ResolvedVc, TransientInstance, TransientValue, Vc,
+};
+use turbo_tasks_fs::{glob::Glob, FileSystemPath};
+
+use super::{
+    CapturedIssues, Issue, IssueReporter, IssueSeverity, IssueStage, Issues, OptionIssueSource,
+    OptionStyledString, StyledString,
+};
+
+/// What to do with the issues matched by an [IssueRule].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueRuleAction {
+    /// Don't report the issue at all.
+    Ignore,
+    /// Report the issue with another severity.
+    Severity(IssueSeverity),
+}
+
+/// Ignores issues or changes their severity. An issue matches when it matches
+/// all of the conditions that are set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueRule {
+    /// The stage of the issue, e.g. `resolve` or `analysis`.
+    #[serde(default)]
+    pub stage: Option<RcStr>,
+    /// A regular expression searched for in the title of the issue.
+    #[serde(default)]
+    pub title: Option<RcStr>,
+    /// A glob matching the file path of the issue, relative to the project
+    /// directory, e.g. `vendor/**`. Files outside of the project start with
+    /// `../`.
+    #[serde(default)]
+    pub path: Option<RcStr>,
+    /// The severity the issue is reported with.
+    #[serde(default)]
+    pub severity: Option<IssueSeverity>,
+    pub action: IssueRuleAction,
+}
+
+/// A list of [IssueRule]s. The first matching rule applies.
+#[turbo_tasks::value(shared)]
+pub struct IssueRules {
+    pub rules: Vec<IssueRule>,
+    /// The directory the path globs of the rules are relative to. Without it,
+    /// they are relative to the root of the file system of the issue.
+    pub project_path: Option<ResolvedVc<FileSystemPath>>,
+}
+
+#[turbo_tasks::value_impl]
+impl IssueRules {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        IssueRules {
+            rules: Vec::new(),
+            project_path: None,
+        }
+        .cell()
+    }
+}
+
+/// An [IssueRule] with parsed patterns.
+struct CompiledIssueRule<'a> {
+    rule: &'a IssueRule,
+    title: Option<Regex>,
+    path: Option<Glob>,
+}
+
+impl<'a> CompiledIssueRule<'a> {
+    fn new(rule: &'a IssueRule) -> Result<Self> {
+        Ok(CompiledIssueRule {
+            rule,
+            title: rule
+                .title
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .context("invalid title pattern in issue rule")?,
+            path: rule
+                .path
+                .as_deref()
+                .map(Glob::parse)
+                .transpose()
+                .context("invalid path pattern in issue rule")?,
+        })
+    }
+
+    async fn matches(
+        &self,
+        issue: Vc<Box<dyn Issue>>,
+        project_path: Option<&FileSystemPath>,
+    ) -> Result<bool> {
+        if let Some(severity) = self.rule.severity {
+            if *issue.severity().await? != severity {
+                return Ok(false);
+            }
+        }
+        if let Some(stage) = &self.rule.stage {
+            if issue.stage().await?.to_string() != **stage {
+                return Ok(false);
+            }
+        }
+        if let Some(title) = &self.title {
+            if !title.is_match(&issue.title().await?.to_unstyled_string()) {
+                return Ok(false);
+            }
+        }
+        if let Some(path) = &self.path {
+            let file_path = issue.file_path().await?;
+            let relative_path = match project_path {
+                Some(project_path) => project_path.get_relative_path_to(&file_path),
+                None => Some(file_path.path.clone()),
+            };
+            let Some(relative_path) = relative_path else {
+                return Ok(false);
+            };
+            if !path.execute(relative_path.strip_prefix("./").unwrap_or(&relative_path)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl CapturedIssues {
+    /// Applies `rules` to the captured issues, dropping ignored issues and
+    /// wrapping issues with a changed severity.
+    pub async fn with_rules(&self, rules: &IssueRules) -> Result<CapturedIssues> {
+        if rules.rules.is_empty() {
+            return Ok(self.clone());
+        }
+
+        let project_path = match rules.project_path {
+            Some(project_path) => Some(project_path.await?),
+            None => None,
+        };
+        let project_path = project_path.as_deref();
+        let rules = rules
+            .rules
+            .iter()
+            .map(CompiledIssueRule::new)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut issues = AutoSet::new();
+        'issues: for issue in self.iter() {
+            for rule in &rules {
+                if rule.matches(issue, project_path).await? {
+                    match rule.rule.action {
+                        IssueRuleAction::Ignore => {}
+                        IssueRuleAction::Severity(severity) => {
+                            issues.insert(Vc::upcast(SeverityOverrideIssue::new(
+                                issue,
+                                severity.cell(),
+                            )));
+                        }
+                    }
+                    continue 'issues;
+                }
+            }
+            issues.insert(issue);
+        }
+
+        Ok(CapturedIssues {
+            issues,
+            #[cfg(feature = "issue_path")]
+            processing_path: self.processing_path,
+        })
+    }
+}
+
+/// Reports an issue with another severity.
+#[turbo_tasks::value]
+struct SeverityOverrideIssue {
+    issue: ResolvedVc<Box<dyn Issue>>,
+    severity: ResolvedVc<IssueSeverity>,
+}
+
+#[turbo_tasks::value_impl]
+impl SeverityOverrideIssue {
+    #[turbo_tasks::function]
+    fn new(issue: ResolvedVc<Box<dyn Issue>>, severity: ResolvedVc<IssueSeverity>) -> Vc<Self> {
+        SeverityOverrideIssue { issue, severity }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SeverityOverrideIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        *self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.issue.file_path()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        self.issue.stage()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        self.issue.title()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        self.issue.description()
+    }
+
+    #[turbo_tasks::function]
+    fn detail(&self) -> Vc<OptionStyledString> {
+        self.issue.detail()
+    }
+
+    #[turbo_tasks::function]
+    fn documentation_link(&self) -> Vc<RcStr> {
+        self.issue.documentation_link()
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        self.issue.source()
+    }
+
+    #[turbo_tasks::function]
+    fn sub_issues(&self) -> Vc<Issues> {
+        self.issue.sub_issues()
+    }
+}
+
+/// An [IssueReporter] applying [IssueRules] before passing the issues on to
+/// another reporter.
+#[turbo_tasks::value]
+pub struct IssueRulesReporter {
+    reporter: ResolvedVc<Box<dyn IssueReporter>>,
+    rules: ResolvedVc<IssueRules>,
+}
+
+#[turbo_tasks::value_impl]
+impl IssueRulesReporter {
+    #[turbo_tasks::function]
+    pub fn new(
+        reporter: ResolvedVc<Box<dyn IssueReporter>>,
+        rules: ResolvedVc<IssueRules>,
+    ) -> Vc<Self> {
+        IssueRulesReporter { reporter, rules }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl IssueReporter for IssueRulesReporter {
+    #[turbo_tasks::function]
+    async fn report_issues(
+        &self,
+        issues: TransientInstance<CapturedIssues>,
+        source: TransientValue<RawVc>,
+        min_failing_severity: Vc<IssueSeverity>,
+    ) -> Result<Vc<bool>> {
+        let issues = issues.with_rules(&self.rules.await?).await?;
+        Ok(self.reporter.report_issues(
+            TransientInstance::new(issues),
+            source,
+            min_failing_severity,
+        ))
+    }
+}
@@ -0,0 +1,273 @@
+#![feature(arbitrary_self_types)]
+#![feature(arbitrary_self_types_pointers)]
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::{Completion, RcStr, ResolvedVc, Vc};
+use turbo_tasks_fs::{FileSystem, FileSystemPath, VirtualFileSystem};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_core::issue::{
+    rules::{IssueRule, IssueRuleAction, IssueRules},
+    Issue, IssueDescriptionExt, IssueExt, IssueSeverity, IssueStage, StyledString,
+};
+
+static REGISTRATION: Registration = register!(turbopack_core::register);
+
+#[turbo_tasks::value(serialization = "none")]
+struct TestIssue {
+    path: ResolvedVc<FileSystemPath>,
+    stage: IssueStage,
+    title: RcStr,
+    severity: IssueSeverity,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for TestIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        *self.path
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        self.stage.clone().cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(self.title.clone()).cell()
+    }
+}
+
+/// Emits a warning in the project, a warning in its vendored code and an
+/// error in a package outside of the project, which lives in `apps/web`.
+#[turbo_tasks::function]
+async fn emit_issues(root: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+    for (path, stage, title, severity) in [
+        (
+            "apps/web/src/index.js",
+            IssueStage::Resolve,
+            "Module not found: pkg",
+            IssueSeverity::Warning,
+        ),
+        (
+            "apps/web/vendor/lib.js",
+            IssueStage::Analysis,
+            "Dynamic require",
+            IssueSeverity::Warning,
+        ),
+        (
+            "node_modules/pkg/index.js",
+            IssueStage::Parse,
+            "Unexpected token",
+            IssueSeverity::Error,
+        ),
+    ] {
+        TestIssue {
+            path: root.join(path.into()).to_resolved().await?,
+            stage,
+            title: title.into(),
+            severity,
+        }
+        .cell()
+        .emit();
+    }
+    Ok(Completion::new())
+}
+
+fn rule(action: IssueRuleAction) -> IssueRule {
+    IssueRule {
+        stage: None,
+        title: None,
+        path: None,
+        severity: None,
+        action,
+    }
+}
+
+/// Applies `rules` to the emitted issues and returns the titles and
+/// severities of the remaining issues, sorted by title.
+async fn apply(
+    rules: Vec<IssueRule>,
+    project_path: Option<&str>,
+) -> Result<Vec<(String, IssueSeverity)>> {
+    let root = VirtualFileSystem::new_with_name("project".into()).root();
+    let project_path = match project_path {
+        Some(project_path) => Some(root.join(project_path.into()).to_resolved().await?),
+        None => None,
+    };
+    let issues = emit_issues(root);
+    issues.strongly_consistent().await?;
+    let captured = issues
+        .peek_issues_with_path()
+        .await?
+        .with_rules(&IssueRules {
+            rules,
+            project_path,
+        })
+        .await?;
+
+    let mut result = vec![];
+    for issue in captured.iter() {
+        result.push((
+            issue.title().await?.to_unstyled_string(),
+            *issue.severity().await?,
+        ));
+    }
+    result.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(result)
+}
+
+fn titles(issues: &[(String, IssueSeverity)]) -> Vec<&str> {
+    issues.iter().map(|(title, _)| &**title).collect()
+}
+
+#[tokio::test]
+async fn without_rules_all_issues_are_kept() {
+    run(&REGISTRATION, || async {
+        let issues = apply(vec![], Some("apps/web")).await?;
+        assert_eq!(
+            issues,
+            [
+                ("Dynamic require".to_string(), IssueSeverity::Warning),
+                ("Module not found: pkg".to_string(), IssueSeverity::Warning),
+                ("Unexpected token".to_string(), IssueSeverity::Error),
+            ]
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn matches_stage() {
+    run(&REGISTRATION, || async {
+        let issues = apply(
+            vec![IssueRule {
+                stage: Some("analysis".into()),
+                ..rule(IssueRuleAction::Ignore)
+            }],
+            Some("apps/web"),
+        )
+        .await?;
+        assert_eq!(
+            titles(&issues),
+            ["Module not found: pkg", "Unexpected token"]
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn matches_title_pattern() {
+    run(&REGISTRATION, || async {
+        let issues = apply(
+            vec![IssueRule {
+                title: Some("^Module not found: .+$".into()),
+                ..rule(IssueRuleAction::Ignore)
+            }],
+            Some("apps/web"),
+        )
+        .await?;
+        assert_eq!(titles(&issues), ["Dynamic require", "Unexpected token"]);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn matches_paths_relative_to_the_project() {
+    run(&REGISTRATION, || async {
+        let ignore_path = |path: &str| IssueRule {
+            path: Some(path.into()),
+            ..rule(IssueRuleAction::Ignore)
+        };
+
+        let issues = apply(vec![ignore_path("vendor/**")], Some("apps/web")).await?;
+        assert_eq!(
+            titles(&issues),
+            ["Module not found: pkg", "Unexpected token"]
+        );
+
+        // Paths aren't relative to the root of the file system.
+        let issues = apply(vec![ignore_path("apps/web/vendor/**")], Some("apps/web")).await?;
+        assert_eq!(issues.len(), 3);
+
+        // Files outside of the project start with `../`.
+        let issues = apply(vec![ignore_path("../node_modules/**")], Some("apps/web")).await?;
+        assert_eq!(
+            titles(&issues),
+            ["Dynamic require", "Module not found: pkg"]
+        );
+
+        // Without a project, paths are relative to the root of the file system.
+        let issues = apply(vec![ignore_path("apps/web/vendor/**")], None).await?;
+        assert_eq!(
+            titles(&issues),
+            ["Module not found: pkg", "Unexpected token"]
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn changes_the_severity_of_matching_issues() {
+    run(&REGISTRATION, || async {
+        let issues = apply(
+            vec![IssueRule {
+                severity: Some(IssueSeverity::Error),
+                ..rule(IssueRuleAction::Severity(IssueSeverity::Warning))
+            }],
+            Some("apps/web"),
+        )
+        .await?;
+        assert_eq!(
+            issues,
+            [
+                ("Dynamic require".to_string(), IssueSeverity::Warning),
+                ("Module not found: pkg".to_string(), IssueSeverity::Warning),
+                ("Unexpected token".to_string(), IssueSeverity::Warning),
+            ]
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn applies_the_first_matching_rule() {
+    run(&REGISTRATION, || async {
+        let issues = apply(
+            vec![
+                IssueRule {
+                    stage: Some("resolve".into()),
+                    ..rule(IssueRuleAction::Severity(IssueSeverity::Error))
+                },
+                // Matches all issues, but the resolve issue already matched.
+                rule(IssueRuleAction::Ignore),
+            ],
+            Some("apps/web"),
+        )
+        .await?;
+        assert_eq!(
+            issues,
+            [("Module not found: pkg".to_string(), IssueSeverity::Error)]
+        );
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,636 @@
+//! CSS Modules `@value` rules and ICSS `:import`/`:export` blocks.
+//!
+//! Neither CSS parser understands these rules, so they are extracted from the
+//! source text before parsing and replaced with whitespace, which keeps all
+//! other positions in the stylesheet intact. Value names in the remaining
+//! stylesheet are then substituted textually.
+
+use std::ops::Range;
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use turbo_tasks::{FxIndexMap, RcStr, ReadRef, ResolvedVc, Value, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    issue::{
+        Issue, IssueExt, IssueSource, IssueStage, OptionIssueSource, OptionStyledString,
+        StyledString,
+    },
+    module::Module,
+    reference::ModuleReference,
+    resolve::{origin::ResolveOrigin, parse::Request},
+    source::Source,
+    source_pos::SourcePos,
+};
+
+use crate::{
+    process::{ParseCss, ParseCssResult},
+    references::icss::IcssImportReference,
+    ModuleCssAsset,
+};
+
+/// Values a stylesheet exports with `@value` and `:export`.
+#[turbo_tasks::value(transparent)]
+pub struct IcssExports(FxIndexMap<String, String>);
+
+#[turbo_tasks::value_impl]
+impl IcssExports {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(FxIndexMap::default())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum IcssRule {
+    /// `@value name: value;`
+    Value { name: String, value: String },
+    /// `@value a, b as c from "./x.css";` or `:import("./x.css") { c: b; }`
+    Import(IcssImport),
+    /// `:export { name: value; }`
+    Export(Vec<(String, String)>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct IcssImport {
+    /// Either a quoted request or the name of a value holding one.
+    pub specifier: String,
+    /// Pairs of `(local, imported)` names.
+    pub names: Vec<(String, String)>,
+    /// `@value` imports are exported again, `:import`s are not.
+    pub reexport: bool,
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct IcssRules {
+    pub rules: Vec<IcssRule>,
+    /// The source ranges of all extracted rules.
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl IcssRules {
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+static VALUE_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^([\w-]+(?:\s+as\s+[\w-]+)?(?:\s*,\s*[\w-]+(?:\s+as\s+[\w-]+)?)*)\s+from\s+("[^"]*"|'[^']*'|[\w-]+)$"#,
+    )
+    .unwrap()
+});
+
+static VALUE_DEFINITION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)^([\w-]+)\s*:?\s*(.+)$").unwrap());
+
+/// Finds all top-level `@value`, `:import` and `:export` rules.
+pub(crate) fn extract_icss(code: &str) -> IcssRules {
+    let bytes = code.as_bytes();
+    let mut result = IcssRules::default();
+    let mut depth = 0usize;
+    let mut pos = 0;
+    // Whether there was nothing but whitespace since the last statement.
+    let mut at_statement_start = true;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => pos = skip_comment(bytes, pos),
+            b'"' | b'\'' => {
+                pos = skip_string(bytes, pos);
+                at_statement_start = false;
+            }
+            b'{' => {
+                depth += 1;
+                pos += 1;
+                at_statement_start = true;
+            }
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                pos += 1;
+                at_statement_start = true;
+            }
+            b';' => {
+                pos += 1;
+                at_statement_start = true;
+            }
+            b'@' if depth == 0
+                && at_statement_start
+                && code[pos..].starts_with("@value")
+                && bytes.get(pos + 6).is_some_and(|c| c.is_ascii_whitespace()) =>
+            {
+                let end = find_statement_end(bytes, pos);
+                let range = pos..(end + 1).min(bytes.len());
+                if let Some(rule) = parse_value_rule(code[pos + 6..end].trim(), range.clone()) {
+                    result.rules.push(rule);
+                }
+                pos = range.end;
+                result.ranges.push(range);
+            }
+            b':' if depth == 0
+                && at_statement_start
+                && (code[pos..].starts_with(":import") || code[pos..].starts_with(":export")) =>
+            {
+                let Some(open) = find_byte(bytes, pos, b'{') else {
+                    break;
+                };
+                let Some(close) = find_block_end(bytes, open) else {
+                    break;
+                };
+                let range = pos..close + 1;
+                let declarations = parse_declarations(&code[open + 1..close]);
+                if code[pos..].starts_with(":export") {
+                    result.rules.push(IcssRule::Export(declarations));
+                } else {
+                    let specifier = code[pos + ":import".len()..open].trim();
+                    let specifier = specifier
+                        .strip_prefix('(')
+                        .and_then(|s| s.strip_suffix(')'))
+                        .unwrap_or(specifier)
+                        .trim();
+                    result.rules.push(IcssRule::Import(IcssImport {
+                        specifier: specifier.to_string(),
+                        names: declarations,
+                        reexport: false,
+                        range: range.clone(),
+                    }));
+                }
+                pos = range.end;
+                result.ranges.push(range);
+                at_statement_start = true;
+            }
+            c if c.is_ascii_whitespace() => pos += 1,
+            _ => {
+                pos += 1;
+                at_statement_start = false;
+            }
+        }
+    }
+
+    result
+}
+
+fn parse_value_rule(prelude: &str, range: Range<usize>) -> Option<IcssRule> {
+    if let Some(captures) = VALUE_IMPORT_RE.captures(prelude) {
+        let names = captures[1]
+            .split(',')
+            .map(
+                |name| match name.split_whitespace().collect::<Vec<_>>()[..] {
+                    [imported, "as", local] => (local.to_string(), imported.to_string()),
+                    _ => (name.trim().to_string(), name.trim().to_string()),
+                },
+            )
+            .collect();
+        return Some(IcssRule::Import(IcssImport {
+            specifier: captures[2].to_string(),
+            names,
+            reexport: true,
+            range,
+        }));
+    }
+    let captures = VALUE_DEFINITION_RE.captures(prelude)?;
+    Some(IcssRule::Value {
+        name: captures[1].to_string(),
+        value: captures[2].trim().to_string(),
+    })
+}
+
+fn parse_declarations(block: &str) -> Vec<(String, String)> {
+    block
+        .split(';')
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| (name.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn skip_comment(bytes: &[u8], pos: usize) -> usize {
+    let mut pos = pos + 2;
+    while pos < bytes.len() && !bytes[pos..].starts_with(b"*/") {
+        pos += 1;
+    }
+    (pos + 2).min(bytes.len())
+}
+
+fn skip_string(bytes: &[u8], pos: usize) -> usize {
+    let quote = bytes[pos];
+    let mut pos = pos + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            c if c == quote || c == b'\n' => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the position of the `;` ending the statement at `pos`.
+fn find_statement_end(bytes: &[u8], mut pos: usize) -> usize {
+    let mut parens = 0usize;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => pos = skip_comment(bytes, pos),
+            b'"' | b'\'' => pos = skip_string(bytes, pos),
+            b'(' => {
+                parens += 1;
+                pos += 1;
+            }
+            b')' => {
+                parens = parens.saturating_sub(1);
+                pos += 1;
+            }
+            b';' if parens == 0 => return pos,
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn find_byte(bytes: &[u8], mut pos: usize, byte: u8) -> Option<usize> {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => pos = skip_comment(bytes, pos),
+            b'"' | b'\'' => pos = skip_string(bytes, pos),
+            c if c == byte => return Some(pos),
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// Returns the position of the `}` closing the block opened at `open`.
+fn find_block_end(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut pos = open;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => pos = skip_comment(bytes, pos),
+            b'"' | b'\'' => pos = skip_string(bytes, pos),
+            b'{' => {
+                depth += 1;
+                pos += 1;
+            }
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+                pos += 1;
+            }
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// Replaces the given ranges with whitespace, keeping line breaks.
+pub(crate) fn blank_ranges(code: &str, ranges: &[Range<usize>]) -> String {
+    let mut bytes = code.as_bytes().to_vec();
+    for range in ranges {
+        for byte in &mut bytes[range.clone()] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    }
+    // Ranges start and end at ASCII characters, so no UTF-8 sequence was split.
+    String::from_utf8(bytes).unwrap_or_else(|_| code.to_string())
+}
+
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c == b'-' || c >= 0x80
+}
+
+fn is_ident_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'-' || c >= 0x80
+}
+
+/// Replaces value names in `code`.
+///
+/// With `only_in_values`, names are only replaced in declaration values and
+/// at-rule preludes, not in selectors or property names.
+pub(crate) fn replace_values(
+    code: &str,
+    values: &FxIndexMap<String, String>,
+    only_in_values: bool,
+) -> String {
+    if values.is_empty() {
+        return code.to_string();
+    }
+
+    let bytes = code.as_bytes();
+    let mut result = String::with_capacity(code.len());
+    let mut last = 0;
+    let mut pos = 0;
+    let mut statement_start = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => pos = skip_comment(bytes, pos),
+            b'"' | b'\'' => pos = skip_string(bytes, pos),
+            b'{' | b'}' | b';' => {
+                pos += 1;
+                statement_start = pos;
+            }
+            c if is_ident_start(c)
+                && !pos.checked_sub(1).is_some_and(|prev| {
+                    is_ident_char(bytes[prev]) || matches!(bytes[prev], b'.' | b'#' | b'@' | b'$')
+                }) =>
+            {
+                let start = pos;
+                while pos < bytes.len() && is_ident_char(bytes[pos]) {
+                    pos += 1;
+                }
+                if bytes.get(pos) == Some(&b'(') {
+                    // A function name. The contents of `url()` are not identifiers.
+                    if code[start..pos].eq_ignore_ascii_case("url") {
+                        pos = find_byte(bytes, pos, b')').unwrap_or(bytes.len());
+                    }
+                    continue;
+                }
+                let Some(value) = values.get(&code[start..pos]) else {
+                    continue;
+                };
+                if !only_in_values || is_value_position(code, statement_start, pos) {
+                    result.push_str(&code[last..start]);
+                    result.push_str(value);
+                    last = pos;
+                }
+            }
+            _ => pos += 1,
+        }
+    }
+    result.push_str(&code[last..]);
+    result
+}
+
+/// Whether the identifier ending at `end` is part of a declaration value or
+/// an at-rule prelude.
+fn is_value_position(code: &str, statement_start: usize, end: usize) -> bool {
+    let before = code[statement_start..end].trim_start();
+    if before.starts_with('@') {
+        return true;
+    }
+    if !before.contains(':') {
+        // A property name or a selector.
+        return false;
+    }
+    // `a:hover {` is a selector, `color: red;` is a declaration.
+    let rest = &code[end..];
+    match rest.find(['{', '}', ';']) {
+        Some(i) => rest.as_bytes()[i] != b'{',
+        None => true,
+    }
+}
+
+/// The result of resolving the ICSS rules of a stylesheet.
+pub(crate) struct ResolvedIcss {
+    /// All values that can be used in the stylesheet.
+    pub values: FxIndexMap<String, String>,
+    pub exports: FxIndexMap<String, String>,
+    pub references: Vec<Vc<Box<dyn ModuleReference>>>,
+}
+
+/// Adds the values and exports of a rule that doesn't import from another
+/// file. Imports are returned, as they have to be resolved.
+pub(crate) fn apply_local_rule(
+    rule: IcssRule,
+    values: &mut FxIndexMap<String, String>,
+    exports: &mut FxIndexMap<String, String>,
+) -> Option<IcssImport> {
+    match rule {
+        IcssRule::Value { name, value } => {
+            let value = replace_values(&value, values, false);
+            values.insert(name.clone(), value.clone());
+            exports.insert(name, value);
+            None
+        }
+        IcssRule::Export(declarations) => {
+            for (name, value) in declarations {
+                exports.insert(name, replace_values(&value, values, false));
+            }
+            None
+        }
+        IcssRule::Import(import) => Some(import),
+    }
+}
+
+/// Resolves imported values and computes the exports of a stylesheet.
+pub(crate) async fn resolve_icss(
+    rules: IcssRules,
+    code: &str,
+    source: Vc<Box<dyn Source>>,
+    origin: Vc<Box<dyn ResolveOrigin>>,
+    file: Vc<FileSystemPath>,
+) -> Result<ResolvedIcss> {
+    let mut values = FxIndexMap::default();
+    let mut exports = FxIndexMap::default();
+    let mut references = Vec::new();
+
+    for rule in rules.rules {
+        let Some(import) = apply_local_rule(rule, &mut values, &mut exports) else {
+            continue;
+        };
+        let specifier = match unquote(&import.specifier) {
+            Some(specifier) => specifier.to_string(),
+            // `@value colors: "./colors.css"; @value primary from colors;`
+            None => match values.get(&import.specifier).and_then(|v| unquote(v)) {
+                Some(specifier) => specifier.to_string(),
+                None => import.specifier.clone(),
+            },
+        };
+        let issue_source = issue_source(source, code, &import.range);
+        let reference = IcssImportReference::new(
+            origin,
+            Request::parse(Value::new(RcStr::from(specifier.as_str()).into())),
+            issue_source,
+        );
+        references.push(Vc::upcast(reference));
+
+        let Some(module) = *reference.resolve_reference().first_module().await? else {
+            // Unresolvable requests are reported by the resolver.
+            continue;
+        };
+        let imported = module_icss_exports(module).await?;
+        for (local, name) in import.names {
+            let Some(value) = imported.as_ref().and_then(|imported| imported.get(&name)) else {
+                IcssImportIssue {
+                    file,
+                    message: format!("\"{name}\" is not exported by \"{specifier}\"").into(),
+                    source: issue_source,
+                }
+                .cell()
+                .emit();
+                continue;
+            };
+            if import.reexport {
+                exports.insert(local.clone(), value.clone());
+            }
+            values.insert(local, value.clone());
+        }
+    }
+
+    Ok(ResolvedIcss {
+        values,
+        exports,
+        references,
+    })
+}
+
+/// Returns the ICSS exports of a CSS module or stylesheet.
+async fn module_icss_exports(
+    module: ResolvedVc<Box<dyn Module>>,
+) -> Result<Option<ReadRef<IcssExports>>> {
+    let module = match ResolvedVc::try_downcast_type::<ModuleCssAsset>(module).await? {
+        Some(css_module) => css_module.inner().module(),
+        None => *module,
+    };
+    let Some(module) = Vc::try_resolve_sidecast::<Box<dyn ParseCss>>(module).await? else {
+        return Ok(None);
+    };
+    Ok(match &*module.parse_css().await? {
+        ParseCssResult::Ok { icss_exports, .. } => Some(icss_exports.await?),
+        ParseCssResult::Unparseable | ParseCssResult::NotFound => None,
+    })
+}
+
+fn unquote(s: &str) -> Option<&str> {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| s.strip_prefix(quote)?.strip_suffix(quote))
+}
+
+fn issue_source(source: Vc<Box<dyn Source>>, code: &str, range: &Range<usize>) -> Vc<IssueSource> {
+    let pos = |offset: usize| {
+        let before = &code[..offset];
+        let line = before.matches('\n').count();
+        let column = offset - before.rfind('\n').map_or(0, |i| i + 1);
+        SourcePos { line, column }
+    };
+    IssueSource::from_line_col(source, pos(range.start), pos(range.end))
+}
+
+#[turbo_tasks::value]
+struct IcssImportIssue {
+    file: Vc<FileSystemPath>,
+    message: RcStr,
+    source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for IcssImportIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::Resolve.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("An issue occurred while importing a CSS value".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(Some(self.source.resolve_source_map(self.file)))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.message.clone()).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::FxIndexMap;
+
+    use super::{blank_ranges, extract_icss, replace_values, IcssImport, IcssRule};
+
+    #[test]
+    fn extracts_rules() {
+        let code = r#"@value primary: #BF4040;
+@value small: (max-width: 599px);
+@value secondary, accent as highlight from "./colors.css";
+:import("./tokens.css") {
+  spacing: space-m;
+}
+:export {
+  primary: primary;
+}
+.a { color: primary; }
+/* @value commented: 1px; */
+"#;
+        let rules = extract_icss(code);
+        assert_eq!(
+            rules.rules,
+            vec![
+                IcssRule::Value {
+                    name: "primary".into(),
+                    value: "#BF4040".into()
+                },
+                IcssRule::Value {
+                    name: "small".into(),
+                    value: "(max-width: 599px)".into()
+                },
+                IcssRule::Import(IcssImport {
+                    specifier: "\"./colors.css\"".into(),
+                    names: vec![
+                        ("secondary".into(), "secondary".into()),
+                        ("highlight".into(), "accent".into()),
+                    ],
+                    reexport: true,
+                    range: rules.ranges[2].clone(),
+                }),
+                IcssRule::Import(IcssImport {
+                    specifier: "\"./tokens.css\"".into(),
+                    names: vec![("spacing".into(), "space-m".into())],
+                    reexport: false,
+                    range: rules.ranges[3].clone(),
+                }),
+                IcssRule::Export(vec![("primary".into(), "primary".into())]),
+            ]
+        );
+
+        let blanked = blank_ranges(code, &rules.ranges);
+        assert_eq!(blanked.len(), code.len());
+        assert_eq!(blanked.lines().count(), code.lines().count());
+        assert_eq!(
+            blanked.trim_start().lines().next(),
+            Some(".a { color: primary; }")
+        );
+    }
+
+    #[test]
+    fn replaces_values_in_declarations_and_at_rules() {
+        let values: FxIndexMap<String, String> = [
+            ("primary".to_string(), "#BF4040".to_string()),
+            ("small".to_string(), "(max-width: 599px)".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let code = r#"
+.primary { color: primary; border: 1px solid primary; }
+.a:hover { background: url(primary.png); content: "primary"; }
+@media small { .b { color: primary } }
+.c { primary-color: blue; }
+"#;
+        assert_eq!(
+            replace_values(code, &values, true),
+            r#"
+.primary { color: #BF4040; border: 1px solid #BF4040; }
+.a:hover { background: url(primary.png); content: "primary"; }
+@media (max-width: 599px) { .b { color: #BF4040 } }
+.c { primary-color: blue; }
+"#
+        );
+    }
+}
@@ -9,14 +9,18 @@ mod asset;
 pub mod chunk;
 mod code_gen;
 pub mod embed;
+pub(crate) mod icss;
 mod lifetime_util;
 mod module_asset;
 pub(crate) mod parse;
 pub(crate) mod process;
 pub(crate) mod references;
+pub mod sass;
+pub mod standalone;
 pub(crate) mod util;
 
 pub use asset::CssModuleAsset;
+pub use icss::IcssExports;
 pub use module_asset::ModuleCssAsset;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{trace::TraceRawVcs, TaskInput};
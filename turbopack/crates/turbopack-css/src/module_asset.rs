@@ -24,11 +24,13 @@ use turbopack_ecmascript::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
         EcmascriptChunkType, EcmascriptExports,
     },
+    property_mangling::{ExposedProperties, PropertyNames},
     utils::StringifyJs,
     ParseResultSourceMap,
 };
 
 use crate::{
+    icss::IcssExports,
     process::{CssWithPlaceholderResult, ProcessCss},
     references::{compose::CssModuleComposeReference, internal::InternalCssAssetReference},
 };
@@ -148,7 +150,7 @@ struct ModuleCssClasses(FxIndexMap<String, Vec<ModuleCssClass>>);
 #[turbo_tasks::value_impl]
 impl ModuleCssAsset {
     #[turbo_tasks::function]
-    fn inner(&self) -> Vc<ProcessResult> {
+    pub(crate) fn inner(&self) -> Vc<ProcessResult> {
         self.asset_context.process(
             self.source,
             Value::new(ReferenceType::Css(CssReferenceSubType::Internal)),
@@ -208,6 +210,23 @@ impl ModuleCssAsset {
         Ok(Vc::cell(classes))
     }
 
+    /// Values exported with `@value` and `:export`.
+    #[turbo_tasks::function]
+    async fn icss_exports(self: Vc<Self>) -> Result<Vc<IcssExports>> {
+        let inner = self.inner().module();
+
+        let inner = Vc::try_resolve_sidecast::<Box<dyn ProcessCss>>(inner)
+            .await?
+            .context("inner asset should be CSS processable")?;
+
+        Ok(match &*inner.get_css_with_placeholder().await? {
+            CssWithPlaceholderResult::Ok { icss_exports, .. } => *icss_exports,
+            CssWithPlaceholderResult::Unparseable | CssWithPlaceholderResult::NotFound => {
+                IcssExports::empty()
+            }
+        })
+    }
+
     #[turbo_tasks::function]
     async fn module_references(self: Vc<Self>) -> Result<Vc<ModuleReferences>> {
         let mut references = vec![];
@@ -252,6 +271,23 @@ impl EcmascriptChunkPlaceable for ModuleCssAsset {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl ExposedProperties for ModuleCssAsset {
+    /// The exported classes and ICSS values.
+    #[turbo_tasks::function]
+    async fn exposed_property_names(self: Vc<Self>) -> Result<Vc<PropertyNames>> {
+        let classes = self.classes().await?;
+        let icss_exports = self.icss_exports().await?;
+        Ok(Vc::cell(
+            classes
+                .keys()
+                .chain(icss_exports.keys())
+                .map(|name| name.as_str().into())
+                .collect(),
+        ))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl ResolveOrigin for ModuleCssAsset {
     #[turbo_tasks::function]
@@ -311,8 +347,21 @@ impl EcmascriptChunkItem for ModuleChunkItem {
     #[turbo_tasks::function]
     async fn content(&self) -> Result<Vc<EcmascriptChunkItemContent>> {
         let classes = self.module.classes().await?;
+        let icss_exports = self.module.icss_exports().await?;
 
         let mut code = "__turbopack_export_value__({\n".to_string();
+        for (export_name, value) in &*icss_exports {
+            // Class names take precedence over values with the same name.
+            if classes.contains_key(export_name) {
+                continue;
+            }
+            writeln!(
+                code,
+                "  {}: {},",
+                StringifyJs(export_name),
+                StringifyJs(value)
+            )?;
+        }
         for (export_name, class_names) in &*classes {
             let mut exported_class_names = Vec::with_capacity(class_names.len());
 
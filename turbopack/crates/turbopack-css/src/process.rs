@@ -3,12 +3,12 @@ use std::{
     sync::{Arc, RwLock},
 };
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use lightningcss::{
     css_modules::{CssModuleExport, CssModuleExports, CssModuleReference, Pattern, Segment},
     dependencies::{Dependency, ImportDependency, Location, SourceRange},
     stylesheet::{ParserOptions, PrinterOptions, StyleSheet, ToCssResult},
-    targets::{Features, Targets},
+    targets::{Browsers, Features, Targets},
     values::url::Url,
     visit_types,
     visitor::Visit,
@@ -55,6 +55,7 @@ use turbopack_core::{
 use turbopack_swc_utils::emitter::IssueEmitter;
 
 use crate::{
+    icss::{blank_ranges, extract_icss, replace_values, resolve_icss, IcssExports},
     lifetime_util::stylesheet_into_static,
     parse::InlineSourcesContentConfig,
     references::{
@@ -117,6 +118,7 @@ impl StyleSheetLike<'_, '_> {
         enable_srcmap: bool,
         remove_imports: bool,
         handle_nesting: bool,
+        browsers: Option<Browsers>,
     ) -> Result<CssOutput> {
         match self {
             StyleSheetLike::LightningCss(ss) => {
@@ -126,13 +128,14 @@ impl StyleSheetLike<'_, '_> {
                     None
                 };
 
-                let targets = if handle_nesting {
-                    Targets {
-                        include: Features::Nesting,
-                        ..Default::default()
-                    }
-                } else {
-                    Default::default()
+                let targets = Targets {
+                    browsers,
+                    include: if handle_nesting {
+                        Features::Nesting
+                    } else {
+                        Features::empty()
+                    },
+                    ..Default::default()
                 };
 
                 let result = ss.to_css(PrinterOptions {
@@ -307,6 +310,12 @@ pub enum ParseCssResult {
 
         url_references: Vc<UnresolvedUrlReferences>,
 
+        icss_exports: Vc<IcssExports>,
+
+        /// The source map of a transformed source, e.g. compiled Sass, which
+        /// the source map of the css is chained with.
+        original_source_map: Vc<OptionSourceMap>,
+
         #[turbo_tasks(trace_ignore)]
         options: ParserOptions<'static, 'static>,
     },
@@ -329,6 +338,9 @@ pub enum CssWithPlaceholderResult {
         #[turbo_tasks(trace_ignore)]
         exports: Option<FxIndexMap<String, CssModuleExport>>,
 
+        /// Values exported with `@value` and `:export`.
+        icss_exports: Vc<IcssExports>,
+
         #[turbo_tasks(trace_ignore)]
         placeholders: HashMap<String, Url<'static>>,
     },
@@ -369,6 +381,7 @@ pub async fn process_css_with_placeholder(
             stylesheet,
             references,
             url_references,
+            icss_exports,
             code,
             ..
         } => {
@@ -378,8 +391,15 @@ pub async fn process_css_with_placeholder(
                 _ => bail!("this case should be filtered out while parsing"),
             };
 
-            let (result, _) =
-                stylesheet.to_css(cm.clone(), &code, MinifyType::NoMinify, false, false, false)?;
+            let (result, _) = stylesheet.to_css(
+                cm.clone(),
+                &code,
+                MinifyType::NoMinify,
+                false,
+                false,
+                false,
+                None,
+            )?;
 
             let exports = result.exports.map(|exports| {
                 let mut exports = exports.into_iter().collect::<FxIndexMap<_, _>>();
@@ -393,6 +413,7 @@ pub async fn process_css_with_placeholder(
                 parse_result,
                 cm: cm.clone(),
                 exports,
+                icss_exports: *icss_exports,
                 references: *references,
                 url_references: *url_references,
                 placeholders: HashMap::new(),
@@ -404,6 +425,12 @@ pub async fn process_css_with_placeholder(
     }
 }
 
+/// The browsers of a browserslist query, which the css is compiled for.
+pub(crate) fn browsers(query: &str) -> Result<Option<Browsers>> {
+    Browsers::from_browserslist(query.split(',').map(str::trim))
+        .map_err(|err| anyhow!("Invalid browserslist query \"{query}\": {err}"))
+}
+
 #[turbo_tasks::function]
 pub async fn finalize_css(
     result: Vc<CssWithPlaceholderResult>,
@@ -418,13 +445,18 @@ pub async fn finalize_css(
             url_references,
             ..
         } => {
-            let (mut stylesheet, code) = match &*parse_result.await? {
+            let (mut stylesheet, code, original_source_map) = match &*parse_result.await? {
                 ParseCssResult::Ok {
                     stylesheet,
                     options,
                     code,
+                    original_source_map,
                     ..
-                } => (stylesheet.to_static(options.clone()), *code),
+                } => (
+                    stylesheet.to_static(options.clone()),
+                    *code,
+                    *original_source_map,
+                ),
                 ParseCssResult::Unparseable => return Ok(FinalCssResult::Unparseable.into()),
                 ParseCssResult::NotFound => return Ok(FinalCssResult::NotFound.into()),
             };
@@ -448,12 +480,15 @@ pub async fn finalize_css(
                 _ => bail!("this case should be filtered out while parsing"),
             };
             let (result, srcmap) =
-                stylesheet.to_css(cm.clone(), &code, minify_type, true, true, true)?;
+                stylesheet.to_css(cm.clone(), &code, minify_type, true, true, true, None)?;
 
             Ok(FinalCssResult::Ok {
                 output_code: result.code,
                 exports: result.exports,
-                source_map: srcmap.unwrap().cell(),
+                source_map: srcmap
+                    .unwrap()
+                    .with_original_source_map(original_source_map)
+                    .cell(),
             }
             .into())
         }
@@ -533,114 +568,50 @@ async fn process_content(
     ty: CssModuleAssetType,
     use_swc_css: bool,
 ) -> Result<Vc<ParseCssResult>> {
-    #[allow(clippy::needless_lifetimes)]
-    fn without_warnings<'o, 'i>(config: ParserOptions<'o, 'i>) -> ParserOptions<'o, 'static> {
-        ParserOptions {
-            filename: config.filename,
-            css_modules: config.css_modules,
-            source_index: config.source_index,
-            error_recovery: config.error_recovery,
-            warnings: None,
-            flags: config.flags,
-        }
-    }
-
-    let config = ParserOptions {
-        css_modules: match ty {
-            CssModuleAssetType::Module => Some(lightningcss::css_modules::Config {
-                pattern: Pattern {
-                    segments: smallvec![
-                        Segment::Name,
-                        Segment::Literal("__"),
-                        Segment::Hash,
-                        Segment::Literal("__"),
-                        Segment::Local,
-                    ],
-                },
-                dashed_idents: false,
-                grid: false,
-                ..Default::default()
-            }),
-
-            _ => None,
-        },
-        filename: filename.to_string(),
-        error_recovery: true,
-        ..Default::default()
+    // `@value` and ICSS rules are not understood by the parsers, so they are
+    // extracted and the values are substituted beforehand.
+    let icss = extract_icss(&code);
+    let (code, icss_exports, icss_references) = if icss.is_empty() {
+        (code, IcssExports::empty(), Vec::new())
+    } else {
+        let blanked = blank_ranges(&code, &icss.ranges);
+        let resolved = resolve_icss(icss, &code, source, origin, fs_path_vc).await?;
+        (
+            replace_values(&blanked, &resolved.values, true),
+            Vc::cell(resolved.exports),
+            resolved.references,
+        )
     };
 
+    let config = parser_options(ty, filename);
+
     let cm: Arc<swc_core::common::SourceMap> = Default::default();
 
     let stylesheet = if !use_swc_css {
-        StyleSheetLike::LightningCss({
-            let warnings: Arc<RwLock<_>> = Default::default();
-
-            match StyleSheet::parse(
-                &code,
-                ParserOptions {
-                    warnings: Some(warnings.clone()),
-                    ..config.clone()
-                },
-            ) {
-                Ok(mut ss) => {
-                    if matches!(ty, CssModuleAssetType::Module) {
-                        let mut validator = CssValidator { errors: Vec::new() };
-                        ss.visit(&mut validator).unwrap();
-
-                        for err in validator.errors {
-                            err.report(source, fs_path_vc);
-                        }
-                    }
-
-                    for err in warnings.read().unwrap().iter() {
-                        match err.kind {
-                            lightningcss::error::ParserError::UnexpectedToken(_)
-                            | lightningcss::error::ParserError::UnexpectedImportRule
-                            | lightningcss::error::ParserError::SelectorError(..)
-                            | lightningcss::error::ParserError::EndOfInput => {
-                                let source = err.loc.as_ref().map(|loc| {
-                                    let pos = SourcePos {
-                                        line: loc.line as _,
-                                        column: loc.column as _,
-                                    };
-                                    IssueSource::from_line_col(source, pos, pos)
-                                });
-
-                                ParsingIssue {
-                                    file: fs_path_vc,
-                                    msg: Vc::cell(err.to_string().into()),
-                                    source,
-                                }
-                                .cell()
-                                .emit();
-                                return Ok(ParseCssResult::Unparseable.cell());
-                            }
-
-                            _ => {
-                                // Ignore
-                            }
-                        }
+        StyleSheetLike::LightningCss(match parse_lightningcss(&code, &config) {
+            Ok(mut ss) => {
+                if matches!(ty, CssModuleAssetType::Module) {
+                    let mut validator = CssValidator { errors: Vec::new() };
+                    ss.visit(&mut validator).unwrap();
+
+                    for err in validator.errors {
+                        err.report(source, fs_path_vc);
                     }
-
-                    stylesheet_into_static(&ss, without_warnings(config.clone()))
                 }
-                Err(e) => {
-                    let source = e.loc.as_ref().map(|loc| {
-                        let pos = SourcePos {
-                            line: loc.line as _,
-                            column: loc.column as _,
-                        };
-                        IssueSource::from_line_col(source, pos, pos)
-                    });
-                    ParsingIssue {
-                        file: fs_path_vc,
-                        msg: Vc::cell(e.to_string().into()),
-                        source,
-                    }
-                    .cell()
-                    .emit();
-                    return Ok(ParseCssResult::Unparseable.cell());
+
+                ss
+            }
+            Err(err) => {
+                ParsingIssue {
+                    file: fs_path_vc,
+                    msg: Vc::cell(err.message.into()),
+                    source: err
+                        .pos
+                        .map(|pos| IssueSource::from_line_col(source, pos, pos)),
                 }
+                .cell()
+                .emit();
+                return Ok(ParseCssResult::Unparseable.cell());
             }
         })
     } else {
@@ -721,8 +692,15 @@ async fn process_content(
     let config = without_warnings(config);
     let mut stylesheet = stylesheet.to_static(config.clone());
 
-    let (references, url_references) =
+    let (mut references, url_references) =
         analyze_references(&mut stylesheet, source, origin, import_context)?;
+    references.extend(icss_references);
+
+    let original_source_map =
+        match Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(source).await? {
+            Some(generator) => generator.generate_source_map(),
+            None => OptionSourceMap::none(),
+        };
 
     Ok(ParseCssResult::Ok {
         cm,
@@ -730,11 +708,112 @@ async fn process_content(
         stylesheet,
         references: Vc::cell(references),
         url_references: Vc::cell(url_references),
+        icss_exports,
+        original_source_map,
         options: config,
     }
     .cell())
 }
 
+/// The options css is parsed with. CSS modules class names are hashed with
+/// `filename`.
+pub(crate) fn parser_options<'o, 'i>(
+    ty: CssModuleAssetType,
+    filename: &str,
+) -> ParserOptions<'o, 'i> {
+    ParserOptions {
+        css_modules: match ty {
+            CssModuleAssetType::Module => Some(lightningcss::css_modules::Config {
+                pattern: Pattern {
+                    segments: smallvec![
+                        Segment::Name,
+                        Segment::Literal("__"),
+                        Segment::Hash,
+                        Segment::Literal("__"),
+                        Segment::Local,
+                    ],
+                },
+                dashed_idents: false,
+                grid: false,
+                ..Default::default()
+            }),
+
+            _ => None,
+        },
+        filename: filename.to_string(),
+        error_recovery: true,
+        ..Default::default()
+    }
+}
+
+#[allow(clippy::needless_lifetimes)]
+fn without_warnings<'o, 'i>(config: ParserOptions<'o, 'i>) -> ParserOptions<'o, 'static> {
+    ParserOptions {
+        filename: config.filename,
+        css_modules: config.css_modules,
+        source_index: config.source_index,
+        error_recovery: config.error_recovery,
+        warnings: None,
+        flags: config.flags,
+    }
+}
+
+/// A syntax error that makes a stylesheet unusable.
+pub(crate) struct CssSyntaxError {
+    pub message: String,
+    pub pos: Option<SourcePos>,
+}
+
+impl CssSyntaxError {
+    fn new<T: std::fmt::Display>(err: &lightningcss::error::Error<T>) -> Self {
+        CssSyntaxError {
+            message: err.to_string(),
+            pos: err.loc.as_ref().map(|loc| SourcePos {
+                line: loc.line as _,
+                column: loc.column as _,
+            }),
+        }
+    }
+}
+
+/// Parses `code` with lightningcss. The parser recovers from most errors, but
+/// some of them are still errors, as the stylesheet would be wrong.
+pub(crate) fn parse_lightningcss<'i>(
+    code: &'i str,
+    config: &ParserOptions<'static, 'i>,
+) -> Result<StyleSheet<'static, 'static>, CssSyntaxError> {
+    let warnings: Arc<RwLock<_>> = Default::default();
+
+    let ss = StyleSheet::parse(
+        code,
+        ParserOptions {
+            warnings: Some(warnings.clone()),
+            ..config.clone()
+        },
+    )
+    .map_err(|err| CssSyntaxError::new(&err))?;
+
+    for err in warnings.read().unwrap().iter() {
+        match err.kind {
+            lightningcss::error::ParserError::UnexpectedToken(_)
+            | lightningcss::error::ParserError::UnexpectedImportRule
+            | lightningcss::error::ParserError::SelectorError(..)
+            | lightningcss::error::ParserError::EndOfInput => {
+                return Err(CssSyntaxError::new(err));
+            }
+
+            _ => {
+                // Ignore
+            }
+        }
+    }
+
+    Ok(stylesheet_into_static(
+        &ss,
+        without_warnings(config.clone()),
+    ))
+}
+
 /// Visitor that lints wrong css module usage.
 ///
 /// ```css
@@ -743,12 +822,12 @@ async fn process_content(
 /// ```
 ///
 /// is wrong for a css module because it doesn't have a class name.
-struct CssValidator {
-    errors: Vec<CssError>,
+pub(crate) struct CssValidator {
+    pub errors: Vec<CssError>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
-enum CssError {
+pub(crate) enum CssError {
     SwcSelectorInModuleNotPure { span: Span },
     LightningCssSelectorInModuleNotPure { selector: String },
 }
@@ -769,10 +848,10 @@ impl CssError {
                 .cell()
                 .emit();
             }
-            CssError::LightningCssSelectorInModuleNotPure { selector } => {
+            CssError::LightningCssSelectorInModuleNotPure { .. } => {
                 ParsingIssue {
                     file,
-                    msg: Vc::cell(format!("{CSS_MODULE_ERROR}, (lightningcss, {selector})").into()),
+                    msg: Vc::cell(self.to_string().into()),
                     source: None,
                 }
                 .cell()
@@ -782,6 +861,17 @@ impl CssError {
     }
 }
 
+impl std::fmt::Display for CssError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CssError::SwcSelectorInModuleNotPure { .. } => write!(f, "{CSS_MODULE_ERROR}"),
+            CssError::LightningCssSelectorInModuleNotPure { selector } => {
+                write!(f, "{CSS_MODULE_ERROR}, (lightningcss, {selector})")
+            }
+        }
+    }
+}
+
 const CSS_MODULE_ERROR: &str =
     "Selector is not pure (pure selectors must contain at least one local class or id)";
 
@@ -945,6 +1035,9 @@ pub enum ParseCssResultSourceMap {
     Parcel {
         #[turbo_tasks(debug_ignore, trace_ignore)]
         source_map: parcel_sourcemap::SourceMap,
+
+        /// The source map of the source the css was parsed from.
+        original_source_map: Option<Vc<OptionSourceMap>>,
     },
 
     Swc {
@@ -955,6 +1048,9 @@ pub enum ParseCssResultSourceMap {
         /// (SWC) SourceMap.
         #[turbo_tasks(debug_ignore, trace_ignore)]
         mappings: Vec<(BytePos, LineCol)>,
+
+        /// The source map of the source the css was parsed from.
+        original_source_map: Option<Vc<OptionSourceMap>>,
     },
 }
 
@@ -966,7 +1062,10 @@ impl PartialEq for ParseCssResultSourceMap {
 
 impl ParseCssResultSourceMap {
     pub fn new_lightningcss(source_map: parcel_sourcemap::SourceMap) -> Self {
-        ParseCssResultSourceMap::Parcel { source_map }
+        ParseCssResultSourceMap::Parcel {
+            source_map,
+            original_source_map: None,
+        }
     }
 
     pub fn new_swc(
@@ -976,58 +1075,111 @@ impl ParseCssResultSourceMap {
         ParseCssResultSourceMap::Swc {
             source_map,
             mappings,
+            original_source_map: None,
         }
     }
+
+    /// Maps the css back to the sources of a transformed source, which are
+    /// described by `original`.
+    pub fn with_original_source_map(mut self, original: Vc<OptionSourceMap>) -> Self {
+        match &mut self {
+            ParseCssResultSourceMap::Parcel {
+                original_source_map,
+                ..
+            }
+            | ParseCssResultSourceMap::Swc {
+                original_source_map,
+                ..
+            } => *original_source_map = Some(original),
+        }
+        self
+    }
 }
 
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for ParseCssResultSourceMap {
     #[turbo_tasks::function]
-    fn generate_source_map(&self) -> Vc<OptionSourceMap> {
-        match self {
-            ParseCssResultSourceMap::Parcel { source_map } => {
-                let mut builder = SourceMapBuilder::new(None);
-
-                for src in source_map.get_sources() {
-                    builder.add_source(&format!("{SOURCE_MAP_PREFIX}{src}"));
-                }
-
-                for (idx, content) in source_map.get_sources_content().iter().enumerate() {
-                    builder.set_source_contents(idx as _, Some(content));
-                }
+    async fn generate_source_map(&self) -> Result<Vc<OptionSourceMap>> {
+        let (ParseCssResultSourceMap::Parcel {
+            original_source_map,
+            ..
+        }
+        | ParseCssResultSourceMap::Swc {
+            original_source_map,
+            ..
+        }) = self;
+        let original_src_map = match original_source_map {
+            Some(original) => match *original.await? {
+                Some(original) => Some(original.await?.to_source_map().await?),
+                None => None,
+            },
+            None => None,
+        };
+        let input_map = if let Some(map) = original_src_map.as_ref() {
+            map.as_regular_source_map()
+        } else {
+            None
+        };
 
-                for m in source_map.get_mappings() {
-                    builder.add_raw(
-                        m.generated_line,
-                        m.generated_column,
-                        m.original.map(|v| v.original_line).unwrap_or_default(),
-                        m.original.map(|v| v.original_column).unwrap_or_default(),
-                        Some(0),
-                        None,
-                        false,
-                    );
+        let map = match self {
+            ParseCssResultSourceMap::Parcel { source_map, .. } => {
+                let map = parcel_to_source_map(source_map, SOURCE_MAP_PREFIX);
+                match input_map {
+                    // `adjust_mappings` maps the css to the sources of the
+                    // transformed source.
+                    Some(input_map) => {
+                        let mut input_map = input_map.into_owned();
+                        input_map.adjust_mappings(&map);
+                        input_map
+                    }
+                    None => map,
                 }
-
-                Vc::cell(Some(
-                    turbopack_core::source_map::SourceMap::new_regular(builder.into_sourcemap())
-                        .cell(),
-                ))
             }
             ParseCssResultSourceMap::Swc {
                 source_map,
                 mappings,
-            } => {
-                let map = source_map.build_source_map_with_config(
-                    mappings,
-                    None,
-                    InlineSourcesContentConfig {},
-                );
-                Vc::cell(Some(
-                    turbopack_core::source_map::SourceMap::new_regular(map).cell(),
-                ))
-            }
-        }
+                ..
+            } => source_map.build_source_map_with_config(
+                mappings,
+                input_map.as_deref(),
+                InlineSourcesContentConfig {},
+            ),
+        };
+        Ok(Vc::cell(Some(
+            turbopack_core::source_map::SourceMap::new_regular(map).cell(),
+        )))
+    }
+}
+
+/// Converts a lightningcss source map. The names of the sources are prefixed
+/// with `source_prefix`.
+pub(crate) fn parcel_to_source_map(
+    source_map: &parcel_sourcemap::SourceMap,
+    source_prefix: &str,
+) -> swc_core::base::sourcemap::SourceMap {
+    let mut builder = SourceMapBuilder::new(None);
+
+    for src in source_map.get_sources() {
+        builder.add_source(&format!("{source_prefix}{src}"));
+    }
+
+    for (idx, content) in source_map.get_sources_content().iter().enumerate() {
+        builder.set_source_contents(idx as _, Some(content));
+    }
+
+    for m in source_map.get_mappings() {
+        builder.add_raw(
+            m.generated_line,
+            m.generated_column,
+            m.original.map(|v| v.original_line).unwrap_or_default(),
+            m.original.map(|v| v.original_column).unwrap_or_default(),
+            Some(0),
+            None,
+            false,
+        );
     }
+
+    builder.into_sourcemap()
 }
 
 struct SwcDepCollector<'a> {
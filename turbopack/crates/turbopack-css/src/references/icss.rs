@@ -0,0 +1,65 @@
+use anyhow::Result;
+use turbo_tasks::{RcStr, Value, ValueToString, Vc};
+use turbopack_core::{
+    chunk::ChunkableModuleReference,
+    issue::IssueSource,
+    reference::ModuleReference,
+    reference_type::CssReferenceSubType,
+    resolve::{origin::ResolveOrigin, parse::Request, ModuleResolveResult},
+};
+
+use crate::references::css_resolve;
+
+/// A reference to the stylesheet values are imported from, either with
+/// `@value ... from ...` or an ICSS `:import(...)` block.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct IcssImportReference {
+    pub origin: Vc<Box<dyn ResolveOrigin>>,
+    pub request: Vc<Request>,
+    pub issue_source: Vc<IssueSource>,
+}
+
+#[turbo_tasks::value_impl]
+impl IcssImportReference {
+    /// Creates a new [`IcssImportReference`].
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: Vc<Box<dyn ResolveOrigin>>,
+        request: Vc<Request>,
+        issue_source: Vc<IssueSource>,
+    ) -> Vc<Self> {
+        Self::cell(IcssImportReference {
+            origin,
+            request,
+            issue_source,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleReference for IcssImportReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> Vc<ModuleResolveResult> {
+        // Like `composes`, this imports symbols of another stylesheet.
+        css_resolve(
+            self.origin,
+            self.request,
+            Value::new(CssReferenceSubType::Compose),
+            Some(self.issue_source),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for IcssImportReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<RcStr>> {
+        Ok(Vc::cell(
+            format!("icss import {}", self.request.to_string().await?,).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for IcssImportReference {}
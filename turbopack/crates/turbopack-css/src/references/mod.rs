@@ -30,6 +30,7 @@ use crate::{
 };
 
 pub(crate) mod compose;
+pub(crate) mod icss;
 pub(crate) mod import;
 pub(crate) mod internal;
 pub(crate) mod url;
@@ -0,0 +1,1352 @@
+//! Compiles Sass and SCSS stylesheets to CSS.
+//!
+//! Requests in `@use`, `@forward` and `@import` rules are resolved up front with
+//! the turbopack resolver, so every loaded partial is tracked for invalidation.
+//! The resolved stylesheets are then handed to the compiler through an
+//! in-memory file system.
+//!
+//! The compiler doesn't emit source maps. Instead, every block gets a comment
+//! with its position in the stylesheet before compiling. The compiler keeps
+//! the comments in the rules they belong to, so the lines of the compiled CSS
+//! are mapped to the rule they were generated from, and the comments are
+//! removed again.
+
+use std::{io, ops::Range, path::Path};
+
+use anyhow::{bail, Result};
+use rustc_hash::FxHashMap;
+use swc_core::base::sourcemap::SourceMapBuilder;
+use turbo_tasks::{FxIndexMap, RcStr, ResolvedVc, Value, ValueToString, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemEntryType, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    ident::AssetIdent,
+    issue::{Issue, IssueExt, IssueStage, OptionStyledString, StyledString},
+    reference_type::{CssReferenceSubType, ReferenceType},
+    resolve::{options::ResolveOptions, parse::Request, resolve},
+    source::Source,
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap},
+    source_transform::SourceTransform,
+    SOURCE_MAP_PREFIX,
+};
+
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Default)]
+pub struct SassTransformOptions {
+    /// Directories that are searched for stylesheets which can't be found
+    /// relative to the importing file (`includePaths` in sass-loader).
+    pub include_paths: Vec<ResolvedVc<FileSystemPath>>,
+    /// Sass code that is prepended to every entry stylesheet.
+    pub additional_data: Option<RcStr>,
+}
+
+/// A [SourceTransform] that compiles `.scss` and `.sass` sources to CSS.
+#[turbo_tasks::value]
+pub struct SassTransform {
+    resolve_options: ResolvedVc<ResolveOptions>,
+    options: ResolvedVc<SassTransformOptions>,
+    rename_as: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl SassTransform {
+    /// `rename_as` is applied to the ident of the compiled source, e.g.
+    /// `*.css`, so that CSS module rules match it.
+    #[turbo_tasks::function]
+    pub fn new(
+        resolve_options: ResolvedVc<ResolveOptions>,
+        options: ResolvedVc<SassTransformOptions>,
+        rename_as: RcStr,
+    ) -> Vc<Self> {
+        SassTransform {
+            resolve_options,
+            options,
+            rename_as,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for SassTransform {
+    #[turbo_tasks::function]
+    fn transform(
+        self: ResolvedVc<Self>,
+        source: ResolvedVc<Box<dyn Source>>,
+    ) -> Vc<Box<dyn Source>> {
+        Vc::upcast(
+            SassCompiledSource {
+                transform: self,
+                source,
+            }
+            .cell(),
+        )
+    }
+}
+
+#[turbo_tasks::value]
+struct SassCompiledSource {
+    transform: ResolvedVc<SassTransform>,
+    source: ResolvedVc<Box<dyn Source>>,
+}
+
+#[turbo_tasks::value_impl]
+impl Source for SassCompiledSource {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<Vc<AssetIdent>> {
+        Ok(self
+            .source
+            .ident()
+            .rename_as(self.transform.await?.rename_as.clone()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for SassCompiledSource {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        Ok(*self.compile().await?.content)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for SassCompiledSource {
+    #[turbo_tasks::function]
+    async fn generate_source_map(self: Vc<Self>) -> Result<Vc<OptionSourceMap>> {
+        Ok(Vc::cell(self.compile().await?.source_map.map(|map| *map)))
+    }
+}
+
+#[turbo_tasks::value]
+struct SassCompileResult {
+    content: ResolvedVc<AssetContent>,
+    source_map: Option<ResolvedVc<SourceMap>>,
+}
+
+impl SassCompileResult {
+    fn not_found() -> Vc<Self> {
+        SassCompileResult {
+            content: AssetContent::File(FileContent::NotFound.resolved_cell()).resolved_cell(),
+            source_map: None,
+        }
+        .cell()
+    }
+}
+
+/// A stylesheet with all resolvable requests rewritten to absolute paths in
+/// the in-memory file system passed to the compiler.
+#[turbo_tasks::value]
+struct PreparedSass {
+    code: RcStr,
+    /// The code with a [source marker][SOURCE_MARKER] in every block.
+    marked_code: RcStr,
+    /// The stylesheet before rewriting, used for the source map.
+    original: RcStr,
+    dependencies: Vec<ResolvedVc<FileSystemPath>>,
+}
+
+#[turbo_tasks::value_impl]
+impl SassCompiledSource {
+    #[turbo_tasks::function]
+    async fn compile(self: Vc<Self>) -> Result<Vc<SassCompileResult>> {
+        let this = self.await?;
+        let transform = this.transform.await?;
+        let options = transform.options.await?;
+
+        let path = this.source.ident().path();
+        let syntax = SassSyntax::from_path(&*path.await?);
+        let AssetContent::File(file) = *this.source.content().await? else {
+            bail!("Sass transform only supports transforming files");
+        };
+        let FileContent::Content(content) = &*file.await? else {
+            return Ok(SassCompileResult::not_found());
+        };
+        let original = content.content().to_str()?;
+
+        // `additionalData` shifts the entry down by this many lines.
+        let (code, first_line) = match options.additional_data.as_deref() {
+            Some(additional_data) => (
+                format!("{additional_data}\n{original}"),
+                additional_data.matches('\n').count() + 1,
+            ),
+            None => (original.to_string(), 0),
+        };
+
+        let entry_path = virtual_path(&*path.await?);
+        let entry_dir = path.parent().resolve().await?;
+        let (code, marked_code, dependencies) = prepare_sass(
+            &code,
+            syntax,
+            &entry_path,
+            first_line,
+            entry_dir,
+            None,
+            *transform.resolve_options,
+            &options.include_paths,
+        )
+        .await?;
+
+        // Load all transitively imported stylesheets.
+        let mut files = FxIndexMap::default();
+        let mut queue = dependencies;
+        while let Some(dependency) = queue.pop() {
+            let virtual_path = virtual_path(&*dependency.await?);
+            if files.contains_key(&virtual_path) {
+                continue;
+            }
+            let prepared = prepare_sass_partial(
+                *dependency,
+                entry_dir,
+                *transform.resolve_options,
+                *transform.options,
+            )
+            .await?;
+            queue.extend(prepared.dependencies.iter().copied());
+            files.insert(virtual_path, (dependency, prepared));
+        }
+
+        let marked_files = files
+            .iter()
+            .map(|(path, (_, prepared))| (path.clone(), prepared.marked_code.clone()))
+            .collect();
+        let compiled =
+            turbo_tasks::spawn_blocking(move || compile_sass(marked_code, marked_files, syntax))
+                .await;
+        let (css, source_map) = match compiled {
+            Ok(css) => {
+                let (css, positions) = strip_source_markers(&css);
+
+                let mut sources = FxHashMap::default();
+                sources.insert(
+                    entry_path,
+                    (
+                        path.to_string().await?.clone_value(),
+                        RcStr::from(&*original),
+                    ),
+                );
+                for (virtual_path, (dependency, prepared)) in &files {
+                    sources.insert(
+                        virtual_path.clone(),
+                        (
+                            dependency.to_string().await?.clone_value(),
+                            prepared.original.clone(),
+                        ),
+                    );
+                }
+                let source_map =
+                    SourceMap::new_regular(build_source_map(&positions, &sources)).resolved_cell();
+                (css, Some(source_map))
+            }
+            // The markers add lines to the indented syntax, which would shift
+            // the positions in the error. Without them, the error is reported
+            // at the right position, or the stylesheet still compiles if a
+            // marker was misplaced.
+            Err(_) => {
+                let files = files
+                    .iter()
+                    .map(|(path, (_, prepared))| (path.clone(), prepared.code.clone()))
+                    .collect();
+                match turbo_tasks::spawn_blocking(move || compile_sass(code, files, syntax)).await {
+                    Ok(css) => (css, None),
+                    Err(message) => {
+                        SassCompileIssue {
+                            file: path.to_resolved().await?,
+                            message: message.into(),
+                        }
+                        .cell()
+                        .emit();
+                        return Ok(SassCompileResult::not_found());
+                    }
+                }
+            }
+        };
+
+        Ok(SassCompileResult {
+            content: AssetContent::file(File::from(css).into())
+                .to_resolved()
+                .await?,
+            source_map,
+        }
+        .cell())
+    }
+}
+
+/// Compiles `code` with the stylesheets it loads in `files`, keyed by their
+/// virtual paths. This is CPU heavy and blocks.
+fn compile_sass(
+    code: String,
+    files: FxHashMap<String, RcStr>,
+    syntax: SassSyntax,
+) -> Result<String, String> {
+    let fs = SassFs { files };
+    let options = grass_compiler::Options::default()
+        .fs(&fs)
+        .input_syntax(syntax.into())
+        .style(grass_compiler::OutputStyle::Expanded)
+        .quiet(true);
+    grass_compiler::from_string(code, &options).map_err(|err| err.to_string())
+}
+
+#[turbo_tasks::function]
+async fn prepare_sass_partial(
+    path: Vc<FileSystemPath>,
+    entry_dir: Vc<FileSystemPath>,
+    resolve_options: Vc<ResolveOptions>,
+    options: Vc<SassTransformOptions>,
+) -> Result<Vc<PreparedSass>> {
+    let FileContent::Content(file) = &*path.read().await? else {
+        bail!("Sass file {} not found", path.to_string().await?);
+    };
+    let original = file.content().to_str()?;
+    let path_ref = path.await?;
+    let (code, marked_code, dependencies) = prepare_sass(
+        &original,
+        SassSyntax::from_path(&path_ref),
+        &virtual_path(&path_ref),
+        0,
+        path.parent(),
+        Some(entry_dir),
+        resolve_options,
+        &options.await?.include_paths,
+    )
+    .await?;
+    Ok(PreparedSass {
+        code: code.into(),
+        marked_code: marked_code.into(),
+        original: original.into_owned().into(),
+        dependencies,
+    }
+    .cell())
+}
+
+/// Rewrites all resolvable requests in `code` to absolute paths and returns
+/// the rewritten code, the rewritten code with [source markers][SOURCE_MARKER]
+/// and the resolved stylesheets.
+///
+/// When the stylesheet is a partial of an entry in another directory,
+/// relative `url()`s are rebased onto `entry_dir`, because the compiled CSS is
+/// resolved relative to the entry.
+///
+/// The markers refer to the stylesheet at `virtual_path`, which starts at
+/// `first_line` of `code`.
+#[allow(clippy::too_many_arguments)]
+async fn prepare_sass(
+    code: &str,
+    syntax: SassSyntax,
+    virtual_path: &str,
+    first_line: usize,
+    dir: Vc<FileSystemPath>,
+    entry_dir: Option<Vc<FileSystemPath>>,
+    resolve_options: Vc<ResolveOptions>,
+    include_paths: &[ResolvedVc<FileSystemPath>],
+) -> Result<(String, String, Vec<ResolvedVc<FileSystemPath>>)> {
+    let scanned = scan_sass(code, syntax);
+
+    let mut replacements = Vec::new();
+    let mut dependencies = Vec::new();
+    for import in scanned.imports {
+        let Some(resolved) =
+            resolve_sass_request(&import.request, dir, resolve_options, include_paths).await?
+        else {
+            // Leave the request alone and let the compiler report it.
+            continue;
+        };
+        let quote = &code[import.range.start..import.range.start + 1];
+        let mut replacement = format!("{quote}{}{quote}", virtual_path(&*resolved.await?));
+        if import.kind == SassImportKind::Use && !import.has_namespace {
+            // The default namespace is derived from the request, which is
+            // no longer visible to the compiler.
+            replacement.push_str(" as ");
+            replacement.push_str(default_namespace(&import.request));
+        }
+        replacements.push((import.range, replacement));
+        dependencies.push(resolved);
+    }
+
+    if let Some(entry_dir) = entry_dir {
+        let dir_ref = dir.await?;
+        let entry_dir_ref = entry_dir.await?;
+        if *dir_ref != *entry_dir_ref {
+            for url in scanned.urls {
+                let Some(target) = *dir.try_join(code[url.clone()].into()).await? else {
+                    continue;
+                };
+                if let Some(relative) = entry_dir_ref.get_relative_path_to(&*target.await?) {
+                    replacements.push((url, relative.to_string()));
+                }
+            }
+        }
+    }
+
+    let mut markers = Vec::new();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(code.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    for block in scanned.blocks {
+        let line = line_starts.partition_point(|&start| start <= block.start) - 1;
+        if line < first_line {
+            continue;
+        }
+        let column = block.start - line_starts[line];
+        let marker = format!(
+            "/*!{SOURCE_MARKER} {virtual_path}:{}:{column}*/",
+            line - first_line
+        );
+        let marker = match block.indentation {
+            Some(indentation) => format!("\n{indentation}{marker}"),
+            None => marker,
+        };
+        markers.push((block.body..block.body, marker));
+    }
+
+    let code_without_markers = apply_replacements(code, replacements.clone());
+    replacements.extend(markers);
+    Ok((
+        code_without_markers,
+        apply_replacements(code, replacements),
+        dependencies,
+    ))
+}
+
+fn apply_replacements(code: &str, mut replacements: Vec<(Range<usize>, String)>) -> String {
+    replacements.sort_by_key(|(range, _)| range.start);
+    let mut result = String::with_capacity(code.len());
+    let mut last = 0;
+    for (range, replacement) in replacements {
+        result.push_str(&code[last..range.start]);
+        result.push_str(&replacement);
+        last = range.end;
+    }
+    result.push_str(&code[last..]);
+    result
+}
+
+/// Resolves a Sass request the way Sass does: relative to the importing
+/// file, then in the include paths, trying partials (`_name`), all Sass
+/// extensions and index files. Requests that can't be found on disk are
+/// resolved as package requests (a leading `~` is ignored).
+async fn resolve_sass_request(
+    request: &str,
+    dir: Vc<FileSystemPath>,
+    resolve_options: Vc<ResolveOptions>,
+    include_paths: &[ResolvedVc<FileSystemPath>],
+) -> Result<Option<ResolvedVc<FileSystemPath>>> {
+    if let Some(package_request) = request.strip_prefix('~') {
+        return resolve_sass_package(package_request, dir, resolve_options).await;
+    }
+
+    let candidates = sass_candidates(request);
+    for base in std::iter::once(dir).chain(include_paths.iter().map(|path| **path)) {
+        for candidate in &candidates {
+            let Some(path) = *base.try_join(candidate.as_str().into()).await? else {
+                continue;
+            };
+            if matches!(*path.get_type().await?, FileSystemEntryType::File) {
+                return Ok(Some(path));
+            }
+        }
+    }
+
+    if request.starts_with('.') || request.starts_with('/') {
+        return Ok(None);
+    }
+    resolve_sass_package(request, dir, resolve_options).await
+}
+
+async fn resolve_sass_package(
+    request: &str,
+    dir: Vc<FileSystemPath>,
+    resolve_options: Vc<ResolveOptions>,
+) -> Result<Option<ResolvedVc<FileSystemPath>>> {
+    // The request itself comes last, so that the `main`/`exports` of a
+    // package are used when no stylesheet matches.
+    for candidate in sass_candidates(request)
+        .into_iter()
+        .chain(std::iter::once(request.to_string()))
+    {
+        let result = resolve(
+            dir,
+            Value::new(ReferenceType::Css(CssReferenceSubType::AtImport(None))),
+            Request::parse_string(candidate.into()),
+            resolve_options,
+        )
+        .first_source()
+        .await?;
+        if let Some(source) = *result {
+            return Ok(Some(source.ident().path().to_resolved().await?));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the paths Sass tries for a request, in order.
+fn sass_candidates(request: &str) -> Vec<String> {
+    let (dir, name) = match request.rsplit_once('/') {
+        Some((dir, name)) => (format!("{dir}/"), name),
+        None => (String::new(), request),
+    };
+
+    if [".scss", ".sass", ".css"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+    {
+        return vec![format!("{dir}_{name}"), request.to_string()];
+    }
+
+    let mut candidates = Vec::new();
+    for ext in ["scss", "sass", "css"] {
+        candidates.push(format!("{dir}_{name}.{ext}"));
+        candidates.push(format!("{dir}{name}.{ext}"));
+    }
+    for ext in ["scss", "sass", "css"] {
+        candidates.push(format!("{request}/_index.{ext}"));
+        candidates.push(format!("{request}/index.{ext}"));
+    }
+    candidates
+}
+
+/// The namespace of a `@use` rule without `as`: the last component of the
+/// request, without extension.
+fn default_namespace(request: &str) -> &str {
+    let name = request.rsplit('/').next().unwrap_or(request);
+    name.split('.').next().unwrap_or(name)
+}
+
+fn virtual_path(path: &FileSystemPath) -> String {
+    format!("/{}", path.path)
+}
+
+/// The start of the comments that mark the position of a block, e.g.
+/// `/*!turbopack-sass-source /styles/index.scss:12:2*/`, with the virtual path
+/// of the stylesheet and the zero-based line and column of the rule.
+const SOURCE_MARKER: &str = "turbopack-sass-source";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SourcePosition {
+    virtual_path: String,
+    line: u32,
+    column: u32,
+}
+
+fn parse_source_marker(line: &str) -> Option<SourcePosition> {
+    let marker = line
+        .trim()
+        .strip_prefix("/*!")?
+        .strip_prefix(SOURCE_MARKER)?
+        .strip_prefix(' ')?
+        .strip_suffix("*/")?;
+    let (marker, column) = marker.rsplit_once(':')?;
+    let (virtual_path, line) = marker.rsplit_once(':')?;
+    Some(SourcePosition {
+        virtual_path: virtual_path.to_string(),
+        line: line.parse().ok()?,
+        column: column.parse().ok()?,
+    })
+}
+
+/// Removes the source markers from the compiled CSS. Returns the CSS and the
+/// position of the rule each of its lines was generated from.
+fn strip_source_markers(css: &str) -> (String, Vec<Option<SourcePosition>>) {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut positions: Vec<Option<SourcePosition>> = Vec::new();
+    let mut current = None;
+    let mut input = css.lines().peekable();
+    while let Some(line) = input.next() {
+        let Some(position) = parse_source_marker(line) else {
+            lines.push(line);
+            positions.push(current.clone());
+            continue;
+        };
+        // The marker is the first child of the block, so it follows the
+        // selectors of the rule.
+        if lines.last().is_some_and(|last| last.ends_with('{')) {
+            let mut start = lines.len() - 1;
+            while start > 0 && lines[start - 1].ends_with(',') {
+                start -= 1;
+            }
+            if input.peek().is_some_and(|next| next.trim() == "}") {
+                // The compiler omits empty rules, which are only empty
+                // without the marker.
+                input.next();
+                lines.truncate(start);
+                positions.truncate(start);
+                continue;
+            }
+            for rule_position in &mut positions[start..] {
+                *rule_position = Some(position.clone());
+            }
+        }
+        current = Some(position);
+    }
+
+    let mut css = lines.join("\n");
+    if !css.is_empty() {
+        css.push('\n');
+    }
+    (css, positions)
+}
+
+/// Maps every line of the compiled CSS to its position in `sources`, which
+/// are the names and contents of the stylesheets by virtual path.
+fn build_source_map(
+    positions: &[Option<SourcePosition>],
+    sources: &FxHashMap<String, (RcStr, RcStr)>,
+) -> swc_core::base::sourcemap::SourceMap {
+    let mut builder = SourceMapBuilder::new(None);
+    let mut source_ids = FxHashMap::default();
+    for (line, position) in positions.iter().enumerate() {
+        let Some(position) = position else {
+            continue;
+        };
+        let Some((name, content)) = sources.get(&position.virtual_path) else {
+            continue;
+        };
+        let source_id = *source_ids
+            .entry(position.virtual_path.as_str())
+            .or_insert_with(|| {
+                let id = builder.add_source(&format!("{SOURCE_MAP_PREFIX}{name}"));
+                builder.set_source_contents(id, Some(content.as_str()));
+                id
+            });
+        builder.add_raw(
+            line as u32,
+            0,
+            position.line,
+            position.column,
+            Some(source_id),
+            None,
+            false,
+        );
+    }
+    builder.into_sourcemap()
+}
+
+#[derive(Debug)]
+struct SassFs {
+    files: FxHashMap<String, RcStr>,
+}
+
+impl SassFs {
+    fn get(&self, path: &Path) -> Option<&str> {
+        let path = path.to_string_lossy().replace('\\', "/");
+        self.files.get(path.as_str()).map(|code| code.as_str())
+    }
+}
+
+impl grass_compiler::Fs for SassFs {
+    fn is_dir(&self, _path: &Path) -> bool {
+        false
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.get(path).is_some()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.get(path)
+            .map(|code| code.as_bytes().to_vec())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SassSyntax {
+    Scss,
+    /// The indented `.sass` syntax.
+    Indented,
+}
+
+impl SassSyntax {
+    fn from_path(path: &FileSystemPath) -> Self {
+        if path.extension_ref() == Some("sass") {
+            SassSyntax::Indented
+        } else {
+            SassSyntax::Scss
+        }
+    }
+}
+
+impl From<SassSyntax> for grass_compiler::InputSyntax {
+    fn from(syntax: SassSyntax) -> Self {
+        match syntax {
+            SassSyntax::Scss => grass_compiler::InputSyntax::Scss,
+            SassSyntax::Indented => grass_compiler::InputSyntax::Sass,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SassImportKind {
+    Use,
+    Forward,
+    Import,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SassImport {
+    kind: SassImportKind,
+    /// The request without quotes.
+    request: String,
+    /// The quoted request in the source.
+    range: Range<usize>,
+    /// Whether a `@use` rule names its namespace with `as`.
+    has_namespace: bool,
+}
+
+/// The block of a rule, which gets a [source marker][SOURCE_MARKER].
+#[derive(Debug, PartialEq, Eq)]
+struct SassBlock {
+    /// The start of the rule.
+    start: usize,
+    /// Where the marker is inserted: after the `{`, or at the end of the
+    /// rule's line in the indented syntax.
+    body: usize,
+    /// The indentation of the children in the indented syntax, where the
+    /// marker needs a line of its own.
+    indentation: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ScannedSass {
+    imports: Vec<SassImport>,
+    /// Relative `url()` values, without quotes.
+    urls: Vec<Range<usize>>,
+    blocks: Vec<SassBlock>,
+}
+
+/// Finds the requests of `@use`, `@forward` and `@import` rules that load
+/// Sass stylesheets, the relative `url()`s and the blocks of a stylesheet.
+///
+/// Plain CSS imports, built-in modules (`sass:math`) and requests with
+/// interpolation are skipped, they are handled by the compiler.
+fn scan_sass(code: &str, syntax: SassSyntax) -> ScannedSass {
+    let mut scanner = Scanner {
+        code: code.as_bytes(),
+        syntax,
+        pos: 0,
+        depth: 0,
+        statement_start: None,
+        skipped_depth: None,
+        result: ScannedSass::default(),
+    };
+    scanner.scan();
+    let mut result = scanner.result;
+    if syntax == SassSyntax::Indented {
+        result.blocks = scan_indented_blocks(code);
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    /// Style rules and CSS at-rules, which are emitted as a block.
+    Rule,
+    /// Mixins, includes and control flow, whose contents end up in the
+    /// surrounding block. A marker would claim the rest of that block.
+    Inline,
+    /// Function bodies, nested properties (`font: { family: serif; }`) and
+    /// custom properties, which can't contain comments.
+    WithoutComments,
+}
+
+fn block_kind(rule: &str) -> BlockKind {
+    if let Some(at_rule) = rule.strip_prefix('@') {
+        let name = at_rule
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .next();
+        return match name {
+            Some("function") => BlockKind::WithoutComments,
+            Some("mixin" | "include" | "if" | "else" | "each" | "for" | "while") => {
+                BlockKind::Inline
+            }
+            _ => BlockKind::Rule,
+        };
+    }
+    let is_declaration = rule.starts_with("--")
+        || rule.match_indices(':').any(|(index, _)| {
+            rule[index + 1..]
+                .chars()
+                .next()
+                .map_or(true, char::is_whitespace)
+        });
+    if is_declaration {
+        BlockKind::WithoutComments
+    } else {
+        BlockKind::Rule
+    }
+}
+
+/// Finds the blocks of the indented syntax, which are the lines followed by
+/// more indented lines.
+fn scan_indented_blocks(code: &str) -> Vec<SassBlock> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in code.split_inclusive('\n') {
+        lines.push((offset, line.trim_end_matches(['\n', '\r'])));
+        offset += line.len();
+    }
+    let indentation = |line: &str| line.len() - line.trim_start().len();
+
+    let mut blocks = Vec::new();
+    // The indentation of a comment or a block without comments, whose more
+    // indented lines are skipped.
+    let mut skipped_below = None;
+    for (index, &(offset, line)) in lines.iter().enumerate() {
+        let rule = line.trim();
+        if rule.is_empty() {
+            continue;
+        }
+        let indent = indentation(line);
+        match skipped_below {
+            Some(skipped) if indent > skipped => continue,
+            _ => skipped_below = None,
+        }
+        if rule.starts_with("//") || rule.starts_with("/*") {
+            skipped_below = Some(indent);
+            continue;
+        }
+        let Some(&(_, child)) = lines[index + 1..]
+            .iter()
+            .find(|(_, line)| !line.trim().is_empty())
+        else {
+            continue;
+        };
+        let child_indent = indentation(child);
+        if child_indent <= indent {
+            continue;
+        }
+        match block_kind(rule) {
+            BlockKind::Rule => {}
+            BlockKind::Inline => continue,
+            BlockKind::WithoutComments => {
+                skipped_below = Some(indent);
+                continue;
+            }
+        }
+        blocks.push(SassBlock {
+            start: offset + indent,
+            body: offset + line.len(),
+            indentation: Some(child[..child_indent].to_string()),
+        });
+    }
+    blocks
+}
+
+struct Scanner<'a> {
+    code: &'a [u8],
+    syntax: SassSyntax,
+    pos: usize,
+    /// The number of open blocks.
+    depth: usize,
+    statement_start: Option<usize>,
+    /// The depth of an open block without comments.
+    skipped_depth: Option<usize>,
+    result: ScannedSass,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.code.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.code[self.pos..].starts_with(s.as_bytes())
+    }
+
+    fn scan(&mut self) {
+        while let Some(c) = self.peek() {
+            let is_comment = self.starts_with("//") || self.starts_with("/*");
+            if self.statement_start.is_none() && !c.is_ascii_whitespace() && !is_comment {
+                self.statement_start = Some(self.pos);
+            }
+            match c {
+                b'/' if is_comment => self.skip_comment(),
+                b'"' | b'\'' => {
+                    self.read_string();
+                }
+                b'@' => {
+                    self.pos += 1;
+                    let keyword = self.read_ident();
+                    match keyword {
+                        "use" => self.scan_rule(SassImportKind::Use),
+                        "forward" => self.scan_rule(SassImportKind::Forward),
+                        "import" => self.scan_rule(SassImportKind::Import),
+                        _ => {}
+                    }
+                }
+                b'u' if self.starts_with("url(")
+                    && !self.pos.checked_sub(1).is_some_and(|prev| {
+                        self.code[prev].is_ascii_alphanumeric() || self.code[prev] == b'-'
+                    }) =>
+                {
+                    self.pos += 4;
+                    self.scan_url();
+                }
+                b'#' if self.starts_with("#{") => self.skip_interpolation(),
+                b'{' if self.syntax == SassSyntax::Scss => self.open_block(),
+                b'}' if self.syntax == SassSyntax::Scss => self.close_block(),
+                b';' => {
+                    self.statement_start = None;
+                    self.pos += 1;
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn skip_interpolation(&mut self) {
+        self.pos += 2;
+        let mut depth = 1;
+        while let Some(c) = self.peek() {
+            match c {
+                b'"' | b'\'' => {
+                    self.read_string();
+                }
+                b'{' => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                b'}' => {
+                    self.pos += 1;
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn open_block(&mut self) {
+        let start = self.statement_start.take().unwrap_or(self.pos);
+        let rule = std::str::from_utf8(&self.code[start..self.pos])
+            .unwrap_or_default()
+            .trim_end();
+        self.pos += 1;
+        if self.skipped_depth.is_none() {
+            match block_kind(rule) {
+                BlockKind::Rule => self.result.blocks.push(SassBlock {
+                    start,
+                    body: self.pos,
+                    indentation: None,
+                }),
+                BlockKind::Inline => {}
+                BlockKind::WithoutComments => self.skipped_depth = Some(self.depth),
+            }
+        }
+        self.depth += 1;
+    }
+
+    fn close_block(&mut self) {
+        self.pos += 1;
+        self.statement_start = None;
+        self.depth = self.depth.saturating_sub(1);
+        if self.skipped_depth == Some(self.depth) {
+            self.skipped_depth = None;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.starts_with("//") {
+            while self.peek().is_some_and(|c| c != b'\n') {
+                self.pos += 1;
+            }
+        } else {
+            self.pos += 2;
+            while self.peek().is_some() && !self.starts_with("*/") {
+                self.pos += 1;
+            }
+            self.pos = (self.pos + 2).min(self.code.len());
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            match c {
+                b'\n' if self.syntax == SassSyntax::Indented => break,
+                c if c.is_ascii_whitespace() => self.pos += 1,
+                b'/' if self.starts_with("/*") => self.skip_comment(),
+                _ => break,
+            }
+        }
+    }
+
+    fn read_ident(&mut self) -> &'a str {
+        let code = self.code;
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+        {
+            self.pos += 1;
+        }
+        // Identifiers only consist of ASCII characters.
+        std::str::from_utf8(&code[start..self.pos]).unwrap_or_default()
+    }
+
+    /// Reads a quoted string and returns the range including the quotes.
+    fn read_string(&mut self) -> Range<usize> {
+        let start = self.pos;
+        let quote = self.code[start];
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'\\' => self.pos += 1,
+                c if c == quote => break,
+                b'\n' => break,
+                _ => {}
+            }
+        }
+        self.pos = self.pos.min(self.code.len());
+        start..self.pos
+    }
+
+    fn at_end_of_statement(&self) -> bool {
+        match self.peek() {
+            None | Some(b';' | b'{' | b'}') => true,
+            Some(b'\n') => self.syntax == SassSyntax::Indented,
+            _ => false,
+        }
+    }
+
+    /// Skips to the end of the current statement, returning whether an `as`
+    /// keyword was seen.
+    fn skip_statement(&mut self) -> bool {
+        let mut has_as = false;
+        let mut depth = 0usize;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => break,
+                Some(_) if depth == 0 && self.at_end_of_statement() => break,
+                Some(b'"' | b'\'') => {
+                    self.read_string();
+                }
+                Some(b'(') => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                Some(b')') => {
+                    depth = depth.saturating_sub(1);
+                    self.pos += 1;
+                }
+                Some(c) if c.is_ascii_alphabetic() => {
+                    if self.read_ident() == "as" && depth == 0 {
+                        has_as = true;
+                    }
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+        has_as
+    }
+
+    fn scan_rule(&mut self, kind: SassImportKind) {
+        loop {
+            self.skip_whitespace();
+            if !matches!(self.peek(), Some(b'"' | b'\'')) {
+                self.skip_statement();
+                return;
+            }
+            let range = self.read_string();
+            if range.len() < 2 {
+                return;
+            }
+            let request =
+                String::from_utf8_lossy(&self.code[range.start + 1..range.end - 1]).into_owned();
+
+            self.skip_whitespace();
+            let mut is_plain_css = false;
+            let mut has_namespace = false;
+            let mut is_last = true;
+            if kind == SassImportKind::Import {
+                if self.peek() == Some(b',') {
+                    self.pos += 1;
+                    is_last = false;
+                } else if !self.at_end_of_statement() {
+                    // Media queries or `supports()` make this a plain CSS import.
+                    is_plain_css = true;
+                    self.skip_statement();
+                }
+                is_plain_css |= is_plain_css_import(&request);
+            } else {
+                has_namespace = self.skip_statement();
+            }
+
+            if !is_plain_css && !request.starts_with("sass:") && !request.contains("#{") {
+                self.result.imports.push(SassImport {
+                    kind,
+                    request,
+                    range,
+                    has_namespace,
+                });
+            }
+            if is_last {
+                return;
+            }
+        }
+    }
+
+    fn scan_url(&mut self) {
+        self.skip_whitespace();
+        let range = match self.peek() {
+            Some(b'"' | b'\'') => {
+                let range = self.read_string();
+                if range.len() < 2 {
+                    return;
+                }
+                range.start + 1..range.end - 1
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != b')' && c != b'\n') {
+                    self.pos += 1;
+                }
+                let mut end = self.pos;
+                while end > start && self.code[end - 1].is_ascii_whitespace() {
+                    end -= 1;
+                }
+                start..end
+            }
+        };
+        let url = &self.code[range.clone()];
+        let is_relative = !url.is_empty()
+            && !url.starts_with(b"/")
+            && !url.starts_with(b"#")
+            && !url.starts_with(b"~")
+            && !url.starts_with(b"$")
+            && !url.windows(2).any(|w| w == b"#{")
+            && !url.contains(&b':');
+        if is_relative {
+            self.result.urls.push(range);
+        }
+    }
+}
+
+fn is_plain_css_import(request: &str) -> bool {
+    request.ends_with(".css")
+        || request.starts_with("http://")
+        || request.starts_with("https://")
+        || request.starts_with("//")
+}
+
+#[turbo_tasks::value]
+struct SassCompileIssue {
+    file: ResolvedVc<FileSystemPath>,
+    message: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for SassCompileIssue {
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        *self.file
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::SourceTransform.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Compiling Sass failed".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.message.clone()).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        default_namespace, sass_candidates, scan_sass, strip_source_markers, SassImportKind,
+        SassSyntax, SourcePosition,
+    };
+
+    fn requests(code: &str, syntax: SassSyntax) -> Vec<(SassImportKind, String, bool)> {
+        scan_sass(code, syntax)
+            .imports
+            .into_iter()
+            .map(|import| {
+                assert_eq!(
+                    &code[import.range.start + 1..import.range.end - 1],
+                    import.request
+                );
+                (import.kind, import.request, import.has_namespace)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scans_scss_rules() {
+        let code = r##"
+            @use "sass:math";
+            @use 'variables';
+            @use "theme" as t;
+            @use "config" with ($primary: "blue;");
+            @forward "mixins" show button;
+            // @import "commented";
+            /* @import "commented"; */
+            @import "a", "b";
+            @import "print.css";
+            @import "https://fonts.googleapis.com/css?family=Roboto";
+            @import "landscape" screen and (orientation: landscape);
+            @import "#{$theme}/colors";
+            .a { content: "@import 'not-a-rule'"; }
+        "##;
+        assert_eq!(
+            requests(code, SassSyntax::Scss),
+            vec![
+                (SassImportKind::Use, "variables".to_string(), false),
+                (SassImportKind::Use, "theme".to_string(), true),
+                (SassImportKind::Use, "config".to_string(), false),
+                (SassImportKind::Forward, "mixins".to_string(), false),
+                (SassImportKind::Import, "a".to_string(), false),
+                (SassImportKind::Import, "b".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_indented_rules() {
+        let code = "@use \"variables\"\n@import \"a\"\n.a\n  color: red\n";
+        assert_eq!(
+            requests(code, SassSyntax::Indented),
+            vec![
+                (SassImportKind::Use, "variables".to_string(), false),
+                (SassImportKind::Import, "a".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_relative_urls() {
+        let code = r#"
+            .a { background: url(images/a.png); }
+            .b { background: url( "../b.png" ); }
+            .c { background: url(/c.png), url(data:image/png;base64,AA), url(#{$d}); }
+        "#;
+        let urls: Vec<_> = scan_sass(code, SassSyntax::Scss)
+            .urls
+            .into_iter()
+            .map(|range| &code[range])
+            .collect();
+        assert_eq!(urls, vec!["images/a.png", "../b.png"]);
+    }
+
+    #[test]
+    fn scans_scss_blocks() {
+        let code = r#"
+            @function double($n) { @return $n * 2; }
+            .a {
+              font: { family: serif; }
+              --shadow: { color: red; };
+              @include button { color: red; }
+              &:hover { color: blue; }
+            }
+            @for $i from 1 through 2 {
+              .b-#{$i} { width: 1px; }
+            }
+        "#;
+        let blocks: Vec<_> = scan_sass(code, SassSyntax::Scss)
+            .blocks
+            .into_iter()
+            .map(|block| (&code[block.start..block.body], block.indentation))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![(".a {", None), ("&:hover {", None), (".b-#{$i} {", None)]
+        );
+    }
+
+    #[test]
+    fn scans_indented_blocks() {
+        let code = concat!(
+            ".a\n",
+            "  color: red\n",
+            "  &:hover\n",
+            "    color: blue\n",
+            "@mixin button\n",
+            "  .c\n",
+            "    color: red\n",
+        );
+        let blocks: Vec<_> = scan_sass(code, SassSyntax::Indented)
+            .blocks
+            .into_iter()
+            .map(|block| (&code[block.start..block.body], block.indentation))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                (".a", Some("  ".to_string())),
+                ("&:hover", Some("    ".to_string())),
+                (".c", Some("    ".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_source_markers() {
+        let css = "\
+.a,
+.b {
+  /*!turbopack-sass-source /a.scss:0:0*/
+  color: red;
+}
+
+.a {
+  /*!turbopack-sass-source /a.scss:1:2*/
+}
+
+.a:hover {
+  /*!turbopack-sass-source /a.scss:2:2*/
+  color: blue;
+}
+";
+        let (css, positions) = strip_source_markers(css);
+        assert_eq!(
+            css,
+            ".a,\n.b {\n  color: red;\n}\n\n\n.a:hover {\n  color: blue;\n}\n"
+        );
+        let position = |line, column| {
+            Some(SourcePosition {
+                virtual_path: "/a.scss".to_string(),
+                line,
+                column,
+            })
+        };
+        let mut expected = vec![position(0, 0); 6];
+        expected.extend(vec![position(2, 2); 3]);
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn candidates() {
+        assert_eq!(
+            sass_candidates("foo/bar.scss"),
+            vec!["foo/_bar.scss", "foo/bar.scss"]
+        );
+        assert_eq!(
+            &sass_candidates("foo/bar")[..4],
+            [
+                "foo/_bar.scss",
+                "foo/bar.scss",
+                "foo/_bar.sass",
+                "foo/bar.sass"
+            ]
+        );
+        assert!(sass_candidates("foo").contains(&"foo/_index.scss".to_string()));
+    }
+
+    #[test]
+    fn namespaces() {
+        assert_eq!(default_namespace("src/corners"), "corners");
+        assert_eq!(default_namespace("corners.scss"), "corners");
+        assert_eq!(default_namespace("theme"), "theme");
+    }
+}
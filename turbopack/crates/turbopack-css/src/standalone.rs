@@ -0,0 +1,204 @@
+//! Processing of a single stylesheet outside of a project, e.g. for tooling.
+//!
+//! The css is parsed and printed the same way as in builds, so the output
+//! matches. As there's no module graph, `@import` rules and `url()`s are kept
+//! as they are, and `@value` imports from other files are reported as warnings.
+
+use anyhow::{anyhow, Result};
+use lightningcss::{css_modules::CssModuleExports, visitor::Visit};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::FxIndexMap;
+use turbopack_core::chunk::MinifyType;
+
+use crate::{
+    icss::{apply_local_rule, blank_ranges, extract_icss, replace_values},
+    process::{
+        browsers, parcel_to_source_map, parse_lightningcss, parser_options, CssValidator,
+        ParseCssResultSourceMap,
+    },
+    CssModuleAssetType, StyleSheetLike,
+};
+
+pub struct StandaloneCssOptions {
+    /// The name of the stylesheet. CSS modules class names are hashed with it,
+    /// and it's the source of the source map.
+    pub filename: String,
+    pub ty: CssModuleAssetType,
+    /// A browserslist query of the browsers to compile the css for, like the
+    /// query of the environment in builds. Nesting is always compiled away.
+    pub browserslist_query: Option<String>,
+    pub minify_type: MinifyType,
+    pub source_map: bool,
+}
+
+/// The options of the `turbopackCssTransform` bindings, e.g.
+/// `{ "cssModules": true, "targets": "chrome 100" }`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StandaloneCssTransformOptions {
+    /// The name of the file, which CSS modules class names are hashed with.
+    pub filename: Option<String>,
+    pub css_modules: bool,
+    /// A browserslist query of the browsers to compile the css for.
+    pub targets: Option<String>,
+    pub minify: bool,
+    pub source_maps: bool,
+}
+
+impl From<StandaloneCssTransformOptions> for StandaloneCssOptions {
+    fn from(options: StandaloneCssTransformOptions) -> Self {
+        StandaloneCssOptions {
+            filename: options.filename.unwrap_or_else(|| "input.css".to_string()),
+            ty: if options.css_modules {
+                CssModuleAssetType::Module
+            } else {
+                CssModuleAssetType::Default
+            },
+            browserslist_query: options.targets,
+            minify_type: if options.minify {
+                MinifyType::Minify
+            } else {
+                MinifyType::NoMinify
+            },
+            source_map: options.source_maps,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StandaloneCssResult {
+    pub code: String,
+    /// The class names of a CSS module, keyed by their local name.
+    pub exports: Option<CssModuleExports>,
+    /// Values exported with `@value` and `:export`.
+    pub icss_exports: FxIndexMap<String, String>,
+    /// The source map as JSON.
+    #[serde(rename = "map")]
+    pub source_map: Option<String>,
+    /// Problems that builds report as issues, but that don't prevent the css
+    /// from being processed.
+    pub warnings: Vec<String>,
+}
+
+/// Parses, compiles and prints `code`. Syntax errors are returned as errors.
+pub fn process_standalone_css(
+    code: &str,
+    options: &StandaloneCssOptions,
+) -> Result<StandaloneCssResult> {
+    let browsers = match &options.browserslist_query {
+        Some(query) => browsers(query)?,
+        None => None,
+    };
+
+    let mut warnings = Vec::new();
+
+    let icss = extract_icss(code);
+    let (code, icss_exports) = if icss.is_empty() {
+        (code.to_string(), FxIndexMap::default())
+    } else {
+        let blanked = blank_ranges(code, &icss.ranges);
+        let mut values = FxIndexMap::default();
+        let mut exports = FxIndexMap::default();
+        for rule in icss.rules {
+            if let Some(import) = apply_local_rule(rule, &mut values, &mut exports) {
+                warnings.push(format!(
+                    "Values can't be imported from {} outside of a project",
+                    import.specifier
+                ));
+            }
+        }
+        (replace_values(&blanked, &values, true), exports)
+    };
+
+    let config = parser_options(options.ty, &options.filename);
+    let mut stylesheet = parse_lightningcss(&code, &config).map_err(|err| anyhow!(err.message))?;
+
+    if matches!(options.ty, CssModuleAssetType::Module) {
+        let mut validator = CssValidator { errors: Vec::new() };
+        stylesheet.visit(&mut validator).unwrap();
+        warnings.extend(validator.errors.iter().map(ToString::to_string));
+    }
+
+    let (result, source_map) = StyleSheetLike::LightningCss(stylesheet).to_css(
+        Default::default(),
+        &code,
+        options.minify_type,
+        options.source_map,
+        false,
+        true,
+        browsers,
+    )?;
+
+    let source_map = match source_map {
+        Some(ParseCssResultSourceMap::Parcel { source_map, .. }) => {
+            let mut json = vec![];
+            parcel_to_source_map(&source_map, "").to_writer(&mut json)?;
+            Some(String::from_utf8(json)?)
+        }
+        _ => None,
+    };
+
+    Ok(StandaloneCssResult {
+        code: result.code,
+        exports: result.exports,
+        icss_exports,
+        source_map,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use turbopack_core::chunk::MinifyType;
+
+    use super::{process_standalone_css, StandaloneCssOptions};
+    use crate::CssModuleAssetType;
+
+    fn options(ty: CssModuleAssetType) -> StandaloneCssOptions {
+        StandaloneCssOptions {
+            filename: "button.module.css".into(),
+            ty,
+            browserslist_query: None,
+            minify_type: MinifyType::NoMinify,
+            source_map: true,
+        }
+    }
+
+    #[test]
+    fn test_css_module() {
+        let result = process_standalone_css(
+            "@value primary: red;\n.button { color: primary; & .icon { color: blue; } }\n",
+            &options(CssModuleAssetType::Module),
+        )
+        .unwrap();
+
+        let exports = result.exports.unwrap();
+        let button = &exports["button"].name;
+        assert_ne!(button, "button");
+        assert!(button.ends_with("__button"));
+        // Nesting is compiled away and values are substituted, like in builds.
+        assert!(result.code.contains(&format!(".{button} .")));
+        assert!(result.code.contains("color: red"));
+        assert_eq!(result.icss_exports["primary"], "red");
+        assert!(result.warnings.is_empty());
+        assert!(result.source_map.unwrap().contains("button.module.css"));
+    }
+
+    #[test]
+    fn test_not_pure_selector() {
+        let result =
+            process_standalone_css("div { color: red }", &options(CssModuleAssetType::Module))
+                .unwrap();
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        assert!(process_standalone_css(
+            ".a { color: red }\n@import \"b.css\";",
+            &options(CssModuleAssetType::Default)
+        )
+        .is_err());
+    }
+}
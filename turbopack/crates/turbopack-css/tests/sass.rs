@@ -0,0 +1,89 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{DiskFileSystem, FileContent, FileSystem};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    file_source::FileSource,
+    resolve::options::ResolveOptions,
+    source::Source,
+    source_map::{GenerateSourceMap, OriginalToken, Token},
+    source_transform::SourceTransform,
+};
+use turbopack_css::sass::{SassTransform, SassTransformOptions};
+
+static REGISTRATION: Registration = register!(turbopack_css::register);
+
+#[tokio::test]
+async fn use_partial() {
+    run(&REGISTRATION, || async {
+        let fs = DiskFileSystem::new(
+            "fixtures".into(),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/sass").into(),
+            vec![],
+        );
+        let source = Vc::upcast(FileSource::new(fs.root().join("styles/index.scss".into())));
+        let transform = SassTransform::new(
+            ResolveOptions::default().cell(),
+            SassTransformOptions::default().cell(),
+            "*.css".into(),
+        );
+        let compiled: Vc<Box<dyn Source>> = transform.transform(source);
+
+        assert_eq!(
+            &*compiled.ident().path().await?.path,
+            "styles/index.scss.css"
+        );
+        let AssetContent::File(file) = *compiled.content().await? else {
+            panic!("expected a file");
+        };
+        let FileContent::Content(file) = &*file.await? else {
+            panic!("the stylesheet failed to compile");
+        };
+        let css = file.content().to_str()?;
+        // The members of the partial are available through its namespace.
+        assert!(css.contains(".button {\n  padding: 4px;\n  color: #0070f3;\n}"));
+        assert!(css.contains(".button:hover {\n  color: red;\n}"));
+        assert!(!css.contains("@use"));
+
+        // Every rule is mapped to its position in the stylesheet it was
+        // written in.
+        let generator = Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(compiled)
+            .await?
+            .expect("the compiled source has a source map");
+        let source_map =
+            (*generator.generate_source_map().await?).expect("the stylesheet compiled");
+        let lines: Vec<_> = css.lines().collect();
+        for (rule, file, line, column) in [
+            (".link {", "styles/_variables.scss", 6, 0),
+            ("  padding: 4px;", "styles/index.scss", 2, 0),
+            (".button:hover {", "styles/index.scss", 6, 2),
+        ] {
+            let generated_line = lines
+                .iter()
+                .position(|l| *l == rule)
+                .unwrap_or_else(|| panic!("{rule} is missing in {css}"));
+            let token = source_map.lookup_token(generated_line, 2).await?;
+            let Token::Original(OriginalToken {
+                original_file,
+                original_line,
+                original_column,
+                ..
+            }) = &*token
+            else {
+                panic!("{rule} is not mapped");
+            };
+            assert!(
+                original_file.ends_with(file),
+                "{rule} maps to {original_file}"
+            );
+            assert_eq!((*original_line, *original_column), (line, column), "{rule}");
+        }
+        assert!(!css.contains("turbopack-sass-source"));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
@@ -32,6 +32,10 @@ pub struct DevHtmlAsset {
     path: Vc<FileSystemPath>,
     entries: Vec<DevHtmlEntry>,
     body: Option<RcStr>,
+    /// Whether chunks are referenced relative to the HTML file instead of the
+    /// root of the server, so the page also works when it's served from a
+    /// subdirectory or opened from disk.
+    relative_chunk_paths: bool,
 }
 
 #[turbo_tasks::function]
@@ -72,6 +76,7 @@ impl DevHtmlAsset {
             path,
             entries,
             body: None,
+            relative_chunk_paths: false,
         }
         .cell()
     }
@@ -86,6 +91,7 @@ impl DevHtmlAsset {
             path,
             entries,
             body: Some(body),
+            relative_chunk_paths: false,
         }
         .cell()
     }
@@ -106,6 +112,13 @@ impl DevHtmlAsset {
         html.body = Some(body);
         Ok(html.cell())
     }
+
+    #[turbo_tasks::function]
+    pub async fn with_relative_chunk_paths(self: Vc<Self>) -> Result<Vc<Self>> {
+        let mut html: DevHtmlAsset = self.await?.clone_value();
+        html.relative_chunk_paths = true;
+        Ok(html.cell())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -117,7 +130,11 @@ impl DevHtmlAsset {
         let mut chunk_paths = vec![];
         for chunk in &*self.chunks().await? {
             let chunk_path = &*chunk.ident().path().await?;
-            if let Some(relative_path) = context_path.get_path_to(chunk_path) {
+            if this.relative_chunk_paths {
+                if let Some(relative_path) = context_path.get_relative_path_to(chunk_path) {
+                    chunk_paths.push(relative_path);
+                }
+            } else if let Some(relative_path) = context_path.get_path_to(chunk_path) {
                 chunk_paths.push(format!("/{relative_path}").into());
             }
         }
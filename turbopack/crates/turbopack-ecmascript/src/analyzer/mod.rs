@@ -564,6 +564,9 @@ impl From<&CompileTimeDefineValue> for JsValue {
             CompileTimeDefineValue::JSON(_) => {
                 JsValue::unknown_empty(false, "compile time injected JSON")
             }
+            CompileTimeDefineValue::Expression(_) => {
+                JsValue::unknown_empty(true, "compile time injected expression")
+            }
         }
     }
 }
@@ -1681,6 +1684,14 @@ impl JsValue {
                         "import.meta",
                         "The import.meta object"
                     ),
+                    WellKnownObjectKind::Navigator => (
+                        "navigator",
+                        "The global navigator object: https://developer.mozilla.org/en-US/docs/Web/API/Navigator"
+                    ),
+                    WellKnownObjectKind::NavigatorServiceWorker => (
+                        "navigator.serviceWorker",
+                        "The navigator.serviceWorker property: https://developer.mozilla.org/en-US/docs/Web/API/Navigator/serviceWorker"
+                    ),
                 };
                 if depth > 0 {
                     let i = hints.len();
@@ -1791,6 +1802,14 @@ impl JsValue {
                       "Worker".to_string(),
                       "The standard Worker constructor: https://developer.mozilla.org/en-US/docs/Web/API/Worker/Worker"
                     ),
+                    WellKnownFunctionKind::SharedWorkerConstructor => (
+                      "SharedWorker".to_string(),
+                      "The standard SharedWorker constructor: https://developer.mozilla.org/en-US/docs/Web/API/SharedWorker/SharedWorker"
+                    ),
+                    WellKnownFunctionKind::ServiceWorkerRegister => (
+                      "navigator.serviceWorker.register".to_string(),
+                      "The standard ServiceWorkerContainer.register method: https://developer.mozilla.org/en-US/docs/Web/API/ServiceWorkerContainer/register"
+                    ),
                     WellKnownFunctionKind::URLConstructor => (
                       "URL".to_string(),
                       "The standard URL constructor: https://developer.mozilla.org/en-US/docs/Web/API/URL/URL"
@@ -3636,6 +3655,8 @@ pub enum WellKnownObjectKind {
     NodeBuffer,
     RequireCache,
     ImportMeta,
+    Navigator,
+    NavigatorServiceWorker,
 }
 
 impl WellKnownObjectKind {
@@ -3653,6 +3674,8 @@ impl WellKnownObjectKind {
             Self::NodeBuffer => Some(&["Buffer"]),
             Self::RequireCache => Some(&["require", "cache"]),
             Self::ImportMeta => Some(&["import", "meta"]),
+            Self::Navigator => Some(&["navigator"]),
+            Self::NavigatorServiceWorker => Some(&["navigator", "serviceWorker"]),
             _ => None,
         }
     }
@@ -3810,6 +3833,8 @@ pub enum WellKnownFunctionKind {
     NodeResolveFrom,
     NodeProtobufLoad,
     WorkerConstructor,
+    SharedWorkerConstructor,
+    ServiceWorkerRegister,
     URLConstructor,
 }
 
@@ -3947,6 +3972,13 @@ pub mod test_utils {
                     true,
                     "ignored Worker constructor",
                 ),
+                "SharedWorker" => JsValue::unknown_if(
+                    ignore,
+                    JsValue::WellKnownFunction(WellKnownFunctionKind::SharedWorkerConstructor),
+                    true,
+                    "ignored SharedWorker constructor",
+                ),
+                "navigator" => JsValue::WellKnownObject(WellKnownObjectKind::Navigator),
                 "define" => JsValue::WellKnownFunction(WellKnownFunctionKind::Define),
                 "URL" => JsValue::WellKnownFunction(WellKnownFunctionKind::URLConstructor),
                 "process" => JsValue::WellKnownObject(WellKnownObjectKind::NodeProcess),
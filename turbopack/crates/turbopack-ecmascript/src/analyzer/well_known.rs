@@ -582,6 +582,8 @@ pub async fn well_known_object_member(
         WellKnownObjectKind::NodePreGyp => node_pre_gyp(prop),
         WellKnownObjectKind::NodeExpressApp => express(prop),
         WellKnownObjectKind::NodeProtobufLoader => protobuf_loader(prop),
+        WellKnownObjectKind::Navigator => navigator(prop),
+        WellKnownObjectKind::NavigatorServiceWorker => navigator_service_worker(prop),
         #[allow(unreachable_patterns)]
         _ => {
             return Ok((
@@ -804,3 +806,37 @@ fn protobuf_loader(prop: JsValue) -> JsValue {
         ),
     }
 }
+
+fn navigator(prop: JsValue) -> JsValue {
+    match prop.as_str() {
+        Some("serviceWorker") => {
+            JsValue::WellKnownObject(WellKnownObjectKind::NavigatorServiceWorker)
+        }
+        _ => JsValue::unknown(
+            JsValue::member(
+                Box::new(JsValue::WellKnownObject(WellKnownObjectKind::Navigator)),
+                Box::new(prop),
+            ),
+            true,
+            "unsupported property on navigator object",
+        ),
+    }
+}
+
+fn navigator_service_worker(prop: JsValue) -> JsValue {
+    match prop.as_str() {
+        Some("register") => {
+            JsValue::WellKnownFunction(WellKnownFunctionKind::ServiceWorkerRegister)
+        }
+        _ => JsValue::unknown(
+            JsValue::member(
+                Box::new(JsValue::WellKnownObject(
+                    WellKnownObjectKind::NavigatorServiceWorker,
+                )),
+                Box::new(prop),
+            ),
+            true,
+            "unsupported property on navigator.serviceWorker object",
+        ),
+    }
+}
@@ -0,0 +1,757 @@
+use std::{future::Future, io::Write, pin::Pin, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_core::{
+    common::{
+        BytePos, Globals, LineCol, Mark, SourceMap as SwcSourceMap, SyntaxContext, DUMMY_SP,
+        GLOBALS,
+    },
+    ecma::{
+        ast::{
+            AssignPat, AssignPatProp, BindingIdent, Callee, ComputedPropName, Decl, Expr, Ident,
+            IdentName, KeyValuePatProp, KeyValueProp, Lit, MemberExpr, MemberProp, ModuleItem,
+            ObjectLit, ObjectPatProp, Pat, Program, Prop, PropName, PropOrSpread, Stmt, Str,
+        },
+        atoms::Atom,
+        codegen::{text_writer::JsWriter, Emitter},
+        transforms::base::resolver,
+        visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+    quote,
+};
+use turbo_tasks::{FxIndexSet, RcStr, ReadRef, ResolvedVc, TryJoinIterExt, ValueToString, Vc};
+use turbopack_core::{
+    chunk::{ChunkItemExt, ChunkableModule, ChunkingContext},
+    code_builder::CodeBuilder,
+    module::{Module, Modules},
+    source_map::OptionSourceMap,
+};
+
+use super::{concatenation_info, ConcatenationInfo};
+use crate::{
+    chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
+    magic_identifier, module_code_gens,
+    parse::ParseResult,
+    process_content_with_code_gens,
+    references::esm::{base::ReferencedAsset, EsmExport},
+    swc_comments::ImmutableComments,
+    utils::StringifyJs,
+    EcmascriptModuleAsset, EcmascriptModuleContent, ParseResultSourceMap,
+};
+
+/// The calls that load a module from the module registry.
+const IMPORT_FUNCTIONS: [&str; 3] = [
+    "__turbopack_import__",
+    "__turbopack_external_import__",
+    "__turbopack_external_require__",
+];
+
+/// A part of the concatenated output, in evaluation order.
+enum Segment {
+    /// Evaluates a module outside of the group, because a module of the group
+    /// imports it before another module of the group.
+    Evaluate(ResolvedVc<Box<dyn Module>>),
+    /// The code of a module of the group.
+    Module(ResolvedVc<Box<dyn Module>>),
+}
+
+/// Orders the modules of the group like they would be evaluated as separate
+/// modules, i.e. every module after the modules it imports.
+fn evaluation_order(
+    module: ResolvedVc<Box<dyn Module>>,
+    infos: &FxHashMap<ResolvedVc<Box<dyn Module>>, ReadRef<ConcatenationInfo>>,
+    group: &FxIndexSet<ResolvedVc<Box<dyn Module>>>,
+    visited: &mut FxHashSet<ResolvedVc<Box<dyn Module>>>,
+    segments: &mut Vec<Segment>,
+) {
+    if !visited.insert(module) {
+        return;
+    }
+    // Imports of other modules are hoisted to the top of the module, so they
+    // only need to be evaluated explicitly when a module of the group comes
+    // after them.
+    let mut pending = Vec::new();
+    for &import in &infos[&module].imports {
+        if !group.contains(&import) {
+            pending.push(import);
+        } else if !visited.contains(&import) {
+            segments.extend(pending.drain(..).map(Segment::Evaluate));
+            evaluation_order(import, infos, group, visited, segments);
+        }
+    }
+    segments.push(Segment::Module(module));
+}
+
+/// A module of the group after code generation, resolved again so its
+/// top-level bindings can be renamed.
+struct ConcatenatedModule {
+    module: ResolvedVc<Box<dyn Module>>,
+    program: Program,
+    top_level_ctxt: SyntaxContext,
+    source_map: Arc<SwcSourceMap>,
+    comments: Arc<ImmutableComments>,
+    original_source_map: Vc<OptionSourceMap>,
+    names: ModuleNames,
+}
+
+impl ConcatenatedModule {
+    async fn new(
+        module: ResolvedVc<Box<dyn Module>>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Result<Self> {
+        let ecmascript = ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module)
+            .await?
+            .context("Only Ecmascript modules can be concatenated")?;
+        let parsed = ecmascript.parse().await?;
+        let ParseResult::Ok {
+            program,
+            comments,
+            eval_context,
+            globals,
+            source_map,
+        } = &*parsed
+        else {
+            bail!(
+                "Unable to concatenate {}, as it can't be parsed",
+                module.ident().to_string().await?
+            );
+        };
+        let analyze = ecmascript.analyze().await?;
+        let code_gens = module_code_gens(
+            chunking_context,
+            analyze.references,
+            analyze.code_generation,
+            analyze.async_module,
+            analyze.exports,
+            chunking_context.used_exports().module_used_exports(*module),
+            None,
+        )
+        .await?;
+        let code_gens = code_gens.iter().map(|cg| &**cg).collect::<Vec<_>>();
+
+        let mut program = program.clone();
+        process_content_with_code_gens(
+            &mut program,
+            globals,
+            Some(eval_context.top_level_mark),
+            &code_gens,
+        );
+
+        // After hygiene, names are unique within their scope, so the program can
+        // be resolved again to tell top-level bindings apart from local bindings
+        // with the same name.
+        let (top_level_ctxt, names) = GLOBALS.set(&Globals::new(), || {
+            program.visit_mut_with(&mut ClearSyntaxContext);
+            let unresolved_mark = Mark::new();
+            let top_level_mark = Mark::new();
+            program.visit_mut_with(&mut resolver(unresolved_mark, top_level_mark, false));
+            let top_level_ctxt = SyntaxContext::empty().apply_mark(top_level_mark);
+
+            let mut names = ModuleNames {
+                top_level_ctxt,
+                unresolved_ctxt: SyntaxContext::empty().apply_mark(unresolved_mark),
+                ..Default::default()
+            };
+            program.visit_with(&mut names);
+            (top_level_ctxt, names)
+        });
+
+        Ok(ConcatenatedModule {
+            module,
+            program,
+            top_level_ctxt,
+            source_map: source_map.clone(),
+            comments: comments.clone(),
+            original_source_map: analyze.source_map,
+            names,
+        })
+    }
+
+    fn print(&self) -> Result<(Vec<u8>, Vec<(BytePos, LineCol)>)> {
+        let mut bytes = vec![];
+        let mut mappings = vec![];
+        let comments = self.comments.consumable();
+        let mut emitter = Emitter {
+            cfg: swc_core::ecma::codegen::Config::default(),
+            cm: self.source_map.clone(),
+            comments: Some(&comments),
+            wr: JsWriter::new(
+                self.source_map.clone(),
+                "\n",
+                &mut bytes,
+                Some(&mut mappings),
+            ),
+        };
+        emitter.emit_program(&self.program)?;
+        Ok((bytes, mappings))
+    }
+}
+
+struct ClearSyntaxContext;
+
+impl VisitMut for ClearSyntaxContext {
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        ident.ctxt = SyntaxContext::empty();
+    }
+}
+
+/// Returns the name of a binding loading another module, e.g. `var x =
+/// __turbopack_import__("./a.js")`.
+fn import_binding(stmt: &Stmt) -> Option<&Atom> {
+    let Stmt::Decl(Decl::Var(var)) = stmt else {
+        return None;
+    };
+    let [declarator] = &*var.decls else {
+        return None;
+    };
+    let (Pat::Ident(name), Some(init)) = (&declarator.name, &declarator.init) else {
+        return None;
+    };
+    let Expr::Call(call) = &**init else {
+        return None;
+    };
+    let Callee::Expr(callee) = &call.callee else {
+        return None;
+    };
+    match &**callee {
+        Expr::Ident(callee) if IMPORT_FUNCTIONS.contains(&&*callee.sym) => Some(&name.sym),
+        _ => None,
+    }
+}
+
+fn is_esm_exports_call(stmt: &Stmt) -> bool {
+    let Stmt::Expr(expr) = stmt else {
+        return false;
+    };
+    let Expr::Call(call) = &*expr.expr else {
+        return false;
+    };
+    matches!(
+        &call.callee,
+        Callee::Expr(callee) if matches!(&**callee, Expr::Ident(ident) if &*ident.sym == "__turbopack_esm__")
+    )
+}
+
+/// Returns the name of a static property access, `a.name` or `a["name"]`.
+pub(super) fn static_prop(prop: &MemberProp) -> Option<&Atom> {
+    match prop {
+        MemberProp::Ident(ident) => Some(&ident.sym),
+        MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+            Expr::Lit(Lit::Str(str)) => Some(&str.value),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+/// The names a module uses.
+#[derive(Default)]
+struct ModuleNames {
+    top_level_ctxt: SyntaxContext,
+    unresolved_ctxt: SyntaxContext,
+    /// The top-level bindings, in order of appearance.
+    top_level: FxIndexSet<Atom>,
+    /// All identifiers, including globals and local bindings.
+    all: FxHashSet<Atom>,
+    /// Identifiers that don't refer to a binding of the module, e.g. globals.
+    free: FxHashSet<Atom>,
+    /// Bindings that load other modules.
+    import_bindings: FxHashSet<Atom>,
+    /// Static property accesses on top-level bindings, `(binding, property)`.
+    accesses: FxIndexSet<(Atom, Atom)>,
+    /// Top-level bindings that are used as a value, i.e. other than through a
+    /// static property access.
+    values: FxHashSet<Atom>,
+}
+
+impl ModuleNames {
+    fn add_top_level(&mut self, name: &Atom) {
+        self.all.insert(name.clone());
+        self.top_level.insert(name.clone());
+    }
+}
+
+impl Visit for ModuleNames {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if let Some(name) = import_binding(stmt) {
+            // The declaration is not a use of the binding.
+            self.import_bindings.insert(name.clone());
+            self.add_top_level(name);
+            return;
+        }
+        stmt.visit_children_with(self);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Member(MemberExpr { obj, prop, .. }) = expr {
+            if let (Expr::Ident(obj), Some(prop)) = (&**obj, static_prop(prop)) {
+                if obj.ctxt == self.top_level_ctxt {
+                    self.add_top_level(&obj.sym);
+                    self.accesses.insert((obj.sym.clone(), prop.clone()));
+                    return;
+                }
+            }
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        if ident.ctxt == self.top_level_ctxt {
+            self.add_top_level(&ident.sym);
+            self.values.insert(ident.sym.clone());
+        } else {
+            if ident.ctxt == self.unresolved_ctxt {
+                self.free.insert(ident.sym.clone());
+            }
+            self.all.insert(ident.sym.clone());
+        }
+    }
+}
+
+/// What an export of a module of the group refers to.
+enum ExportBinding {
+    /// A top-level binding of a module of the group.
+    Local(ResolvedVc<Box<dyn Module>>, RcStr),
+    /// An export of a module outside of the group, through the binding that
+    /// loads it.
+    Imported(RcStr, RcStr),
+    /// The namespace object of a module outside of the group.
+    Namespace(RcStr),
+    Missing,
+}
+
+/// Follows the re-exports of `export` of `module` until it reaches a local
+/// binding or a module outside of the group.
+fn resolve_export<'a>(
+    module: ResolvedVc<Box<dyn Module>>,
+    export: RcStr,
+    group: &'a FxIndexSet<ResolvedVc<Box<dyn Module>>>,
+    depth: usize,
+) -> Pin<Box<dyn Future<Output = Result<ExportBinding>> + Send + 'a>> {
+    Box::pin(async move {
+        if depth > 100 {
+            bail!(
+                "Unable to resolve the export `{}`, as it re-exports itself",
+                export
+            );
+        }
+        let Some(placeable) =
+            ResolvedVc::try_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+        else {
+            return Ok(ExportBinding::Missing);
+        };
+        let EcmascriptExports::EsmExports(exports) = *placeable.get_exports().await? else {
+            return Ok(ExportBinding::Missing);
+        };
+        let exports = exports.expand_exports().await?;
+        Ok(match exports.exports.get(&export) {
+            Some(EsmExport::LocalBinding(name, _)) => {
+                let name = if name == "default" {
+                    magic_identifier::mangle("default export").into()
+                } else {
+                    name.clone()
+                };
+                ExportBinding::Local(module, name)
+            }
+            Some(EsmExport::ImportedBinding(reference, name, _)) => {
+                let referenced =
+                    ReferencedAsset::from_resolve_result(reference.resolve_reference()).await?;
+                match &*referenced {
+                    ReferencedAsset::Some(target)
+                        if group.contains(&ResolvedVc::upcast(*target)) =>
+                    {
+                        resolve_export(ResolvedVc::upcast(*target), name.clone(), group, depth + 1)
+                            .await?
+                    }
+                    referenced => match referenced.get_ident().await? {
+                        Some(ident) => ExportBinding::Imported(ident.into(), name.clone()),
+                        None => ExportBinding::Missing,
+                    },
+                }
+            }
+            Some(EsmExport::ImportedNamespace(reference)) => {
+                let referenced =
+                    ReferencedAsset::from_resolve_result(reference.resolve_reference()).await?;
+                match referenced.get_ident().await? {
+                    Some(ident) => ExportBinding::Namespace(ident.into()),
+                    None => ExportBinding::Missing,
+                }
+            }
+            Some(EsmExport::Error) | None => ExportBinding::Missing,
+        })
+    })
+}
+
+/// Returns the expression that reads an export, with the renames of the modules
+/// of the group applied.
+fn export_expr(
+    binding: ExportBinding,
+    renames: &FxHashMap<ResolvedVc<Box<dyn Module>>, FxHashMap<Atom, Atom>>,
+) -> Expr {
+    match binding {
+        ExportBinding::Local(module, name) => {
+            let name = Atom::from(name.as_str());
+            let name = renames[&module].get(&name).cloned().unwrap_or(name);
+            Expr::Ident(Ident::new(name, DUMMY_SP, Default::default()))
+        }
+        ExportBinding::Imported(binding, name) => Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(Expr::Ident(Ident::new(
+                binding.as_str().into(),
+                DUMMY_SP,
+                Default::default(),
+            ))),
+            prop: MemberProp::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: name.as_str().into(),
+                    raw: None,
+                }))),
+            }),
+        }),
+        ExportBinding::Namespace(binding) => Expr::Ident(Ident::new(
+            binding.as_str().into(),
+            DUMMY_SP,
+            Default::default(),
+        )),
+        ExportBinding::Missing => quote!("void 0" as Expr),
+    }
+}
+
+/// Declares `binding` as the namespace object of `module`, a module of the
+/// group, for the uses of `binding` that can't be replaced by the exports
+/// themselves, e.g. `ns?.name`.
+async fn namespace_object(
+    binding: &Atom,
+    module: ResolvedVc<Box<dyn Module>>,
+    group: &FxIndexSet<ResolvedVc<Box<dyn Module>>>,
+    renames: &FxHashMap<ResolvedVc<Box<dyn Module>>, FxHashMap<Atom, Atom>>,
+) -> Result<Stmt> {
+    let placeable = ResolvedVc::try_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module)
+        .await?
+        .context("Only Ecmascript modules can be concatenated")?;
+    let mut props = Vec::new();
+    if let EcmascriptExports::EsmExports(exports) = *placeable.get_exports().await? {
+        for export in exports.expand_exports().await?.exports.keys() {
+            let value = export_expr(
+                resolve_export(module, export.clone(), group, 0).await?,
+                renames,
+            );
+            props.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Str(Str {
+                    span: DUMMY_SP,
+                    value: export.as_str().into(),
+                    raw: None,
+                }),
+                value: Box::new(quote!(
+                    "({ enumerable: true, get: () => $value })" as Expr,
+                    value: Expr = value
+                )),
+            }))));
+        }
+    }
+    Ok(quote!(
+        "var $name = Object.freeze(Object.create(null, $props));" as Stmt,
+        name = Ident::new(binding.clone(), DUMMY_SP, Default::default()),
+        props: Expr = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props,
+        })
+    ))
+}
+
+/// Returns a name based on `name` that isn't in `used`, and marks it as used.
+fn unique_name(name: &Atom, used: &mut FxHashSet<Atom>) -> Atom {
+    let mut i = 1;
+    loop {
+        let candidate: Atom = format!("{name}_{i}").into();
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+        i += 1;
+    }
+}
+
+/// Rewrites a module of the group to refer to the other modules of the group
+/// directly.
+struct ConcatenationRewriter<'a> {
+    top_level_ctxt: SyntaxContext,
+    is_root: bool,
+    renames: &'a FxHashMap<Atom, Atom>,
+    /// The bindings loading modules of the group.
+    group_bindings: &'a FxHashMap<Atom, ResolvedVc<Box<dyn Module>>>,
+    /// The replacements of property accesses on `group_bindings`.
+    replacements: &'a FxHashMap<(Atom, Atom), Expr>,
+    /// The namespace objects of the bindings of `group_bindings` that are used
+    /// as a value.
+    namespaces: &'a FxHashMap<Atom, Stmt>,
+}
+
+impl ConcatenationRewriter<'_> {
+    fn rename(&self, ident: &Ident) -> Option<Atom> {
+        if ident.ctxt != self.top_level_ctxt {
+            return None;
+        }
+        self.renames.get(&ident.sym).cloned()
+    }
+
+    fn should_remove(&self, stmt: &Stmt) -> bool {
+        if let Some(name) = import_binding(stmt) {
+            return self.group_bindings.contains_key(name) && !self.namespaces.contains_key(name);
+        }
+        // Only the root of the group is a module at runtime.
+        !self.is_root && is_esm_exports_call(stmt)
+    }
+}
+
+impl VisitMut for ConcatenationRewriter<'_> {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.retain(|item| !matches!(item, ModuleItem::Stmt(stmt) if self.should_remove(stmt)));
+        items.visit_mut_children_with(self);
+        // The namespace objects refer to the renamed bindings already.
+        for item in items.iter_mut() {
+            if let ModuleItem::Stmt(stmt) = item {
+                if let Some(namespace) =
+                    import_binding(stmt).and_then(|name| self.namespaces.get(name))
+                {
+                    *stmt = namespace.clone();
+                }
+            }
+        }
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Member(MemberExpr { obj, prop, .. }) = expr {
+            if let Expr::Ident(obj) = &**obj {
+                if obj.ctxt == self.top_level_ctxt && self.group_bindings.contains_key(&obj.sym) {
+                    if let Some(prop) = static_prop(prop) {
+                        if let Some(replacement) =
+                            self.replacements.get(&(obj.sym.clone(), prop.clone()))
+                        {
+                            *expr = replacement.clone();
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+        expr.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        if ident.ctxt != self.top_level_ctxt {
+            return;
+        }
+        if let Some(name) = self.rename(ident) {
+            ident.sym = name;
+        }
+    }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        if let Prop::Shorthand(ident) = prop {
+            if let Some(name) = self.rename(ident) {
+                *prop = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(IdentName::new(ident.sym.clone(), ident.span)),
+                    value: Box::new(Expr::Ident(Ident::new(name, ident.span, ident.ctxt))),
+                });
+                return;
+            }
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_pat_prop(&mut self, prop: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { key, value, span }) = prop {
+            if let Some(name) = self.rename(key) {
+                let binding = Pat::Ident(BindingIdent::from(Ident::new(name, key.span, key.ctxt)));
+                let mut value = value.take();
+                value.visit_mut_with(self);
+                *prop = ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(IdentName::new(key.sym.clone(), key.span)),
+                    value: Box::new(match value {
+                        Some(default) => Pat::Assign(AssignPat {
+                            span: *span,
+                            left: Box::new(binding),
+                            right: default,
+                        }),
+                        None => binding,
+                    }),
+                });
+                return;
+            }
+        }
+        prop.visit_mut_children_with(self);
+    }
+}
+
+/// Generates the code of a group of concatenated modules. `modules` starts
+/// with `root`, the module the group is emitted as.
+#[turbo_tasks::function]
+pub(crate) async fn concatenated_module_content(
+    root: Vc<EcmascriptModuleAsset>,
+    modules: Vc<Modules>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+) -> Result<Vc<EcmascriptModuleContent>> {
+    let modules = modules.await?;
+    let group = modules.iter().copied().collect::<FxIndexSet<_>>();
+    let root = ResolvedVc::upcast::<Box<dyn Module>>(root.to_resolved().await?);
+
+    let infos = modules
+        .iter()
+        .map(|&module| async move { Ok((module, concatenation_info(*module).await?)) })
+        .try_join()
+        .await?
+        .into_iter()
+        .collect::<FxHashMap<_, _>>();
+    let mut segments = Vec::new();
+    evaluation_order(
+        root,
+        &infos,
+        &group,
+        &mut FxHashSet::default(),
+        &mut segments,
+    );
+
+    let mut concatenated = segments
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Module(module) => Some(*module),
+            Segment::Evaluate(_) => None,
+        })
+        .map(|module| ConcatenatedModule::new(module, chunking_context))
+        .try_join()
+        .await?;
+
+    let mut group_bindings = FxHashMap::default();
+    for &module in &group {
+        let placeable = ResolvedVc::try_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module)
+            .await?
+            .context("Only Ecmascript modules can be concatenated")?;
+        let ident = ReferencedAsset::get_ident_from_placeable(&placeable).await?;
+        group_bindings.insert(Atom::from(ident), module);
+    }
+
+    // Top-level bindings of modules other than the root are renamed when any
+    // other module of the group uses the same name, as the binding would be
+    // shadowed by or shadow the other one. The root keeps its names, unless
+    // another module uses the name as a global, which the binding would shadow.
+    let free_names = concatenated
+        .iter()
+        .filter(|module| module.module != root)
+        .flat_map(|module| &module.names.free)
+        .collect::<FxHashSet<_>>();
+    let mut name_counts = FxHashMap::<Atom, usize>::default();
+    for module in &concatenated {
+        for name in &module.names.all {
+            *name_counts.entry(name.clone()).or_default() += 1;
+        }
+    }
+    let mut used_names = name_counts.keys().cloned().collect::<FxHashSet<_>>();
+    let mut renames = FxHashMap::default();
+    for module in &concatenated {
+        let mut module_renames = FxHashMap::default();
+        for name in &module.names.top_level {
+            if module.names.import_bindings.contains(name) {
+                continue;
+            }
+            let collides = if module.module == root {
+                free_names.contains(name)
+            } else {
+                name_counts[name] > 1
+            };
+            if collides {
+                module_renames.insert(name.clone(), unique_name(name, &mut used_names));
+            }
+        }
+        renames.insert(module.module, module_renames);
+    }
+
+    let mut replacements = FxHashMap::default();
+    for module in &concatenated {
+        for (binding, prop) in &module.names.accesses {
+            let Some(&target) = group_bindings.get(binding) else {
+                continue;
+            };
+            let key = (binding.clone(), prop.clone());
+            if replacements.contains_key(&key) {
+                continue;
+            }
+            let replacement = export_expr(
+                resolve_export(target, prop.as_str().into(), &group, 0).await?,
+                &renames,
+            );
+            replacements.insert(key, replacement);
+        }
+    }
+
+    // Modules whose namespace object is needed are usually not concatenated, but
+    // uses introduced by code generation, e.g. in optional chains, get a
+    // namespace object in the group.
+    let mut namespaces = FxHashMap::default();
+    for module in &concatenated {
+        for binding in &module.names.values {
+            if let Some(&target) = group_bindings.get(binding) {
+                if !namespaces.contains_key(binding) {
+                    let namespace = namespace_object(binding, target, &group, &renames).await?;
+                    namespaces.insert(binding.clone(), namespace);
+                }
+            }
+        }
+    }
+
+    for module in &mut concatenated {
+        let mut rewriter = ConcatenationRewriter {
+            top_level_ctxt: module.top_level_ctxt,
+            is_root: module.module == root,
+            renames: &renames[&module.module],
+            group_bindings: &group_bindings,
+            replacements: &replacements,
+            namespaces: &namespaces,
+        };
+        module.program.visit_mut_with(&mut rewriter);
+    }
+
+    let mut code = CodeBuilder::default();
+    let mut concatenated = concatenated.into_iter();
+    for segment in segments {
+        match segment {
+            Segment::Evaluate(module) => {
+                let Some(module) =
+                    ResolvedVc::try_sidecast::<Box<dyn ChunkableModule>>(module).await?
+                else {
+                    continue;
+                };
+                let id = module.as_chunk_item(chunking_context).id().await?;
+                writeln!(code, "__turbopack_import__({});", StringifyJs(&*id))?;
+            }
+            Segment::Module(_) => {
+                let module = concatenated
+                    .next()
+                    .context("Every module segment has a concatenated module")?;
+                let (bytes, mappings) = module.print()?;
+                code.push_source(
+                    &bytes.into(),
+                    Some(Vc::upcast(
+                        ParseResultSourceMap::new(
+                            module.source_map.clone(),
+                            mappings,
+                            module.original_source_map,
+                        )
+                        .cell(),
+                    )),
+                );
+                writeln!(code)?;
+            }
+        }
+    }
+    let code = code.build().cell();
+
+    Ok(EcmascriptModuleContent {
+        inner_code: code.await?.source_code().clone(),
+        source_map: Some(Vc::upcast(code)),
+        is_esm: true,
+    }
+    .cell())
+}
@@ -0,0 +1,531 @@
+//! Module concatenation, also known as scope hoisting.
+//!
+//! Groups of ES modules are emitted into the scope of a single module factory.
+//! The exports of concatenated modules are referenced directly instead of
+//! through the module registry, which makes the output smaller and faster to
+//! evaluate.
+//!
+//! A module is concatenated into a group when all of its importers are in the
+//! group and import it statically. Modules that depend on being a separate
+//! module at runtime, e.g. CommonJS modules or modules calling `eval`, are
+//! never concatenated into another module.
+
+mod content;
+
+use std::{collections::VecDeque, hash::Hash};
+
+use anyhow::Result;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use swc_core::ecma::{
+    ast::{CallExpr, Callee, Expr, Ident, ImportDecl, MemberExpr},
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::{
+    trace::TraceRawVcs, FxIndexMap, FxIndexSet, RcStr, ResolvedVc, TryJoinIterExt, Value, Vc,
+};
+use turbopack_core::{
+    chunk::module_concatenation::ModuleConcatenation,
+    module::{Module, Modules},
+    reference::{ModuleReference, ModuleReferences},
+    resolve::parse::Request,
+};
+
+pub(crate) use self::content::concatenated_module_content;
+use crate::{
+    analyzer::{graph::EvalContext, imports::Reexport},
+    chunk::EcmascriptExports,
+    parse::ParseResult,
+    references::esm::EsmAssetReference,
+    EcmascriptModuleAsset,
+};
+
+/// The reason a module can't be concatenated into another module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub enum ConcatenationBailout {
+    /// The module is not an ES module, or is not an Ecmascript module at all.
+    NotEsm,
+    /// The module failed to parse.
+    Unparseable,
+    /// The module calls `eval`, which can access any binding in its scope.
+    Eval,
+    /// The module uses `require`, `module` or `exports`, or re-exports a
+    /// CommonJS module.
+    CommonJsInterop,
+}
+
+/// What module concatenation needs to know about a module.
+#[turbo_tasks::value]
+struct ConcatenationInfo {
+    bailout: Option<ConcatenationBailout>,
+    is_self_async: bool,
+    /// The modules imported with static ESM imports, in import order.
+    imports: Vec<ResolvedVc<Box<dyn Module>>>,
+    /// The modules referenced in any other way, e.g. dynamic imports, CommonJS
+    /// requires or namespace imports that are accessed dynamically.
+    other_references: Vec<ResolvedVc<Box<dyn Module>>>,
+}
+
+/// The result of scanning the code of an ES module for constructs that
+/// prevent concatenation.
+#[turbo_tasks::value]
+struct EsmScanResult {
+    bailout: Option<ConcatenationBailout>,
+    /// The requests of imports whose namespace object is needed at runtime,
+    /// e.g. `import * as ns from "./a"` with `ns[key]` or `export * as ns from
+    /// "./a"`.
+    dynamic_namespace_requests: Vec<ResolvedVc<Request>>,
+}
+
+#[turbo_tasks::function]
+async fn scan_esm(module: Vc<EcmascriptModuleAsset>) -> Result<Vc<EsmScanResult>> {
+    let parsed = module.parse().await?;
+    let ParseResult::Ok {
+        program,
+        eval_context,
+        globals,
+        ..
+    } = &*parsed
+    else {
+        return Ok(EsmScanResult {
+            bailout: Some(ConcatenationBailout::Unparseable),
+            dynamic_namespace_requests: vec![],
+        }
+        .cell());
+    };
+
+    let module_type = module.determine_module_type().await?.module_type;
+    if !eval_context.is_esm(module_type) {
+        return Ok(EsmScanResult {
+            bailout: Some(ConcatenationBailout::NotEsm),
+            dynamic_namespace_requests: vec![],
+        }
+        .cell());
+    }
+
+    let mut scanner = EsmScanner {
+        eval_context,
+        bailout: None,
+        dynamic_namespace_imports: FxIndexSet::default(),
+    };
+    swc_core::common::GLOBALS.set(globals, || program.visit_with(&mut scanner));
+    for (index, reexport) in eval_context.imports.reexports() {
+        if matches!(reexport, Reexport::Namespace { .. }) {
+            scanner.dynamic_namespace_imports.insert(index);
+        }
+    }
+
+    if scanner.bailout.is_none() {
+        if let EcmascriptExports::EsmExports(exports) = *module.get_exports().await? {
+            if !exports.expand_exports().await?.dynamic_exports.is_empty() {
+                scanner.bailout = Some(ConcatenationBailout::CommonJsInterop);
+            }
+        }
+    }
+
+    let references = eval_context.imports.references().collect::<Vec<_>>();
+    let dynamic_namespace_requests = scanner
+        .dynamic_namespace_imports
+        .iter()
+        .map(|&index| {
+            Request::parse(Value::new(
+                RcStr::from(&*references[index].module_path).into(),
+            ))
+            .to_resolved()
+        })
+        .try_join()
+        .await?;
+
+    Ok(EsmScanResult {
+        bailout: scanner.bailout,
+        dynamic_namespace_requests,
+    }
+    .cell())
+}
+
+struct EsmScanner<'a> {
+    eval_context: &'a EvalContext,
+    bailout: Option<ConcatenationBailout>,
+    /// Indices of the imports in the [ImportMap] whose namespace object is
+    /// accessed dynamically.
+    ///
+    /// [ImportMap]: crate::analyzer::imports::ImportMap
+    dynamic_namespace_imports: FxIndexSet<usize>,
+}
+
+impl EsmScanner<'_> {
+    fn is_unresolved(&self, ident: &Ident) -> bool {
+        ident.ctxt.outer() == self.eval_context.unresolved_mark
+    }
+
+    fn namespace_import(&self, ident: &Ident) -> Option<usize> {
+        match self.eval_context.imports.get_binding(&ident.to_id()) {
+            Some((index, None)) => Some(index),
+            _ => None,
+        }
+    }
+}
+
+impl Visit for EsmScanner<'_> {
+    fn visit_import_decl(&mut self, _: &ImportDecl) {
+        // The local bindings of imports are not accesses.
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let Callee::Expr(callee) = &call.callee {
+            if let Expr::Ident(ident) = &**callee {
+                if &*ident.sym == "eval" && self.is_unresolved(ident) {
+                    self.bailout = Some(ConcatenationBailout::Eval);
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        // Only plain `ns.name` and `ns["name"]` expressions are rewritten to the
+        // export itself. Optional chains, assignment targets and any other use
+        // of `ns` need the namespace object.
+        if let Expr::Member(MemberExpr { obj, prop, .. }) = expr {
+            if let Expr::Ident(obj) = &**obj {
+                if self.namespace_import(obj).is_some() && content::static_prop(prop).is_some() {
+                    return;
+                }
+            }
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        if let Some(index) = self.namespace_import(ident) {
+            self.dynamic_namespace_imports.insert(index);
+        } else if self.bailout.is_none()
+            && matches!(&*ident.sym, "require" | "module" | "exports")
+            && self.is_unresolved(ident)
+        {
+            self.bailout = Some(ConcatenationBailout::CommonJsInterop);
+        }
+    }
+}
+
+pub(crate) async fn referenced_modules(
+    reference: Vc<Box<dyn ModuleReference>>,
+) -> Result<Vec<ResolvedVc<Box<dyn Module>>>> {
+    Ok(reference
+        .resolve_reference()
+        .primary_modules()
+        .await?
+        .clone_value())
+}
+
+#[turbo_tasks::function]
+async fn concatenation_info(module: ResolvedVc<Box<dyn Module>>) -> Result<Vc<ConcatenationInfo>> {
+    let references = module.references().await?;
+
+    let Some(ecmascript) = ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        let other_references = references
+            .iter()
+            .map(|&reference| referenced_modules(reference))
+            .try_join()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        return Ok(ConcatenationInfo {
+            bailout: Some(ConcatenationBailout::NotEsm),
+            is_self_async: false,
+            imports: vec![],
+            other_references,
+        }
+        .cell());
+    };
+
+    let scan = scan_esm(*ecmascript).await?;
+    let is_self_async = match *ecmascript.get_async_module().await? {
+        Some(async_module) => {
+            *async_module
+                .is_self_async(ecmascript.analyze().await?.references)
+                .await?
+        }
+        None => false,
+    };
+
+    let mut imports = Vec::new();
+    let mut other_references = Vec::new();
+    for &reference in references.iter() {
+        let modules = referenced_modules(reference).await?;
+        if let Some(esm_reference) =
+            Vc::try_resolve_downcast_type::<EsmAssetReference>(reference).await?
+        {
+            let esm_reference = esm_reference.await?;
+            let is_static = esm_reference.annotations.chunking_type().is_none()
+                && !scan
+                    .dynamic_namespace_requests
+                    .contains(&esm_reference.request.to_resolved().await?);
+            if is_static {
+                imports.extend(modules);
+                continue;
+            }
+        }
+        other_references.extend(modules);
+    }
+
+    Ok(ConcatenationInfo {
+        bailout: scan.bailout,
+        is_self_async,
+        imports,
+        other_references,
+    }
+    .cell())
+}
+
+/// A module in the graph that [concatenation_groups] groups.
+struct GraphNode<T> {
+    can_be_concatenated: bool,
+    is_self_async: bool,
+    /// The modules imported with static ESM imports, in import order.
+    imports: Vec<T>,
+    other_references: Vec<T>,
+}
+
+/// Groups the modules reachable from `entries` into the groups of modules to
+/// concatenate. Returns the groups with more than one module, each starting
+/// with its root.
+fn concatenation_groups<T>(entries: &[T], graph: &FxIndexMap<T, GraphNode<T>>) -> Vec<Vec<T>>
+where
+    T: Copy + Eq + Hash,
+{
+    let mut importers: FxHashMap<T, Vec<(T, bool)>> = FxHashMap::default();
+    for (&module, node) in graph {
+        for &import in &node.imports {
+            importers.entry(import).or_default().push((module, true));
+        }
+        for &reference in &node.other_references {
+            importers
+                .entry(reference)
+                .or_default()
+                .push((module, false));
+        }
+    }
+
+    // Importing an async module makes the importer async, and async modules
+    // need their own module to be awaited.
+    let mut async_modules = FxHashSet::default();
+    let mut queue = graph
+        .iter()
+        .filter(|(_, node)| node.is_self_async)
+        .map(|(&module, _)| module)
+        .collect::<VecDeque<_>>();
+    while let Some(module) = queue.pop_front() {
+        if !async_modules.insert(module) {
+            continue;
+        }
+        for &(importer, is_static) in importers.get(&module).into_iter().flatten() {
+            if is_static {
+                queue.push_back(importer);
+            }
+        }
+    }
+
+    let is_candidate = |module: &T| {
+        graph
+            .get(module)
+            .is_some_and(|node| node.can_be_concatenated)
+            && !async_modules.contains(module)
+    };
+
+    // Roots are picked in topological order, so importers are grouped before
+    // the modules they import.
+    let mut order = Vec::with_capacity(graph.len());
+    let mut visited = FxHashSet::default();
+    for &entry in entries {
+        let mut stack = vec![(entry, false)];
+        while let Some((module, children_visited)) = stack.pop() {
+            if children_visited {
+                order.push(module);
+                continue;
+            }
+            if !visited.insert(module) {
+                continue;
+            }
+            stack.push((module, true));
+            if let Some(node) = graph.get(&module) {
+                for &child in node.imports.iter().chain(&node.other_references).rev() {
+                    stack.push((child, false));
+                }
+            }
+        }
+    }
+    order.reverse();
+
+    let entries = entries.iter().copied().collect::<FxHashSet<_>>();
+    let mut assigned = FxHashSet::default();
+    let mut groups = Vec::new();
+    for root in order {
+        if !is_candidate(&root) || !assigned.insert(root) {
+            continue;
+        }
+        let can_join = |group: &FxIndexSet<T>, module: &T| {
+            !group.contains(module)
+                && !assigned.contains(module)
+                && !entries.contains(module)
+                && is_candidate(module)
+                && importers[module]
+                    .iter()
+                    .all(|(importer, is_static)| *is_static && group.contains(importer))
+        };
+        let mut group = FxIndexSet::default();
+        group.insert(root);
+        // A module can only join once all of its importers joined, so repeat
+        // until the group doesn't grow anymore.
+        loop {
+            let len = group.len();
+            let mut index = 0;
+            while let Some(&module) = group.get_index(index) {
+                index += 1;
+                for import in &graph[&module].imports {
+                    if can_join(&group, import) {
+                        group.insert(*import);
+                    }
+                }
+            }
+            if group.len() == len {
+                break;
+            }
+        }
+        if group.len() > 1 {
+            assigned.extend(group.iter().copied());
+            groups.push(group.into_iter().collect());
+        }
+    }
+    groups
+}
+
+/// Computes the groups of modules to concatenate for the module graph of
+/// `entries`. Entries are never concatenated into another module, as they need
+/// to be instantiated by id.
+#[turbo_tasks::function]
+pub async fn module_concatenation(entries: Vc<Modules>) -> Result<Vc<ModuleConcatenation>> {
+    let entries = entries.await?;
+
+    let mut graph = FxIndexMap::default();
+    let mut current = entries.iter().copied().collect::<FxIndexSet<_>>();
+    while !current.is_empty() {
+        let infos = current
+            .iter()
+            .map(|&module| async move { Ok((module, concatenation_info(*module).await?)) })
+            .try_join()
+            .await?;
+        let mut next = FxIndexSet::default();
+        for (module, info) in infos {
+            next.extend(
+                info.imports
+                    .iter()
+                    .chain(&info.other_references)
+                    .copied()
+                    .filter(|child| !graph.contains_key(child) && !current.contains(child)),
+            );
+            graph.insert(
+                module,
+                GraphNode {
+                    can_be_concatenated: info.bailout.is_none(),
+                    is_self_async: info.is_self_async,
+                    imports: info.imports.clone(),
+                    other_references: info.other_references.clone(),
+                },
+            );
+        }
+        current = next;
+    }
+
+    let mut groups = FxIndexMap::default();
+    for group in concatenation_groups(&entries, &graph) {
+        groups.insert(group[0], ResolvedVc::cell(group));
+    }
+    Ok(ModuleConcatenation::new(groups))
+}
+
+/// The references of a group of concatenated modules, without the references
+/// between modules of the group.
+#[turbo_tasks::function]
+pub(crate) async fn concatenated_module_references(
+    modules: Vc<Modules>,
+) -> Result<Vc<ModuleReferences>> {
+    let modules = modules.await?;
+    let group = modules.iter().copied().collect::<FxHashSet<_>>();
+
+    let mut references = FxIndexSet::default();
+    for module in modules.iter() {
+        for &reference in module.references().await?.iter() {
+            let targets = referenced_modules(reference).await?;
+            if !targets.is_empty() && targets.iter().all(|target| group.contains(target)) {
+                continue;
+            }
+            references.insert(reference);
+        }
+    }
+    Ok(Vc::cell(references.into_iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::FxIndexMap;
+
+    use super::{concatenation_groups, GraphNode};
+
+    fn node(imports: &[u32]) -> GraphNode<u32> {
+        GraphNode {
+            can_be_concatenated: true,
+            is_self_async: false,
+            imports: imports.to_vec(),
+            other_references: vec![],
+        }
+    }
+
+    #[test]
+    fn test_groups_modules_with_a_single_entry() {
+        // 0 -> 1 -> 2, 0 -> 3 and 1 -> 3: 3 is only imported from the group.
+        let graph = FxIndexMap::from_iter([
+            (0, node(&[1, 3])),
+            (1, node(&[2, 3])),
+            (2, node(&[])),
+            (3, node(&[])),
+        ]);
+        assert_eq!(concatenation_groups(&[0], &graph), vec![vec![0, 1, 3, 2]]);
+    }
+
+    #[test]
+    fn test_keeps_shared_modules_separate() {
+        // 2 is imported by both entries, so it can't be inlined into either.
+        let graph = FxIndexMap::from_iter([
+            (0, node(&[2])),
+            (1, node(&[2, 3])),
+            (2, node(&[])),
+            (3, node(&[])),
+        ]);
+        assert_eq!(concatenation_groups(&[0, 1], &graph), vec![vec![1, 3]]);
+    }
+
+    #[test]
+    fn test_falls_back_for_bailouts_and_dynamic_access() {
+        let mut graph = FxIndexMap::from_iter([
+            (0, node(&[1, 2, 6])),
+            (1, node(&[])),
+            (2, node(&[])),
+            (3, node(&[4])),
+            (4, node(&[])),
+            (5, node(&[])),
+            (6, node(&[])),
+            (7, node(&[3])),
+        ]);
+        // 1 calls `eval`.
+        graph[&1].can_be_concatenated = false;
+        // 2 is also loaded with `import()` from 5.
+        graph[&0].other_references.push(5);
+        graph[&5].other_references.push(2);
+        // 4 has top level await, which makes 3 and 7 async as well.
+        graph[&4].is_self_async = true;
+        assert_eq!(concatenation_groups(&[0, 7], &graph), vec![vec![0, 6]]);
+    }
+}
@@ -0,0 +1,567 @@
+use std::{collections::HashSet, io::Write, path::Path, sync::Arc};
+
+use anyhow::{Context, Result};
+use swc_core::{
+    common::{
+        comments::Comments, source_map::SourceMapGenConfig, BytePos, FileName, LineCol,
+        SourceMap as SwcSourceMap, GLOBALS,
+    },
+    ecma::{
+        ast::Program,
+        codegen::{text_writer::JsWriter, Emitter},
+    },
+};
+use swc_typescript::fast_dts::{FastDts, FastDtsOptions};
+use turbo_tasks::{RcStr, ResolvedVc, Value, ValueToString, Vc};
+use turbo_tasks_fs::{to_sys_path, File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    issue::{
+        Issue, IssueExt, IssueSeverity, IssueSource, IssueStage, OptionIssueSource,
+        OptionStyledString, StyledString,
+    },
+    module::Modules,
+    output::{OutputAsset, OutputAssets},
+    source::Source,
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMap, SourceMapAsset},
+    SOURCE_MAP_PREFIX,
+};
+
+use crate::{
+    parse::{parse, ParseResult},
+    transform::EcmascriptInputTransforms,
+    EcmascriptModuleAsset, EcmascriptModuleAssetType,
+};
+
+/// A `.d.ts` file with the declarations of a TypeScript module.
+///
+/// The declarations are emitted with the isolated declarations transform, so
+/// exported functions, classes and variables need explicit type annotations.
+/// Constructs that would need type inference are reported as
+/// [DeclarationIssue]s.
+#[turbo_tasks::value(shared)]
+pub struct EcmascriptDeclarationAsset {
+    path: ResolvedVc<FileSystemPath>,
+    module: ResolvedVc<EcmascriptModuleAsset>,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDeclarationAsset {
+    /// Creates a new [`Vc<EcmascriptDeclarationAsset>`] at `path` with the
+    /// declarations of `module`, which must be a TypeScript module.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: ResolvedVc<FileSystemPath>,
+        module: ResolvedVc<EcmascriptModuleAsset>,
+    ) -> Vc<Self> {
+        EcmascriptDeclarationAsset { path, module }.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(&self) -> Result<Vc<Code>> {
+        let module = self.module.await?;
+        Ok(declaration_code(
+            module.source,
+            Value::new(module.ty),
+            self.path.await?.file_name().into(),
+            relative_source_path(*self.path, module.source.ident().path()).await?,
+        ))
+    }
+}
+
+/// Returns the path of `source` relative to the directory of the declaration
+/// file at `declaration`, for the `sources` of its source map. Paths on
+/// different file systems are related through their location on disk.
+async fn relative_source_path(
+    declaration: Vc<FileSystemPath>,
+    source: Vc<FileSystemPath>,
+) -> Result<RcStr> {
+    if let Some(path) = declaration
+        .parent()
+        .await?
+        .get_relative_path_to(&*source.await?)
+    {
+        return Ok(path);
+    }
+    if let (Some(declaration), Some(source)) =
+        (to_sys_path(declaration).await?, to_sys_path(source).await?)
+    {
+        if let Some(path) = declaration
+            .parent()
+            .and_then(|directory| relative_sys_path(directory, &source))
+        {
+            return Ok(path.into());
+        }
+    }
+    Ok(format!("{SOURCE_MAP_PREFIX}{}", source.to_string().await?).into())
+}
+
+/// Returns the path of `to` relative to the directory `from`, with `/`
+/// separators.
+fn relative_sys_path(from: &Path, to: &Path) -> Option<String> {
+    let mut from_components = from.components().peekable();
+    let mut to_components = to.components().peekable();
+    while from_components.peek().is_some() && from_components.peek() == to_components.peek() {
+        from_components.next();
+        to_components.next();
+    }
+    let mut segments = Vec::new();
+    if from_components.peek().is_none() {
+        segments.push(".");
+    }
+    for _ in from_components {
+        segments.push("..");
+    }
+    for component in to_components {
+        segments.push(component.as_os_str().to_str()?);
+    }
+    Some(segments.join("/"))
+}
+
+/// Emits the declarations of the TypeScript module `source`, followed by a
+/// comment referencing their source map at `{file_name}.map`. The source map
+/// refers to the module as `source_path`.
+#[turbo_tasks::function]
+async fn declaration_code(
+    source: Vc<Box<dyn Source>>,
+    ty: Value<EcmascriptModuleAssetType>,
+    file_name: RcStr,
+    source_path: RcStr,
+) -> Result<Vc<Code>> {
+    // Declarations are emitted from the original TypeScript, before types are
+    // stripped by the module's transforms.
+    let parsed = parse(source, ty, EcmascriptInputTransforms::empty()).await?;
+    let ParseResult::Ok {
+        program,
+        comments,
+        eval_context,
+        globals,
+        source_map,
+    } = &*parsed
+    else {
+        // Parse errors are already reported by the module itself.
+        return Ok(CodeBuilder::default().build().cell());
+    };
+
+    let swc_file_name = FileName::Custom(source.ident().to_string().await?.to_string());
+    let mut program = program.clone();
+    let dts_issues = GLOBALS.set(globals, || {
+        let mut fast_dts = FastDts::new(
+            swc_file_name.into(),
+            eval_context.unresolved_mark,
+            FastDtsOptions::default(),
+        );
+        fast_dts.transform(&mut program)
+    });
+    for dts_issue in dts_issues {
+        DeclarationIssue {
+            source: IssueSource::from_swc_offsets(
+                source,
+                dts_issue.range.span.lo.to_usize(),
+                dts_issue.range.span.hi.to_usize(),
+            ),
+            message: dts_issue.message.to_string().into(),
+        }
+        .cell()
+        .emit();
+    }
+
+    let (src, mappings) = print_declarations(source_map.clone(), &program, &**comments)?;
+
+    let mut code = CodeBuilder::default();
+    code.push_source(
+        &src.into(),
+        Some(Vc::upcast(
+            DeclarationSourceMap {
+                files_map: source_map.clone(),
+                mappings,
+                source_path,
+            }
+            .cell(),
+        )),
+    );
+    write!(
+        code,
+        "\n//# sourceMappingURL={}.map",
+        urlencoding::encode(&file_name)
+    )?;
+    Ok(code.build().cell())
+}
+
+fn print_declarations(
+    cm: Arc<SwcSourceMap>,
+    program: &Program,
+    comments: &dyn Comments,
+) -> Result<(String, Vec<(BytePos, LineCol)>)> {
+    let mut mappings = vec![];
+    let mut buf = vec![];
+    {
+        let mut emitter = Emitter {
+            cfg: swc_core::ecma::codegen::Config::default(),
+            comments: Some(comments),
+            cm: cm.clone(),
+            wr: JsWriter::new(cm, "\n", &mut buf, Some(&mut mappings)),
+        };
+        emitter
+            .emit_program(program)
+            .context("failed to emit declarations")?;
+    }
+    Ok((String::from_utf8(buf)?, mappings))
+}
+
+/// The source map of emitted declarations, which refers to the TypeScript
+/// module by its path relative to the declaration file, as `tsc` does.
+#[turbo_tasks::value(shared, serialization = "none", eq = "manual")]
+struct DeclarationSourceMap {
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    files_map: Arc<SwcSourceMap>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    mappings: Vec<(BytePos, LineCol)>,
+    source_path: RcStr,
+}
+
+impl PartialEq for DeclarationSourceMap {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.files_map, &other.files_map)
+            && self.mappings == other.mappings
+            && self.source_path == other.source_path
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for DeclarationSourceMap {
+    #[turbo_tasks::function]
+    fn generate_source_map(&self) -> Vc<OptionSourceMap> {
+        let map = self.files_map.build_source_map_with_config(
+            &self.mappings,
+            None,
+            DeclarationSourceMapConfig {
+                source_path: &self.source_path,
+            },
+        );
+        Vc::cell(Some(SourceMap::new_regular(map).cell()))
+    }
+}
+
+/// Names the only file of a declaration source map by the path of its module.
+struct DeclarationSourceMapConfig<'a> {
+    source_path: &'a str,
+}
+
+impl SourceMapGenConfig for DeclarationSourceMapConfig<'_> {
+    fn file_name_to_source(&self, _f: &FileName) -> String {
+        self.source_path.to_string()
+    }
+
+    fn inline_sources_content(&self, _f: &FileName) -> bool {
+        true
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptDeclarationAsset {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<RcStr> {
+        Vc::cell("TypeScript declarations".into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDeclarationAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(*self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        Ok(Vc::cell(vec![ResolvedVc::upcast(
+            SourceMapAsset::new(Vc::upcast(self)).to_resolved().await?,
+        )]))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDeclarationAsset {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptDeclarationAsset {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
+
+/// Returns the path of the declaration file of a TypeScript file, e.g.
+/// `index.d.ts` for `index.ts` and `index.d.mts` for `index.mts`.
+fn declaration_file_path(path: &str) -> Option<String> {
+    if path.ends_with(".d.ts") || path.ends_with(".d.mts") || path.ends_with(".d.cts") {
+        return None;
+    }
+    let (stem, extension) = path.rsplit_once('.')?;
+    let extension = match extension {
+        "ts" | "tsx" => "d.ts",
+        "mts" => "d.mts",
+        "cts" => "d.cts",
+        _ => return None,
+    };
+    Some(format!("{stem}.{extension}"))
+}
+
+/// Returns the declaration files of the TypeScript modules in `modules`. Only
+/// modules in `source_root` outside of `node_modules` are included, and their
+/// declarations are placed at the same relative path in `output_root`.
+#[turbo_tasks::function]
+pub async fn declaration_assets(
+    modules: Vc<Modules>,
+    source_root: Vc<FileSystemPath>,
+    output_root: Vc<FileSystemPath>,
+) -> Result<Vc<OutputAssets>> {
+    let source_root = source_root.await?;
+    let mut paths = HashSet::new();
+    let mut assets = Vec::new();
+    for &module in modules.await?.iter() {
+        let Some(module) = ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module).await?
+        else {
+            continue;
+        };
+        if !matches!(
+            module.await?.ty,
+            EcmascriptModuleAssetType::Typescript { .. }
+        ) {
+            continue;
+        }
+        let module_path = module.ident().path().await?;
+        let Some(relative_path) = source_root.get_path_to(&module_path) else {
+            continue;
+        };
+        if relative_path
+            .split('/')
+            .any(|segment| segment == "node_modules")
+        {
+            continue;
+        }
+        let Some(declaration_path) = declaration_file_path(relative_path) else {
+            continue;
+        };
+        if !paths.insert(declaration_path.clone()) {
+            continue;
+        }
+        let path = output_root
+            .join(declaration_path.into())
+            .to_resolved()
+            .await?;
+        assets.push(ResolvedVc::upcast(
+            EcmascriptDeclarationAsset::new(*path, *module)
+                .to_resolved()
+                .await?,
+        ));
+    }
+    Ok(Vc::cell(assets))
+}
+
+/// A construct of a TypeScript module that can't be emitted as a declaration
+/// without type inference, e.g. an exported function without a return type.
+#[turbo_tasks::value(shared)]
+pub struct DeclarationIssue {
+    source: Vc<IssueSource>,
+    message: RcStr,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DeclarationIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.source.file_path()
+    }
+
+    #[turbo_tasks::function]
+    fn stage(&self) -> Vc<IssueStage> {
+        IssueStage::CodeGen.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Unable to emit TypeScript declarations".into()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                format!(
+                    "{}\n\nDeclarations are emitted without type inference, so add an explicit \
+                     type annotation.",
+                    self.message
+                )
+                .into(),
+            )
+            .cell(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> Vc<OptionIssueSource> {
+        Vc::cell(Some(self.source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, path::Path};
+
+    use anyhow::Result;
+    use turbo_tasks::{TurboTasks, Value, Vc};
+    use turbo_tasks_fs::{File, FileSystem, VirtualFileSystem};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_core::{
+        asset::AssetContent,
+        code_builder::Code,
+        issue::{Issue, IssueDescriptionExt, IssueSeverity},
+        source_map::GenerateSourceMap,
+        virtual_source::VirtualSource,
+    };
+
+    use super::{declaration_code, declaration_file_path, relative_source_path, relative_sys_path};
+    use crate::EcmascriptModuleAssetType;
+
+    async fn run(test: impl Future<Output = Result<()>> + Send + 'static) -> Result<()> {
+        crate::register();
+        TurboTasks::new(MemoryBackend::new(usize::MAX))
+            .run_once(test)
+            .await
+    }
+
+    /// The declarations of `src/index.ts` with the contents `code`, emitted to
+    /// `dist/src/index.d.ts`.
+    async fn declarations(code: &str) -> Result<Vc<Code>> {
+        let root = VirtualFileSystem::new_with_name("project".into()).root();
+        let path = root.join("src/index.ts".into());
+        let source = VirtualSource::new(path, AssetContent::file(File::from(code).into()));
+        Ok(declaration_code(
+            Vc::upcast(source),
+            Value::new(EcmascriptModuleAssetType::Typescript {
+                tsx: false,
+                analyze_types: false,
+            }),
+            "index.d.ts".into(),
+            relative_source_path(root.join("dist/src/index.d.ts".into()), path).await?,
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_emits_declarations() -> Result<()> {
+        run(async {
+            let code = declarations(
+                "/** Adds two numbers. */\nexport function add(a: number, b: number): number {\n  \
+                 return a + b;\n}\nexport const answer: number = 42;\nconst internal = 1;\n",
+            )
+            .await?;
+            let declarations = code.await?.source_code().to_str()?.into_owned();
+
+            for expected in [
+                "/** Adds two numbers. */",
+                "export declare function add(a: number, b: number): number;",
+                "export declare const answer: number;",
+                "\n//# sourceMappingURL=index.d.ts.map",
+            ] {
+                assert!(declarations.contains(expected), "{declarations}");
+            }
+            assert!(!declarations.contains("return"), "{declarations}");
+            assert!(!declarations.contains("internal"), "{declarations}");
+            assert!(code.peek_issues_with_path().await?.is_empty_ref());
+
+            // The source map refers to the module relative to the declaration file.
+            let map = (*code.generate_source_map().await?).expect("declarations have a source map");
+            let map: serde_json::Value = serde_json::from_reader(map.to_rope().await?.read())?;
+            let sources = map["sections"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .flat_map(|section| section["map"]["sources"].as_array().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(sources, ["../../src/index.ts"]);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_reports_declarations_needing_type_inference() -> Result<()> {
+        run(async {
+            let code =
+                declarations("export function add(a: number, b: number) {\n  return a + b;\n}\n")
+                    .await?;
+            code.strongly_consistent().await?;
+
+            let issues = code.peek_issues_with_path().await?;
+            assert_eq!(issues.len(), 1);
+            let issue = issues.iter().next().unwrap();
+            assert_eq!(*issue.severity().await?, IssueSeverity::Error);
+            assert_eq!(
+                issue.title().await?.to_unstyled_string(),
+                "Unable to emit TypeScript declarations"
+            );
+            assert_eq!(&*issue.file_path().await?.path, "src/index.ts");
+            let description = (*issue.description().await?).unwrap().await?;
+            let description = description.to_unstyled_string();
+            assert!(
+                description.contains("add an explicit type annotation"),
+                "{description}"
+            );
+            assert!(issue.source().await?.is_some());
+            Ok(())
+        })
+        .await
+    }
+
+    #[test]
+    fn test_declaration_file_path() {
+        assert_eq!(
+            declaration_file_path("src/index.ts").as_deref(),
+            Some("src/index.d.ts")
+        );
+        assert_eq!(
+            declaration_file_path("src/button.tsx").as_deref(),
+            Some("src/button.d.ts")
+        );
+        assert_eq!(
+            declaration_file_path("src/server.mts").as_deref(),
+            Some("src/server.d.mts")
+        );
+        assert_eq!(
+            declaration_file_path("src/legacy.cts").as_deref(),
+            Some("src/legacy.d.cts")
+        );
+        assert_eq!(declaration_file_path("src/types.d.ts"), None);
+        assert_eq!(declaration_file_path("src/index.js"), None);
+    }
+
+    #[test]
+    fn test_relative_sys_path() {
+        assert_eq!(
+            relative_sys_path(Path::new("/app/dist/src"), Path::new("/app/src/index.ts"))
+                .as_deref(),
+            Some("../../src/index.ts")
+        );
+        assert_eq!(
+            relative_sys_path(Path::new("/app/src"), Path::new("/app/src/index.ts")).as_deref(),
+            Some("./index.ts")
+        );
+    }
+}
@@ -1,11 +1,18 @@
+use std::collections::BTreeSet;
+
 use anyhow::Result;
+use rustc_hash::FxHashSet;
 use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
     FxIndexMap, FxIndexSet, RcStr, ResolvedVc, TryJoinIterExt, ValueToString, Vc,
 };
+use turbo_tasks_fs::FileSystemPath;
 use turbo_tasks_hash::hash_xxh3_hash64;
 use turbopack_core::{
-    chunk::ModuleId,
+    chunk::{
+        module_id_strategies::{DeterministicModuleIdStrategy, ModuleIdRecords},
+        ModuleId,
+    },
     module::{Module, Modules},
     reference::ModuleReference,
 };
@@ -166,6 +173,17 @@ pub async fn children_modules_idents(
 
 const JS_MAX_SAFE_INTEGER: u64 = (1u64 << 53) - 1;
 
+/// Returns the power of ten that ids of `count` modules are taken modulo of.
+fn digit_mask(count: usize) -> u64 {
+    // 5% fill rate, as done in Webpack
+    // https://github.com/webpack/webpack/blob/27cf3e59f5f289dfc4d76b7a1df2edbc4e651589/lib/ids/IdHelpers.js#L366-L405
+    let optimal_range = count * 20;
+    std::cmp::min(
+        10u64.pow((optimal_range as f64).log10().ceil() as u32),
+        JS_MAX_SAFE_INTEGER,
+    )
+}
+
 // Note(LichuAcu): This could be split into two functions: one that merges the preprocessed module
 // ids and another that generates the final, optimized module ids. Thoughts?
 pub async fn merge_preprocessed_module_ids(
@@ -179,13 +197,7 @@ pub async fn merge_preprocessed_module_ids(
         }
     }
 
-    // 5% fill rate, as done in Webpack
-    // https://github.com/webpack/webpack/blob/27cf3e59f5f289dfc4d76b7a1df2edbc4e651589/lib/ids/IdHelpers.js#L366-L405
-    let optimal_range = merged_module_ids.len() * 20;
-    let digit_mask = std::cmp::min(
-        10u64.pow((optimal_range as f64).log10().ceil() as u32),
-        JS_MAX_SAFE_INTEGER,
-    );
+    let digit_mask = digit_mask(merged_module_ids.len());
 
     let mut module_id_map = FxIndexMap::default();
     let mut used_ids = FxIndexSet::default();
@@ -204,3 +216,153 @@ pub async fn merge_preprocessed_module_ids(
 
     Ok(module_id_map)
 }
+
+/// Assigns short numeric ids to all modules reachable from `root_modules`.
+/// The ids are derived from the idents of the modules relative to `root`, so
+/// they don't depend on the machine the project is built on. Modules in the
+/// records file at `records` keep their recorded id.
+#[turbo_tasks::function]
+pub async fn deterministic_module_id_strategy(
+    root_modules: Vc<Modules>,
+    root: ResolvedVc<FileSystemPath>,
+    records: Option<Vc<FileSystemPath>>,
+) -> Result<Vc<DeterministicModuleIdStrategy>> {
+    let records = match records {
+        Some(path) => ModuleIdRecords::read(path).await?,
+        None => ModuleIdRecords::default(),
+    };
+
+    let children_modules = AdjacencyMap::new()
+        .skip_duplicates()
+        .visit(
+            root_modules
+                .await?
+                .iter()
+                .map(|module| ReferencedModule::Module(**module).resolved_cell())
+                .collect::<Vec<_>>(),
+            get_children_modules,
+        )
+        .await
+        .completed()?
+        .into_inner()
+        .into_reverse_topological();
+
+    let mut idents = BTreeSet::new();
+    for child_module in children_modules {
+        match *child_module.await? {
+            ReferencedModule::Module(module) => {
+                idents.insert(
+                    module
+                        .ident()
+                        .to_relative_string(*root)
+                        .await?
+                        .clone_value(),
+                );
+            }
+            ReferencedModule::AsyncLoaderModule(module) => {
+                let loader_ident = module
+                    .ident()
+                    .with_modifier(Vc::cell("async loader".into()));
+                idents.insert(loader_ident.to_relative_string(*root).await?.clone_value());
+                idents.insert(
+                    module
+                        .ident()
+                        .to_relative_string(*root)
+                        .await?
+                        .clone_value(),
+                );
+            }
+        }
+    }
+
+    Ok(DeterministicModuleIdStrategy::new(
+        root,
+        assign_deterministic_module_ids(&idents, &records),
+    ))
+}
+
+/// Assigns an id to each of `idents`. Recorded ids are kept. The other ids are
+/// hashes of the idents, and collisions are resolved in the order of `idents`,
+/// so the result only depends on the set of modules.
+fn assign_deterministic_module_ids(
+    idents: &BTreeSet<RcStr>,
+    records: &ModuleIdRecords,
+) -> FxIndexMap<RcStr, u64> {
+    let digit_mask = digit_mask(idents.len());
+
+    let mut module_id_map = FxIndexMap::default();
+    let mut used_ids = FxHashSet::default();
+    for ident in idents {
+        if let Some(&id) = records.modules.get(ident) {
+            if used_ids.insert(id) {
+                module_id_map.insert(ident.clone(), id);
+            }
+        }
+    }
+
+    for ident in idents {
+        if module_id_map.contains_key(ident) {
+            continue;
+        }
+        let full_hash = hash_xxh3_hash64(ident);
+        let mut id = full_hash % digit_mask;
+        let mut i = 1u64;
+        while used_ids.contains(&id) {
+            // If the id is already used, seek to find another available id.
+            id = hash_xxh3_hash64(full_hash.wrapping_add(i)) % digit_mask;
+            i += 1;
+        }
+        used_ids.insert(id);
+        module_id_map.insert(ident.clone(), id);
+    }
+
+    module_id_map.sort_keys();
+    module_id_map
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use turbo_tasks::RcStr;
+    use turbopack_core::chunk::module_id_strategies::ModuleIdRecords;
+
+    use super::assign_deterministic_module_ids;
+
+    fn idents(count: usize) -> BTreeSet<RcStr> {
+        (0..count)
+            .map(|i| format!("src/module{i}.js").into())
+            .collect()
+    }
+
+    #[test]
+    fn test_ids_are_unique_and_short() {
+        let ids = assign_deterministic_module_ids(&idents(1000), &ModuleIdRecords::default());
+        assert_eq!(ids.len(), 1000);
+        assert_eq!(ids.values().collect::<BTreeSet<_>>().len(), 1000);
+        assert!(ids.values().all(|&id| id < 100_000));
+        assert_eq!(
+            ids,
+            assign_deterministic_module_ids(&idents(1000), &ModuleIdRecords::default())
+        );
+    }
+
+    #[test]
+    fn test_keeps_recorded_ids() {
+        let previous = assign_deterministic_module_ids(&idents(3), &ModuleIdRecords::default());
+        let records = ModuleIdRecords {
+            modules: previous
+                .iter()
+                .map(|(ident, id)| (ident.clone(), *id))
+                .chain([("src/removed.js".into(), 1)])
+                .collect(),
+        };
+        // More modules change the range of the ids, but not the recorded ones.
+        let ids = assign_deterministic_module_ids(&idents(10), &records);
+        for (ident, id) in &previous {
+            assert_eq!(ids.get(ident), Some(id));
+        }
+        assert!(!ids.contains_key("src/removed.js"));
+        assert_eq!(ids.values().collect::<BTreeSet<_>>().len(), 10);
+    }
+}
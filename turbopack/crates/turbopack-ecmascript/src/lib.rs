@@ -14,6 +14,8 @@ pub mod async_chunk;
 pub mod chunk;
 pub mod chunk_group_files_asset;
 pub mod code_gen;
+pub mod concatenation;
+pub mod declarations;
 mod errors;
 pub mod global_module_id_strategy;
 pub mod magic_identifier;
@@ -21,6 +23,7 @@ pub mod manifest;
 pub mod minify;
 pub mod parse;
 mod path_visitor;
+pub mod property_mangling;
 pub mod references;
 pub mod side_effect_optimization;
 pub(crate) mod special_cases;
@@ -30,6 +33,7 @@ pub mod text;
 pub(crate) mod transform;
 pub mod tree_shake;
 pub mod typescript;
+pub mod used_exports;
 pub mod utils;
 pub mod webpack;
 pub mod worker_chunk;
@@ -66,7 +70,8 @@ use turbo_tasks_fs::{rope::Rope, FileJsonContent, FileSystemPath};
 use turbopack_core::{
     asset::{Asset, AssetContent},
     chunk::{
-        AsyncModuleInfo, ChunkItem, ChunkType, ChunkableModule, ChunkingContext, EvaluatableAsset,
+        used_exports::ModuleUsedExports, AsyncModuleInfo, ChunkItem, ChunkType, ChunkableModule,
+        ChunkingContext, EvaluatableAsset,
     },
     compile_time_info::CompileTimeInfo,
     context::AssetContext,
@@ -90,6 +95,7 @@ use self::{
 };
 use crate::{
     chunk::EcmascriptChunkPlaceable,
+    concatenation::{concatenated_module_content, concatenated_module_references},
     references::{analyse_ecmascript_module, async_module::OptionAsyncModule},
     transform::remove_shebang,
 };
@@ -395,6 +401,9 @@ impl EcmascriptAnalyzable for EcmascriptModuleAsset {
             analyze.async_module,
             analyze.source_map,
             analyze.exports,
+            chunking_context
+                .used_exports()
+                .module_used_exports(Vc::upcast(self)),
             async_module_info,
         ))
     }
@@ -626,8 +635,17 @@ impl ChunkItem for ModuleChunkItem {
     }
 
     #[turbo_tasks::function]
-    fn references(&self) -> Vc<ModuleReferences> {
-        self.module.references()
+    async fn references(&self) -> Result<Vc<ModuleReferences>> {
+        let concatenated_modules = self
+            .chunking_context
+            .module_concatenation()
+            .concatenated_modules(Vc::upcast(self.module));
+        if concatenated_modules.await?.is_empty() {
+            return Ok(self.module.references());
+        }
+        // The modules concatenated into this one are not chunk items themselves,
+        // so their references are chunked as references of this item.
+        Ok(concatenated_module_references(concatenated_modules))
     }
 
     #[turbo_tasks::function]
@@ -685,10 +703,17 @@ impl EcmascriptChunkItem for ModuleChunkItem {
             .get_async_module()
             .module_options(async_module_info);
 
-        // TODO check if we need to pass async_module_info at all
-        let content = this
-            .module
-            .module_content(this.chunking_context, async_module_info);
+        let concatenated_modules = this
+            .chunking_context
+            .module_concatenation()
+            .concatenated_modules(Vc::upcast(this.module));
+        let content = if concatenated_modules.await?.is_empty() {
+            // TODO check if we need to pass async_module_info at all
+            this.module
+                .module_content(this.chunking_context, async_module_info)
+        } else {
+            concatenated_module_content(this.module, concatenated_modules, this.chunking_context)
+        };
 
         Ok(EcmascriptChunkItemContent::new(
             content,
@@ -722,44 +747,20 @@ impl EcmascriptModuleContent {
         async_module: Vc<OptionAsyncModule>,
         source_map: Vc<OptionSourceMap>,
         exports: Vc<EcmascriptExports>,
+        used_exports: Vc<ModuleUsedExports>,
         async_module_info: Option<Vc<AsyncModuleInfo>>,
     ) -> Result<Vc<Self>> {
-        let mut code_gens = Vec::new();
-        for r in references.await?.iter() {
-            let r = r.resolve().await?;
-            if let Some(code_gen) =
-                Vc::try_resolve_sidecast::<Box<dyn CodeGenerateableWithAsyncModuleInfo>>(r).await?
-            {
-                code_gens.push(code_gen.code_generation(chunking_context, async_module_info));
-            } else if let Some(code_gen) =
-                Vc::try_resolve_sidecast::<Box<dyn CodeGenerateable>>(r).await?
-            {
-                code_gens.push(code_gen.code_generation(chunking_context));
-            }
-        }
-        if let Some(async_module) = *async_module.await? {
-            code_gens.push(async_module.code_generation(
-                chunking_context,
-                async_module_info,
-                references,
-            ));
-        }
-        for c in code_generation.await?.iter() {
-            match c {
-                CodeGen::CodeGenerateable(c) => {
-                    code_gens.push(c.code_generation(chunking_context));
-                }
-                CodeGen::CodeGenerateableWithAsyncModuleInfo(c) => {
-                    code_gens.push(c.code_generation(chunking_context, async_module_info));
-                }
-            }
-        }
-        if let EcmascriptExports::EsmExports(exports) = *exports.await? {
-            code_gens.push(exports.code_generation(chunking_context));
-        }
-
         // need to keep that around to allow references into that
-        let code_gens = code_gens.into_iter().try_join().await?;
+        let code_gens = module_code_gens(
+            chunking_context,
+            references,
+            code_generation,
+            async_module,
+            exports,
+            used_exports,
+            async_module_info,
+        )
+        .await?;
         let code_gens = code_gens.iter().map(|cg| &**cg).collect::<Vec<_>>();
 
         gen_content_with_code_gens(parsed, ident, specified_module_type, &code_gens, source_map)
@@ -784,6 +785,54 @@ impl EcmascriptModuleContent {
     }
 }
 
+/// Collects the code generations of a module's references, code
+/// generateables and exports. Only the `used_exports` are exported.
+async fn module_code_gens(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    references: Vc<ModuleReferences>,
+    code_generation: Vc<CodeGenerateables>,
+    async_module: Vc<OptionAsyncModule>,
+    exports: Vc<EcmascriptExports>,
+    used_exports: Vc<ModuleUsedExports>,
+    async_module_info: Option<Vc<AsyncModuleInfo>>,
+) -> Result<Vec<ReadRef<CodeGeneration>>> {
+    let mut code_gens = Vec::new();
+    for r in references.await?.iter() {
+        let r = r.resolve().await?;
+        if let Some(code_gen) =
+            Vc::try_resolve_sidecast::<Box<dyn CodeGenerateableWithAsyncModuleInfo>>(r).await?
+        {
+            code_gens.push(code_gen.code_generation(chunking_context, async_module_info));
+        } else if let Some(code_gen) =
+            Vc::try_resolve_sidecast::<Box<dyn CodeGenerateable>>(r).await?
+        {
+            code_gens.push(code_gen.code_generation(chunking_context));
+        }
+    }
+    if let Some(async_module) = *async_module.await? {
+        code_gens.push(async_module.code_generation(
+            chunking_context,
+            async_module_info,
+            references,
+        ));
+    }
+    for c in code_generation.await?.iter() {
+        match c {
+            CodeGen::CodeGenerateable(c) => {
+                code_gens.push(c.code_generation(chunking_context));
+            }
+            CodeGen::CodeGenerateableWithAsyncModuleInfo(c) => {
+                code_gens.push(c.code_generation(chunking_context, async_module_info));
+            }
+        }
+    }
+    if let EcmascriptExports::EsmExports(exports) = *exports.await? {
+        code_gens.push(exports.code_generation_for_used_exports(used_exports));
+    }
+
+    code_gens.into_iter().try_join().await
+}
+
 async fn gen_content_with_code_gens(
     parsed: Vc<ParseResult>,
     ident: Vc<AssetIdent>,
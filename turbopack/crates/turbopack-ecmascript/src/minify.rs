@@ -5,35 +5,138 @@ use swc_core::{
     base::{try_with_handler, Compiler},
     common::{
         comments::{Comments, SingleThreadedComments},
-        BytePos, FileName, FilePathMapping, LineCol, Mark, SourceMap as SwcSourceMap, GLOBALS,
+        BytePos, FileName, FilePathMapping, LineCol, Mark, SourceMap as SwcSourceMap, DUMMY_SP,
+        GLOBALS,
     },
     ecma::{
         self,
-        ast::{EsVersion, Program},
+        ast::{EsVersion, Expr, Ident, IdentName, MemberExpr, MemberProp, Program},
         codegen::{
             text_writer::{self, JsWriter, WriteJs},
             Emitter,
         },
-        minifier::option::{ExtraOptions, MangleOptions, MinifyOptions},
+        minifier::option::{
+            CompressOptions, ExtraOptions, MangleOptions, MinifyOptions as SwcMinifyOptions,
+        },
         parser::{lexer::Lexer, Parser, StringInput, Syntax},
         transforms::base::fixer::paren_remover,
+        visit::VisitMutWith,
     },
 };
-use turbo_tasks::Vc;
+use turbo_tasks::{RcStr, ResolvedVc, Vc};
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     code_builder::{Code, CodeBuilder},
     source_map::GenerateSourceMap,
 };
 
-use crate::ParseResultSourceMap;
+use crate::{
+    property_mangling::{PropertyNameCache, PropertyRenamer},
+    ParseResultSourceMap,
+};
+
+/// Options for minifying Ecmascript chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+pub struct MinifyOptions {
+    /// Keep the names of classes, e.g. for `constructor.name`.
+    pub keep_classnames: bool,
+    /// Keep the names of functions, e.g. for `Function.prototype.name`.
+    pub keep_fnames: bool,
+    /// Remove calls to `console.*`.
+    pub drop_console: bool,
+    /// Functions whose calls have no side effects, e.g. `console.info` or
+    /// `Math.floor`. Calls to them are removed when their result is unused.
+    pub pure_funcs: Vec<RcStr>,
+    /// The ECMAScript version the minified code may use, e.g. `5` or `2020`.
+    pub ecma: Option<u32>,
+    /// The maximum number of times the compressor runs.
+    pub passes: Option<usize>,
+    /// The mangled names of properties. Only properties that have a mangled
+    /// name are mangled.
+    pub mangled_properties: Option<ResolvedVc<PropertyNameCache>>,
+}
+
+impl MinifyOptions {
+    fn swc_options(&self) -> Result<SwcMinifyOptions> {
+        let ecma = match self.ecma {
+            None => None,
+            Some(5) => Some(EsVersion::Es5),
+            Some(2015 | 6) => Some(EsVersion::Es2015),
+            Some(2016 | 7) => Some(EsVersion::Es2016),
+            Some(2017 | 8) => Some(EsVersion::Es2017),
+            Some(2018 | 9) => Some(EsVersion::Es2018),
+            Some(2019 | 10) => Some(EsVersion::Es2019),
+            Some(2020 | 11) => Some(EsVersion::Es2020),
+            Some(2021 | 12) => Some(EsVersion::Es2021),
+            Some(2022 | 13) => Some(EsVersion::Es2022),
+            Some(ecma) => bail!("Unsupported ECMAScript version {ecma} for minification"),
+        };
+
+        let mut compress = CompressOptions {
+            keep_classnames: self.keep_classnames,
+            keep_fnames: self.keep_fnames,
+            drop_console: self.drop_console,
+            pure_funcs: self.pure_funcs.iter().map(|name| pure_func(name)).collect(),
+            ..Default::default()
+        };
+        if let Some(ecma) = ecma {
+            compress.ecma = ecma;
+        }
+        if let Some(passes) = self.passes {
+            compress.passes = passes;
+        }
+
+        Ok(SwcMinifyOptions {
+            compress: Some(compress),
+            mangle: Some(MangleOptions {
+                reserved: vec!["AbortSignal".into()],
+                keep_class_names: self.keep_classnames,
+                keep_fn_names: self.keep_fnames,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+/// Parses a function name like `console.info` into the expression the
+/// compressor compares callees with.
+fn pure_func(name: &str) -> Box<Expr> {
+    let mut segments = name.split('.');
+    let mut expr = Box::new(Expr::Ident(Ident::new(
+        segments.next().unwrap_or_default().into(),
+        DUMMY_SP,
+        Default::default(),
+    )));
+    for segment in segments {
+        expr = Box::new(Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: expr,
+            prop: MemberProp::Ident(IdentName::new(segment.into(), DUMMY_SP)),
+        }));
+    }
+    expr
+}
 
 #[turbo_tasks::function]
-pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>> {
+pub async fn minify(
+    path: Vc<FileSystemPath>,
+    code: Vc<Code>,
+    options: Vc<MinifyOptions>,
+) -> Result<Vc<Code>> {
     let path = path.await?;
+    let options = options.await?;
+    let minify_options = options.swc_options()?;
+    let mangled_properties = match options.mangled_properties {
+        Some(mangled_properties) => Some(mangled_properties.await?),
+        None => None,
+    };
     let original_map = code.generate_source_map();
     let code = code.await?;
 
+    let cancellation = turbo_tasks::cancellation_token();
+
     let cm = Arc::new(SwcSourceMap::new(FilePathMapping::empty()));
     let compiler = Arc::new(Compiler::new(cm.clone()));
     let fm = compiler.cm.new_source_file(
@@ -61,12 +164,23 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
                     )
                 }
             };
+            // Minification is the most expensive step and the result would be discarded.
+            if cancellation.is_cancelled() {
+                bail!("Minifying {} was cancelled", path.path);
+            }
+
             let comments = SingleThreadedComments::default();
             let unresolved_mark = Mark::new();
             let top_level_mark = Mark::new();
 
             Ok(compiler.run_transform(handler, false, || {
-                let program = program.apply(paren_remover(Some(&comments)));
+                let mut program = program.apply(paren_remover(Some(&comments)));
+
+                if let Some(mangled_properties) = &mangled_properties {
+                    program.visit_mut_with(&mut PropertyRenamer {
+                        names: &mangled_properties.props,
+                    });
+                }
 
                 let mut program = program.apply(swc_core::ecma::transforms::base::resolver(
                     unresolved_mark,
@@ -79,14 +193,7 @@ pub async fn minify(path: Vc<FileSystemPath>, code: Vc<Code>) -> Result<Vc<Code>
                     cm.clone(),
                     Some(&comments),
                     None,
-                    &MinifyOptions {
-                        compress: Some(Default::default()),
-                        mangle: Some(MangleOptions {
-                            reserved: vec!["AbortSignal".into()],
-                            ..Default::default()
-                        }),
-                        ..Default::default()
-                    },
+                    &minify_options,
                     &ExtraOptions {
                         top_level_mark,
                         unresolved_mark,
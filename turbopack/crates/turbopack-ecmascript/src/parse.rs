@@ -1,6 +1,6 @@
 use std::{future::Future, sync::Arc};
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use swc_core::{
     base::SwcComments,
     common::{
@@ -275,6 +275,7 @@ async fn parse_file_content(
     let parser_handler = Handler::with_emitter(true, false, emitter.clone());
     let globals = Arc::new(Globals::new());
     let globals_ref = &globals;
+    let cancellation = turbo_tasks::cancellation_token();
 
     let mut result = WrapFuture::new(
         async {
@@ -355,6 +356,11 @@ async fn parse_file_content(
                 }
             };
 
+            // The remaining passes are expensive on large files and the result would be discarded.
+            if cancellation.is_cancelled() {
+                bail!("Parsing {ident} was cancelled");
+            }
+
             let unresolved_mark = Mark::new();
             let top_level_mark = Mark::new();
 
@@ -387,6 +393,10 @@ async fn parse_file_content(
             parsed_program.mutate(swc_core::ecma::lints::rules::lint_to_fold(rules));
             drop(span);
 
+            if cancellation.is_cancelled() {
+                bail!("Parsing {ident} was cancelled");
+            }
+
             let transform_context = TransformContext {
                 comments: &comments,
                 source_map: &source_map,
@@ -0,0 +1,724 @@
+//! Property mangling for minified output.
+//!
+//! Properties matching a regex are renamed to short names. The names are
+//! assigned once for the whole module graph, so a property gets the same name
+//! in all chunks. A name cache file keeps the names of previous builds, which
+//! keeps mangled names stable across deploys.
+//!
+//! Only properties written as identifiers are mangled, e.g. `a.prop` or
+//! `{ prop: 1 }`. Properties that are also written as strings, e.g.
+//! `a["prop"]`, and the export names of ES modules are never mangled. Neither
+//! are the properties of objects exposed by other modules, e.g. the keys of
+//! JSON modules and the classes of CSS modules (see [ExposedProperties]), any
+//! property used by the runtime code, and the properties of JavaScript builtins
+//! and the DOM (see [RESERVED_PROPERTIES]).
+
+use std::{collections::BTreeSet, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    atoms::JsWord,
+    common::{util::take::Take, FileName, FilePathMapping, SourceMap},
+    ecma::{
+        ast::{
+            AssignPat, AssignPatProp, BinExpr, BinaryOp, EsVersion, Expr, IdentName, JSXAttrName,
+            KeyValuePatProp, KeyValueProp, Lit, MemberProp, ObjectPatProp, Pat, Prop, PropName,
+            SuperProp,
+        },
+        parser::{lexer::Lexer, Parser, StringInput, Syntax},
+        visit::{Visit, VisitMut, VisitMutWith, VisitWith},
+    },
+};
+use turbo_tasks::{Completion, FxIndexMap, FxIndexSet, RcStr, ResolvedVc, TryJoinIterExt, Vc};
+use turbo_tasks_fs::{File, FileContent, FileSystemPath};
+use turbopack_core::{
+    code_builder::Code,
+    module::{Module, Modules},
+    reference::all_modules_and_affecting_sources,
+};
+
+use crate::{chunk::EcmascriptExports, parse::ParseResult, EcmascriptModuleAsset};
+
+/// Properties of JavaScript builtins and the DOM, which are never mangled and
+/// never used as a mangled name. Like terser's `domprops`, but limited to the
+/// properties that are commonly accessed.
+const RESERVED_PROPERTIES: &[&str] = &[
+    // Object
+    "__proto__",
+    "__defineGetter__",
+    "__defineSetter__",
+    "__lookupGetter__",
+    "__lookupSetter__",
+    "constructor",
+    "hasOwnProperty",
+    "isPrototypeOf",
+    "propertyIsEnumerable",
+    "toLocaleString",
+    "toString",
+    "valueOf",
+    "toJSON",
+    "prototype",
+    // Functions and classes
+    "apply",
+    "arguments",
+    "bind",
+    "call",
+    "caller",
+    "length",
+    "name",
+    // Modules
+    "__esModule",
+    "default",
+    // Arrays, strings and collections
+    "at",
+    "concat",
+    "entries",
+    "every",
+    "fill",
+    "filter",
+    "find",
+    "findIndex",
+    "flat",
+    "flatMap",
+    "forEach",
+    "includes",
+    "indexOf",
+    "join",
+    "keys",
+    "lastIndexOf",
+    "map",
+    "pop",
+    "push",
+    "reduce",
+    "reverse",
+    "shift",
+    "slice",
+    "some",
+    "sort",
+    "splice",
+    "unshift",
+    "values",
+    "charAt",
+    "charCodeAt",
+    "endsWith",
+    "match",
+    "padEnd",
+    "padStart",
+    "replace",
+    "search",
+    "split",
+    "startsWith",
+    "substring",
+    "toLowerCase",
+    "toUpperCase",
+    "trim",
+    "add",
+    "clear",
+    "delete",
+    "get",
+    "has",
+    "set",
+    "size",
+    // Promises, iterators and errors
+    "catch",
+    "finally",
+    "then",
+    "done",
+    "next",
+    "return",
+    "throw",
+    "value",
+    "cause",
+    "message",
+    "stack",
+    // DOM
+    "addEventListener",
+    "removeEventListener",
+    "dispatchEvent",
+    "appendChild",
+    "removeChild",
+    "insertBefore",
+    "replaceChild",
+    "cloneNode",
+    "append",
+    "prepend",
+    "remove",
+    "before",
+    "after",
+    "attributes",
+    "getAttribute",
+    "setAttribute",
+    "removeAttribute",
+    "hasAttribute",
+    "children",
+    "childNodes",
+    "firstChild",
+    "lastChild",
+    "nextSibling",
+    "previousSibling",
+    "parentNode",
+    "parentElement",
+    "ownerDocument",
+    "nodeName",
+    "nodeType",
+    "nodeValue",
+    "tagName",
+    "id",
+    "className",
+    "classList",
+    "dataset",
+    "style",
+    "innerHTML",
+    "outerHTML",
+    "innerText",
+    "textContent",
+    "querySelector",
+    "querySelectorAll",
+    "getElementById",
+    "getElementsByClassName",
+    "getElementsByTagName",
+    "createElement",
+    "createTextNode",
+    "body",
+    "head",
+    "documentElement",
+    "focus",
+    "blur",
+    "click",
+    "contains",
+    "closest",
+    "getBoundingClientRect",
+    "offsetHeight",
+    "offsetWidth",
+    "scrollTop",
+    "scrollLeft",
+    "scrollIntoView",
+    "clientX",
+    "clientY",
+    "x",
+    "y",
+    "width",
+    "height",
+    "top",
+    "left",
+    "right",
+    "bottom",
+    "src",
+    "href",
+    "type",
+    "target",
+    "currentTarget",
+    "key",
+    "code",
+    "detail",
+    "checked",
+    "disabled",
+    "selected",
+    "preventDefault",
+    "stopPropagation",
+    "data",
+    "origin",
+    "postMessage",
+    "location",
+    "pathname",
+    "hash",
+];
+
+/// Options for mangling properties.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug)]
+pub struct ManglePropertiesOptions {
+    /// Only properties matching this regex are mangled.
+    pub regex: RcStr,
+    /// Properties that are never mangled.
+    pub reserved: Vec<RcStr>,
+    /// A JSON file with the mangled names of previous builds. Names in it are
+    /// reused for the same properties.
+    pub name_cache: Option<ResolvedVc<FileSystemPath>>,
+}
+
+/// The mangled names of properties, keyed by their original name.
+#[turbo_tasks::value]
+pub struct PropertyNameCache {
+    pub props: FxIndexMap<RcStr, RcStr>,
+}
+
+/// The contents of a name cache file.
+#[derive(Default, Serialize, Deserialize)]
+struct NameCacheFile {
+    #[serde(default)]
+    props: FxIndexMap<RcStr, RcStr>,
+}
+
+#[turbo_tasks::value_impl]
+impl PropertyNameCache {
+    /// Writes the names to a name cache file, to be used by the next build.
+    #[turbo_tasks::function]
+    pub async fn write(&self, path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+        let content = serde_json::to_string_pretty(&NameCacheFile {
+            props: self.props.clone(),
+        })?;
+        Ok(path.write(FileContent::Content(File::from(content)).cell()))
+    }
+}
+
+/// Names of properties that are never mangled.
+#[turbo_tasks::value(transparent)]
+pub struct PropertyNames(Vec<RcStr>);
+
+/// A module whose code isn't an Ecmascript program, but which exposes an
+/// object to Ecmascript modules, e.g. the parsed value of a JSON module.
+#[turbo_tasks::value_trait]
+pub trait ExposedProperties {
+    /// The names of the properties of the exposed object, including the
+    /// properties of nested objects.
+    fn exposed_property_names(self: Vc<Self>) -> Vc<PropertyNames>;
+}
+
+/// The property names in the code of a module.
+#[turbo_tasks::value]
+#[derive(Default)]
+struct ModulePropertyNames {
+    /// Properties written as identifiers, which can be mangled.
+    identifiers: Vec<RcStr>,
+    /// Properties written as strings and export names, which are kept.
+    quoted: Vec<RcStr>,
+}
+
+#[turbo_tasks::function]
+async fn module_property_names(
+    module: ResolvedVc<Box<dyn Module>>,
+) -> Result<Vc<ModulePropertyNames>> {
+    let Some(ecmascript) = ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        let Some(exposed) = ResolvedVc::try_sidecast::<Box<dyn ExposedProperties>>(module).await?
+        else {
+            return Ok(ModulePropertyNames::default().cell());
+        };
+        return Ok(ModulePropertyNames {
+            identifiers: vec![],
+            quoted: exposed.exposed_property_names().await?.clone_value(),
+        }
+        .cell());
+    };
+    let parsed = ecmascript.parse().await?;
+    let ParseResult::Ok { program, .. } = &*parsed else {
+        return Ok(ModulePropertyNames::default().cell());
+    };
+
+    let mut collector = PropertyNameCollector::default();
+    program.visit_with(&mut collector);
+
+    // Exports are accessed with strings on the namespace object.
+    if let EcmascriptExports::EsmExports(exports) = *ecmascript.get_exports().await? {
+        let exports = exports.await?;
+        collector.quoted.extend(
+            exports
+                .exports
+                .keys()
+                .map(|name| JsWord::from(name.as_str())),
+        );
+    }
+
+    Ok(ModulePropertyNames {
+        identifiers: collector
+            .identifiers
+            .iter()
+            .map(|name| name.as_str().into())
+            .collect(),
+        quoted: collector
+            .quoted
+            .iter()
+            .map(|name| name.as_str().into())
+            .collect(),
+    }
+    .cell())
+}
+
+/// The property names in `code`, all of which are kept. The runtime code isn't
+/// part of the module graph, but it's minified with the modules' code.
+#[turbo_tasks::function]
+async fn code_property_names(code: Vc<Code>) -> Result<Vc<ModulePropertyNames>> {
+    let code = code.await?;
+    let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+    let fm = cm.new_source_file(
+        FileName::Anon.into(),
+        code.source_code().to_str()?.to_string(),
+    );
+    let lexer = Lexer::new(
+        Syntax::default(),
+        EsVersion::latest(),
+        StringInput::from(&*fm),
+        None,
+    );
+    let program = Parser::new_from(lexer)
+        .parse_program()
+        .map_err(|err| anyhow!("Unable to parse the runtime code: {:?}", err.kind()))?;
+
+    let mut collector = PropertyNameCollector::default();
+    program.visit_with(&mut collector);
+    Ok(ModulePropertyNames {
+        identifiers: vec![],
+        quoted: collector
+            .identifiers
+            .iter()
+            .chain(collector.quoted.iter())
+            .map(|name| name.as_str().into())
+            .collect(),
+    }
+    .cell())
+}
+
+#[derive(Default)]
+struct PropertyNameCollector {
+    identifiers: FxIndexSet<JsWord>,
+    quoted: FxIndexSet<JsWord>,
+}
+
+impl Visit for PropertyNameCollector {
+    fn visit_member_prop(&mut self, prop: &MemberProp) {
+        match prop {
+            MemberProp::Ident(ident) => {
+                self.identifiers.insert(ident.sym.clone());
+            }
+            MemberProp::Computed(computed) => {
+                if let Expr::Lit(Lit::Str(str)) = &*computed.expr {
+                    self.quoted.insert(str.value.clone());
+                }
+            }
+            MemberProp::PrivateName(_) => {}
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_super_prop(&mut self, prop: &SuperProp) {
+        match prop {
+            SuperProp::Ident(ident) => {
+                self.identifiers.insert(ident.sym.clone());
+            }
+            SuperProp::Computed(computed) => {
+                if let Expr::Lit(Lit::Str(str)) = &*computed.expr {
+                    self.quoted.insert(str.value.clone());
+                }
+            }
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_prop_name(&mut self, name: &PropName) {
+        match name {
+            PropName::Ident(ident) => {
+                self.identifiers.insert(ident.sym.clone());
+            }
+            PropName::Str(str) => {
+                self.quoted.insert(str.value.clone());
+            }
+            _ => {}
+        }
+        name.visit_children_with(self);
+    }
+
+    fn visit_prop(&mut self, prop: &Prop) {
+        if let Prop::Shorthand(ident) = prop {
+            self.identifiers.insert(ident.sym.clone());
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_object_pat_prop(&mut self, prop: &ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { key, .. }) = prop {
+            self.identifiers.insert(key.sym.clone());
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_bin_expr(&mut self, expr: &BinExpr) {
+        // `"prop" in object`
+        if expr.op == BinaryOp::In {
+            if let Expr::Lit(Lit::Str(str)) = &*expr.left {
+                self.quoted.insert(str.value.clone());
+            }
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_jsx_attr_name(&mut self, name: &JSXAttrName) {
+        // JSX attributes become the keys of the props object.
+        if let JSXAttrName::Ident(ident) = name {
+            self.quoted.insert(ident.sym.clone());
+        }
+        name.visit_children_with(self);
+    }
+}
+
+/// Assigns mangled names to the properties of the module graph of `entries`
+/// that match the options. The properties of `runtime_code`, which is minified
+/// with the chunks, are kept.
+#[turbo_tasks::function]
+pub async fn property_name_cache(
+    entries: Vc<Modules>,
+    runtime_code: Vc<Code>,
+    options: Vc<ManglePropertiesOptions>,
+) -> Result<Vc<PropertyNameCache>> {
+    let options = options.await?;
+    let regex = Regex::new(&options.regex)
+        .with_context(|| format!("Invalid property mangling regex {}", options.regex))?;
+
+    let previous = match options.name_cache {
+        Some(path) => match &*path.read().await? {
+            FileContent::Content(file) => {
+                serde_json::from_str::<NameCacheFile>(&file.content().to_str()?)
+                    .context("Unable to parse the property name cache")?
+            }
+            FileContent::NotFound => NameCacheFile::default(),
+        },
+        None => NameCacheFile::default(),
+    };
+
+    let mut modules = FxIndexSet::default();
+    for &entry in entries.await?.iter() {
+        modules.extend(
+            all_modules_and_affecting_sources(*entry)
+                .await?
+                .iter()
+                .copied(),
+        );
+    }
+    let mut names = modules
+        .iter()
+        .map(|&module| module_property_names(*module))
+        .try_join()
+        .await?;
+    names.push(code_property_names(runtime_code).await?);
+
+    let mut candidates = BTreeSet::new();
+    let mut kept = reserved_properties(&options.reserved);
+    for names in &names {
+        for name in &names.identifiers {
+            if regex.is_match(name) {
+                candidates.insert(name.clone());
+            } else {
+                kept.insert(name.clone());
+            }
+        }
+        kept.extend(names.quoted.iter().cloned());
+    }
+    candidates.retain(|name| !kept.contains(name));
+
+    Ok(PropertyNameCache {
+        props: assign_mangled_names(&previous.props, &candidates, &kept),
+    }
+    .cell())
+}
+
+/// Returns the properties that are never mangled: the builtin
+/// [RESERVED_PROPERTIES] and the `reserved` ones of the options.
+fn reserved_properties(reserved: &[RcStr]) -> FxHashSet<RcStr> {
+    RESERVED_PROPERTIES
+        .iter()
+        .map(|&name| RcStr::from(name))
+        .chain(reserved.iter().cloned())
+        .collect()
+}
+
+/// Assigns a mangled name to each of the `candidates`, reusing the names of
+/// `previous`. Mangled names never collide with the `kept` properties.
+fn assign_mangled_names(
+    previous: &FxIndexMap<RcStr, RcStr>,
+    candidates: &BTreeSet<RcStr>,
+    kept: &FxHashSet<RcStr>,
+) -> FxIndexMap<RcStr, RcStr> {
+    let mut props = FxIndexMap::default();
+    let mut taken = kept.clone();
+    // Names of properties that no longer exist are kept too, so they get the same
+    // name when they're added back.
+    for (name, mangled) in previous {
+        if !kept.contains(name) && taken.insert(mangled.clone()) {
+            props.insert(name.clone(), mangled.clone());
+        }
+    }
+
+    let mut mangled_names = (0..).map(mangled_name);
+    for name in candidates {
+        if props.contains_key(name) {
+            continue;
+        }
+        let mangled = mangled_names
+            .find(|mangled| !taken.contains(mangled))
+            .unwrap();
+        taken.insert(mangled.clone());
+        props.insert(name.clone(), mangled);
+    }
+    props
+}
+
+/// Returns the `index`th shortest property name.
+fn mangled_name(mut index: usize) -> RcStr {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ$_";
+    const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ$_0123456789";
+
+    let mut name = String::new();
+    name.push(FIRST[index % FIRST.len()] as char);
+    index /= FIRST.len();
+    while index > 0 {
+        index -= 1;
+        name.push(REST[index % REST.len()] as char);
+        index /= REST.len();
+    }
+    name.into()
+}
+
+/// Renames the properties written as identifiers to their mangled names.
+pub(crate) struct PropertyRenamer<'a> {
+    pub names: &'a FxIndexMap<RcStr, RcStr>,
+}
+
+impl PropertyRenamer<'_> {
+    fn rename(&self, name: &JsWord) -> Option<JsWord> {
+        self.names
+            .get(name.as_str())
+            .map(|mangled| mangled.as_str().into())
+    }
+}
+
+impl VisitMut for PropertyRenamer<'_> {
+    fn visit_mut_member_prop(&mut self, prop: &mut MemberProp) {
+        if let MemberProp::Ident(ident) = prop {
+            if let Some(mangled) = self.rename(&ident.sym) {
+                ident.sym = mangled;
+            }
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_super_prop(&mut self, prop: &mut SuperProp) {
+        if let SuperProp::Ident(ident) = prop {
+            if let Some(mangled) = self.rename(&ident.sym) {
+                ident.sym = mangled;
+            }
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop_name(&mut self, name: &mut PropName) {
+        if let PropName::Ident(ident) = name {
+            if let Some(mangled) = self.rename(&ident.sym) {
+                ident.sym = mangled;
+            }
+        }
+        name.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_prop(&mut self, prop: &mut Prop) {
+        if let Prop::Shorthand(ident) = prop {
+            if let Some(mangled) = self.rename(&ident.sym) {
+                *prop = Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(IdentName::new(mangled, ident.span)),
+                    value: Box::new(Expr::Ident(ident.clone())),
+                });
+                return;
+            }
+        }
+        prop.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_object_pat_prop(&mut self, prop: &mut ObjectPatProp) {
+        if let ObjectPatProp::Assign(AssignPatProp { key, value, span }) = prop {
+            if let Some(mangled) = self.rename(&key.sym) {
+                let binding = Pat::Ident(key.clone().into());
+                let mut value = value.take();
+                value.visit_mut_with(self);
+                *prop = ObjectPatProp::KeyValue(KeyValuePatProp {
+                    key: PropName::Ident(IdentName::new(mangled, key.span)),
+                    value: Box::new(match value {
+                        Some(default) => Pat::Assign(AssignPat {
+                            span: *span,
+                            left: Box::new(binding),
+                            right: default,
+                        }),
+                        None => binding,
+                    }),
+                });
+                return;
+            }
+        }
+        prop.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rustc_hash::FxHashSet;
+    use turbo_tasks::{FxIndexMap, RcStr};
+
+    use super::{assign_mangled_names, mangled_name, reserved_properties};
+
+    fn names(names: &[&str]) -> Vec<RcStr> {
+        names.iter().map(|&name| name.into()).collect()
+    }
+
+    #[test]
+    fn test_mangled_names_are_unique() {
+        let names = (0..10_000).map(mangled_name).collect::<FxHashSet<_>>();
+        assert_eq!(names.len(), 10_000);
+        assert_eq!(mangled_name(0), "a");
+        assert_eq!(mangled_name(54), "aa");
+    }
+
+    #[test]
+    fn test_reuses_cached_names() {
+        let previous = FxIndexMap::from_iter([
+            ("_removed".into(), "a".into()),
+            ("_second".into(), "b".into()),
+        ]);
+        let candidates = BTreeSet::from_iter(names(&["_first", "_second", "_third"]));
+        let kept = FxHashSet::from_iter(names(&["c"]));
+        let props = assign_mangled_names(&previous, &candidates, &kept);
+        assert_eq!(
+            props.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("_removed".into(), "a".into()),
+                ("_second".into(), "b".into()),
+                ("_first".into(), "d".into()),
+                ("_third".into(), "e".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drops_cached_names_that_collide() {
+        // `_a` is quoted now, and `b` became an unmangled property.
+        let previous =
+            FxIndexMap::from_iter([("_a".into(), "a".into()), ("_b".into(), "b".into())]);
+        let candidates = BTreeSet::from_iter(names(&["_b"]));
+        let kept = FxHashSet::from_iter(names(&["_a", "b"]));
+        let props = assign_mangled_names(&previous, &candidates, &kept);
+        assert_eq!(
+            props.into_iter().collect::<Vec<_>>(),
+            vec![("_b".into(), "a".into())]
+        );
+    }
+
+    #[test]
+    fn test_keeps_builtin_properties() {
+        let kept = reserved_properties(&names(&["_keep"]));
+        for name in ["__proto__", "__defineGetter__", "prototype", "id", "_keep"] {
+            assert!(kept.contains(name), "{name} is not reserved");
+        }
+
+        // Mangled names don't shadow builtin properties, e.g. `x` or `id`.
+        let candidates = (0..1_000)
+            .map(|index| format!("_prop{index}").into())
+            .collect::<BTreeSet<_>>();
+        let props = assign_mangled_names(&FxIndexMap::default(), &candidates, &kept);
+        assert_eq!(props.len(), 1_000);
+        for mangled in props.values() {
+            assert!(!kept.contains(mangled), "{mangled} is reserved");
+        }
+    }
+}
@@ -1,5 +1,14 @@
-use anyhow::Result;
-use swc_core::quote;
+use anyhow::{anyhow, bail, Result};
+use swc_core::{
+    common::{BytePos, DUMMY_SP},
+    ecma::{
+        ast::{EsVersion, Expr, ExprStmt, ParenExpr, Stmt},
+        parser::{lexer::Lexer, Parser, StringInput, Syntax},
+        utils::DropSpan,
+        visit::VisitMutWith,
+    },
+    quote,
+};
 use turbo_tasks::{Value, Vc};
 use turbopack_core::{chunk::ChunkingContext, compile_time_info::CompileTimeDefineValue};
 
@@ -33,18 +42,103 @@ impl CodeGenerateable for ConstantValue {
         &self,
         _context: Vc<Box<dyn ChunkingContext>>,
     ) -> Result<Vc<CodeGeneration>> {
-        let value = self.value.clone();
+        let value = match &self.value {
+            CompileTimeDefineValue::Bool(true) => {
+                quote!("(\"TURBOPACK compile-time value\", true)" as Expr)
+            }
+            CompileTimeDefineValue::Bool(false) => {
+                quote!("(\"TURBOPACK compile-time value\", false)" as Expr)
+            }
+            CompileTimeDefineValue::String(s) => {
+                quote!("(\"TURBOPACK compile-time value\", $e)" as Expr, e: Expr = s.to_string().into())
+            }
+            CompileTimeDefineValue::JSON(s) => {
+                quote!("(\"TURBOPACK compile-time value\", JSON.parse($e))" as Expr, e: Expr = s.to_string().into())
+            }
+            // Not wrapped in a sequence expression, so that calling a member
+            // expression keeps its `this`.
+            CompileTimeDefineValue::Expression(s) => Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(parse_expression(s)?),
+            }),
+        };
         let path = &self.path.await?;
 
         let visitor = create_visitor!(path, visit_mut_expr(expr: &mut Expr) {
-            *expr = match value {
-                CompileTimeDefineValue::Bool(true) => quote!("(\"TURBOPACK compile-time value\", true)" as Expr),
-                CompileTimeDefineValue::Bool(false) => quote!("(\"TURBOPACK compile-time value\", false)" as Expr),
-                CompileTimeDefineValue::String(ref s) => quote!("(\"TURBOPACK compile-time value\", $e)" as Expr, e: Expr = s.to_string().into()),
-                CompileTimeDefineValue::JSON(ref s) => quote!("(\"TURBOPACK compile-time value\", JSON.parse($e))" as Expr, e: Expr = s.to_string().into()),
-            };
+            *expr = value.clone();
         });
 
         Ok(CodeGeneration::visitors(vec![visitor]))
     }
 }
+
+/// Parses the expression of a [CompileTimeDefineValue::Expression]. It's
+/// parsed in parentheses, so that e.g. an object literal isn't a block.
+pub fn parse_expression(expression: &str) -> Result<Expr> {
+    let source = format!("({expression})");
+    let lexer = Lexer::new(
+        Syntax::Es(Default::default()),
+        EsVersion::latest(),
+        StringInput::new(&source, BytePos(1), BytePos(1 + source.len() as u32)),
+        None,
+    );
+    let mut parser = Parser::new_from(lexer);
+    let script = parser.parse_script().map_err(|err| {
+        anyhow!(
+            "Failed to parse the compile-time define `{expression}`: {}",
+            err.kind().msg()
+        )
+    })?;
+    if !parser.take_errors().is_empty() {
+        bail!("Failed to parse the compile-time define `{expression}`");
+    }
+
+    let mut body = script.body;
+    match (body.pop(), body.is_empty()) {
+        (Some(Stmt::Expr(ExprStmt { expr, .. })), true) => match *expr {
+            Expr::Paren(ParenExpr { mut expr, .. }) => {
+                // The spans point into the define, not into the module.
+                expr.visit_mut_with(&mut DropSpan);
+                Ok(*expr)
+            }
+            _ => bail!("The compile-time define `{expression}` is not a single expression"),
+        },
+        _ => bail!("The compile-time define `{expression}` is not a single expression"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::ecma::ast::{Expr, Lit};
+
+    use super::parse_expression;
+
+    #[test]
+    fn test_parse_expression() {
+        assert!(matches!(
+            parse_expression("globalThis.app").unwrap(),
+            Expr::Member(_)
+        ));
+        assert!(matches!(
+            parse_expression("1").unwrap(),
+            Expr::Lit(Lit::Num(_))
+        ));
+        // An object literal, not a block.
+        let Expr::Object(object) = parse_expression("{ a: 1, b: [2] }").unwrap() else {
+            panic!("not an object literal");
+        };
+        assert_eq!(object.props.len(), 2);
+        // A sequence is a single expression.
+        assert!(matches!(parse_expression("a, b").unwrap(), Expr::Seq(_)));
+    }
+
+    #[test]
+    fn test_parse_expression_errors() {
+        for expression in ["", "a +", "a b", "a) + (b", "a); (b", "let a = 1"] {
+            assert!(
+                parse_expression(expression).is_err(),
+                "`{expression}` is not an expression"
+            );
+        }
+    }
+}
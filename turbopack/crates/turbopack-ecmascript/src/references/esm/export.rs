@@ -5,12 +5,15 @@ use std::{
 };
 
 use anyhow::Result;
+use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
 use swc_core::{
+    atoms::JsWord,
     common::DUMMY_SP,
     ecma::ast::{
         AssignTarget, ComputedPropName, Expr, ExprStmt, Ident, KeyValueProp, Lit, MemberExpr,
-        MemberProp, ObjectLit, Prop, PropName, PropOrSpread, SimpleAssignTarget, Stmt, Str,
+        MemberProp, ObjectLit, Program, Prop, PropName, PropOrSpread, SimpleAssignTarget, Stmt,
+        Str,
     },
     quote, quote_expr,
 };
@@ -19,7 +22,7 @@ use turbo_tasks::{
 };
 use turbo_tasks_fs::glob::Glob;
 use turbopack_core::{
-    chunk::ChunkingContext,
+    chunk::{used_exports::ModuleUsedExports, ChunkingContext},
     ident::AssetIdent,
     issue::{analyze::AnalyzeIssue, IssueExt, IssueSeverity, StyledString},
     module::Module,
@@ -30,7 +33,8 @@ use super::base::ReferencedAsset;
 use crate::{
     chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
     code_gen::{CodeGenerateable, CodeGeneration, CodeGenerationHoistedStmt},
-    magic_identifier,
+    create_visitor, magic_identifier,
+    used_exports::remove_unused_declarations,
 };
 
 #[derive(Clone, Hash, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
@@ -471,13 +475,16 @@ impl EsmExports {
 }
 
 #[turbo_tasks::value_impl]
-impl CodeGenerateable for EsmExports {
+impl EsmExports {
+    /// Generates the code for the exports in `used_exports`. Unused exports
+    /// are omitted, and so are the top level declarations only they reference.
     #[turbo_tasks::function]
-    async fn code_generation(
+    pub async fn code_generation_for_used_exports(
         self: Vc<Self>,
-        _context: Vc<Box<dyn ChunkingContext>>,
+        used_exports: Vc<ModuleUsedExports>,
     ) -> Result<Vc<CodeGeneration>> {
         let expanded = self.expand_exports().await?;
+        let used_exports = used_exports.await?;
 
         let mut dynamic_exports = Vec::<Box<Expr>>::new();
         for dynamic_export_asset in &expanded.dynamic_exports {
@@ -490,7 +497,14 @@ impl CodeGenerateable for EsmExports {
         }
 
         let mut props = Vec::new();
+        let mut roots = FxHashSet::default();
         for (exported, local) in &expanded.exports {
+            if !used_exports.is_used(exported) {
+                continue;
+            }
+            if let EsmExport::LocalBinding(name, _) = local {
+                roots.insert(JsWord::from(name.as_str()));
+            }
             let expr = match local {
                 EsmExport::Error => Some(quote!(
                     "(() => { throw new Error(\"Failed binding. See build errors!\"); })" as Expr,
@@ -592,8 +606,17 @@ impl CodeGenerateable for EsmExports {
             None
         };
 
+        let visitors = match &*used_exports {
+            ModuleUsedExports::All => vec![],
+            ModuleUsedExports::Only(_) => {
+                vec![create_visitor!(visit_mut_program(program: &mut Program) {
+                    remove_unused_declarations(program, &roots);
+                })]
+            }
+        };
+
         Ok(CodeGeneration::new(
-            vec![],
+            visitors,
             [dynamic_stmt
                 .clone()
                 .map(|stmt| CodeGenerationHoistedStmt::new("__turbopack_dynamic__".into(), stmt))]
@@ -609,3 +632,14 @@ impl CodeGenerateable for EsmExports {
         ))
     }
 }
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for EsmExports {
+    #[turbo_tasks::function]
+    fn code_generation(
+        self: Vc<Self>,
+        _context: Vc<Box<dyn ChunkingContext>>,
+    ) -> Vc<CodeGeneration> {
+        self.code_generation_for_used_exports(ModuleUsedExports::All.cell())
+    }
+}
@@ -56,14 +56,14 @@ use turbo_tasks::{
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     compile_time_info::{
-        CompileTimeInfo, DefineableNameSegment, FreeVarReference, FreeVarReferences,
+        CompileTimeInfo, DefineableNameSegment, FreeVarReference, FreeVarReferences, PublicEnvVars,
     },
     environment::Rendering,
     error::PrettyPrintError,
     issue::{analyze::AnalyzeIssue, IssueExt, IssueSeverity, IssueSource, StyledString},
     module::Module,
     reference::{ModuleReference, ModuleReferences, SourceMapReference},
-    reference_type::{CommonJsReferenceSubType, ReferenceType},
+    reference_type::{CommonJsReferenceSubType, ReferenceType, WorkerReferenceSubType},
     resolve::{
         find_context_file,
         origin::{PlainResolveOrigin, ResolveOrigin, ResolveOriginExt},
@@ -1273,6 +1273,7 @@ async fn compile_time_info_for_module_type(
         environment: compile_time_info.environment,
         defines: compile_time_info.defines,
         free_var_references: FreeVarReferences(free_var_references).cell(),
+        public_env_vars: compile_time_info.public_env_vars,
     }
     .cell())
 }
@@ -1364,15 +1365,24 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                 }
                 return Ok(());
             }
-            JsValue::WellKnownFunction(WellKnownFunctionKind::WorkerConstructor) => {
+            JsValue::WellKnownFunction(
+                kind @ (WellKnownFunctionKind::WorkerConstructor
+                | WellKnownFunctionKind::SharedWorkerConstructor),
+            ) => {
+                let (constructor, worker_type) = match kind {
+                    WellKnownFunctionKind::SharedWorkerConstructor => {
+                        ("SharedWorker", WorkerReferenceSubType::SharedWorker)
+                    }
+                    _ => ("Worker", WorkerReferenceSubType::WebWorker),
+                };
                 let args = linked_args(args).await?;
-                if let [url @ JsValue::Url(_, JsValueUrlKind::Relative)] = &args[..] {
+                if let [url @ JsValue::Url(_, JsValueUrlKind::Relative), ..] = &args[..] {
                     let pat = js_value_to_pattern(url);
                     if !pat.has_constant_parts() {
                         let (args, hints) = explain_args(&args);
                         handler.span_warn_with_code(
                             span,
-                            &format!("new Worker({args}) is very dynamic{hints}",),
+                            &format!("new {constructor}({args}) is very dynamic{hints}",),
                             DiagnosticId::Lint(
                                 errors::failed_to_analyse::ecmascript::NEW_WORKER.to_string(),
                             ),
@@ -1386,6 +1396,8 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                         analysis.add_reference(WorkerAssetReference::new(
                             origin,
                             Request::parse(Value::new(pat)),
+                            Value::new(worker_type),
+                            None,
                             Vc::cell(ast_path.to_vec()),
                             issue_source(source, span),
                             in_try,
@@ -1397,7 +1409,7 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                 let (args, hints) = explain_args(&args);
                 handler.span_warn_with_code(
                     span,
-                    &format!("new Worker({args}) is not statically analyse-able{hints}",),
+                    &format!("new {constructor}({args}) is not statically analyse-able{hints}",),
                     DiagnosticId::Error(
                         errors::failed_to_analyse::ecmascript::DYNAMIC_IMPORT.to_string(),
                     ),
@@ -1437,6 +1449,54 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                 .await?;
             }
         }
+        JsValue::WellKnownFunction(WellKnownFunctionKind::ServiceWorkerRegister) => {
+            let args = linked_args(args).await?;
+            if let [url @ JsValue::Url(_, JsValueUrlKind::Relative), ..] = &args[..] {
+                let pat = js_value_to_pattern(url);
+                if !pat.has_constant_parts() {
+                    let (args, hints) = explain_args(&args);
+                    handler.span_warn_with_code(
+                        span,
+                        &format!("navigator.serviceWorker.register({args}) is very dynamic{hints}",),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::SERVICE_WORKER_REGISTER
+                                .to_string(),
+                        ),
+                    );
+                    if ignore_dynamic_requests {
+                        return Ok(());
+                    }
+                }
+
+                // The requested scope is checked against the location of the emitted worker,
+                // the options are passed to the browser unchanged.
+                let scope = match args.get(1) {
+                    Some(JsValue::Object { parts, .. }) => parts.iter().find_map(|part| {
+                        if let ObjectPart::KeyValue(key, value) = part {
+                            if key.as_str() == Some("scope") {
+                                return value.as_str().map(RcStr::from);
+                            }
+                        }
+                        None
+                    }),
+                    _ => None,
+                };
+
+                if *compile_time_info.environment().rendering().await? == Rendering::Client {
+                    analysis.add_reference(WorkerAssetReference::new(
+                        origin,
+                        Request::parse(Value::new(pat)),
+                        Value::new(WorkerReferenceSubType::ServiceWorker),
+                        scope,
+                        Vc::cell(ast_path.to_vec()),
+                        issue_source(source, span),
+                        in_try,
+                    ));
+                }
+            }
+            // `navigator.serviceWorker.register("/sw.js")` with a plain string refers to an
+            // already deployed script and is left untouched.
+        }
         JsValue::WellKnownFunction(WellKnownFunctionKind::Import) => {
             let args = linked_args(args).await?;
             if args.len() == 1 {
@@ -2090,6 +2150,9 @@ async fn handle_member(
                     return Ok(());
                 }
             }
+            if let Some(public_env_vars) = compile_time_info.public_env_vars {
+                check_public_env_var(ast_path, &obj, &prop, span, public_env_vars, state).await?;
+            }
         }
     }
     match (obj, prop) {
@@ -2161,6 +2224,63 @@ async fn handle_free_var(
     Ok(())
 }
 
+fn is_assign_target(ast_path: &[AstParentKind]) -> bool {
+    matches!(
+        ast_path,
+        [
+            ..,
+            AstParentKind::AssignExpr(AssignExprField::Left),
+            AstParentKind::AssignTarget(AssignTargetField::Simple),
+            AstParentKind::SimpleAssignTarget(SimpleAssignTargetField::Member),
+        ]
+    )
+}
+
+/// Reports reading `process.env.X` when `X` isn't one of the
+/// `public_env_vars`, as it would be `undefined`. Packages often read env
+/// variables that are optional, so reads in `node_modules` are only warnings.
+async fn check_public_env_var(
+    ast_path: &[AstParentKind],
+    obj: &JsValue,
+    prop: &DefineableNameSegment,
+    span: Span,
+    public_env_vars: Vc<PublicEnvVars>,
+    state: &AnalysisState<'_>,
+) -> Result<()> {
+    let DefineableNameSegment::Name(name) = prop else {
+        return Ok(());
+    };
+    let process_env = [
+        DefineableNameSegment::from("env"),
+        DefineableNameSegment::from("process"),
+    ];
+    if is_assign_target(ast_path)
+        || !obj
+            .iter_defineable_name_rev()
+            .eq(process_env.iter().map(Cow::Borrowed))
+        || public_env_vars.await?.contains(name)
+    {
+        return Ok(());
+    }
+    let message = format!(
+        "`process.env.{name}` is not a public environment variable, so it is `undefined` here. \
+         Mark it as `public` in the env schema to inline it."
+    );
+    let code =
+        DiagnosticId::Error(errors::failed_to_analyse::ecmascript::PRIVATE_ENV_VAR.to_string());
+    let path = state.source.ident().path().await?;
+    if path
+        .path
+        .split('/')
+        .any(|segment| segment == "node_modules")
+    {
+        state.handler.span_warn_with_code(span, &message, code);
+    } else {
+        state.handler.span_err_with_code(span, &message, code);
+    }
+    Ok(())
+}
+
 async fn handle_free_var_reference(
     ast_path: &[AstParentKind],
     value: &FreeVarReference,
@@ -2169,15 +2289,7 @@ async fn handle_free_var_reference(
     analysis: &mut AnalyzeEcmascriptModuleResultBuilder,
 ) -> Result<bool> {
     // We don't want to replace assignments as this would lead to invalid code.
-    if matches!(
-        ast_path,
-        [
-            ..,
-            AstParentKind::AssignExpr(AssignExprField::Left),
-            AstParentKind::AssignTarget(AssignTargetField::Simple),
-            AstParentKind::SimpleAssignTarget(SimpleAssignTargetField::Member),
-        ]
-    ) {
+    if is_assign_target(ast_path) {
         return Ok(false);
     }
 
@@ -2545,6 +2657,13 @@ async fn value_visitor_inner(
                 true,
                 "ignored Worker constructor",
             ),
+            "SharedWorker" => JsValue::unknown_if(
+                ignore,
+                JsValue::WellKnownFunction(WellKnownFunctionKind::SharedWorkerConstructor),
+                true,
+                "ignored SharedWorker constructor",
+            ),
+            "navigator" => JsValue::WellKnownObject(WellKnownObjectKind::Navigator),
             "define" => JsValue::WellKnownFunction(WellKnownFunctionKind::Define),
             "URL" => JsValue::WellKnownFunction(WellKnownFunctionKind::URLConstructor),
             "process" => JsValue::WellKnownObject(WellKnownObjectKind::NodeProcess),
@@ -1,6 +1,6 @@
 use anyhow::{bail, Result};
 use swc_core::{
-    ecma::ast::{Expr, ExprOrSpread, Lit, NewExpr},
+    ecma::ast::{CallExpr, Expr, ExprOrSpread, Lit, NewExpr},
     quote_expr,
 };
 use turbo_tasks::{RcStr, ResolvedVc, Value, ValueToString, Vc};
@@ -25,6 +25,9 @@ use crate::{
 pub struct WorkerAssetReference {
     pub origin: Vc<Box<dyn ResolveOrigin>>,
     pub request: Vc<Request>,
+    pub worker_type: WorkerReferenceSubType,
+    /// The `scope` option of a service worker registration, if it's a constant.
+    pub scope: Option<RcStr>,
     pub path: Vc<AstPath>,
     pub issue_source: Vc<IssueSource>,
     pub in_try: bool,
@@ -36,6 +39,8 @@ impl WorkerAssetReference {
     pub fn new(
         origin: Vc<Box<dyn ResolveOrigin>>,
         request: Vc<Request>,
+        worker_type: Value<WorkerReferenceSubType>,
+        scope: Option<RcStr>,
         path: Vc<AstPath>,
         issue_source: Vc<IssueSource>,
         in_try: bool,
@@ -43,6 +48,8 @@ impl WorkerAssetReference {
         Self::cell(WorkerAssetReference {
             origin,
             request,
+            worker_type: worker_type.into_value(),
+            scope,
             path,
             issue_source,
             in_try,
@@ -57,8 +64,7 @@ impl WorkerAssetReference {
         let module = url_resolve(
             self.origin,
             self.request,
-            // TODO support more worker types
-            Value::new(ReferenceType::Worker(WorkerReferenceSubType::WebWorker)),
+            Value::new(ReferenceType::Worker(self.worker_type.clone())),
             Some(self.issue_source),
             self.in_try,
         );
@@ -79,7 +85,11 @@ impl WorkerAssetReference {
             return Ok(None);
         };
 
-        Ok(Some(WorkerLoaderModule::new(*chunkable)))
+        Ok(Some(WorkerLoaderModule::new(
+            *chunkable,
+            Value::new(self.worker_type.clone()),
+            self.scope.clone(),
+        )))
     }
 }
 
@@ -103,7 +113,12 @@ impl ValueToString for WorkerAssetReference {
     #[turbo_tasks::function]
     async fn to_string(&self) -> Result<Vc<RcStr>> {
         Ok(Vc::cell(
-            format!("new Worker {}", self.request.to_string().await?,).into(),
+            format!(
+                "{} {}",
+                worker_expression_name(&self.worker_type),
+                self.request.to_string().await?,
+            )
+            .into(),
         ))
     }
 }
@@ -128,31 +143,29 @@ impl CodeGenerateable for WorkerAssetReference {
 
         let path = &self.path.await?;
 
+        let name = worker_expression_name(&self.worker_type);
         let visitor = create_visitor!(path, visit_mut_expr(expr: &mut Expr) {
-            let message = if let Expr::New(NewExpr { args, ..}) = expr {
-                if let Some(args) = args {
-                    match args.iter_mut().next() {
-                        Some(ExprOrSpread { spread: None, expr }) => {
-                            let item_id = Expr::Lit(Lit::Str(item_id.to_string().into()));
-                            *expr = quote_expr!(
-                                "__turbopack_require__($item_id)",
-                                item_id: Expr = item_id
-                            );
-                            return;
-                        }
-                        // These are SWC bugs: https://github.com/swc-project/swc/issues/5394
-                        Some(ExprOrSpread { spread: Some(_), expr: _ }) => {
-                            "spread operator is illegal in new Worker() expressions."
-                        }
-                        _ => {
-                            "new Worker() expressions require at least 1 argument"
-                        }
+            let message = match expr {
+                Expr::New(NewExpr { args: Some(args), .. })
+                | Expr::Call(CallExpr { args, .. }) => match args.iter_mut().next() {
+                    Some(ExprOrSpread { spread: None, expr }) => {
+                        let item_id = Expr::Lit(Lit::Str(item_id.to_string().into()));
+                        *expr = quote_expr!(
+                            "__turbopack_require__($item_id)",
+                            item_id: Expr = item_id
+                        );
+                        return;
                     }
-                } else {
-                    "new Worker() expressions require at least 1 argument"
+                    // These are SWC bugs: https://github.com/swc-project/swc/issues/5394
+                    Some(ExprOrSpread { spread: Some(_), expr: _ }) => {
+                        format!("spread operator is illegal in {name}() expressions.")
+                    }
+                    _ => format!("{name}() expressions require at least 1 argument"),
+                },
+                Expr::New(NewExpr { args: None, .. }) => {
+                    format!("{name}() expressions require at least 1 argument")
                 }
-            } else {
-                "visitor must be executed on a NewExpr"
+                _ => "visitor must be executed on a NewExpr or CallExpr".to_string(),
             };
             *expr = *quote_expr!(
                 "(() => { throw new Error($message); })()",
@@ -163,3 +176,13 @@ impl CodeGenerateable for WorkerAssetReference {
         Ok(CodeGeneration::visitors(vec![visitor]))
     }
 }
+
+/// The source expression a worker reference was created from, used in descriptions and error
+/// messages.
+fn worker_expression_name(worker_type: &WorkerReferenceSubType) -> &'static str {
+    match worker_type {
+        WorkerReferenceSubType::SharedWorker => "new SharedWorker",
+        WorkerReferenceSubType::ServiceWorker => "navigator.serviceWorker.register",
+        _ => "new Worker",
+    }
+}
@@ -58,6 +58,9 @@ impl EcmascriptChunkItem for EcmascriptModuleLocalsChunkItem {
             analyze_result.async_module,
             analyze_result.source_map,
             exports,
+            chunking_context
+                .used_exports()
+                .module_used_exports(Vc::upcast(self.module)),
             async_module_info,
         );
 
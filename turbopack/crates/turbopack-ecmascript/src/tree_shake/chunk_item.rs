@@ -55,6 +55,9 @@ impl EcmascriptChunkItem for EcmascriptModulePartChunkItem {
             analyze.async_module,
             analyze.source_map,
             analyze.exports,
+            self.chunking_context
+                .used_exports()
+                .module_used_exports(Vc::upcast(self.module)),
             async_module_info,
         );
 
@@ -0,0 +1,931 @@
+//! Analysis of the exports of ES modules that are used by any importer.
+//!
+//! The analysis runs over the whole module graph of a production build. An
+//! export is used when any module imports it, accesses it through a namespace
+//! import, or re-exports it from a used export. Exports of `export *` are
+//! followed through [EsmExports::expand_exports].
+//!
+//! Unused exports are omitted from the generated code, and so are the top level
+//! declarations that are only referenced by them. Imports used by such
+//! declarations only count as used when the export that keeps the declaration
+//! is used.
+//!
+//! The facade and locals modules of the `reexports-only` tree shaking mode and
+//! the module parts of the `module-fragments` mode are tracked like the modules
+//! they are created from, so uses of a barrel file reach the modules it
+//! re-exports.
+//!
+//! [EsmExports::expand_exports]: crate::references::esm::EsmExports::expand_exports
+
+use std::{collections::VecDeque, hash::Hash};
+
+use anyhow::Result;
+use rustc_hash::{FxHashMap, FxHashSet};
+use swc_core::{
+    atoms::JsWord,
+    ecma::{
+        ast::{
+            Class, ClassMember, Decl, DefaultDecl, ExportDecl, ExportDefaultDecl, Expr, Ident,
+            ImportDecl, Lit, MemberExpr, MemberProp, ModuleDecl, ModuleItem, NamedExport, Pat,
+            Program, Prop, PropOrSpread, Stmt,
+        },
+        visit::{Visit, VisitWith},
+    },
+};
+use turbo_tasks::{FxIndexMap, FxIndexSet, RcStr, ReadRef, ResolvedVc, TryJoinIterExt, Value, Vc};
+use turbopack_core::{
+    chunk::used_exports::{ModuleUsedExports, UsedExports},
+    module::{Module, Modules},
+    reference::ModuleReference,
+    resolve::{parse::Request, ModulePart},
+};
+
+use crate::{
+    analyzer::graph::EvalContext,
+    chunk::{EcmascriptChunkPlaceable, EcmascriptExports},
+    concatenation::referenced_modules,
+    magic_identifier,
+    parse::ParseResult,
+    references::esm::{EsmAssetReference, EsmExport, EsmExports},
+    side_effect_optimization::{
+        facade::module::EcmascriptModuleFacadeModule, locals::module::EcmascriptModuleLocalsModule,
+    },
+    tree_shake::asset::EcmascriptModulePartAsset,
+    EcmascriptModuleAsset, EcmascriptParsable,
+};
+
+/// How a module uses the exports of the modules it references.
+#[turbo_tasks::value]
+struct ExportUsageInfo {
+    /// Whether the used exports of this module are tracked. Only the exports
+    /// of ES modules, and of the modules tree shaking splits them into, are
+    /// tracked.
+    is_tracked: bool,
+    /// The exports used by this module regardless of its own used exports, for
+    /// each module it references.
+    uses: Vec<(ResolvedVc<Box<dyn Module>>, ModuleUsedExports)>,
+    /// The exports used by the declarations that are only kept when an export
+    /// of this module is used, keyed by that export.
+    export_uses: Vec<(RcStr, Vec<(ResolvedVc<Box<dyn Module>>, ModuleUsedExports)>)>,
+    /// The exports of this module that re-export an export of another module,
+    /// with the name of the export in that module, or `None` when it
+    /// re-exports the namespace object.
+    reexports: Vec<(RcStr, ResolvedVc<Box<dyn Module>>, Option<RcStr>)>,
+}
+
+#[turbo_tasks::function]
+async fn export_usage_info(module: ResolvedVc<Box<dyn Module>>) -> Result<Vc<ExportUsageInfo>> {
+    let references = module.references().await?;
+
+    let Some(tracked) = tracked_module(module).await? else {
+        return untracked_usage_info(&references).await;
+    };
+
+    let mut targets = Vec::new();
+    for &reference in references.iter() {
+        let kind = match Vc::try_resolve_downcast_type::<EsmAssetReference>(reference).await? {
+            Some(esm_reference) => {
+                let esm_reference = esm_reference.await?;
+                let part = match esm_reference.export_name {
+                    Some(part) => match &*part.await? {
+                        ModulePart::Export(export) => {
+                            ImportPart::Export(export.await?.clone_value())
+                        }
+                        ModulePart::Evaluation => ImportPart::Evaluation,
+                        _ => ImportPart::Module,
+                    },
+                    None => ImportPart::Module,
+                };
+                ReferenceKind::Import {
+                    request: esm_reference.request.to_resolved().await?,
+                    part,
+                }
+            }
+            None => ReferenceKind::Other,
+        };
+        targets.push((referenced_modules(reference).await?, kind));
+    }
+
+    let (mut uses, export_uses) = match tracked.code {
+        Some(parsed) => {
+            let ParseResult::Ok {
+                program,
+                eval_context,
+                globals,
+                ..
+            } = &*parsed
+            else {
+                return untracked_usage_info(&references).await;
+            };
+            let Program::Module(program) = program else {
+                return untracked_usage_info(&references).await;
+            };
+
+            let import_requests = eval_context
+                .imports
+                .references()
+                .map(|reference| {
+                    Request::parse(Value::new(RcStr::from(&*reference.module_path).into()))
+                        .to_resolved()
+                })
+                .try_join()
+                .await?;
+            let items = swc_core::common::GLOBALS.set(globals, || {
+                program
+                    .body
+                    .iter()
+                    .map(|item| TopLevelItem::new(item, eval_context))
+                    .collect::<Vec<_>>()
+            });
+
+            let always_kept = always_kept_items(&items);
+            let kept_uses = always_kept.iter().map(|&index| &items[index].uses);
+            let uses = reference_uses(&targets, &import_requests, kept_uses, true);
+
+            let declarations = removable_declarations(&items, &always_kept);
+            let mut export_uses = Vec::new();
+            let exports = tracked.exports.await?;
+            for (exported, export) in &exports.exports {
+                let EsmExport::LocalBinding(name, _) = export else {
+                    continue;
+                };
+                let items = items_kept_by(&items, &declarations, &JsWord::from(name.as_str()))
+                    .into_iter()
+                    .map(|index| &items[index].uses);
+                let used = reference_uses(&targets, &import_requests, items, false);
+                if !used.is_empty() {
+                    export_uses.push((exported.clone(), used));
+                }
+            }
+            (uses, export_uses)
+        }
+        // Facade modules have no code, they only re-export other modules.
+        None => (
+            targets
+                .iter()
+                .flat_map(|(modules, _)| {
+                    modules
+                        .iter()
+                        .map(|&module| (module, ModuleUsedExports::none()))
+                })
+                .collect(),
+            vec![],
+        ),
+    };
+
+    let expanded = tracked.exports.expand_exports().await?;
+    // The exports of modules re-exported with `export *` that are only known at
+    // runtime can't be tracked.
+    uses.extend(
+        expanded
+            .dynamic_exports
+            .iter()
+            .map(|&module| (ResolvedVc::upcast(module), ModuleUsedExports::All)),
+    );
+    let mut reexports = Vec::new();
+    for (exported, export) in &expanded.exports {
+        let (reference, name) = match export {
+            EsmExport::ImportedBinding(reference, name, _) => (*reference, Some(name.clone())),
+            EsmExport::ImportedNamespace(reference) => (*reference, None),
+            EsmExport::LocalBinding(..) | EsmExport::Error => continue,
+        };
+        for module in referenced_modules(reference).await? {
+            reexports.push((exported.clone(), module, name.clone()));
+        }
+    }
+
+    Ok(ExportUsageInfo {
+        is_tracked: true,
+        uses,
+        export_uses,
+        reexports,
+    }
+    .cell())
+}
+
+/// Any export of the modules an untracked module references might be used.
+async fn untracked_usage_info(
+    references: &[Vc<Box<dyn ModuleReference>>],
+) -> Result<Vc<ExportUsageInfo>> {
+    let uses = references
+        .iter()
+        .map(|&reference| referenced_modules(reference))
+        .try_join()
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|module| (module, ModuleUsedExports::All))
+        .collect();
+    Ok(ExportUsageInfo {
+        is_tracked: false,
+        uses,
+        export_uses: vec![],
+        reexports: vec![],
+    }
+    .cell())
+}
+
+/// A module whose used exports are tracked.
+struct TrackedModule {
+    exports: Vc<EsmExports>,
+    /// The parsed code of the module, or `None` for facade modules, which only
+    /// re-export other modules.
+    code: Option<ReadRef<ParseResult>>,
+}
+
+/// Returns the exports and the code of the module when its used exports can be
+/// tracked, i.e. when it's an ES module or a part of one that tree shaking
+/// created.
+async fn tracked_module(module: ResolvedVc<Box<dyn Module>>) -> Result<Option<TrackedModule>> {
+    let (original, code) = if let Some(ecmascript) =
+        ResolvedVc::try_downcast_type::<EcmascriptModuleAsset>(module).await?
+    {
+        (*ecmascript, Some(ecmascript.parse().await?))
+    } else if let Some(locals) =
+        ResolvedVc::try_downcast_type::<EcmascriptModuleLocalsModule>(module).await?
+    {
+        // The locals module contains the code of the original module without
+        // its re-exports.
+        let original = locals.await?.module;
+        (original, Some(original.parse().await?))
+    } else if let Some(part) =
+        ResolvedVc::try_downcast_type::<EcmascriptModulePartAsset>(module).await?
+    {
+        (part.await?.full_module, Some(part.failsafe_parse().await?))
+    } else if let Some(facade) =
+        ResolvedVc::try_downcast_type::<EcmascriptModuleFacadeModule>(module).await?
+    {
+        let EcmascriptExports::EsmExports(exports) = *facade.get_exports().await? else {
+            return Ok(None);
+        };
+        return Ok(Some(TrackedModule {
+            exports: *exports,
+            code: None,
+        }));
+    } else {
+        return Ok(None);
+    };
+
+    let parsed = original.parse().await?;
+    let ParseResult::Ok { eval_context, .. } = &*parsed else {
+        return Ok(None);
+    };
+    let module_type = original.determine_module_type().await?.module_type;
+    if !eval_context.is_esm(module_type) {
+        return Ok(None);
+    }
+    let Some(placeable) =
+        ResolvedVc::try_sidecast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+    else {
+        return Ok(None);
+    };
+    let EcmascriptExports::EsmExports(exports) = *placeable.get_exports().await? else {
+        return Ok(None);
+    };
+    Ok(Some(TrackedModule {
+        exports: *exports,
+        code,
+    }))
+}
+
+/// The part of the imported module an ESM reference refers to.
+enum ImportPart {
+    Module,
+    /// A single export, which tree shaking creates a reference for per
+    /// import specifier.
+    Export(RcStr),
+    /// Only the side effects of the module.
+    Evaluation,
+}
+
+enum ReferenceKind {
+    Import {
+        request: ResolvedVc<Request>,
+        part: ImportPart,
+    },
+    /// Dynamic imports, requires, workers etc. expose the whole module.
+    Other,
+}
+
+/// Collects the exports `imports_uses` use from the referenced modules.
+/// Imports are keyed by their index in `import_requests`. With `all_targets`,
+/// every referenced module is included, even when none of its exports are
+/// used, and references that are not imports of the code use all exports.
+fn reference_uses<'a>(
+    targets: &[(Vec<ResolvedVc<Box<dyn Module>>>, ReferenceKind)],
+    import_requests: &[ResolvedVc<Request>],
+    imports_uses: impl IntoIterator<Item = &'a FxIndexMap<usize, ModuleUsedExports>>,
+    all_targets: bool,
+) -> Vec<(ResolvedVc<Box<dyn Module>>, ModuleUsedExports)> {
+    let mut uses_by_request = FxHashMap::default();
+    for imports_uses in imports_uses {
+        for (&index, used) in imports_uses {
+            uses_by_request
+                .entry(import_requests[index])
+                .or_insert_with(ModuleUsedExports::none)
+                .extend(used);
+        }
+    }
+
+    let mut uses = Vec::new();
+    for (modules, kind) in targets {
+        let used = match kind {
+            ReferenceKind::Import { request, part } => {
+                match uses_by_request.get(request) {
+                    Some(used) => match part {
+                        ImportPart::Module => used.clone(),
+                        ImportPart::Export(export) if used.is_used(export) => {
+                            ModuleUsedExports::Only(FxIndexSet::from_iter([export.clone()]))
+                        }
+                        ImportPart::Export(_) | ImportPart::Evaluation => ModuleUsedExports::none(),
+                    },
+                    // Imports that are not in the code, e.g. of the other parts of
+                    // a module that tree shaking split.
+                    None if !import_requests.contains(request) && all_targets => {
+                        ModuleUsedExports::All
+                    }
+                    None => ModuleUsedExports::none(),
+                }
+            }
+            ReferenceKind::Other if all_targets => ModuleUsedExports::All,
+            ReferenceKind::Other => ModuleUsedExports::none(),
+        };
+        if !all_targets && used == ModuleUsedExports::none() {
+            continue;
+        }
+        uses.extend(modules.iter().map(|&module| (module, used.clone())));
+    }
+    uses
+}
+
+/// A top level item of a module, as [remove_unused_declarations] sees it after
+/// code generation removed the `export` keywords.
+struct TopLevelItem {
+    /// The names declared by the item, when it's a declaration that is removed
+    /// when unused.
+    declared: Option<Vec<JsWord>>,
+    /// How often each identifier occurs in the item.
+    idents: FxHashMap<JsWord, usize>,
+    /// The exports the item uses from the imports, keyed by the index of the
+    /// import in the [ImportMap].
+    ///
+    /// [ImportMap]: crate::analyzer::imports::ImportMap
+    uses: FxIndexMap<usize, ModuleUsedExports>,
+}
+
+impl TopLevelItem {
+    fn new(item: &ModuleItem, eval_context: &EvalContext) -> Self {
+        let declared = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => {
+                removable_declaration_names(decl)
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl,
+                ..
+            })) => {
+                let default_name = || JsWord::from(magic_identifier::mangle("default export"));
+                match decl {
+                    DefaultDecl::Fn(decl) => Some(vec![decl
+                        .ident
+                        .as_ref()
+                        .map_or_else(default_name, |ident| ident.sym.clone())]),
+                    DefaultDecl::Class(decl) if is_pure_class(&decl.class) => Some(vec![decl
+                        .ident
+                        .as_ref()
+                        .map_or_else(default_name, |ident| ident.sym.clone())]),
+                    _ => None,
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export))
+                if is_pure_expr(&export.expr) =>
+            {
+                Some(vec![magic_identifier::mangle("default export").into()])
+            }
+            _ => None,
+        };
+
+        // Imports and re-exports are removed by code generation, so they don't
+        // keep declarations.
+        let is_removed = matches!(
+            item,
+            ModuleItem::ModuleDecl(
+                ModuleDecl::Import(_) | ModuleDecl::ExportNamed(_) | ModuleDecl::ExportAll(_)
+            )
+        );
+        let mut counter = IdentCounter::default();
+        let mut scanner = ImportUsageScanner {
+            eval_context,
+            uses: FxIndexMap::default(),
+        };
+        if !is_removed {
+            item.visit_with(&mut counter);
+            item.visit_with(&mut scanner);
+        }
+        TopLevelItem {
+            declared,
+            idents: counter.counts,
+            uses: scanner.uses,
+        }
+    }
+}
+
+/// Returns the items that [remove_unused_declarations] keeps when none of the
+/// exports are used, i.e. all items that are not removable declarations and
+/// the declarations they reference, directly or through other declarations.
+fn always_kept_items(items: &[TopLevelItem]) -> FxIndexSet<usize> {
+    let mut remaining = (0..items.len()).collect::<FxIndexSet<_>>();
+    loop {
+        let mut counts = FxHashMap::<&JsWord, usize>::default();
+        for &index in &remaining {
+            for (name, count) in &items[index].idents {
+                *counts.entry(name).or_default() += count;
+            }
+        }
+        let len = remaining.len();
+        remaining.retain(|&index| match &items[index].declared {
+            Some(names) => names
+                .iter()
+                .any(|name| counts.get(name).copied().unwrap_or_default() > 1),
+            None => true,
+        });
+        if remaining.len() == len {
+            return remaining;
+        }
+    }
+}
+
+/// Returns the items declaring each name, for the removable declarations that
+/// are not in `always_kept`.
+fn removable_declarations<'a>(
+    items: &'a [TopLevelItem],
+    always_kept: &FxIndexSet<usize>,
+) -> FxHashMap<&'a JsWord, Vec<usize>> {
+    let mut declarations = FxHashMap::<_, Vec<_>>::default();
+    for (index, item) in items.iter().enumerate() {
+        if always_kept.contains(&index) {
+            continue;
+        }
+        for name in item.declared.iter().flatten() {
+            declarations.entry(name).or_default().push(index);
+        }
+    }
+    declarations
+}
+
+/// Returns the declarations that are kept in addition to the always kept items
+/// when `name` is used: the declarations of `name` and the declarations they
+/// reference.
+fn items_kept_by(
+    items: &[TopLevelItem],
+    declarations: &FxHashMap<&JsWord, Vec<usize>>,
+    name: &JsWord,
+) -> FxIndexSet<usize> {
+    let mut kept = declarations
+        .get(name)
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect::<FxIndexSet<_>>();
+    let mut index = 0;
+    while let Some(&item) = kept.get_index(index) {
+        index += 1;
+        for name in items[item].idents.keys() {
+            kept.extend(declarations.get(name).into_iter().flatten().copied());
+        }
+    }
+    kept
+}
+
+/// Collects the exports a module uses from its imports.
+struct ImportUsageScanner<'a> {
+    eval_context: &'a EvalContext,
+    /// The used exports, keyed by the index of the import in the [ImportMap].
+    ///
+    /// [ImportMap]: crate::analyzer::imports::ImportMap
+    uses: FxIndexMap<usize, ModuleUsedExports>,
+}
+
+impl ImportUsageScanner<'_> {
+    fn add(&mut self, index: usize, used: ModuleUsedExports) {
+        self.uses
+            .entry(index)
+            .or_insert_with(ModuleUsedExports::none)
+            .extend(&used);
+    }
+
+    fn namespace_import(&self, ident: &Ident) -> Option<usize> {
+        match self.eval_context.imports.get_binding(&ident.to_id()) {
+            Some((index, None)) => Some(index),
+            _ => None,
+        }
+    }
+}
+
+impl Visit for ImportUsageScanner<'_> {
+    fn visit_import_decl(&mut self, _: &ImportDecl) {
+        // The local bindings of imports are not uses.
+    }
+
+    fn visit_named_export(&mut self, _: &NamedExport) {
+        // `export { a }` of an imported binding is a re-export, which is only
+        // used when the export itself is used.
+    }
+
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        if let Expr::Ident(obj) = &*member.obj {
+            if let Some(index) = self.namespace_import(obj) {
+                let name = match &member.prop {
+                    MemberProp::Ident(prop) => Some(prop.sym.as_str()),
+                    MemberProp::Computed(computed) => match &*computed.expr {
+                        Expr::Lit(Lit::Str(str)) => Some(str.value.as_str()),
+                        _ => None,
+                    },
+                    MemberProp::PrivateName(_) => None,
+                };
+                if let Some(name) = name {
+                    self.add(
+                        index,
+                        ModuleUsedExports::Only(FxIndexSet::from_iter([name.into()])),
+                    );
+                    return;
+                }
+            }
+        }
+        member.visit_children_with(self);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        match self.eval_context.imports.get_binding(&ident.to_id()) {
+            Some((index, Some(export))) => {
+                self.add(
+                    index,
+                    ModuleUsedExports::Only(FxIndexSet::from_iter([export])),
+                );
+            }
+            // The namespace object escapes, so any export might be used.
+            Some((index, None)) => self.add(index, ModuleUsedExports::All),
+            None => {}
+        }
+    }
+}
+
+/// A module in the graph that [propagate_used_exports] analyzes.
+struct UsageNode<T> {
+    is_tracked: bool,
+    uses: Vec<(T, ModuleUsedExports)>,
+    export_uses: Vec<(RcStr, Vec<(T, ModuleUsedExports)>)>,
+    reexports: Vec<(RcStr, T, Option<RcStr>)>,
+}
+
+/// Computes the used exports of the tracked modules of `graph`. All exports of
+/// the `entries` are used.
+fn propagate_used_exports<T>(
+    entries: &[T],
+    graph: &FxIndexMap<T, UsageNode<T>>,
+) -> FxIndexMap<T, ModuleUsedExports>
+where
+    T: Copy + Eq + Hash,
+{
+    fn mark<T: Copy + Eq + Hash>(
+        used: &mut FxIndexMap<T, ModuleUsedExports>,
+        queue: &mut VecDeque<T>,
+        module: T,
+        exports: &ModuleUsedExports,
+    ) {
+        if let Some(module_used) = used.get_mut(&module) {
+            if module_used.extend(exports) {
+                queue.push_back(module);
+            }
+        }
+    }
+
+    let mut used = graph
+        .iter()
+        .filter(|(_, node)| node.is_tracked)
+        .map(|(&module, _)| (module, ModuleUsedExports::none()))
+        .collect::<FxIndexMap<_, _>>();
+    let mut queue = VecDeque::new();
+    for &entry in entries {
+        mark(&mut used, &mut queue, entry, &ModuleUsedExports::All);
+    }
+    for node in graph.values() {
+        for (module, exports) in &node.uses {
+            mark(&mut used, &mut queue, *module, exports);
+        }
+    }
+
+    // Re-exports forward the use of an export to the module it comes from, and
+    // the declarations of used exports use the exports of other modules.
+    while let Some(module) = queue.pop_front() {
+        let module_used = used[&module].clone();
+        for (exported, uses) in &graph[&module].export_uses {
+            if module_used.is_used(exported) {
+                for (target, exports) in uses {
+                    mark(&mut used, &mut queue, *target, exports);
+                }
+            }
+        }
+        for (exported, target, name) in &graph[&module].reexports {
+            if !module_used.is_used(exported) {
+                continue;
+            }
+            let exports = match name {
+                Some(name) => ModuleUsedExports::Only(FxIndexSet::from_iter([name.clone()])),
+                None => ModuleUsedExports::All,
+            };
+            mark(&mut used, &mut queue, *target, &exports);
+        }
+    }
+    used
+}
+
+/// Computes the exports of the modules in the graph of `entries` that are used
+/// by any importer.
+#[turbo_tasks::function]
+pub async fn used_exports(entries: Vc<Modules>) -> Result<Vc<UsedExports>> {
+    let entries = entries.await?;
+
+    let mut graph = FxIndexMap::default();
+    let mut current = entries.iter().copied().collect::<FxIndexSet<_>>();
+    while !current.is_empty() {
+        let infos = current
+            .iter()
+            .map(|&module| async move { Ok((module, export_usage_info(module).await?)) })
+            .try_join()
+            .await?;
+        let mut next = FxIndexSet::default();
+        for (module, info) in infos {
+            next.extend(
+                info.uses
+                    .iter()
+                    .chain(info.export_uses.iter().flat_map(|(_, uses)| uses))
+                    .map(|(child, _)| *child)
+                    .chain(info.reexports.iter().map(|(_, child, _)| *child))
+                    .filter(|child| !graph.contains_key(child) && !current.contains(child)),
+            );
+            graph.insert(
+                module,
+                UsageNode {
+                    is_tracked: info.is_tracked,
+                    uses: info.uses.clone(),
+                    export_uses: info.export_uses.clone(),
+                    reexports: info.reexports.clone(),
+                },
+            );
+        }
+        current = next;
+    }
+
+    Ok(UsedExports::new(propagate_used_exports(&entries, &graph)))
+}
+
+/// Removes the top level declarations that have no side effects and are
+/// neither referenced nor named in `roots`. Removal is repeated until nothing
+/// changes, so declarations only referenced by removed ones are removed too.
+pub(crate) fn remove_unused_declarations(program: &mut Program, roots: &FxHashSet<JsWord>) {
+    let Program::Module(module) = program else {
+        return;
+    };
+    loop {
+        let mut counter = IdentCounter::default();
+        module.visit_with(&mut counter);
+        let len = module.body.len();
+        module.body.retain(|item| {
+            let ModuleItem::Stmt(Stmt::Decl(decl)) = item else {
+                return true;
+            };
+            let Some(names) = removable_declaration_names(decl) else {
+                return true;
+            };
+            // Every declared name is counted once for its own binding.
+            names
+                .iter()
+                .any(|name| roots.contains(name) || counter.counts.get(name) > Some(&1))
+        });
+        if module.body.len() == len {
+            break;
+        }
+    }
+}
+
+/// Counts the identifiers in a module by name. Names shadowed in nested scopes
+/// are counted as well, which keeps their top level declarations.
+#[derive(Default)]
+struct IdentCounter {
+    counts: FxHashMap<JsWord, usize>,
+}
+
+impl Visit for IdentCounter {
+    fn visit_ident(&mut self, ident: &Ident) {
+        *self.counts.entry(ident.sym.clone()).or_default() += 1;
+    }
+}
+
+/// Returns the names declared by a top level declaration that can be removed
+/// when unused.
+fn removable_declaration_names(decl: &Decl) -> Option<Vec<JsWord>> {
+    match decl {
+        Decl::Fn(decl) => Some(vec![decl.ident.sym.clone()]),
+        Decl::Class(decl) if is_pure_class(&decl.class) => Some(vec![decl.ident.sym.clone()]),
+        Decl::Var(decl) => decl
+            .decls
+            .iter()
+            .map(|declarator| match &declarator.name {
+                Pat::Ident(ident) if declarator.init.as_deref().map_or(true, is_pure_expr) => {
+                    Some(ident.id.sym.clone())
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+fn is_pure_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Fn(_) | Expr::Arrow(_) => true,
+        Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+        Expr::Paren(paren) => is_pure_expr(&paren.expr),
+        Expr::Class(class) => is_pure_class(&class.class),
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .flatten()
+            .all(|element| element.spread.is_none() && is_pure_expr(&element.expr)),
+        Expr::Object(object) => object.props.iter().all(|prop| match prop {
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::KeyValue(prop) => !prop.key.is_computed() && is_pure_expr(&prop.value),
+                Prop::Method(method) => !method.key.is_computed(),
+                _ => false,
+            },
+            PropOrSpread::Spread(_) => false,
+        }),
+        _ => false,
+    }
+}
+
+/// Whether defining the class has no side effects. Decorators, computed keys,
+/// static initializers and `extends` can run arbitrary code.
+fn is_pure_class(class: &Class) -> bool {
+    class.decorators.is_empty()
+        && class.super_class.is_none()
+        && class.body.iter().all(|member| match member {
+            ClassMember::Constructor(_) | ClassMember::PrivateMethod(_) | ClassMember::Empty(_) => {
+                true
+            }
+            ClassMember::Method(method) => {
+                !method.key.is_computed() && method.function.decorators.is_empty()
+            }
+            ClassMember::ClassProp(prop) => {
+                !prop.key.is_computed()
+                    && prop.decorators.is_empty()
+                    && (!prop.is_static || prop.value.as_deref().map_or(true, is_pure_expr))
+            }
+            ClassMember::PrivateProp(prop) => {
+                prop.decorators.is_empty()
+                    && (!prop.is_static || prop.value.as_deref().map_or(true, is_pure_expr))
+            }
+            _ => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use rustc_hash::FxHashMap;
+    use turbo_tasks::{FxIndexMap, FxIndexSet, RcStr};
+    use turbopack_core::chunk::used_exports::ModuleUsedExports;
+
+    use super::{
+        always_kept_items, items_kept_by, propagate_used_exports, removable_declarations,
+        TopLevelItem, UsageNode,
+    };
+
+    fn only(names: &[&str]) -> ModuleUsedExports {
+        ModuleUsedExports::Only(names.iter().map(|&name| RcStr::from(name)).collect())
+    }
+
+    fn node(uses: &[(u32, ModuleUsedExports)]) -> UsageNode<u32> {
+        UsageNode {
+            is_tracked: true,
+            uses: uses.to_vec(),
+            export_uses: vec![],
+            reexports: vec![],
+        }
+    }
+
+    #[test]
+    fn test_collects_imported_exports() {
+        let graph = FxIndexMap::from_iter([
+            (0, node(&[(1, only(&["a"])), (2, only(&[]))])),
+            (1, node(&[(2, only(&["b", "c"]))])),
+            (2, node(&[])),
+        ]);
+        let used = propagate_used_exports(&[0], &graph);
+        assert_eq!(used[&0], ModuleUsedExports::All);
+        assert_eq!(used[&1], only(&["a"]));
+        assert_eq!(used[&2], only(&["b", "c"]));
+    }
+
+    #[test]
+    fn test_follows_used_reexports() {
+        // 1 is a barrel file: `export { x as a } from "./2"; export * from "./3";
+        // export * as ns from "./4"`, and only `a` and `b` are imported from it.
+        let mut graph = FxIndexMap::from_iter([
+            (0, node(&[(1, only(&["a", "b"]))])),
+            (1, node(&[(2, only(&[])), (3, only(&[])), (4, only(&[]))])),
+            (2, node(&[])),
+            (3, node(&[])),
+            (4, node(&[])),
+        ]);
+        graph[&1].reexports = vec![
+            ("a".into(), 2, Some("x".into())),
+            ("b".into(), 3, Some("b".into())),
+            ("c".into(), 3, Some("c".into())),
+            ("ns".into(), 4, None),
+        ];
+        let used = propagate_used_exports(&[0], &graph);
+        assert_eq!(used[&1], only(&["a", "b"]));
+        assert_eq!(used[&2], only(&["x"]));
+        assert_eq!(used[&3], only(&["b"]));
+        assert_eq!(used[&4], only(&[]));
+    }
+
+    #[test]
+    fn test_untracked_modules_use_all_exports() {
+        // 1 is a CommonJS module requiring 2, and 0 uses the namespace of 3.
+        let mut graph = FxIndexMap::from_iter([
+            (0, node(&[(1, only(&["a"])), (3, ModuleUsedExports::All)])),
+            (1, node(&[(2, ModuleUsedExports::All)])),
+            (2, node(&[])),
+            (3, node(&[])),
+        ]);
+        graph[&1].is_tracked = false;
+        let used = propagate_used_exports(&[0], &graph);
+        assert!(!used.contains_key(&1));
+        assert_eq!(used[&2], ModuleUsedExports::All);
+        assert_eq!(used[&3], ModuleUsedExports::All);
+    }
+
+    #[test]
+    fn test_follows_uses_of_kept_declarations() {
+        // 1 exports `x`, whose declaration is the only use of `y` of 2.
+        let mut graph = FxIndexMap::from_iter([
+            (0, node(&[(1, only(&["x"]))])),
+            (1, node(&[(2, only(&[])), (3, only(&[]))])),
+            (2, node(&[])),
+            (3, node(&[])),
+        ]);
+        graph[&1].export_uses = vec![
+            ("x".into(), vec![(2, only(&["y"]))]),
+            ("unused".into(), vec![(3, only(&["z"]))]),
+        ];
+        let used = propagate_used_exports(&[0], &graph);
+        assert_eq!(used[&2], only(&["y"]));
+        assert_eq!(used[&3], only(&[]));
+    }
+
+    fn item(declared: Option<&[&str]>, idents: &[&str], imports: &[usize]) -> TopLevelItem {
+        let mut counts = FxHashMap::default();
+        for &ident in idents {
+            *counts.entry(ident.into()).or_default() += 1;
+        }
+        TopLevelItem {
+            declared: declared.map(|names| names.iter().map(|&name| name.into()).collect()),
+            idents: counts,
+            uses: imports
+                .iter()
+                .map(|&index| (index, ModuleUsedExports::All))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_attributes_imports_to_the_declarations_using_them() {
+        let items = [
+            // function used() { return a(); }
+            item(Some(&["used"]), &["used", "a"], &[0]),
+            // function unused() { return b() + helper(); }
+            item(Some(&["unused"]), &["unused", "b", "helper"], &[1]),
+            // function helper() { return c(); }
+            item(Some(&["helper"]), &["helper", "c"], &[2]),
+            // console.log(used);
+            item(None, &["console", "used"], &[]),
+            // function recursive() { return recursive() + d(); }
+            item(Some(&["recursive"]), &["recursive", "recursive", "d"], &[3]),
+        ];
+        let always_kept = always_kept_items(&items);
+        assert_eq!(always_kept, FxIndexSet::from_iter([0, 3, 4]));
+
+        let declarations = removable_declarations(&items, &always_kept);
+        assert_eq!(
+            items_kept_by(&items, &declarations, &"unused".into()),
+            FxIndexSet::from_iter([1, 2])
+        );
+        assert_eq!(
+            items_kept_by(&items, &declarations, &"helper".into()),
+            FxIndexSet::from_iter([2])
+        );
+        assert!(items_kept_by(&items, &declarations, &"used".into()).is_empty());
+    }
+}
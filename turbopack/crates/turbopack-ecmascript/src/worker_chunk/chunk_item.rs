@@ -1,18 +1,21 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use indoc::formatdoc;
-use turbo_tasks::{RcStr, TryJoinIterExt, Value, ValueToString, Vc};
+use turbo_tasks::{RcStr, ResolvedVc, TryJoinIterExt, Value, ValueToString, Vc};
+use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo, ChunkData, ChunkItem, ChunkType, ChunkingContext,
         ChunkingContextExt, ChunksData, EvaluatableAsset, EvaluatableAssets,
     },
     ident::AssetIdent,
+    issue::{code_gen::CodeGenerationIssue, IssueExt, IssueSeverity, StyledString},
     module::Module,
     output::OutputAssets,
     reference::{ModuleReferences, SingleOutputAssetReference},
+    reference_type::WorkerReferenceSubType,
 };
 
-use super::module::WorkerLoaderModule;
+use super::{isolated::IsolatedWorkerBundle, module::WorkerLoaderModule};
 use crate::{
     chunk::{
         data::EcmascriptChunkData, EcmascriptChunkItem, EcmascriptChunkItemContent,
@@ -32,6 +35,19 @@ pub fn worker_modifier() -> Vc<RcStr> {
     Vc::cell("worker".into())
 }
 
+/// Whether the worker needs to be emitted as a single file at a stable URL instead of being
+/// bootstrapped from a blob URL that loads its chunks.
+///
+/// Service workers can't be registered from blob URLs and can't lazily load chunks from outside of
+/// their scope. Shared workers are only shared between pages that construct them with the same URL,
+/// which a per-page blob URL never is.
+fn is_isolated(worker_type: &WorkerReferenceSubType) -> bool {
+    matches!(
+        worker_type,
+        WorkerReferenceSubType::SharedWorker | WorkerReferenceSubType::ServiceWorker
+    )
+}
+
 #[turbo_tasks::value_impl]
 impl WorkerLoaderChunkItem {
     #[turbo_tasks::function]
@@ -47,7 +63,7 @@ impl WorkerLoaderChunkItem {
             );
         };
 
-        Ok(self.chunking_context.evaluated_chunk_group_assets(
+        let chunks = self.chunking_context.evaluated_chunk_group_assets(
             AssetIdent::from_path(
                 self.chunking_context
                     .chunk_path(module.inner.ident(), ".js".into()),
@@ -55,7 +71,20 @@ impl WorkerLoaderChunkItem {
             .with_modifier(worker_modifier()),
             EvaluatableAssets::empty().with_entry(evaluatable),
             Value::new(AvailabilityInfo::Root),
-        ))
+        );
+
+        if !is_isolated(&module.worker_type) {
+            return Ok(chunks);
+        }
+
+        let bundle = IsolatedWorkerBundle::new(
+            isolated_worker_path(self.chunking_context, module.inner.ident()),
+            self.chunking_context,
+            chunks,
+        )
+        .to_resolved()
+        .await?;
+        Ok(Vc::cell(vec![ResolvedVc::upcast(bundle)]))
     }
 
     #[turbo_tasks::function]
@@ -68,6 +97,62 @@ impl WorkerLoaderChunkItem {
     }
 }
 
+/// The stable output path of an isolated worker bundle. The file name is derived from the path of
+/// the worker relative to the project, so that workers with the same file name don't collide. It's
+/// not content hashed, so that the browser can detect updates to an installed service worker at
+/// the same URL.
+#[turbo_tasks::function]
+async fn isolated_worker_path(
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    ident: Vc<AssetIdent>,
+) -> Result<Vc<FileSystemPath>> {
+    let name = ident
+        .output_name(chunking_context.context_path(), ".js".into())
+        .await?;
+    Ok(chunking_context.output_root().join(name.clone_value()))
+}
+
+/// Browsers reject service worker registrations with a scope outside of the directory of the
+/// worker script, unless the server allows it with a `Service-Worker-Allowed` header.
+fn check_service_worker_scope(
+    url: &str,
+    scope: &str,
+    path: Vc<FileSystemPath>,
+) -> Option<Vc<CodeGenerationIssue>> {
+    if !is_scope_outside_of_directory(url, scope) {
+        return None;
+    }
+    Some(
+        CodeGenerationIssue {
+            severity: IssueSeverity::Warning.into(),
+            title: StyledString::Text(
+                "Service worker scope is outside of the worker's directory".into(),
+            )
+            .cell(),
+            message: StyledString::Text(
+                format!(
+                    "The service worker is emitted at {url}, so it can only be registered with \
+                     the scope {scope} if the server responds with a `Service-Worker-Allowed: \
+                     {scope}` header."
+                )
+                .into(),
+            )
+            .cell(),
+            path,
+        }
+        .cell(),
+    )
+}
+
+fn is_scope_outside_of_directory(url: &str, scope: &str) -> bool {
+    if !url.starts_with('/') || !scope.starts_with('/') {
+        // Relative URLs are resolved against the page, which isn't known here.
+        return false;
+    }
+    let directory = &url[..=url.rfind('/').unwrap_or(0)];
+    !scope.starts_with(directory)
+}
+
 #[turbo_tasks::value_impl]
 impl EcmascriptChunkItem for WorkerLoaderChunkItem {
     #[turbo_tasks::function]
@@ -77,18 +162,47 @@ impl EcmascriptChunkItem for WorkerLoaderChunkItem {
 
     #[turbo_tasks::function]
     async fn content(self: Vc<Self>) -> Result<Vc<EcmascriptChunkItemContent>> {
-        let chunks_data = self.chunks_data().await?;
-        let chunks_data = chunks_data.iter().try_join().await?;
-        let chunks_data: Vec<_> = chunks_data
-            .iter()
-            .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
-            .collect();
-
-        let code = formatdoc! {
-            r#"
-                __turbopack_export_value__(__turbopack_worker_blob_url__({chunks:#}));
-            "#,
-            chunks = StringifyJs(&chunks_data),
+        let this = self.await?;
+        let module = this.module.await?;
+
+        let code = if is_isolated(&module.worker_type) {
+            let url = this
+                .chunking_context
+                .asset_url(
+                    self.chunks()
+                        .await?
+                        .first()
+                        .context("expected an isolated worker bundle")?
+                        .ident(),
+                )
+                .await?;
+            if let Some(scope) = &module.scope {
+                if let Some(issue) =
+                    check_service_worker_scope(&url, scope, module.inner.ident().path())
+                {
+                    issue.emit();
+                }
+            }
+            formatdoc! {
+                r#"
+                    __turbopack_export_value__({url});
+                "#,
+                url = StringifyJs(&url),
+            }
+        } else {
+            let chunks_data = self.chunks_data().await?;
+            let chunks_data = chunks_data.iter().try_join().await?;
+            let chunks_data: Vec<_> = chunks_data
+                .iter()
+                .map(|chunk_data| EcmascriptChunkData::new(chunk_data))
+                .collect();
+
+            formatdoc! {
+                r#"
+                    __turbopack_export_value__(__turbopack_worker_blob_url__({chunks:#}));
+                "#,
+                chunks = StringifyJs(&chunks_data),
+            }
         };
 
         Ok(EcmascriptChunkItemContent {
@@ -152,3 +266,26 @@ impl ChunkItem for WorkerLoaderChunkItem {
         Vc::upcast(self.module)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_scope_outside_of_directory;
+
+    #[test]
+    fn test_service_worker_scope() {
+        assert!(!is_scope_outside_of_directory(
+            "/_chunks/sw.js",
+            "/_chunks/"
+        ));
+        assert!(!is_scope_outside_of_directory(
+            "/_chunks/sw.js",
+            "/_chunks/app/"
+        ));
+        assert!(is_scope_outside_of_directory("/_chunks/sw.js", "/"));
+        assert!(is_scope_outside_of_directory("/_chunks/sw.js", "/app/"));
+        assert!(!is_scope_outside_of_directory("/sw.js", "/"));
+        // Relative URLs and scopes can't be checked.
+        assert!(!is_scope_outside_of_directory("_chunks/sw.js", "/"));
+        assert!(!is_scope_outside_of_directory("/_chunks/sw.js", "./"));
+    }
+}
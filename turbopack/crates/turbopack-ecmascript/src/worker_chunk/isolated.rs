@@ -0,0 +1,152 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{RcStr, ResolvedVc, ValueToString, Vc};
+use turbo_tasks_fs::{rope::Rope, File, FileContent, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    chunk::ChunkingContext,
+    code_builder::{Code, CodeBuilder},
+    ident::AssetIdent,
+    output::{OutputAsset, OutputAssets},
+    source_map::{GenerateSourceMap, OptionSourceMap, SourceMapAsset},
+};
+
+/// A single JavaScript file that contains all chunks of a worker's chunk group, including the
+/// runtime and the evaluation of the worker entry.
+///
+/// All chunks register themselves with the runtime before it starts, so the runtime only loads
+/// the chunks of dynamic imports. This is required for service workers, which are registered by
+/// URL and can't be bootstrapped from a blob URL.
+#[turbo_tasks::value(shared)]
+pub struct IsolatedWorkerBundle {
+    path: Vc<FileSystemPath>,
+    chunking_context: Vc<Box<dyn ChunkingContext>>,
+    chunks: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl IsolatedWorkerBundle {
+    /// Creates a new [`Vc<IsolatedWorkerBundle>`] at `path` from the chunks of an evaluated chunk
+    /// group. The chunks must be ordered so that the evaluate chunk comes last.
+    #[turbo_tasks::function]
+    pub fn new(
+        path: Vc<FileSystemPath>,
+        chunking_context: Vc<Box<dyn ChunkingContext>>,
+        chunks: Vc<OutputAssets>,
+    ) -> Vc<Self> {
+        IsolatedWorkerBundle {
+            path,
+            chunking_context,
+            chunks,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(&self) -> Result<Vc<Code>> {
+        let mut code = CodeBuilder::default();
+
+        // Chunks of dynamic imports in the worker aren't part of the bundle. The runtime loads
+        // them relative to this location, which the blob URL bootstrap of other workers sets.
+        writeln!(
+            code,
+            "self.TURBOPACK_WORKER_LOCATION = self.location.origin;"
+        )?;
+
+        for &chunk in self.chunks.await?.iter() {
+            if chunk.ident().path().await?.extension_ref() != Some("js") {
+                continue;
+            }
+            let FileContent::Content(file) = &*chunk.content().file_content().await? else {
+                continue;
+            };
+            let content = file.content().to_str()?;
+            // The chunk's own source map is merged into the bundle's source map below.
+            let content = match content.rfind("\n\n//# sourceMappingURL=") {
+                Some(index) => &content[..index],
+                None => &content,
+            };
+            let source_map = Vc::try_resolve_sidecast::<Box<dyn GenerateSourceMap>>(*chunk).await?;
+            code.push_source(&Rope::from(content.to_string()), source_map);
+            writeln!(code)?;
+        }
+
+        if code.has_source_map() {
+            let path = self.path.await?;
+            write!(
+                code,
+                "\n//# sourceMappingURL={}.map",
+                urlencoding::encode(path.file_name())
+            )?;
+        }
+
+        Ok(code.build().cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for IsolatedWorkerBundle {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<RcStr> {
+        Vc::cell("Isolated Worker Bundle".into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for IsolatedWorkerBundle {
+    #[turbo_tasks::function]
+    fn ident(&self) -> Vc<AssetIdent> {
+        AssetIdent::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        let this = self.await?;
+        let mut references = Vec::new();
+
+        if *this
+            .chunking_context
+            .reference_chunk_source_maps(Vc::upcast(self))
+            .await?
+        {
+            references.push(ResolvedVc::upcast(
+                SourceMapAsset::new(Vc::upcast(self)).to_resolved().await?,
+            ));
+        }
+
+        // Assets referenced by the chunks (e.g. static files) are still emitted, but the chunks
+        // themselves are inlined and their source maps are merged.
+        for &chunk in this.chunks.await?.iter() {
+            for &reference in chunk.references().await?.iter() {
+                if ResolvedVc::try_downcast_type::<SourceMapAsset>(reference)
+                    .await?
+                    .is_none()
+                {
+                    references.push(reference);
+                }
+            }
+        }
+
+        Ok(Vc::cell(references))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for IsolatedWorkerBundle {
+    #[turbo_tasks::function]
+    async fn content(self: Vc<Self>) -> Result<Vc<AssetContent>> {
+        let code = self.code().await?;
+        Ok(AssetContent::file(
+            File::from(code.source_code().clone()).into(),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for IsolatedWorkerBundle {
+    #[turbo_tasks::function]
+    fn generate_source_map(self: Vc<Self>) -> Vc<OptionSourceMap> {
+        self.code().generate_source_map()
+    }
+}
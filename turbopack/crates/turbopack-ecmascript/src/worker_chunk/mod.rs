@@ -1,2 +1,3 @@
 pub mod chunk_item;
+pub mod isolated;
 pub mod module;
@@ -2,10 +2,12 @@ use turbo_tasks::Vc;
 use turbo_tasks_fs::FileSystemPath;
 use turbopack_core::issue::{Issue, IssueStage, OptionStyledString, StyledString};
 
-/// An issue that occurred while resolving the parsing or evaluating the .env.
+/// An issue that occurred while resolving the parsing or evaluating the .env,
+/// or while validating env variables.
 #[turbo_tasks::value(shared)]
 pub struct ProcessEnvIssue {
     pub path: Vc<FileSystemPath>,
+    pub title: Vc<StyledString>,
     pub description: Vc<StyledString>,
 }
 
@@ -13,7 +15,7 @@ pub struct ProcessEnvIssue {
 impl Issue for ProcessEnvIssue {
     #[turbo_tasks::function]
     fn title(&self) -> Vc<StyledString> {
-        StyledString::Text("Error loading dotenv file".into()).cell()
+        self.title
     }
 
     #[turbo_tasks::function]
@@ -18,6 +18,7 @@ mod asset;
 pub mod dotenv;
 mod embeddable;
 mod issue;
+pub mod schema;
 mod try_env;
 
 pub use asset::ProcessEnvAsset;
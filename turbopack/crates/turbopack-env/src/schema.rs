@@ -0,0 +1,199 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, FxIndexMap, RcStr, Vc};
+use turbo_tasks_env::{EnvMap, ProcessEnv};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_core::{
+    compile_time_info::{
+        CompileTimeDefineValue, CompileTimeDefines, DefineableNameSegment, PublicEnvVars,
+    },
+    issue::{IssueExt, StyledString},
+};
+
+use crate::ProcessEnvIssue;
+
+/// The type the value of an env variable must have.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub enum EnvVarType {
+    #[default]
+    String,
+    /// A decimal number, e.g. `3000` or `0.5`.
+    Number,
+    /// `true`, `false`, `1` or `0`.
+    Boolean,
+}
+
+impl EnvVarType {
+    fn is_valid(&self, value: &str) -> bool {
+        match self {
+            EnvVarType::String => true,
+            EnvVarType::Number => value.trim().parse::<f64>().is_ok_and(f64::is_finite),
+            EnvVarType::Boolean => matches!(value, "true" | "false" | "1" | "0"),
+        }
+    }
+}
+
+/// The schema of a single env variable.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EnvVarSchema {
+    #[serde(rename = "type")]
+    pub ty: EnvVarType,
+    /// Whether it's an error when the variable isn't set. Variables with a
+    /// default are never missing.
+    pub required: bool,
+    /// The value of the variable when it isn't set.
+    pub default: Option<RcStr>,
+    /// Whether the variable is inlined into client code. Reading any other
+    /// variable there is an error.
+    pub public: bool,
+}
+
+/// The env variables a project uses, keyed by their name.
+#[turbo_tasks::value(transparent)]
+pub struct EnvSchema(#[turbo_tasks(trace_ignore)] FxIndexMap<RcStr, EnvVarSchema>);
+
+#[turbo_tasks::value_impl]
+impl EnvSchema {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        EnvSchema(FxIndexMap::default()).cell()
+    }
+}
+
+/// Validates the env variables against a schema. Variables that are missing
+/// or don't match their type are reported as issues, and missing variables
+/// get their default value.
+#[turbo_tasks::value]
+pub struct ValidatedProcessEnv {
+    prior: Vc<Box<dyn ProcessEnv>>,
+    schema: Vc<EnvSchema>,
+    path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl ValidatedProcessEnv {
+    /// Issues are reported for `path`, which is usually the file the schema
+    /// is defined in.
+    #[turbo_tasks::function]
+    pub fn new(
+        prior: Vc<Box<dyn ProcessEnv>>,
+        schema: Vc<EnvSchema>,
+        path: Vc<FileSystemPath>,
+    ) -> Vc<Self> {
+        ValidatedProcessEnv {
+            prior,
+            schema,
+            path,
+        }
+        .cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ProcessEnv for ValidatedProcessEnv {
+    #[turbo_tasks::function]
+    async fn read_all(&self) -> Result<Vc<EnvMap>> {
+        let prior = self.prior.read_all();
+        let schema = self.schema.await?;
+        if schema.is_empty() {
+            return Ok(prior);
+        }
+
+        let mut vars = prior.await?.clone_value();
+        for (name, var) in &*schema {
+            let description = match vars.get(name) {
+                Some(value) if !var.ty.is_valid(value) => format!(
+                    "The value of `{name}` must be a {ty:?}, but it is \"{value}\".",
+                    ty = var.ty
+                ),
+                Some(_) => continue,
+                None => match &var.default {
+                    Some(default) => {
+                        vars.insert(name.clone(), default.clone());
+                        continue;
+                    }
+                    None if var.required => format!("`{name}` is required, but it isn't set."),
+                    None => continue,
+                },
+            };
+            ProcessEnvIssue {
+                path: self.path,
+                title: StyledString::Text("Invalid environment variable".into()).cell(),
+                description: StyledString::Text(description.into()).cell(),
+            }
+            .cell()
+            .emit();
+        }
+
+        Ok(Vc::cell(vars))
+    }
+}
+
+fn process_env_name(name: &RcStr) -> Vec<DefineableNameSegment> {
+    vec!["process".into(), "env".into(), name.clone().into()]
+}
+
+/// Defines `process.env.X` for the public variables of `schema` that are set
+/// in `env`.
+#[turbo_tasks::function]
+pub async fn public_env_defines(
+    env: Vc<Box<dyn ProcessEnv>>,
+    schema: Vc<EnvSchema>,
+) -> Result<Vc<CompileTimeDefines>> {
+    let vars = env.read_all().await?;
+    Ok(Vc::cell(
+        schema
+            .await?
+            .iter()
+            .filter(|(_, var)| var.public)
+            .filter_map(|(name, _)| {
+                let value = vars.get(name)?;
+                Some((
+                    process_env_name(name),
+                    CompileTimeDefineValue::String(value.clone()),
+                ))
+            })
+            .collect(),
+    ))
+}
+
+/// The variables of `schema` that client code may read. Reading any other
+/// variable is an error, as it is `undefined` in client code.
+#[turbo_tasks::function]
+pub async fn public_env_vars(schema: Vc<EnvSchema>) -> Result<Vc<PublicEnvVars>> {
+    Ok(Vc::cell(
+        schema
+            .await?
+            .iter()
+            .filter(|(_, var)| var.public)
+            .map(|(name, _)| name.clone())
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EnvVarType;
+
+    #[test]
+    fn test_env_var_type_is_valid() {
+        assert!(EnvVarType::String.is_valid(""));
+        assert!(EnvVarType::String.is_valid("anything"));
+
+        for number in ["3000", "0.5", "-1", " 42 ", "1e3"] {
+            assert!(EnvVarType::Number.is_valid(number), "{number}");
+        }
+        for not_number in ["", "eighty", "NaN", "inf", "3000px"] {
+            assert!(!EnvVarType::Number.is_valid(not_number), "{not_number}");
+        }
+
+        for boolean in ["true", "false", "1", "0"] {
+            assert!(EnvVarType::Boolean.is_valid(boolean), "{boolean}");
+        }
+        for not_boolean in ["", "yes", "TRUE", " true"] {
+            assert!(!EnvVarType::Boolean.is_valid(not_boolean), "{not_boolean}");
+        }
+    }
+}
@@ -47,6 +47,7 @@ impl ProcessEnv for TryDotenvProcessEnv {
                 // we want to emit an Issue and fall back to the prior's read.
                 ProcessEnvIssue {
                     path: self.path,
+                    title: StyledString::Text("Error loading dotenv file".into()).cell(),
                     // read_all_with_prior will wrap a current error with a context containing the
                     // failing file, which we don't really care about (we report the filepath as the
                     // Issue context, not the description). So extract the real error.
@@ -0,0 +1,140 @@
+#![allow(clippy::needless_return)] // tokio macro-generated code doesn't respect this
+#![cfg(test)]
+
+use anyhow::Result;
+use turbo_tasks::{FxIndexMap, RcStr, Vc};
+use turbo_tasks_env::{EnvMap, ProcessEnv};
+use turbo_tasks_fs::{FileSystem, VirtualFileSystem};
+use turbo_tasks_testing::{register, run, Registration};
+use turbopack_core::{
+    compile_time_info::{CompileTimeDefineValue, DefineableNameSegment},
+    issue::{Issue, IssueDescriptionExt},
+};
+use turbopack_env::schema::{
+    public_env_defines, public_env_vars, EnvSchema, EnvVarSchema, EnvVarType, ValidatedProcessEnv,
+};
+
+static REGISTRATION: Registration = register!(turbopack_env::register);
+
+fn env(vars: &[(&str, &str)]) -> Vc<Box<dyn ProcessEnv>> {
+    Vc::upcast(Vc::<EnvMap>::cell(
+        vars.iter()
+            .map(|&(name, value)| (name.into(), value.into()))
+            .collect(),
+    ))
+}
+
+/// `PORT` is a number that defaults to `3000`, `DEBUG` a boolean, `API_URL` a
+/// required public string and `SECRET` a private string.
+fn schema() -> Vc<EnvSchema> {
+    Vc::cell(FxIndexMap::from_iter([
+        (
+            "PORT".into(),
+            EnvVarSchema {
+                ty: EnvVarType::Number,
+                default: Some("3000".into()),
+                ..Default::default()
+            },
+        ),
+        (
+            "DEBUG".into(),
+            EnvVarSchema {
+                ty: EnvVarType::Boolean,
+                ..Default::default()
+            },
+        ),
+        (
+            "API_URL".into(),
+            EnvVarSchema {
+                required: true,
+                public: true,
+                ..Default::default()
+            },
+        ),
+        ("SECRET".into(), EnvVarSchema::default()),
+    ]))
+}
+
+/// Validates `vars` against the [schema] and returns the validated variables
+/// and the descriptions of the issues, sorted.
+async fn validate(vars: &[(&str, &str)]) -> Result<(FxIndexMap<RcStr, RcStr>, Vec<String>)> {
+    let path = VirtualFileSystem::new_with_name("project".into())
+        .root()
+        .join("turbopack.config.json".into());
+    let validated = ValidatedProcessEnv::new(env(vars), schema(), path).read_all();
+    validated.strongly_consistent().await?;
+
+    let mut descriptions = vec![];
+    for issue in validated.peek_issues_with_path().await?.iter() {
+        if let Some(description) = *issue.description().await? {
+            descriptions.push(description.await?.to_unstyled_string());
+        }
+    }
+    descriptions.sort();
+    Ok((validated.await?.clone_value(), descriptions))
+}
+
+#[tokio::test]
+async fn missing_variables_get_their_default() {
+    run(&REGISTRATION, || async {
+        let (vars, issues) = validate(&[("API_URL", "https://example.com")]).await?;
+        assert_eq!(issues, Vec::<String>::new());
+        assert_eq!(vars.get("PORT").map(|port| &**port), Some("3000"));
+        assert_eq!(vars.get("DEBUG"), None);
+
+        // Set variables take precedence over the default.
+        let (vars, _) = validate(&[("API_URL", "https://example.com"), ("PORT", "8080")]).await?;
+        assert_eq!(vars.get("PORT").map(|port| &**port), Some("8080"));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn reports_invalid_and_missing_required_variables() {
+    run(&REGISTRATION, || async {
+        let (vars, issues) = validate(&[("PORT", "eighty"), ("DEBUG", "yes")]).await?;
+        assert_eq!(
+            issues,
+            [
+                "The value of `DEBUG` must be a Boolean, but it is \"yes\".",
+                "The value of `PORT` must be a Number, but it is \"eighty\".",
+                "`API_URL` is required, but it isn't set.",
+            ]
+        );
+        // Invalid values are kept, so the code sees what is set.
+        assert_eq!(vars.get("PORT").map(|port| &**port), Some("eighty"));
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn only_public_variables_are_inlined() {
+    run(&REGISTRATION, || async {
+        let defines = public_env_defines(
+            env(&[("API_URL", "https://example.com"), ("SECRET", "hunter2")]),
+            schema(),
+        )
+        .await?;
+        assert_eq!(
+            defines.iter().collect::<Vec<_>>(),
+            [(
+                &vec![
+                    DefineableNameSegment::from("process"),
+                    DefineableNameSegment::from("env"),
+                    DefineableNameSegment::from("API_URL"),
+                ],
+                &CompileTimeDefineValue::String("https://example.com".into()),
+            )]
+        );
+
+        // Client code may read public variables even when they aren't set.
+        assert_eq!(*public_env_vars(schema()).await?, [RcStr::from("API_URL")]);
+        anyhow::Ok(())
+    })
+    .await
+    .unwrap()
+}
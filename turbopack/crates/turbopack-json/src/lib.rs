@@ -22,9 +22,12 @@ use turbopack_core::{
     reference::ModuleReferences,
     source::Source,
 };
-use turbopack_ecmascript::chunk::{
-    EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable, EcmascriptChunkType,
-    EcmascriptExports,
+use turbopack_ecmascript::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkPlaceable,
+        EcmascriptChunkType, EcmascriptExports,
+    },
+    property_mangling::{ExposedProperties, PropertyNames},
 };
 
 #[turbo_tasks::function]
@@ -83,6 +86,37 @@ impl EcmascriptChunkPlaceable for JsonModuleAsset {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl ExposedProperties for JsonModuleAsset {
+    #[turbo_tasks::function]
+    async fn exposed_property_names(&self) -> Result<Vc<PropertyNames>> {
+        fn visit(value: &serde_json::Value, names: &mut Vec<RcStr>) {
+            match value {
+                serde_json::Value::Object(object) => {
+                    for (key, value) in object {
+                        names.push(key.as_str().into());
+                        visit(value, names);
+                    }
+                }
+                serde_json::Value::Array(array) => {
+                    for value in array {
+                        visit(value, names);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut names = vec![];
+        if let FileJsonContent::Content(data) =
+            &*self.source.content().file_content().parse_json().await?
+        {
+            visit(data, &mut names);
+        }
+        Ok(Vc::cell(names))
+    }
+}
+
 #[turbo_tasks::value]
 struct JsonChunkItem {
     module: Vc<JsonModuleAsset>,
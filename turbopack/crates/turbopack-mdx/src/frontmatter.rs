@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FrontmatterKind {
+    /// Fenced by `---`.
+    Yaml,
+    /// Fenced by `+++`.
+    Toml,
+}
+
+/// A frontmatter block at the very start of an mdx document.
+#[derive(Debug)]
+pub(crate) struct Frontmatter<'a> {
+    pub kind: FrontmatterKind,
+    /// The text between the opening and the closing fence.
+    pub value: &'a str,
+    /// The byte offset right after the closing fence.
+    pub end: usize,
+}
+
+impl Frontmatter<'_> {
+    /// Parses the frontmatter into a JSON value. An empty frontmatter is an
+    /// empty object.
+    pub fn to_json(&self) -> Result<Value> {
+        if self.value.trim().is_empty() {
+            return Ok(Value::Object(Default::default()));
+        }
+        match self.kind {
+            FrontmatterKind::Yaml => {
+                serde_yaml::from_str(self.value).context("failed to parse YAML frontmatter")
+            }
+            FrontmatterKind::Toml => {
+                let table: toml::Table =
+                    toml::from_str(self.value).context("failed to parse TOML frontmatter")?;
+                Ok(serde_json::to_value(table)?)
+            }
+        }
+    }
+
+    /// The number of lines the frontmatter occupies, including both fences.
+    pub fn lines(&self) -> usize {
+        self.value.lines().count() + 2
+    }
+}
+
+/// Finds a YAML (`---`) or TOML (`+++`) frontmatter block at the start of
+/// `source`. Like in markdown, the fences must be on their own line.
+pub(crate) fn split_frontmatter(source: &str) -> Option<Frontmatter<'_>> {
+    let mut lines = source.split_inclusive('\n');
+    let opening = lines.next()?;
+    let fence = opening.trim_end();
+    let kind = match fence {
+        "---" => FrontmatterKind::Yaml,
+        "+++" => FrontmatterKind::Toml,
+        _ => return None,
+    };
+
+    let start = opening.len();
+    let mut offset = start;
+    for line in lines {
+        if line.trim_end() == fence {
+            return Some(Frontmatter {
+                kind,
+                value: &source[start..offset],
+                end: offset + line.len(),
+            });
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Replaces the frontmatter block with an mdx ESM export of `value`, so the
+/// frontmatter is exported as `frontmatter` and can be referenced in
+/// expressions of the document. The lines of the block are kept as blank lines
+/// to not shift the locations of errors in the rest of the document.
+pub(crate) fn replace_frontmatter(
+    source: &str,
+    frontmatter: &Frontmatter<'_>,
+    value: &Value,
+) -> String {
+    let newlines = source[..frontmatter.end].matches('\n').count();
+    format!(
+        "export const frontmatter = {value}{}{}",
+        "\n".repeat(newlines),
+        &source[frontmatter.end..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn yaml() {
+        let source = "---\ntitle: Hello\ntags:\n  - a\n---\n\n# Hello\n";
+        let frontmatter = split_frontmatter(source).unwrap();
+        assert_eq!(frontmatter.kind, FrontmatterKind::Yaml);
+        assert_eq!(frontmatter.lines(), 5);
+        let value = frontmatter.to_json().unwrap();
+        assert_eq!(value, json!({ "title": "Hello", "tags": ["a"] }));
+    }
+
+    #[test]
+    fn replace() {
+        let source = "---\ntitle: Hello\n---\n# Hello\n";
+        let frontmatter = split_frontmatter(source).unwrap();
+        let value = frontmatter.to_json().unwrap();
+        assert_eq!(
+            replace_frontmatter(source, &frontmatter, &value),
+            "export const frontmatter = {\"title\":\"Hello\"}\n\n\n# Hello\n"
+        );
+    }
+
+    #[test]
+    fn toml() {
+        let source = "+++\ntitle = \"Hello\"\n+++\n# Hello\n";
+        let frontmatter = split_frontmatter(source).unwrap();
+        assert_eq!(frontmatter.kind, FrontmatterKind::Toml);
+        assert_eq!(frontmatter.to_json().unwrap(), json!({ "title": "Hello" }));
+    }
+
+    #[test]
+    fn empty() {
+        let frontmatter = split_frontmatter("---\n---\n").unwrap();
+        assert_eq!(frontmatter.to_json().unwrap(), json!({}));
+    }
+
+    #[test]
+    fn no_frontmatter() {
+        assert!(split_frontmatter("# Hello\n---\n").is_none());
+        assert!(split_frontmatter("\n---\ntitle: Hello\n---\n").is_none());
+        assert!(split_frontmatter("---\ntitle: Hello\n").is_none());
+    }
+}
@@ -0,0 +1,261 @@
+use std::{collections::HashMap, iter::Peekable, slice::Iter, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use markdown::mdast::Node;
+use serde::Serialize;
+use swc_core::{
+    common::{
+        comments::{Comments, SingleThreadedComments},
+        FileName, FilePathMapping, SourceMap, DUMMY_SP,
+    },
+    ecma::{
+        ast::{
+            CallExpr, EsVersion, Expr, IdentName, JSXAttr, JSXAttrName, JSXAttrOrSpread,
+            JSXAttrValue, JSXElement, JSXElementName, KeyValueProp, Lit, MemberExpr, MemberProp,
+            ObjectLit, Prop, PropName, PropOrSpread,
+        },
+        codegen::{text_writer::JsWriter, Emitter},
+        parser::{lexer::Lexer, EsSyntax, Parser, StringInput, Syntax},
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+
+/// A heading of an mdx document, as it's listed in the table of contents.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct Heading {
+    pub depth: u8,
+    /// The text content of the heading.
+    pub value: String,
+    /// The unique slug of the heading, which is used as its `id`.
+    pub id: String,
+}
+
+/// Collects the headings of a document in document order.
+pub(crate) fn collect_headings(root: &Node) -> Vec<Heading> {
+    let mut slugger = Slugger::default();
+    let mut headings = Vec::new();
+    collect(root, &mut slugger, &mut headings);
+    headings
+}
+
+fn collect(node: &Node, slugger: &mut Slugger, headings: &mut Vec<Heading>) {
+    if let Node::Heading(heading) = node {
+        let value = node.to_string();
+        headings.push(Heading {
+            depth: heading.depth,
+            id: slugger.slug(&value),
+            value,
+        });
+    } else if let Some(children) = node.children() {
+        for child in children {
+            collect(child, slugger, headings);
+        }
+    }
+}
+
+/// Generates unique slugs the same way as `github-slugger`, which is what
+/// `rehype-slug` uses.
+#[derive(Default)]
+struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    fn slug(&mut self, value: &str) -> String {
+        let original = slug(value);
+        let mut result = original.clone();
+        while self.occurrences.contains_key(&result) {
+            let count = self.occurrences.entry(original.clone()).or_default();
+            *count += 1;
+            result = format!("{original}-{count}");
+        }
+        self.occurrences.insert(result.clone(), 0);
+        result
+    }
+}
+
+fn slug(value: &str) -> String {
+    value
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Adds the ids of `headings` to the heading elements of compiled mdx `code`.
+///
+/// Headings are compiled to `_components.h1` to `_components.h6` elements,
+/// which are created in document order, so they are matched with the headings
+/// of the document by their order.
+pub(crate) fn add_heading_ids(code: String, headings: &[Heading]) -> Result<String> {
+    let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+    let fm = cm.new_source_file(FileName::Anon.into(), code);
+    let comments = SingleThreadedComments::default();
+
+    let lexer = Lexer::new(
+        Syntax::Es(EsSyntax {
+            jsx: true,
+            allow_return_outside_function: true,
+            ..Default::default()
+        }),
+        EsVersion::latest(),
+        StringInput::from(&*fm),
+        Some(&comments),
+    );
+    let mut program = Parser::new_from(lexer)
+        .parse_program()
+        .map_err(|err| anyhow!("failed to parse compiled mdx: {}", err.kind().msg()))?;
+
+    program.visit_mut_with(&mut HeadingIds {
+        headings: headings.iter().peekable(),
+    });
+
+    let mut buf = vec![];
+    {
+        let mut emitter = Emitter {
+            cfg: Default::default(),
+            comments: Some(&comments as &dyn Comments),
+            cm: cm.clone(),
+            wr: JsWriter::new(cm.clone(), "\n", &mut buf, None),
+        };
+        emitter
+            .emit_program(&program)
+            .context("failed to emit compiled mdx")?;
+    }
+    Ok(String::from_utf8(buf)?)
+}
+
+struct HeadingIds<'a> {
+    headings: Peekable<Iter<'a, Heading>>,
+}
+
+impl<'a> HeadingIds<'a> {
+    fn next_id(&mut self, depth: u8) -> Option<&'a str> {
+        self.headings
+            .next_if(|heading| heading.depth == depth)
+            .map(|heading| heading.id.as_str())
+    }
+}
+
+impl VisitMut for HeadingIds<'_> {
+    fn visit_mut_call_expr(&mut self, call: &mut CallExpr) {
+        // `_jsx(_components.h2, { children: ... })` or
+        // `React.createElement(_components.h2, null, ...)`
+        let depth = call
+            .args
+            .first()
+            .and_then(|arg| arg.expr.as_member())
+            .and_then(heading_component_depth);
+        if let Some(id) = depth.and_then(|depth| self.next_id(depth)) {
+            let id = PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(IdentName::new("id".into(), DUMMY_SP)),
+                value: Box::new(Expr::Lit(Lit::Str(id.into()))),
+            })));
+            if let Some(props) = call.args.get_mut(1).map(|arg| &mut *arg.expr) {
+                if matches!(props, Expr::Lit(Lit::Null(_))) {
+                    *props = Expr::Object(ObjectLit {
+                        span: DUMMY_SP,
+                        props: vec![],
+                    });
+                }
+                if let Expr::Object(props) = props {
+                    props.props.insert(0, id);
+                }
+            }
+        }
+
+        call.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_jsx_element(&mut self, element: &mut JSXElement) {
+        // `<_components.h2>...</_components.h2>` when jsx is preserved
+        let depth = match &element.opening.name {
+            JSXElementName::JSXMemberExpr(member) => member
+                .obj
+                .as_ident()
+                .filter(|obj| &*obj.sym == "_components")
+                .and_then(|_| heading_depth(&member.prop.sym)),
+            _ => None,
+        };
+        if let Some(id) = depth.and_then(|depth| self.next_id(depth)) {
+            element.opening.attrs.insert(
+                0,
+                JSXAttrOrSpread::JSXAttr(JSXAttr {
+                    span: DUMMY_SP,
+                    name: JSXAttrName::Ident(IdentName::new("id".into(), DUMMY_SP)),
+                    value: Some(JSXAttrValue::Lit(Lit::Str(id.into()))),
+                }),
+            );
+        }
+
+        element.visit_mut_children_with(self);
+    }
+}
+
+fn heading_component_depth(member: &MemberExpr) -> Option<u8> {
+    if !member
+        .obj
+        .as_ident()
+        .is_some_and(|obj| &*obj.sym == "_components")
+    {
+        return None;
+    }
+    match &member.prop {
+        MemberProp::Ident(prop) => heading_depth(&prop.sym),
+        _ => None,
+    }
+}
+
+fn heading_depth(name: &str) -> Option<u8> {
+    match name.strip_prefix('h')?.parse() {
+        Ok(depth @ 1..=6) => Some(depth),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Hello World"), "hello-world");
+        assert_eq!(slugger.slug("Hello World"), "hello-world-1");
+        assert_eq!(slugger.slug("hello-world 1"), "hello-world-1-1");
+        assert_eq!(slugger.slug("What's `new`?"), "whats-new");
+        assert_eq!(slugger.slug("Über_alles"), "über_alles");
+    }
+
+    #[test]
+    fn heading_ids() {
+        let headings = [
+            Heading {
+                depth: 1,
+                value: "Hello".into(),
+                id: "hello".into(),
+            },
+            Heading {
+                depth: 2,
+                value: "World".into(),
+                id: "world".into(),
+            },
+        ];
+        let code = add_heading_ids(
+            "_jsxs(_Fragment, { children: [_jsx(_components.h1, { children: \"Hello\" }), \
+             _jsx(_components.p, {}), _jsx(_components.h2, { children: \"World\" })] });"
+                .into(),
+            &headings,
+        )
+        .unwrap();
+        let hello = code.find("id: \"hello\"").unwrap();
+        let paragraph = code.find("_components.p").unwrap();
+        let world = code.find("id: \"world\"").unwrap();
+        assert!(hello < paragraph && paragraph < world);
+    }
+}
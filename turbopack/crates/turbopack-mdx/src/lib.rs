@@ -2,7 +2,12 @@
 #![feature(arbitrary_self_types)]
 #![feature(arbitrary_self_types_pointers)]
 
-use anyhow::Result;
+mod frontmatter;
+mod headings;
+
+use std::fmt::Write;
+
+use anyhow::{anyhow, Result};
 use mdxjs::{compile, MdxParseOptions, Options};
 use turbo_tasks::{RcStr, ValueDefault, Vc};
 use turbo_tasks_fs::{rope::Rope, File, FileContent, FileSystemPath};
@@ -18,6 +23,11 @@ use turbopack_core::{
     source_transform::SourceTransform,
 };
 
+use crate::{
+    frontmatter::{replace_frontmatter, split_frontmatter},
+    headings::{add_heading_ids, collect_headings},
+};
+
 #[turbo_tasks::function]
 fn modifier() -> Vc<RcStr> {
     Vc::cell("mdx".into())
@@ -48,6 +58,16 @@ pub struct MdxTransformOptions {
     pub provider_import_source: Option<RcStr>,
     /// Determines how to parse mdx contents.
     pub mdx_type: Option<MdxParseConstructs>,
+    /// Adds an `id` to headings, generated from their text the same way as
+    /// `rehype-slug` does.
+    pub heading_ids: Option<bool>,
+    /// Exports the headings of the document as `tableOfContents`, a list of
+    /// `{ depth, value, id }` objects.
+    pub table_of_contents: Option<bool>,
+    /// Enables math syntax (`$x$` and `$$` blocks). Math is compiled to code
+    /// elements with the `math-inline` and `math-display` classes, like
+    /// `remark-math` does.
+    pub math: Option<bool>,
 }
 
 impl Default for MdxTransformOptions {
@@ -59,6 +79,9 @@ impl Default for MdxTransformOptions {
             jsx_import_source: None,
             provider_import_source: None,
             mdx_type: Some(MdxParseConstructs::Commonmark),
+            heading_ids: None,
+            table_of_contents: None,
+            math: None,
         }
     }
 }
@@ -157,9 +180,47 @@ impl MdxTransformedAsset {
             None
         };
 
-        let parse_options = match transform_options.mdx_type {
-            Some(MdxParseConstructs::Gfm) => MdxParseOptions::gfm(),
-            _ => MdxParseOptions::default(),
+        let gfm = matches!(transform_options.mdx_type, Some(MdxParseConstructs::Gfm));
+        let math = transform_options.math.unwrap_or(false);
+        let heading_ids = transform_options.heading_ids.unwrap_or(false);
+        let table_of_contents = transform_options.table_of_contents.unwrap_or(false);
+
+        let mut parse_options = if gfm {
+            MdxParseOptions::gfm()
+        } else {
+            MdxParseOptions::default()
+        };
+        parse_options.constructs.math_flow = math;
+        parse_options.constructs.math_text = math;
+
+        let source = file.content().to_str()?;
+        let mdx_source = match split_frontmatter(&source) {
+            Some(frontmatter) => {
+                let value = match frontmatter.to_json() {
+                    Ok(value) => value,
+                    Err(err) => {
+                        MdxIssue {
+                            path: self.source.ident().path(),
+                            loc: Some(IssueSource::from_line_col(
+                                self.source,
+                                SourcePos { line: 0, column: 0 },
+                                SourcePos {
+                                    line: frontmatter.lines() - 1,
+                                    column: 3,
+                                },
+                            )),
+                            reason: format!("{err:#}"),
+                            mdx_rule_id: "frontmatter".to_string(),
+                            mdx_source: "turbopack-mdx".to_string(),
+                        }
+                        .cell()
+                        .emit();
+                        serde_json::Value::Object(Default::default())
+                    }
+                };
+                replace_frontmatter(&source, &frontmatter, &value)
+            }
+            None => source.to_string(),
         };
 
         let options = Options {
@@ -179,13 +240,33 @@ impl MdxTransformedAsset {
             ..Default::default()
         };
 
-        let result = compile(&file.content().to_str()?, &options);
+        let result = compile(&mdx_source, &options);
 
         match result {
-            Ok(mdx_jsx_component) => Ok(MdxTransformResult {
-                content: AssetContent::file(File::from(Rope::from(mdx_jsx_component)).into()),
+            Ok(mut mdx_jsx_component) => {
+                if heading_ids || table_of_contents {
+                    // The original source is parsed, as the replaced frontmatter would be
+                    // parsed as an expression without a JS parser.
+                    let mdast = markdown::to_mdast(&source, &markdown_parse_options(gfm, math))
+                        .map_err(|err| anyhow!("{err}"))?;
+                    let headings = collect_headings(&mdast);
+                    if heading_ids && !headings.is_empty() {
+                        mdx_jsx_component = add_heading_ids(mdx_jsx_component, &headings)?;
+                    }
+                    if table_of_contents {
+                        writeln!(
+                            mdx_jsx_component,
+                            "export const tableOfContents = {};",
+                            serde_json::to_string(&headings)?
+                        )?;
+                    }
+                }
+
+                Ok(MdxTransformResult {
+                    content: AssetContent::file(File::from(Rope::from(mdx_jsx_component)).into()),
+                }
+                .cell())
             }
-            .cell()),
             Err(err) => {
                 let loc = err.place.map(|p| {
                     let (start, end) = match *p {
@@ -232,6 +313,25 @@ impl MdxTransformedAsset {
     }
 }
 
+/// The `markdown` parse options matching the mdx parse options, for analyzing
+/// the structure of a document.
+fn markdown_parse_options(gfm: bool, math: bool) -> markdown::ParseOptions {
+    let mut options = markdown::ParseOptions::mdx();
+    let constructs = &mut options.constructs;
+    constructs.frontmatter = true;
+    constructs.math_flow = math;
+    constructs.math_text = math;
+    if gfm {
+        constructs.gfm_autolink_literal = true;
+        constructs.gfm_footnote_definition = true;
+        constructs.gfm_label_start_footnote = true;
+        constructs.gfm_strikethrough = true;
+        constructs.gfm_table = true;
+        constructs.gfm_task_list_item = true;
+    }
+    options
+}
+
 #[turbo_tasks::value]
 struct MdxTransformResult {
     content: Vc<AssetContent>,
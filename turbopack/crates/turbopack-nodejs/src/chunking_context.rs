@@ -8,7 +8,9 @@ use turbopack_core::{
     chunk::{
         availability_info::AvailabilityInfo,
         chunk_group::{make_chunk_group, MakeChunkGroupResult},
+        module_concatenation::ModuleConcatenation,
         module_id_strategies::{DevModuleIdStrategy, ModuleIdStrategy},
+        used_exports::UsedExports,
         Chunk, ChunkGroupResult, ChunkItem, ChunkableModule, ChunkingContext,
         EntryChunkGroupResult, EvaluatableAssets, MinifyType, ModuleId,
     },
@@ -21,6 +23,7 @@ use turbopack_ecmascript::{
     async_chunk::module::AsyncLoaderModule,
     chunk::EcmascriptChunk,
     manifest::{chunk_asset::ManifestAsyncModule, loader_item::ManifestLoaderChunkItem},
+    minify::MinifyOptions,
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
@@ -44,6 +47,11 @@ impl NodeJsChunkingContextBuilder {
         self
     }
 
+    pub fn minify_options(mut self, minify_options: Vc<MinifyOptions>) -> Self {
+        self.chunking_context.minify_options = minify_options;
+        self
+    }
+
     pub fn file_tracing(mut self, enable_tracing: bool) -> Self {
         self.chunking_context.enable_file_tracing = enable_tracing;
         self
@@ -69,6 +77,16 @@ impl NodeJsChunkingContextBuilder {
         self
     }
 
+    pub fn module_concatenation(mut self, module_concatenation: Vc<ModuleConcatenation>) -> Self {
+        self.chunking_context.module_concatenation = module_concatenation;
+        self
+    }
+
+    pub fn used_exports(mut self, used_exports: Vc<UsedExports>) -> Self {
+        self.chunking_context.used_exports = used_exports;
+        self
+    }
+
     /// Builds the chunking context.
     pub fn build(self) -> Vc<NodeJsChunkingContext> {
         NodeJsChunkingContext::new(Value::new(self.chunking_context))
@@ -100,10 +118,16 @@ pub struct NodeJsChunkingContext {
     enable_file_tracing: bool,
     /// Whether to minify resulting chunks
     minify_type: MinifyType,
+    /// The options for minifying Ecmascript chunks
+    minify_options: Vc<MinifyOptions>,
     /// Whether to use manifest chunks for lazy compilation
     manifest_chunks: bool,
     /// The strategy to use for generating module ids
     module_id_strategy: Vc<Box<dyn ModuleIdStrategy>>,
+    /// The groups of modules to concatenate into a single scope
+    module_concatenation: Vc<ModuleConcatenation>,
+    /// The exports of modules that are used by any importer
+    used_exports: Vc<UsedExports>,
     /// Whether to use file:// uris for source map sources
     should_use_file_source_map_uris: bool,
 }
@@ -131,9 +155,12 @@ impl NodeJsChunkingContext {
                 environment,
                 runtime_type,
                 minify_type: MinifyType::NoMinify,
+                minify_options: MinifyOptions::default().cell(),
                 manifest_chunks: false,
                 should_use_file_source_map_uris: false,
                 module_id_strategy: Vc::upcast(DevModuleIdStrategy::new()),
+                module_concatenation: ModuleConcatenation::empty(),
+                used_exports: UsedExports::all(),
             },
         }
     }
@@ -152,6 +179,11 @@ impl NodeJsChunkingContext {
     pub fn minify_type(&self) -> MinifyType {
         self.minify_type
     }
+
+    /// Returns the options for minifying Ecmascript chunks.
+    pub fn minify_options(&self) -> Vc<MinifyOptions> {
+        self.minify_options
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -214,6 +246,16 @@ impl ChunkingContext for NodeJsChunkingContext {
         Vc::cell(self.enable_file_tracing)
     }
 
+    #[turbo_tasks::function]
+    fn module_concatenation(&self) -> Vc<ModuleConcatenation> {
+        self.module_concatenation
+    }
+
+    #[turbo_tasks::function]
+    fn used_exports(&self) -> Vc<UsedExports> {
+        self.used_exports
+    }
+
     #[turbo_tasks::function]
     async fn asset_url(self: Vc<Self>, ident: Vc<AssetIdent>) -> Result<Vc<RcStr>> {
         let this = self.await?;
@@ -98,11 +98,13 @@ impl EcmascriptBuildNodeChunkContent {
         }
 
         let code = code.build().cell();
-        if matches!(
-            this.chunking_context.await?.minify_type(),
-            MinifyType::Minify
-        ) {
-            return Ok(minify(chunk_path_vc, code));
+        let chunking_context = this.chunking_context.await?;
+        if matches!(chunking_context.minify_type(), MinifyType::Minify) {
+            return Ok(minify(
+                chunk_path_vc,
+                code,
+                chunking_context.minify_options(),
+            ));
         }
 
         Ok(code)
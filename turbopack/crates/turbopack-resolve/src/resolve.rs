@@ -12,7 +12,9 @@ use turbopack_core::resolve::{
 
 use crate::{
     resolve_options_context::ResolveOptionsContext,
-    typescript::{apply_tsconfig_resolve_options, tsconfig, tsconfig_resolve_options},
+    typescript::{
+        apply_tsconfig_resolve_options, project_tsconfig, tsconfig, tsconfig_resolve_options,
+    },
 };
 
 const NODE_EXTERNALS: [&str; 63] = [
@@ -276,28 +278,89 @@ pub async fn resolve_options(
     resolve_path: Vc<FileSystemPath>,
     options_context: Vc<ResolveOptionsContext>,
 ) -> Result<Vc<ResolveOptions>> {
-    let options_context_value = options_context.await?;
-    if !options_context_value.rules.is_empty() {
-        let context_value = &*resolve_path.await?;
-        for (condition, new_options_context) in options_context_value.rules.iter() {
-            if condition.matches(context_value).await? {
-                return Ok(resolve_options(resolve_path, **new_options_context));
-            }
+    let options_context = options_context_for_path(resolve_path, options_context).await?;
+    let tsconfig = if options_context.await?.enable_typescript {
+        match *find_context_file(resolve_path, tsconfig()).await? {
+            FindContextFileResult::Found(path, _) => Some(*path),
+            FindContextFileResult::NotFound(_) => None,
         }
-    }
-
-    let resolve_options = base_resolve_options(resolve_path, options_context);
+    } else {
+        None
+    };
+    Ok(resolve_options_with_tsconfig(
+        resolve_path,
+        options_context,
+        tsconfig,
+    ))
+}
 
-    let resolve_options = if options_context_value.enable_typescript {
-        let tsconfig = find_context_file(resolve_path, tsconfig()).await?;
-        match *tsconfig {
+/// Returns the resolve options for requests made by the module at
+/// `origin_path`. Unlike [resolve_options], which uses the nearest tsconfig,
+/// this uses the tsconfig of the TypeScript project containing the module,
+/// following the project `references` of the nearest tsconfig.
+#[turbo_tasks::function]
+pub async fn origin_resolve_options(
+    origin_path: Vc<FileSystemPath>,
+    options_context: Vc<ResolveOptionsContext>,
+) -> Result<Vc<ResolveOptions>> {
+    let resolve_path = origin_path.parent().resolve().await?;
+    let options_context = options_context_for_path(resolve_path, options_context).await?;
+    let tsconfig = if options_context.await?.enable_typescript {
+        match *find_context_file(resolve_path, tsconfig()).await? {
             FindContextFileResult::Found(path, _) => {
-                apply_tsconfig_resolve_options(resolve_options, tsconfig_resolve_options(*path))
+                Some(project_tsconfig(*path, origin_path).resolve().await?)
             }
-            FindContextFileResult::NotFound(_) => resolve_options,
+            FindContextFileResult::NotFound(_) => None,
         }
     } else {
-        resolve_options
+        None
+    };
+    // The options are shared by all modules of the directory using the same
+    // tsconfig.
+    Ok(resolve_options_with_tsconfig(
+        resolve_path,
+        options_context,
+        tsconfig,
+    ))
+}
+
+/// Returns the options context of the first rule of `options_context` matching
+/// `resolve_path`, applied recursively.
+async fn options_context_for_path(
+    resolve_path: Vc<FileSystemPath>,
+    mut options_context: Vc<ResolveOptionsContext>,
+) -> Result<Vc<ResolveOptionsContext>> {
+    'rules: loop {
+        let options_context_value = options_context.await?;
+        if !options_context_value.rules.is_empty() {
+            let context_value = &*resolve_path.await?;
+            for (condition, new_options_context) in options_context_value.rules.iter() {
+                if condition.matches(context_value).await? {
+                    options_context = **new_options_context;
+                    continue 'rules;
+                }
+            }
+        }
+        return Ok(options_context);
+    }
+}
+
+#[turbo_tasks::function]
+async fn resolve_options_with_tsconfig(
+    resolve_path: Vc<FileSystemPath>,
+    options_context: Vc<ResolveOptionsContext>,
+    tsconfig: Option<Vc<FileSystemPath>>,
+) -> Result<Vc<ResolveOptions>> {
+    let options_context_value = options_context.await?;
+    let resolve_options = base_resolve_options(resolve_path, options_context);
+
+    let resolve_options = match tsconfig {
+        Some(tsconfig) => apply_tsconfig_resolve_options(
+            resolve_options,
+            tsconfig_resolve_options(tsconfig),
+            resolve_path,
+        ),
+        None => resolve_options,
     };
 
     // Make sure to always apply `options_context.import_map` last, so it properly
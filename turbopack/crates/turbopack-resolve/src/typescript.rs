@@ -1,9 +1,15 @@
-use std::{collections::HashMap, fmt::Write, mem::take};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    mem::take,
+};
 
 use anyhow::Result;
 use serde_json::Value as JsonValue;
 use turbo_tasks::{fxindexset, RcStr, ResolvedVc, Value, ValueDefault, Vc};
-use turbo_tasks_fs::{FileContent, FileJsonContent, FileSystemPath};
+use turbo_tasks_fs::{
+    glob::Glob, FileContent, FileJsonContent, FileSystemEntryType, FileSystemPath,
+};
 use turbopack_core::{
     asset::Asset,
     context::AssetContext,
@@ -214,6 +220,171 @@ pub async fn read_from_tsconfigs<T>(
     Ok(None)
 }
 
+/// Returns the tsconfig of the TypeScript project containing `file`, starting
+/// at `tsconfig`, the nearest tsconfig of the file. When that tsconfig doesn't
+/// include the file, the projects in its `references` are searched in order,
+/// so solution-style tsconfigs pick the referenced project of the file. Falls
+/// back to `tsconfig` when no project includes the file.
+#[turbo_tasks::function]
+pub async fn project_tsconfig(
+    tsconfig: Vc<FileSystemPath>,
+    file: Vc<FileSystemPath>,
+) -> Result<Vc<FileSystemPath>> {
+    let mut visited = HashSet::new();
+    Ok(find_project_tsconfig(tsconfig, file, &mut visited)
+        .await?
+        .unwrap_or(tsconfig))
+}
+
+async fn find_project_tsconfig(
+    tsconfig: Vc<FileSystemPath>,
+    file: Vc<FileSystemPath>,
+    visited: &mut HashSet<RcStr>,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    // Project references can be circular.
+    if !visited.insert(tsconfig.await?.path.clone()) {
+        return Ok(None);
+    }
+
+    let configs = read_tsconfigs(
+        tsconfig.read(),
+        ResolvedVc::upcast(FileSource::new(tsconfig).to_resolved().await?),
+        node_cjs_resolve_options(tsconfig.root()),
+    )
+    .await?;
+    let Some((content, _)) = configs.first() else {
+        return Ok(None);
+    };
+    if tsconfig_includes(&configs, file).await? {
+        return Ok(Some(tsconfig));
+    }
+
+    // `references` are not inherited with `extends`.
+    let FileJsonContent::Content(json) = &*content.await? else {
+        return Ok(None);
+    };
+    let JsonValue::Array(references) = &json["references"] else {
+        return Ok(None);
+    };
+    let dir = tsconfig.parent();
+    for reference in references {
+        let Some(path) = reference["path"].as_str() else {
+            continue;
+        };
+        let Some(path) = *dir.try_join(path.into()).await? else {
+            continue;
+        };
+        // A reference is either a tsconfig file or a directory containing a
+        // `tsconfig.json`.
+        let path = if matches!(&*path.get_type().await?, FileSystemEntryType::Directory) {
+            path.join("tsconfig.json".into())
+        } else {
+            *path
+        };
+        if let Some(project) = Box::pin(find_project_tsconfig(path, file, visited)).await? {
+            return Ok(Some(project));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns whether `file` is part of the project of `configs`, according to
+/// its `files`, `include` and `exclude` options.
+async fn tsconfig_includes(configs: &[TsConfig], file: Vc<FileSystemPath>) -> Result<bool> {
+    let file = &*file.await?;
+
+    let files = read_from_tsconfigs(configs, |json, source| {
+        json["files"]
+            .as_array()
+            .map(|files| (files.clone(), source))
+    })
+    .await?;
+    if let Some((files, source)) = &files {
+        let dir = source.ident().path().parent();
+        for entry in files.iter().filter_map(|entry| entry.as_str()) {
+            if let Some(path) = *dir.try_join(entry.into()).await? {
+                if &*path.await? == file {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    let include = read_from_tsconfigs(configs, |json, source| {
+        json["include"]
+            .as_array()
+            .map(|include| (include.clone(), source))
+    })
+    .await?;
+    let (include, include_source) = match include {
+        Some(include) => include,
+        // Only the listed files are part of the project.
+        None if files.is_some() => return Ok(false),
+        None => (vec![JsonValue::from("**/*")], configs[0].1),
+    };
+    if !matches_tsconfig_patterns(&include, include_source, file).await? {
+        return Ok(false);
+    }
+
+    let exclude = read_from_tsconfigs(configs, |json, source| {
+        json["exclude"]
+            .as_array()
+            .map(|exclude| (exclude.clone(), source))
+    })
+    .await?;
+    if let Some((exclude, exclude_source)) = exclude {
+        if matches_tsconfig_patterns(&exclude, exclude_source, file).await? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Matches `file` against the `include` or `exclude` patterns of a tsconfig.
+/// Patterns are relative to the tsconfig's directory, and patterns without
+/// wildcards or an extension match the contents of a directory.
+async fn matches_tsconfig_patterns(
+    patterns: &[JsonValue],
+    source: ResolvedVc<Box<dyn Source>>,
+    file: &FileSystemPath,
+) -> Result<bool> {
+    for pattern in patterns.iter().filter_map(|pattern| pattern.as_str()) {
+        // Glob character classes are not supported.
+        if pattern.contains('[') {
+            continue;
+        }
+
+        let mut dir = source.ident().path().parent();
+        let mut pattern = pattern;
+        loop {
+            if let Some(rest) = pattern.strip_prefix("./") {
+                pattern = rest;
+            } else if let Some(rest) = pattern.strip_prefix("../") {
+                dir = dir.parent();
+                pattern = rest;
+            } else {
+                break;
+            }
+        }
+        let pattern = pattern.trim_end_matches('/');
+        let last_segment = pattern.rsplit('/').next().unwrap_or_default();
+        let pattern = if pattern.is_empty() || pattern == "." {
+            "**/*".to_string()
+        } else if last_segment.contains(['*', '?', '.']) {
+            pattern.to_string()
+        } else {
+            format!("{pattern}/**/*")
+        };
+
+        if let Some(path) = dir.await?.get_path_to(file) {
+            if Glob::parse(&pattern)?.execute(path) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
 /// Resolve options specific to tsconfig.json.
 #[turbo_tasks::value]
 #[derive(Default)]
@@ -221,6 +392,11 @@ pub struct TsConfigResolveOptions {
     base_url: Option<ResolvedVc<FileSystemPath>>,
     import_map: Option<ResolvedVc<ImportMap>>,
     is_module_resolution_nodenext: bool,
+    /// `compilerOptions.rootDirs`, whose contents are merged into one virtual
+    /// directory for relative requests.
+    root_dirs: Vec<ResolvedVc<FileSystemPath>>,
+    /// `compilerOptions.moduleSuffixes`, e.g. `[".ios", ""]`.
+    module_suffixes: Option<Vec<RcStr>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -333,10 +509,40 @@ pub async fn tsconfig_resolve_options(
     .await?
     .unwrap_or_default();
 
+    let mut root_dirs = Vec::new();
+    if let Some((dirs, source)) = read_from_tsconfigs(&configs, |json, source| {
+        json["compilerOptions"]["rootDirs"]
+            .as_array()
+            .map(|dirs| (dirs.clone(), source))
+    })
+    .await?
+    {
+        let context_dir = source.ident().path().parent();
+        for dir in dirs.iter().filter_map(|dir| dir.as_str()) {
+            if let Some(dir) = *context_dir.try_join(dir.into()).await? {
+                root_dirs.push(dir);
+            }
+        }
+    }
+
+    let module_suffixes = read_from_tsconfigs(&configs, |json, _| {
+        json["compilerOptions"]["moduleSuffixes"]
+            .as_array()
+            .map(|suffixes| {
+                suffixes
+                    .iter()
+                    .filter_map(|suffix| suffix.as_str().map(RcStr::from))
+                    .collect()
+            })
+    })
+    .await?;
+
     Ok(TsConfigResolveOptions {
         base_url,
         import_map,
         is_module_resolution_nodenext,
+        root_dirs,
+        module_suffixes,
     }
     .cell())
 }
@@ -346,10 +552,13 @@ pub fn tsconfig() -> Vc<Vec<RcStr>> {
     Vc::cell(vec!["tsconfig.json".into(), "jsconfig.json".into()])
 }
 
+/// Applies `tsconfig_resolve_options` to the resolve options of requests made
+/// from `resolve_path`.
 #[turbo_tasks::function]
 pub async fn apply_tsconfig_resolve_options(
     resolve_options: Vc<ResolveOptions>,
     tsconfig_resolve_options: Vc<TsConfigResolveOptions>,
+    resolve_path: Vc<FileSystemPath>,
 ) -> Result<Vc<ResolveOptions>> {
     let tsconfig_resolve_options = tsconfig_resolve_options.await?;
     let mut resolve_options = resolve_options.await?.clone_value();
@@ -378,9 +587,85 @@ pub async fn apply_tsconfig_resolve_options(
     resolve_options.enable_typescript_with_output_extension =
         tsconfig_resolve_options.is_module_resolution_nodenext;
 
+    if let Some(root_dirs_import_map) =
+        root_dirs_import_map(&tsconfig_resolve_options.root_dirs, resolve_path).await?
+    {
+        // Relative requests are resolved in the requesting directory first, and
+        // then in the same location of the other root dirs.
+        resolve_options.fallback_import_map = Some(
+            resolve_options
+                .fallback_import_map
+                .map(|import_map| import_map.extend(*root_dirs_import_map))
+                .unwrap_or(*root_dirs_import_map)
+                .to_resolved()
+                .await?,
+        );
+    }
+
+    if let Some(module_suffixes) = &tsconfig_resolve_options.module_suffixes {
+        // Like tsc, each extension is tried with every suffix in order. An
+        // empty suffix has to be listed to try the plain extension.
+        resolve_options.extensions = resolve_options
+            .extensions
+            .iter()
+            .flat_map(|extension| {
+                module_suffixes
+                    .iter()
+                    .map(move |suffix| format!("{suffix}{extension}").into())
+            })
+            .collect();
+    }
+
     Ok(resolve_options.cell())
 }
 
+/// Maps relative requests from `resolve_path` to the same location in the
+/// other root dirs, when `resolve_path` is in one of `root_dirs`.
+async fn root_dirs_import_map(
+    root_dirs: &[ResolvedVc<FileSystemPath>],
+    resolve_path: Vc<FileSystemPath>,
+) -> Result<Option<ResolvedVc<ImportMap>>> {
+    let resolve_path = &*resolve_path.await?;
+    let mut subpath = None;
+    for root_dir in root_dirs {
+        if let Some(path) = root_dir.await?.get_path_to(resolve_path) {
+            subpath = Some((*root_dir, path.to_string()));
+            break;
+        }
+    }
+    let Some((current_root_dir, subpath)) = subpath else {
+        return Ok(None);
+    };
+
+    let target = if subpath.is_empty() {
+        "./*".to_string()
+    } else {
+        format!("./{subpath}/*")
+    };
+    let mut import_map = ImportMap::empty();
+    for (alias, target) in [
+        ("./*", target.clone()),
+        ("../*", target.replace('*', "../*")),
+    ] {
+        let mappings = root_dirs
+            .iter()
+            .filter(|root_dir| **root_dir != current_root_dir)
+            .map(|root_dir| {
+                ImportMapping::PrimaryAlternative(target.clone().into(), Some(*root_dir))
+                    .resolved_cell()
+            })
+            .collect::<Vec<_>>();
+        if mappings.is_empty() {
+            return Ok(None);
+        }
+        import_map.insert_alias(
+            AliasPattern::parse(alias),
+            ImportMapping::Alternatives(mappings).resolved_cell(),
+        );
+    }
+    Ok(Some(import_map.resolved_cell()))
+}
+
 #[turbo_tasks::function]
 pub async fn type_resolve(
     origin: Vc<Box<dyn ResolveOrigin>>,
@@ -14,7 +14,7 @@ use turbo_tasks_fs::{
     FileSystemPath,
 };
 use turbo_tasks_hash::encode_hex;
-use turbopack_cli_utils::issue::{format_issue, LogOptions};
+use turbopack_cli_utils::issue::{format_issue, IssueFormat, LogOptions};
 use turbopack_core::{
     asset::AssetContent,
     issue::{IssueSeverity, PlainIssue, StyledString},
@@ -62,6 +62,8 @@ pub async fn snapshot_issues<I: IntoIterator<Item = ReadRef<PlainIssue>>>(
                 show_all: true,
                 log_detail: true,
                 log_level: IssueSeverity::Info,
+                issue_format: IssueFormat::Human,
+                issue_output: None,
             },
         );
 
@@ -22,7 +22,7 @@ use turbo_tasks_fs::{
 };
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
-    ecmascript::TreeShakingMode,
+    ecmascript::{concatenation::module_concatenation, TreeShakingMode},
     module_options::{EcmascriptOptionsContext, ModuleOptionsContext},
     ModuleAssetContext,
 };
@@ -183,6 +183,8 @@ async fn run(resource: PathBuf, snapshot_mode: IssueSnapshotMode) -> Result<JsRe
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct TestOptions {
     tree_shaking_mode: Option<TreeShakingMode>,
+    #[serde(default)]
+    concatenate_modules: bool,
 }
 
 #[turbo_tasks::value]
@@ -322,17 +324,6 @@ async fn run_test(prepared_test: Vc<PreparedTest>) -> Result<Vc<RunTestResult>>
         Vc::cell("test".into()),
     ));
 
-    let chunking_context = NodeJsChunkingContext::builder(
-        *project_root,
-        chunk_root_path,
-        static_root_path,
-        chunk_root_path,
-        static_root_path,
-        env,
-        RuntimeType::Development,
-    )
-    .build();
-
     let jest_entry_source = FileSource::new(jest_entry_path);
     let test_source = FileSource::new(test_path);
 
@@ -345,6 +336,21 @@ async fn run_test(prepared_test: Vc<PreparedTest>) -> Result<Vc<RunTestResult>>
         .to_resolved()
         .await?;
 
+    let mut chunking_context = NodeJsChunkingContext::builder(
+        *project_root,
+        chunk_root_path,
+        static_root_path,
+        chunk_root_path,
+        static_root_path,
+        env,
+        RuntimeType::Development,
+    );
+    if options.concatenate_modules {
+        chunking_context =
+            chunking_context.module_concatenation(module_concatenation(Vc::cell(vec![test_asset])));
+    }
+    let chunking_context = chunking_context.build();
+
     let jest_entry_asset = asset_context
         .process(
             Vc::upcast(jest_entry_source),
@@ -0,0 +1,7 @@
+const value = "a";
+
+function format() {
+  return `value of a: ${value}`;
+}
+
+export { format as formatA };
@@ -0,0 +1,7 @@
+const value = "b";
+
+function format() {
+  return `value of b: ${value}`;
+}
+
+export { format as formatB };
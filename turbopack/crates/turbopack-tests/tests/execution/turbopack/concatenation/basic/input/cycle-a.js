@@ -0,0 +1,8 @@
+import { order } from "./order.js";
+import { fromB } from "./cycle-b.js";
+
+order.push("a");
+
+export function callB() {
+  return fromB();
+}
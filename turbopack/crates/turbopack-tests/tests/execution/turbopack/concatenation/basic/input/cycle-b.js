@@ -0,0 +1,8 @@
+import { order } from "./order.js";
+import { name } from "./index.js";
+
+order.push("b");
+
+export function fromB() {
+  return `b sees ${name}`;
+}
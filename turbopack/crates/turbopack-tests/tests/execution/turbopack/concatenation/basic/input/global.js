@@ -0,0 +1,3 @@
+export function max(a, b) {
+  return Math.max(a, b);
+}
@@ -0,0 +1,41 @@
+import { order } from "./order.js";
+import { callB } from "./cycle-a.js";
+import { count, increment } from "./counter.js";
+import { formatA } from "./collision-a.js";
+import { formatB } from "./collision-b.js";
+import * as namespace from "./namespace.js";
+import { max } from "./global.js";
+
+const value = "index";
+const Math = "index";
+
+export const name = "index";
+
+it("should evaluate modules of a cycle in import order", () => {
+  expect(order).toEqual(["b", "a"]);
+  expect(callB()).toBe("b sees index");
+});
+
+it("should keep exports live", () => {
+  expect(count).toBe(0);
+  increment();
+  increment();
+  expect(count).toBe(2);
+});
+
+it("should rename colliding top-level bindings", () => {
+  expect(formatA()).toBe("value of a: a");
+  expect(formatB()).toBe("value of b: b");
+  expect(value).toBe("index");
+});
+
+it("should not shadow globals of other modules", () => {
+  expect(max(1, 2)).toBe(2);
+  expect(Math).toBe("index");
+});
+
+it("should keep the namespace object of modules used as a value", () => {
+  expect(namespace.first).toBe(1);
+  expect(namespace?.second).toBe(2);
+  expect(Object.keys(namespace)).toEqual(["first", "second"]);
+});
@@ -0,0 +1,10 @@
+import platform from "./src/platform";
+import other from "./src/other";
+
+it("should prefer files with a module suffix", () => {
+  expect(platform).toBe("web");
+});
+
+it("should fall back to files without a suffix", () => {
+  expect(other).toBe("other");
+});
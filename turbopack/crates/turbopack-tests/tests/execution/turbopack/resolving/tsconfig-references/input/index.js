@@ -0,0 +1,5 @@
+import foo from "./src/index";
+
+it("should resolve paths of the referenced project containing the file", () => {
+  expect(foo).toBe("foo");
+});
@@ -0,0 +1,3 @@
+import foo from "@app/foo";
+
+export default foo;
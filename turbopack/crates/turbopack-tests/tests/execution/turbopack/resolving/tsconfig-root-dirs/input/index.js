@@ -0,0 +1,5 @@
+import button from "./src/components/button";
+
+it("should resolve relative requests in other root dirs", () => {
+  expect(button).toBe("button");
+});
@@ -0,0 +1,3 @@
+import styles from "./button.styles";
+
+export default styles;
@@ -12,7 +12,6 @@ mod graph;
 pub mod module_options;
 pub mod rebase;
 pub mod transition;
-pub(crate) mod unsupported_sass;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -58,8 +57,11 @@ use turbopack_ecmascript::{
     tree_shake::asset::EcmascriptModulePartAsset,
 };
 use turbopack_json::JsonModuleAsset;
+use turbopack_resolve::{
+    resolve::origin_resolve_options, resolve_options_context::ResolveOptionsContext,
+    typescript::type_resolve,
+};
 pub use turbopack_resolve::{resolve::resolve_options, resolve_options_context};
-use turbopack_resolve::{resolve_options_context::ResolveOptionsContext, typescript::type_resolve};
 use turbopack_static::StaticModuleAsset;
 use turbopack_wasm::{module_asset::WebAssemblyModuleAsset, source::WebAssemblySource};
 
@@ -655,8 +657,8 @@ impl AssetContext for ModuleAssetContext {
             self
         };
         // TODO move `apply_commonjs/esm_resolve_options` etc. to here
-        Ok(resolve_options(
-            origin_path.parent().resolve().await?,
+        Ok(origin_resolve_options(
+            origin_path.resolve().await?,
             module_asset_context.await?.resolve_options_context,
         ))
     }
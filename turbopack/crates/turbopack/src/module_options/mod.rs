@@ -16,14 +16,15 @@ use turbopack_core::{
     reference_type::{CssReferenceSubType, ReferenceType, UrlReferenceSubType},
     resolve::options::{ImportMap, ImportMapping},
 };
-use turbopack_css::CssModuleAssetType;
+use turbopack_css::{sass::SassTransform, CssModuleAssetType};
 use turbopack_ecmascript::{EcmascriptInputTransform, EcmascriptOptions, SpecifiedModuleType};
 use turbopack_mdx::MdxTransform;
 use turbopack_node::transforms::{postcss::PostCssTransform, webpack::WebpackLoaders};
 use turbopack_wasm::source::WebAssemblySourceType;
 
 use crate::{
-    evaluate_context::node_evaluate_asset_context, resolve_options_context::ResolveOptionsContext,
+    evaluate_context::node_evaluate_asset_context, resolve_options,
+    resolve_options_context::ResolveOptionsContext,
 };
 
 #[turbo_tasks::function]
@@ -84,6 +85,7 @@ impl ModuleOptions {
                 CssOptionsContext {
                     enable_raw_css,
                     use_swc_css,
+                    enable_sass,
                     ..
                 },
             ref enable_postcss_transform,
@@ -382,6 +384,36 @@ impl ModuleOptions {
             ),
         ];
 
+        if let Some(sass_options) = enable_sass {
+            let resolve_options = resolve_options(path, resolve_options_context);
+            let sass_transform = |rename_as: &str| {
+                ModuleRuleEffect::SourceTransforms(Vc::cell(vec![Vc::upcast(SassTransform::new(
+                    resolve_options,
+                    sass_options,
+                    rename_as.into(),
+                ))]))
+            };
+            let is_sass_module = RuleCondition::any(vec![
+                RuleCondition::ResourcePathEndsWith(".module.scss".to_string()),
+                RuleCondition::ResourcePathEndsWith(".module.sass".to_string()),
+            ]);
+            rules.extend([
+                ModuleRule::new(
+                    RuleCondition::all(vec![
+                        RuleCondition::any(vec![
+                            RuleCondition::ResourcePathEndsWith(".scss".to_string()),
+                            RuleCondition::ResourcePathEndsWith(".sass".to_string()),
+                        ]),
+                        RuleCondition::not(is_sass_module.clone()),
+                    ]),
+                    vec![sass_transform("*.css")],
+                ),
+                // The compiled source is renamed, so that it is picked up by the CSS module
+                // rules below.
+                ModuleRule::new(is_sass_module, vec![sass_transform("*.module.css")]),
+            ]);
+        }
+
         if enable_raw_css {
             rules.extend([
                 ModuleRule::new(
@@ -5,6 +5,7 @@ use turbopack_core::{
     chunk::MinifyType, condition::ContextCondition, environment::Environment,
     resolve::options::ImportMapping,
 };
+use turbopack_css::sass::SassTransformOptions;
 use turbopack_ecmascript::{references::esm::UrlRewriteBehavior, TreeShakingMode};
 pub use turbopack_mdx::MdxTransformOptions;
 use turbopack_node::{
@@ -174,6 +175,8 @@ pub struct CssOptionsContext {
     /// the module graph, but neither asset types can be emitted directly.
     pub enable_raw_css: bool,
     pub use_swc_css: bool,
+    /// Compiles `.scss` and `.sass` files to CSS natively.
+    pub enable_sass: Option<Vc<SassTransformOptions>>,
 
     pub minify_type: MinifyType,
 